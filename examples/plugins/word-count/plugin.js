@@ -0,0 +1,17 @@
+// Example plugin: the toolbar button shows how many words the page has. Every page tells the
+// plugin's copies in the other tabs, through an event, that it was read.
+const words = () => (document.body ? document.body.innerText : "").split(/\s+/).filter(Boolean);
+
+browser.register("countWords", async () => {
+  const url = await browser.currentUrl();
+  const count = words().length;
+  await browser.notify(`${count.toLocaleString()} words on ${new URL(url).host}`);
+});
+
+browser.on("navigate", ({ url }) => {
+  browser.emit("pageRead", { url, words: words().length });
+});
+
+browser.on("pageRead", (detail, from) => {
+  if (from === browser.id) console.debug("Read elsewhere:", detail.url, detail.words, "words");
+});
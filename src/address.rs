@@ -0,0 +1,189 @@
+use std::path::{Path, PathBuf};
+use url::{form_urlencoded, Host, Url};
+
+use crate::settings::{SearchEngine, QUERY_PLACEHOLDER};
+
+// Schemes that are navigated as-is when typed with an explicit scheme
+const KNOWN_SCHEMES: &[&str] = &[
+    "http",
+    "https",
+    "file",
+    "about",
+    "data",
+    "chrome",
+    "view-source",
+];
+
+// Turn address bar input into a URL to load, either the typed URL or a search query
+pub fn normalize_input(input: &str, engine: &SearchEngine) -> String {
+    let input = input.trim();
+    if input.is_empty() {
+        return String::new();
+    }
+
+    // local paths may contain spaces, so they're checked before falling back to a search
+    if let Some(path) = local_path(input) {
+        if let Some(url) = file_url(&path) {
+            return url;
+        }
+    }
+    if input
+        .get(..7)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("file://"))
+    {
+        if let Ok(url) = Url::parse(input) {
+            return url
+                .to_file_path()
+                .ok()
+                .and_then(|path| file_url(&path))
+                .unwrap_or_else(|| url.to_string());
+        }
+    }
+
+    if input.contains(char::is_whitespace) {
+        return search_url(engine, input);
+    }
+
+    if let Ok(url) = Url::parse(input) {
+        if KNOWN_SCHEMES.contains(&url.scheme()) {
+            return url.to_string();
+        }
+    }
+
+    // bare host, optionally with port and path, e.g. "github.com" or "localhost:3000/app"
+    if let Ok(url) = Url::parse(&format!("http://{}", input)) {
+        match url.host() {
+            Some(Host::Ipv4(_)) | Some(Host::Ipv6(_)) => return url.to_string(),
+            Some(Host::Domain("localhost")) => return url.to_string(),
+            Some(Host::Domain(domain)) if looks_like_domain(domain) => {
+                return format!("https://{}", input);
+            }
+            _ => {}
+        }
+    }
+
+    search_url(engine, input)
+}
+
+// `file:` URL of a local path, ending in a slash for directories so they open as a listing
+pub fn file_url(path: &Path) -> Option<String> {
+    let url = if path.is_dir() {
+        Url::from_directory_path(path)
+    } else {
+        Url::from_file_path(path)
+    };
+    url.ok().map(String::from)
+}
+
+// An absolute path or one in the home directory, e.g. "/tmp/page.html" or "~/Downloads"
+fn local_path(input: &str) -> Option<PathBuf> {
+    if input == "~" {
+        return dirs::home_dir();
+    }
+    if let Some(rest) = input.strip_prefix("~/") {
+        return dirs::home_dir().map(|home| home.join(rest));
+    }
+    let path = Path::new(input);
+    path.is_absolute().then(|| path.to_path_buf())
+}
+
+// Search engine URL for free-form text, substituted into the engine's template
+pub fn search_url(engine: &SearchEngine, query: &str) -> String {
+    // percent-encode everything but unreserved characters; a literal `+` was already
+    // encoded, so the remaining ones are spaces and work in paths as well as queries
+    let query: String = form_urlencoded::byte_serialize(query.as_bytes())
+        .collect::<String>()
+        .replace('+', "%20");
+    engine.query_template.replace(QUERY_PLACEHOLDER, &query)
+}
+
+// A dotted name ending in an alphabetic top-level label, e.g. "example.com"
+fn looks_like_domain(domain: &str) -> bool {
+    match domain.rsplit_once('.') {
+        Some((name, tld)) => {
+            !name.is_empty() && tld.len() >= 2 && tld.chars().all(|c| c.is_ascii_alphabetic())
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine() -> SearchEngine {
+        SearchEngine::new("Test", "https://search.test/?q={query}")
+    }
+
+    fn normalize(input: &str) -> String {
+        normalize_input(input, &engine())
+    }
+
+    #[test]
+    fn local_hosts_and_ips_load_over_http() {
+        assert_eq!(normalize("localhost"), "http://localhost/");
+        assert_eq!(normalize("localhost:3000"), "http://localhost:3000/");
+        assert_eq!(normalize("localhost:3000/app"), "http://localhost:3000/app");
+        assert_eq!(normalize("192.168.1.1"), "http://192.168.1.1/");
+        assert_eq!(normalize("127.0.0.1:8080"), "http://127.0.0.1:8080/");
+        assert_eq!(normalize("[::1]:8080"), "http://[::1]:8080/");
+    }
+
+    #[test]
+    fn domains_load_over_https() {
+        assert_eq!(normalize("foo.bar"), "https://foo.bar");
+        assert_eq!(
+            normalize("  example.com/docs?page=2 "),
+            "https://example.com/docs?page=2"
+        );
+        assert_eq!(normalize("https://example.com"), "https://example.com/");
+    }
+
+    #[test]
+    fn text_that_isnt_a_domain_is_searched() {
+        assert_eq!(normalize("foo bar"), "https://search.test/?q=foo%20bar");
+        assert_eq!(normalize("rust"), "https://search.test/?q=rust");
+        assert_eq!(normalize("a.b"), "https://search.test/?q=a.b");
+        assert_eq!(normalize("v1.2"), "https://search.test/?q=v1.2");
+        assert_eq!(
+            normalize("c++ tips"),
+            "https://search.test/?q=c%2B%2B%20tips"
+        );
+        assert_eq!(normalize(""), "");
+    }
+
+    #[test]
+    fn quoted_terms_are_searched() {
+        assert_eq!(
+            normalize("\"example.com\""),
+            "https://search.test/?q=%22example.com%22"
+        );
+        assert_eq!(
+            normalize("\"gpui webview\""),
+            "https://search.test/?q=%22gpui%20webview%22"
+        );
+    }
+
+    #[test]
+    fn known_schemes_are_kept() {
+        assert_eq!(normalize("about:blank"), "about:blank");
+        assert_eq!(normalize("chrome://gpu"), "chrome://gpu");
+        assert_eq!(
+            normalize("file:///nonexistent/page.html"),
+            "file:///nonexistent/page.html"
+        );
+        assert_eq!(
+            normalize("FILE:///nonexistent/page.html"),
+            "file:///nonexistent/page.html"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn absolute_paths_open_as_files() {
+        assert_eq!(
+            normalize("/nonexistent/my page.html"),
+            "file:///nonexistent/my%20page.html"
+        );
+    }
+}
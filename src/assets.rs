@@ -0,0 +1,176 @@
+use anyhow::Result;
+use gpui::{AssetSource, SharedString};
+use std::borrow::Cow;
+
+// Icons compiled into the binary, so an installed build doesn't depend on the source tree
+#[cfg(not(feature = "dev-assets"))]
+#[derive(rust_embed::RustEmbed)]
+#[folder = "assets"]
+pub struct EmbeddedAssets;
+
+#[cfg(not(feature = "dev-assets"))]
+impl AssetSource for EmbeddedAssets {
+    fn load(&self, path: &str) -> Result<Option<Cow<'static, [u8]>>> {
+        Ok(Self::get(path).map(|file| file.data))
+    }
+
+    fn list(&self, path: &str) -> Result<Vec<SharedString>> {
+        Ok(Self::iter()
+            .filter(|file| file.starts_with(path))
+            .map(SharedString::from)
+            .collect())
+    }
+}
+
+// Asset source used by the app: embedded by default, read from disk with `dev-assets`
+#[cfg(not(feature = "dev-assets"))]
+pub fn assets() -> impl AssetSource {
+    EmbeddedAssets
+}
+
+#[cfg(feature = "dev-assets")]
+pub fn assets() -> impl AssetSource {
+    dev::Assets {
+        base: std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets"),
+    }
+}
+
+#[cfg(feature = "dev-assets")]
+pub use dev::{init, RELOAD_ASSETS_KEY};
+
+// Loader reading assets straight from the source tree, reloaded on demand with `ReloadAssets`.
+// Built for tests too, which cover it without the feature.
+#[cfg(any(test, feature = "dev-assets"))]
+#[cfg_attr(not(feature = "dev-assets"), allow(dead_code))]
+mod dev {
+    use super::*;
+    use gpui::{actions, App, KeyBinding};
+    use std::collections::HashMap;
+    use std::fs;
+    use std::io;
+    use std::path::PathBuf;
+    use std::sync::{LazyLock, RwLock};
+
+    actions!(browser, [ReloadAssets]);
+
+    pub const RELOAD_ASSETS_KEY: &str = "secondary-alt-r";
+
+    // Files read so far, keyed by asset path, so an icon drawn again isn't read from disk
+    // again. Contents are leaked so loads can borrow them instead of copying the file each
+    // time; a reload leaks the old ones, which only matters in development builds.
+    // Lives outside `Assets` because the app owns the asset source once it's installed.
+    static CACHE: LazyLock<RwLock<HashMap<String, &'static [u8]>>> =
+        LazyLock::new(Default::default);
+
+    pub fn init(cx: &mut App) {
+        cx.on_action(|_: &ReloadAssets, cx| {
+            clear_cache();
+            cx.refresh_windows();
+        });
+        cx.bind_keys([KeyBinding::new(RELOAD_ASSETS_KEY, ReloadAssets, None)]);
+    }
+
+    // Drop cached files so edits on disk are picked up by the next load
+    pub fn clear_cache() {
+        let mut cache = CACHE.write().unwrap_or_else(|err| err.into_inner());
+        log::debug!("Cleared {} cached assets", cache.len());
+        cache.clear();
+    }
+
+    pub struct Assets {
+        pub base: PathBuf,
+    }
+
+    impl AssetSource for Assets {
+        fn load(&self, path: &str) -> Result<Option<Cow<'static, [u8]>>> {
+            let cached = CACHE
+                .read()
+                .unwrap_or_else(|err| err.into_inner())
+                .get(path)
+                .copied();
+            if let Some(data) = cached {
+                return Ok(Some(Cow::Borrowed(data)));
+            }
+
+            let full_path = self.base.join(path);
+            // pages can ask for assets by name, so nothing outside the asset folder is read
+            if let (Ok(resolved), Ok(base)) = (full_path.canonicalize(), self.base.canonicalize()) {
+                if !resolved.starts_with(&base) {
+                    log::warn!("Refusing asset outside {:?}: {:?}", self.base, full_path);
+                    return Ok(None);
+                }
+            }
+
+            // a missing asset is absent rather than an error, so optional assets can be probed
+            match fs::read(&full_path) {
+                Ok(data) => {
+                    let data: &'static [u8] = Box::leak(data.into_boxed_slice());
+                    CACHE
+                        .write()
+                        .unwrap_or_else(|err| err.into_inner())
+                        .insert(path.to_string(), data);
+                    Ok(Some(Cow::Borrowed(data)))
+                }
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    log::debug!("Asset not found: {:?}", full_path);
+                    Ok(None)
+                }
+                Err(err) => {
+                    log::error!("Failed to load asset {:?}: {}", full_path, err);
+                    Err(err.into())
+                }
+            }
+        }
+
+        fn list(&self, path: &str) -> Result<Vec<SharedString>> {
+            let full_path = self.base.join(path);
+
+            match fs::read_dir(&full_path) {
+                Ok(entries) => {
+                    let files: Vec<SharedString> = entries
+                        .filter_map(|entry| match entry {
+                            Ok(entry) => {
+                                let file_name = entry.file_name();
+                                file_name.into_string().ok().map(SharedString::from)
+                            }
+                            Err(err) => {
+                                log::warn!("Error reading directory entry: {}", err);
+                                None
+                            }
+                        })
+                        .collect();
+
+                    log::trace!("Listed {} files in directory: {:?}", files.len(), full_path);
+                    Ok(files)
+                }
+                Err(err) => {
+                    log::error!("Failed to list directory {:?}: {}", full_path, err);
+                    Err(err.into())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn missing_asset_is_absent() {
+        let assets = dev::Assets {
+            base: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets"),
+        };
+        assert!(matches!(assets.load("missing.svg"), Ok(None)));
+    }
+
+    #[test]
+    fn nothing_outside_the_asset_folder_loads() {
+        let assets = dev::Assets {
+            base: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets"),
+        };
+        assert!(matches!(assets.load("../Cargo.toml"), Ok(None)));
+        assert!(matches!(assets.load("globe.svg"), Ok(Some(_))));
+    }
+}
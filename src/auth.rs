@@ -0,0 +1,209 @@
+use gpui::{div, prelude::*, px, rgba, white, App, Entity, IntoElement, Subscription, Window};
+use gpui_component::input::{InputState, TextInput};
+use gpui_webview::events::AuthCallback;
+use keyring::Entry;
+use serde::{Deserialize, Serialize};
+
+use crate::bridge::TabId;
+use crate::theme::Theme;
+
+// Service name the keychain entries are filed under
+const KEYCHAIN_SERVICE: &str = "browser-app";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+// Keychain entries are per server and realm, like the browser's own password prompt
+pub fn credentials_key(host: &str, port: u16, realm: &str) -> String {
+    format!("{}:{} ({})", host, port, realm)
+}
+
+// Blocks on the OS keychain, which may ask the user to unlock it
+pub fn load_credentials(key: &str) -> Option<Credentials> {
+    let secret = Entry::new(KEYCHAIN_SERVICE, key)
+        .and_then(|entry| entry.get_password())
+        .map_err(|err| {
+            if !matches!(err, keyring::Error::NoEntry) {
+                log::error!("Failed to read credentials for {}: {}", key, err);
+            }
+        })
+        .ok()?;
+    serde_json::from_str(&secret).ok()
+}
+
+pub fn save_credentials(key: &str, credentials: &Credentials) {
+    let result = serde_json::to_string(credentials)
+        .map_err(|err| err.to_string())
+        .and_then(|secret| {
+            Entry::new(KEYCHAIN_SERVICE, key)
+                .and_then(|entry| entry.set_password(&secret))
+                .map_err(|err| err.to_string())
+        });
+    if let Err(err) = result {
+        log::error!("Failed to save credentials for {}: {}", key, err);
+    }
+}
+
+pub fn delete_credentials(key: &str) {
+    let result = Entry::new(KEYCHAIN_SERVICE, key).and_then(|entry| entry.delete_credential());
+    if let Err(err) = result.or_else(|err| match err {
+        keyring::Error::NoEntry => Ok(()),
+        err => Err(err),
+    }) {
+        log::error!("Failed to delete credentials for {}: {}", key, err);
+    }
+}
+
+// A server asking for a user name and password before it answers a request
+pub struct AuthRequest {
+    pub tab_id: TabId,
+    // Keychain entry the credentials are remembered under
+    pub key: String,
+    // Who is asking, e.g. "intranet.example.com" or "proxy.example.com (proxy)"
+    pub server: String,
+    pub realm: String,
+    pub callback: AuthCallback,
+}
+
+// Sign-in dialog answering an `AuthRequest`
+pub struct AuthPrompt {
+    pub request: AuthRequest,
+    pub username: Entity<InputState>,
+    pub password: Entity<InputState>,
+    pub remember: bool,
+    pub _subscriptions: Vec<Subscription>,
+}
+
+fn button(
+    id: &'static str,
+    label: &'static str,
+    primary: bool,
+    theme: &Theme,
+) -> gpui::Stateful<gpui::Div> {
+    div()
+        .id(id)
+        .px_3()
+        .py_1()
+        .rounded_md()
+        .border_1()
+        .border_color(theme.border)
+        .text_xs()
+        .text_color(theme.text)
+        .cursor_pointer()
+        .when(primary, |this| {
+            this.bg(theme.accent)
+                .border_color(theme.accent)
+                .text_color(white())
+        })
+        .hover(|this| this.opacity(0.85))
+        .child(label)
+}
+
+fn field(label: &'static str, state: &Entity<InputState>, theme: &Theme) -> impl IntoElement {
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .child(div().text_xs().text_color(theme.text_dim).child(label))
+        .child(
+            div()
+                .flex()
+                .items_center()
+                .h_8()
+                .px_3()
+                .border_1()
+                .border_color(theme.border)
+                .rounded_md()
+                .child(
+                    TextInput::new(state)
+                        .text_color(theme.text_secondary)
+                        .text_xs()
+                        .border_0(),
+                ),
+        )
+}
+
+// Sign-in dialog over the whole window, blocking the page until it's answered
+pub fn render_auth_prompt(
+    prompt: &AuthPrompt,
+    theme: &Theme,
+    on_toggle_remember: impl Fn(&mut Window, &mut App) + 'static,
+    on_cancel: impl Fn(&mut Window, &mut App) + 'static,
+    on_submit: impl Fn(&mut Window, &mut App) + 'static,
+) -> impl IntoElement {
+    let request = &prompt.request;
+
+    div()
+        .absolute()
+        .inset_0()
+        .occlude()
+        .flex()
+        .items_center()
+        .justify_center()
+        // dims the page the same in light and dark themes
+        .bg(rgba(0x00000080))
+        .child(
+            div()
+                .w(px(340.))
+                .flex()
+                .flex_col()
+                .gap_3()
+                .p_5()
+                .rounded_lg()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.surface)
+                .child(div().text_sm().text_color(theme.text).child("Sign in"))
+                .child(div().text_xs().text_color(theme.text_dim).child(
+                    if request.realm.is_empty() {
+                        format!("{} requires a user name and password.", request.server)
+                    } else {
+                        format!(
+                            "{} requires a user name and password for \"{}\".",
+                            request.server, request.realm
+                        )
+                    },
+                ))
+                .child(field("User name", &prompt.username, theme))
+                .child(field("Password", &prompt.password, theme))
+                .child(
+                    div()
+                        .id("auth-remember")
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .text_xs()
+                        .text_color(theme.text_secondary)
+                        .cursor_pointer()
+                        .on_click(move |_, window, cx| on_toggle_remember(window, cx))
+                        .child(
+                            div()
+                                .size(px(12.))
+                                .rounded_sm()
+                                .border_1()
+                                .border_color(theme.text_muted)
+                                .when(prompt.remember, |this| {
+                                    this.bg(theme.accent).border_color(theme.accent)
+                                }),
+                        )
+                        .child("Remember for this site"),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .justify_end()
+                        .gap_2()
+                        .child(
+                            button("auth-cancel", "Cancel", false, theme)
+                                .on_click(move |_, window, cx| on_cancel(window, cx)),
+                        )
+                        .child(
+                            button("auth-submit", "Sign In", true, theme)
+                                .on_click(move |_, window, cx| on_submit(window, cx)),
+                        ),
+                ),
+        )
+}
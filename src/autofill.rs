@@ -0,0 +1,443 @@
+use gpui::{div, prelude::*, px, App, Context, Global, IntoElement, MouseButton};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::bridge::TabId;
+use crate::preferences;
+use crate::storage;
+use crate::theme::Theme;
+use crate::Main;
+
+const AUTOFILL_FILE: &str = "autofill.json";
+
+// Kinds of form field the profile fills, named like the HTML `autocomplete` tokens they're
+// detected by, see `scripts/autofill.js`. Card numbers, passwords and other sensitive fields
+// are never detected, so never stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FieldKind {
+    Name,
+    // Filled from the parts of `Name`, not stored separately
+    GivenName,
+    FamilyName,
+    Email,
+    Tel,
+    Organization,
+    StreetAddress,
+    // Called address-level2 and address-level1 in `autocomplete`
+    #[serde(rename = "address-level2")]
+    City,
+    #[serde(rename = "address-level1")]
+    Region,
+    PostalCode,
+    Country,
+}
+
+impl FieldKind {
+    // The fields kept in the profile, in the order the preferences show them
+    pub const STORED: &[FieldKind] = &[
+        FieldKind::Name,
+        FieldKind::Email,
+        FieldKind::Tel,
+        FieldKind::Organization,
+        FieldKind::StreetAddress,
+        FieldKind::City,
+        FieldKind::Region,
+        FieldKind::PostalCode,
+        FieldKind::Country,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FieldKind::Name => "Name",
+            FieldKind::GivenName => "First name",
+            FieldKind::FamilyName => "Last name",
+            FieldKind::Email => "Email",
+            FieldKind::Tel => "Phone",
+            FieldKind::Organization => "Organization",
+            FieldKind::StreetAddress => "Street address",
+            FieldKind::City => "City",
+            FieldKind::Region => "State or region",
+            FieldKind::PostalCode => "Postal code",
+            FieldKind::Country => "Country",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+struct AutofillData {
+    profile: BTreeMap<FieldKind, String>,
+    // Whether submitted forms offer to save what was typed into them
+    learn: bool,
+}
+
+impl Default for AutofillData {
+    fn default() -> Self {
+        Self {
+            profile: BTreeMap::new(),
+            learn: true,
+        }
+    }
+}
+
+// The user's name, contact details and address for filling in forms, kept apart from
+// passwords and persisted on every change
+pub struct Autofill {
+    data: AutofillData,
+}
+
+impl Global for Autofill {}
+
+impl Autofill {
+    pub fn load() -> Self {
+        let data = storage::load_json::<AutofillData>(AUTOFILL_FILE)
+            .unwrap_or_else(|err| {
+                log::error!("Failed to load the autofill profile: {}", err);
+                None
+            })
+            .unwrap_or_default();
+        Self { data }
+    }
+
+    pub fn get(&self, kind: FieldKind) -> &str {
+        self.data.profile.get(&kind).map_or("", String::as_str)
+    }
+
+    pub fn learn(&self) -> bool {
+        self.data.learn
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.profile.is_empty()
+    }
+
+    // Values for every kind the profile can fill, splitting the name for forms that ask for
+    // its parts
+    fn values(&self) -> BTreeMap<FieldKind, String> {
+        let mut values = self.data.profile.clone();
+        if let Some((given, family)) = self.get(FieldKind::Name).trim().rsplit_once(' ') {
+            values.insert(FieldKind::GivenName, given.trim().to_string());
+            values.insert(FieldKind::FamilyName, family.to_string());
+        }
+        values
+    }
+
+    // What submitted form values would add to or change in the profile, with the name put
+    // back together from its parts
+    fn changes(&self, submitted: &BTreeMap<FieldKind, String>) -> BTreeMap<FieldKind, String> {
+        let mut submitted = submitted.clone();
+        let given = submitted.remove(&FieldKind::GivenName);
+        let family = submitted.remove(&FieldKind::FamilyName);
+        if let (false, Some(given), Some(family)) =
+            (submitted.contains_key(&FieldKind::Name), given, family)
+        {
+            submitted.insert(FieldKind::Name, format!("{} {}", given, family));
+        }
+        submitted
+            .into_iter()
+            .map(|(kind, value)| (kind, value.trim().to_string()))
+            .filter(|(kind, value)| {
+                FieldKind::STORED.contains(kind) && !value.is_empty() && self.get(*kind) != value
+            })
+            .collect()
+    }
+
+    pub fn set(cx: &mut App, kind: FieldKind, value: &str) {
+        cx.update_global::<Self, _>(|autofill, _| {
+            let value = value.trim();
+            if value.is_empty() {
+                autofill.data.profile.remove(&kind);
+            } else {
+                autofill.data.profile.insert(kind, value.to_string());
+            }
+            autofill.save();
+        });
+    }
+
+    pub fn set_learn(cx: &mut App, learn: bool) {
+        cx.update_global::<Self, _>(|autofill, _| {
+            autofill.data.learn = learn;
+            autofill.save();
+        });
+    }
+
+    pub fn clear(cx: &mut App) {
+        cx.update_global::<Self, _>(|autofill, _| {
+            autofill.data.profile.clear();
+            autofill.save();
+        });
+    }
+
+    fn save(&self) {
+        if let Err(err) = storage::save_json(AUTOFILL_FILE, &self.data) {
+            log::error!("Failed to save the autofill profile: {}", err);
+        }
+    }
+}
+
+// A focused form field the profile could fill, and where it is on the page in CSS pixels
+pub struct AutofillOffer {
+    pub tab_id: TabId,
+    // Kinds of field in the same form, filled all at once
+    kinds: Vec<FieldKind>,
+    x: f32,
+    bottom: f32,
+    width: f32,
+}
+
+// Values typed into a submitted form, waiting for the user to save them to the profile
+pub struct AutofillSave {
+    pub tab_id: TabId,
+    changes: BTreeMap<FieldKind, String>,
+}
+
+impl Main {
+    // A form field got focus, or lost it when `kinds` is empty
+    pub(crate) fn offer_autofill(
+        &mut self,
+        tab_id: TabId,
+        kinds: Vec<FieldKind>,
+        x: f32,
+        bottom: f32,
+        width: f32,
+        cx: &mut Context<Self>,
+    ) {
+        let autofill = cx.global::<Autofill>();
+        let values = autofill.values();
+        let fillable = kinds.iter().any(|kind| values.contains_key(kind));
+        self.autofill_offer =
+            (fillable && self.tabs[self.active_tab].id == tab_id).then(|| AutofillOffer {
+                tab_id,
+                kinds,
+                x,
+                bottom,
+                width,
+            });
+        cx.notify();
+    }
+
+    fn fill_form(&mut self, cx: &mut Context<Self>) {
+        let Some(offer) = self.autofill_offer.take() else {
+            return;
+        };
+        let values: BTreeMap<_, _> = cx
+            .global::<Autofill>()
+            .values()
+            .into_iter()
+            .filter(|(kind, _)| offer.kinds.contains(kind))
+            .collect();
+        if let Some(frame) = self
+            .tabs
+            .iter()
+            .find(|tab| tab.id == offer.tab_id)
+            .and_then(|tab| tab.main_frame.as_ref())
+        {
+            frame.execute_javascript(&format!(
+                "window.__browserAutofill && window.__browserAutofill.fill({})",
+                serde_json::to_string(&values).unwrap_or_default()
+            ));
+        }
+        cx.notify();
+    }
+
+    // A form was submitted with these values; ask before saving anything new
+    pub(crate) fn autofill_submitted(
+        &mut self,
+        tab_id: TabId,
+        origin: String,
+        values: BTreeMap<FieldKind, String>,
+        cx: &mut Context<Self>,
+    ) {
+        let autofill = cx.global::<Autofill>();
+        if self.private || !autofill.learn() || !self.tab_on_origin(tab_id, &origin) {
+            return;
+        }
+        let changes = autofill.changes(&values);
+        if changes.is_empty() {
+            return;
+        }
+        self.autofill_save = Some(AutofillSave { tab_id, changes });
+        cx.notify();
+    }
+
+    fn answer_autofill_save(&mut self, save: bool, cx: &mut Context<Self>) {
+        let Some(prompt) = self.autofill_save.take() else {
+            return;
+        };
+        if save {
+            for (kind, value) in prompt.changes {
+                Autofill::set(cx, kind, &value);
+            }
+        }
+        cx.notify();
+    }
+
+    // Menu under the focused field. `zoom` converts the page's CSS pixels to window pixels.
+    pub(crate) fn render_autofill_offer(
+        &self,
+        zoom: f32,
+        cx: &mut Context<Self>,
+    ) -> Option<impl IntoElement> {
+        let offer = self
+            .autofill_offer
+            .as_ref()
+            .filter(|offer| offer.tab_id == self.tabs[self.active_tab].id)?;
+        let theme = *cx.global::<Theme>();
+        let autofill = cx.global::<Autofill>();
+        let summary = [FieldKind::Name, FieldKind::Email, FieldKind::StreetAddress]
+            .into_iter()
+            .map(|kind| autofill.get(kind))
+            .filter(|value| !value.is_empty())
+            .collect::<Vec<_>>()
+            .join(" · ");
+        let item = |id: &'static str| {
+            div()
+                .id(id)
+                .px_3()
+                .py_1()
+                .rounded_md()
+                .text_xs()
+                .cursor_pointer()
+                .hover(|this| this.bg(theme.hover))
+        };
+
+        Some(
+            div()
+                .absolute()
+                .left(px(offer.x * zoom))
+                .top(px(offer.bottom * zoom + 2.))
+                .w(px((offer.width * zoom).max(240.)))
+                .occlude()
+                .flex()
+                .flex_col()
+                .p_1()
+                .rounded_md()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.surface)
+                .shadow_lg()
+                // on mouse down, before the field's blur hides the menu
+                .child(
+                    item("autofill-fill")
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(|this, _, _, cx| this.fill_form(cx)),
+                        )
+                        .child(div().text_color(theme.text).child("Fill from your profile"))
+                        .child(div().truncate().text_color(theme.text_muted).child(summary)),
+                )
+                .child(
+                    item("autofill-manage")
+                        .text_color(theme.text_secondary)
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(|this, _, _, cx| {
+                                this.autofill_offer = None;
+                                preferences::open(cx);
+                                cx.notify();
+                            }),
+                        )
+                        .child("Manage autofill..."),
+                ),
+        )
+    }
+
+    // Asks whether to keep what was typed into a submitted form, over the top right of the page
+    pub(crate) fn render_autofill_save(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let prompt = self
+            .autofill_save
+            .as_ref()
+            .filter(|prompt| prompt.tab_id == self.tabs[self.active_tab].id)?;
+        let theme = *cx.global::<Theme>();
+        let button = |id: &'static str, label: &'static str, primary: bool| {
+            div()
+                .id(id)
+                .px_3()
+                .py_1()
+                .rounded_md()
+                .text_xs()
+                .cursor_pointer()
+                .when(primary, |this| {
+                    this.bg(theme.accent).text_color(gpui::white())
+                })
+                .when(!primary, |this| {
+                    this.border_1()
+                        .border_color(theme.border)
+                        .text_color(theme.text)
+                        .hover(|this| this.bg(theme.hover))
+                })
+                .child(label)
+        };
+
+        Some(
+            div()
+                .absolute()
+                .top_2()
+                .right_4()
+                .w(px(320.))
+                .occlude()
+                .flex()
+                .flex_col()
+                .gap_3()
+                .p_4()
+                .rounded_lg()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.surface)
+                .shadow_lg()
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(theme.text)
+                        .child("Save these details for autofill?"),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .gap_1()
+                        .children(prompt.changes.iter().map(|(kind, value)| {
+                            div()
+                                .flex()
+                                .gap_2()
+                                .text_xs()
+                                .child(
+                                    div()
+                                        .w(px(100.))
+                                        .flex_none()
+                                        .text_color(theme.text_muted)
+                                        .child(kind.label()),
+                                )
+                                .child(
+                                    div()
+                                        .flex_1()
+                                        .truncate()
+                                        .text_color(theme.text_secondary)
+                                        .child(value.clone()),
+                                )
+                        })),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .justify_end()
+                        .gap_2()
+                        .child(
+                            button("autofill-never", "Never ask", false).on_click(cx.listener(
+                                |this, _, _, cx| {
+                                    Autofill::set_learn(cx, false);
+                                    this.answer_autofill_save(false, cx)
+                                },
+                            )),
+                        )
+                        .child(button("autofill-not-now", "Not now", false).on_click(
+                            cx.listener(|this, _, _, cx| this.answer_autofill_save(false, cx)),
+                        ))
+                        .child(button("autofill-save", "Save", true).on_click(
+                            cx.listener(|this, _, _, cx| this.answer_autofill_save(true, cx)),
+                        )),
+                ),
+        )
+    }
+}
@@ -0,0 +1,412 @@
+use flume::Sender;
+use gpui::Global;
+use gpui_webview::wef::{ResourceRequest, ResourceType};
+use std::collections::HashMap;
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use url::Url;
+
+use crate::bridge::{BridgeEvent, TabId};
+use crate::storage;
+
+// Rules for well-known ad and tracker domains, always loaded
+const BUILTIN_RULES: &str = include_str!("blocklist.txt");
+// Optional EasyList-style list in the config directory, added to the built-in rules
+const USER_RULES_FILE: &str = "blocklist.txt";
+// Shorter URL patterns match too much of the web to be useful
+const MIN_PATTERN_LEN: usize = 4;
+
+// Domains by label from the top-level domain down, so a lookup costs one step per label of
+// the host no matter how many rules there are
+#[derive(Default)]
+struct DomainTrie {
+    children: HashMap<String, DomainTrie>,
+    terminal: bool,
+}
+
+impl DomainTrie {
+    fn insert(&mut self, domain: &str) {
+        let mut node = self;
+        for label in domain.rsplit('.') {
+            node = node.children.entry(label.to_string()).or_default();
+        }
+        node.terminal = true;
+    }
+
+    // Whether `host` is one of the domains or a subdomain of one
+    fn contains(&self, host: &str) -> bool {
+        let mut node = self;
+        for label in host.rsplit('.') {
+            match node.children.get(label) {
+                Some(child) => node = child,
+                None => return false,
+            }
+            if node.terminal {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+/// Compiled content blocking rules, a subset of the EasyList syntax:
+///
+/// - `||example.com^` blocks a domain and its subdomains, `@@||example.com^` exempts one
+/// - `0.0.0.0 example.com` hosts-file lines block a domain the same way
+/// - other rules are URL patterns where `*` matches anything, `^` a separator character
+///   and a leading or trailing `|` anchors the pattern to the start or end of the URL
+///
+/// Cosmetic rules and rules with `$` options are skipped.
+#[derive(Default)]
+pub struct BlockRules {
+    domains: DomainTrie,
+    exceptions: DomainTrie,
+    // Lowercased, with `*` added at unanchored ends
+    patterns: Vec<Vec<u8>>,
+}
+
+impl BlockRules {
+    pub fn parse(&mut self, list: &str) {
+        for line in list.lines() {
+            self.add_rule(line.trim());
+        }
+    }
+
+    fn add_rule(&mut self, rule: &str) {
+        if rule.is_empty()
+            || rule.starts_with(['!', '#', '['])
+            || rule.contains("##")
+            || rule.contains("#@#")
+            || rule.contains('$')
+        {
+            return;
+        }
+
+        if let Some(host) = rule
+            .strip_prefix("0.0.0.0 ")
+            .or_else(|| rule.strip_prefix("127.0.0.1 "))
+        {
+            if let Some(host) = host
+                .split_whitespace()
+                .next()
+                .filter(|host| is_domain(host))
+            {
+                self.domains.insert(&host.to_ascii_lowercase());
+            }
+            return;
+        }
+
+        let (exception, rule) = match rule.strip_prefix("@@") {
+            Some(rule) => (true, rule),
+            None => (false, rule),
+        };
+        if let Some(rest) = rule.strip_prefix("||") {
+            let domain = rest.strip_suffix('^').unwrap_or(rest);
+            if is_domain(domain) {
+                let domain = domain.to_ascii_lowercase();
+                if exception {
+                    self.exceptions.insert(&domain);
+                } else {
+                    self.domains.insert(&domain);
+                }
+                return;
+            }
+        }
+        // only whole domains can be exempted
+        if exception {
+            return;
+        }
+
+        // `||example.com/ads` is matched anywhere in the URL, close enough without its options
+        let rule = rule.trim_start_matches("||");
+        let (start_anchor, rule) = match rule.strip_prefix('|') {
+            Some(rule) => (true, rule),
+            None => (false, rule),
+        };
+        let (end_anchor, rule) = match rule.strip_suffix('|') {
+            Some(rule) => (true, rule),
+            None => (false, rule),
+        };
+        if rule.trim_matches('*').len() < MIN_PATTERN_LEN {
+            return;
+        }
+
+        let mut pattern = Vec::with_capacity(rule.len() + 2);
+        if !start_anchor {
+            pattern.push(b'*');
+        }
+        pattern.extend(rule.to_ascii_lowercase().bytes());
+        if !end_anchor {
+            pattern.push(b'*');
+        }
+        self.patterns.push(pattern);
+    }
+
+    pub fn blocks(&self, url: &str) -> bool {
+        let Ok(parsed) = Url::parse(url) else {
+            return false;
+        };
+        if !matches!(parsed.scheme(), "http" | "https" | "ws" | "wss") {
+            return false;
+        }
+
+        let host = parsed.host_str().unwrap_or_default();
+        if self.exceptions.contains(host) {
+            return false;
+        }
+        if self.domains.contains(host) {
+            return true;
+        }
+
+        let url = url.to_ascii_lowercase();
+        self.patterns
+            .iter()
+            .any(|pattern| wildcard_match(pattern, url.as_bytes()))
+    }
+}
+
+fn is_domain(domain: &str) -> bool {
+    domain.contains('.')
+        && domain != "localhost"
+        && domain
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+}
+
+// Anything but a letter, digit or one of `_-.%`, as in EasyList's `^`
+fn is_separator(byte: u8) -> bool {
+    !(byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'-' | b'.' | b'%'))
+}
+
+// `*` matches any run of bytes, `^` a separator byte or the end of `text`
+fn wildcard_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // last `*` seen, and where in `text` it started matching
+    let mut star = None;
+    loop {
+        if p < pattern.len() {
+            match pattern[p] {
+                b'*' => {
+                    star = Some((p, t));
+                    p += 1;
+                    continue;
+                }
+                b'^' if t == text.len() => {
+                    p += 1;
+                    continue;
+                }
+                b'^' if is_separator(text[t]) => {
+                    p += 1;
+                    t += 1;
+                    continue;
+                }
+                byte if t < text.len() && byte == text[t] => {
+                    p += 1;
+                    t += 1;
+                    continue;
+                }
+                _ => {}
+            }
+        } else if t == text.len() {
+            return true;
+        }
+
+        // let the last `*` swallow one more byte and retry from there
+        match star {
+            Some((star_p, star_t)) if star_t < text.len() => {
+                star = Some((star_p, star_t + 1));
+                p = star_p + 1;
+                t = star_t + 1;
+            }
+            _ => return false,
+        }
+    }
+}
+
+// Rules shared by every tab, compiled once at startup
+pub struct ContentBlocker {
+    rules: Arc<BlockRules>,
+}
+
+impl Global for ContentBlocker {}
+
+impl ContentBlocker {
+    pub fn load() -> Self {
+        let mut rules = BlockRules::default();
+        rules.parse(BUILTIN_RULES);
+        match storage::config_dir().map(|dir| fs::read_to_string(dir.join(USER_RULES_FILE))) {
+            Ok(Ok(list)) => rules.parse(&list),
+            Ok(Err(err)) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Ok(Err(err)) => log::error!("Failed to load {}: {}", USER_RULES_FILE, err),
+            Err(err) => log::error!("Failed to load {}: {}", USER_RULES_FILE, err),
+        }
+        Self {
+            rules: Arc::new(rules),
+        }
+    }
+
+    pub fn tab_blocker(&self, tab_id: TabId, events: Sender<BridgeEvent>) -> TabBlocker {
+        TabBlocker {
+            rules: self.rules.clone(),
+            enabled: Arc::new(AtomicBool::new(true)),
+            tab_id,
+            events,
+        }
+    }
+}
+
+// Decides on the requests of one tab. Called by CEF on its IO thread, so it only reads the
+// rules and a flag, and reports blocked requests to the window through the bridge channel.
+#[derive(Clone)]
+pub struct TabBlocker {
+    rules: Arc<BlockRules>,
+    // Off while the tab shows an allowlisted site
+    enabled: Arc<AtomicBool>,
+    tab_id: TabId,
+    events: Sender<BridgeEvent>,
+}
+
+impl TabBlocker {
+    // Whether `request` may load; navigations themselves are never blocked
+    pub fn should_load(&self, request: &ResourceRequest) -> bool {
+        if request.resource_type() == ResourceType::MainFrame
+            || !self.is_enabled()
+            || !self.rules.blocks(request.url())
+        {
+            return true;
+        }
+
+        _ = self.events.send(BridgeEvent::ResourceBlocked {
+            tab_id: self.tab_id,
+        });
+        false
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(list: &str) -> BlockRules {
+        let mut rules = BlockRules::default();
+        rules.parse(list);
+        rules
+    }
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        wildcard_match(pattern.as_bytes(), text.as_bytes())
+    }
+
+    #[test]
+    fn wildcards_at_either_end_and_in_the_middle() {
+        assert!(matches("*.gif", "banner.gif"));
+        assert!(!matches("*.gif", "banner.gif.html"));
+        assert!(matches("/ads/*", "/ads/"));
+        assert!(matches("/ads/*", "/ads/banner.js"));
+        assert!(!matches("/ads/*", "/img/ads/"));
+        assert!(matches("/banner/*/ad.js", "/banner/123/ad.js"));
+        assert!(matches("/banner/*/ad.js", "/banner/a/b/ad.js"));
+        assert!(!matches("/banner/*/ad.js", "/banner/ad.js"));
+        assert!(matches("*ab*cd*", "xabyabcdz"));
+        assert!(!matches("*ab*cd*", "xcdab"));
+        assert!(matches("*", ""));
+        assert!(!matches("", "a"));
+    }
+
+    #[test]
+    fn caret_matches_a_separator_or_the_end() {
+        assert!(matches("*/ads^*", "https://a.com/ads/1"));
+        assert!(matches("*/ads^*", "https://a.com/ads?x=1"));
+        assert!(matches("*/ads^", "https://a.com/ads"));
+        assert!(!matches("*/ads^*", "https://a.com/adsx"));
+        assert!(!matches("*/ads^*", "https://a.com/ads.js"));
+        assert!(!matches("*/ads^*", "https://a.com/ads-banner"));
+    }
+
+    #[test]
+    fn domain_rules_cover_subdomains_only() {
+        let rules = rules("||example.com^");
+        assert!(rules.blocks("https://example.com/"));
+        assert!(rules.blocks("https://ads.example.com/banner.js"));
+        assert!(rules.blocks("http://a.b.example.com:8080/"));
+        assert!(rules.blocks("wss://example.com/socket"));
+        assert!(!rules.blocks("https://badexample.com/"));
+        assert!(!rules.blocks("https://example.com.evil.net/"));
+        assert!(!rules.blocks("https://example.org/?ref=example.com"));
+        assert!(!rules.blocks("data:text/html,example.com"));
+    }
+
+    #[test]
+    fn domain_rules_are_case_insensitive() {
+        let rules = rules("||Ads.Example.COM^\n0.0.0.0 Tracker.Example.NET");
+        assert!(rules.blocks("https://ADS.example.com/"));
+        assert!(rules.blocks("https://tracker.example.net/pixel"));
+        assert!(!rules.blocks("https://example.com/"));
+    }
+
+    #[test]
+    fn hosts_file_lines() {
+        let rules =
+            rules("0.0.0.0 tracker.test\n127.0.0.1 metrics.test # comment\n127.0.0.1 localhost");
+        assert!(rules.blocks("https://tracker.test/"));
+        assert!(rules.blocks("https://eu.metrics.test/"));
+        assert!(!rules.blocks("http://localhost/"));
+    }
+
+    #[test]
+    fn exceptions_exempt_a_domain_and_its_subdomains() {
+        let rules = rules("||example.com^\n@@||safe.example.com^");
+        assert!(rules.blocks("https://ads.example.com/"));
+        assert!(!rules.blocks("https://safe.example.com/"));
+        assert!(!rules.blocks("https://cdn.safe.example.com/"));
+        assert!(rules.blocks("https://unsafe.example.com/"));
+    }
+
+    #[test]
+    fn exceptions_win_over_url_patterns() {
+        let rules = rules("/banner/*\n@@||example.com^");
+        assert!(rules.blocks("https://other.test/banner/1.png"));
+        assert!(!rules.blocks("https://example.com/banner/1.png"));
+    }
+
+    #[test]
+    fn url_patterns_and_anchors() {
+        let rules = rules("/banner/*/ad.js\n|https://track.\n.swf|");
+        assert!(rules.blocks("https://a.test/banner/300x250/ad.js"));
+        assert!(!rules.blocks("https://a.test/banner/ad.js"));
+        assert!(rules.blocks("https://track.example.com/"));
+        assert!(!rules.blocks("https://a.test/?next=https://track.example.com/"));
+        assert!(rules.blocks("https://a.test/intro.swf"));
+        assert!(!rules.blocks("https://a.test/intro.swf?autoplay=1"));
+    }
+
+    #[test]
+    fn skips_comments_and_unsupported_rules() {
+        let rules = rules(
+            "! comment\n# comment\n[Adblock Plus 2.0]\nexample.com##.ad\nexample.com#@#.ad\n\
+             ||ads.test^$third-party\n@@/banner/\n   \n",
+        );
+        assert!(rules.patterns.is_empty());
+        assert!(rules.domains.children.is_empty());
+        assert!(rules.exceptions.children.is_empty());
+        assert!(!rules.blocks("https://ads.test/"));
+        assert!(!rules.blocks("https://example.com/banner/"));
+    }
+
+    #[test]
+    fn skips_malformed_and_too_short_rules() {
+        let rules = rules("||\n|\n||^\n***\nad\n|*ad*|\n@@\n0.0.0.0 not_a_domain");
+        assert!(rules.patterns.is_empty());
+        assert!(rules.domains.children.is_empty());
+        assert!(!rules.blocks("https://ad.test/ad"));
+    }
+}
@@ -0,0 +1,85 @@
+use gpui::Global;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::storage;
+
+const BOOKMARKS_FILE: &str = "bookmarks.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub url: String,
+    pub title: String,
+    // Seconds since the unix epoch
+    pub added_at: u64,
+    // Optional folder or tag for organizing bookmarks
+    #[serde(default)]
+    pub folder: Option<String>,
+}
+
+// Saved pages, persisted to disk on every change
+pub struct Bookmarks {
+    bookmarks: Vec<Bookmark>,
+}
+
+impl Global for Bookmarks {}
+
+impl Bookmarks {
+    pub fn load() -> Self {
+        let bookmarks = storage::load_json::<Vec<Bookmark>>(BOOKMARKS_FILE)
+            .unwrap_or_else(|err| {
+                log::error!("Failed to load bookmarks: {}", err);
+                None
+            })
+            .unwrap_or_default();
+        Self { bookmarks }
+    }
+
+    pub fn all(&self) -> &[Bookmark] {
+        &self.bookmarks
+    }
+
+    pub fn is_bookmarked(&self, url: &str) -> bool {
+        self.bookmarks.iter().any(|bookmark| bookmark.url == url)
+    }
+
+    pub fn add_bookmark(&mut self, url: &str, title: &str) {
+        if url.is_empty() || self.is_bookmarked(url) {
+            return;
+        }
+
+        self.bookmarks.push(Bookmark {
+            url: url.to_string(),
+            title: title.to_string(),
+            added_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default(),
+            folder: None,
+        });
+        self.save();
+    }
+
+    pub fn remove_bookmark(&mut self, url: &str) {
+        let len = self.bookmarks.len();
+        self.bookmarks.retain(|bookmark| bookmark.url != url);
+        if self.bookmarks.len() != len {
+            self.save();
+        }
+    }
+
+    // Add the page if it isn't bookmarked yet, otherwise remove it
+    pub fn toggle(&mut self, url: &str, title: &str) {
+        if self.is_bookmarked(url) {
+            self.remove_bookmark(url);
+        } else {
+            self.add_bookmark(url, title);
+        }
+    }
+
+    fn save(&self) {
+        if let Err(err) = storage::save_json(BOOKMARKS_FILE, &self.bookmarks) {
+            log::error!("Failed to save bookmarks: {}", err);
+        }
+    }
+}
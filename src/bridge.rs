@@ -0,0 +1,852 @@
+use flume::Sender;
+use futures_util::stream::{self, Stream, StreamExt};
+use gpui::{AssetSource, BackgroundExecutor, Timer};
+use gpui_webview::wef::{Frame, FuncRegistry};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::num::ParseIntError;
+use std::path::{Component, Path};
+use std::time::Duration;
+
+use crate::assets;
+use crate::autofill::FieldKind;
+use crate::console::{ConsoleLevel, ConsoleMessage};
+use crate::context_menu::EditableField;
+use crate::hints::Hint;
+use crate::permissions;
+use crate::plugins::PluginRequest;
+use crate::reader::Article;
+use crate::screenshot::PageMetrics;
+use crate::streams::StreamRegistry;
+use crate::task_manager::TabUsage;
+
+// Scripts injected into every frame as its document starts loading
+const DOCUMENT_START_SCRIPTS: &[&str] = &[include_str!("scripts/links.js")];
+
+// Scripts injected into every frame once it has loaded
+const PAGE_SCRIPTS: &[&str] = &[
+    include_str!("scripts/bridge.js"),
+    include_str!("scripts/downloads.js"),
+    include_str!("scripts/scroll.js"),
+    include_str!("scripts/context_menu.js"),
+    include_str!("scripts/audio.js"),
+    include_str!("scripts/reader.js"),
+    include_str!("scripts/tts.js"),
+    include_str!("scripts/fullscreen.js"),
+    include_str!("scripts/hover_link.js"),
+    include_str!("scripts/hints.js"),
+    include_str!("scripts/user_agent.js"),
+    include_str!("scripts/console.js"),
+    include_str!("scripts/forms.js"),
+    include_str!("scripts/autofill.js"),
+    include_str!("scripts/passwords.js"),
+    include_str!("scripts/picture_in_picture.js"),
+    include_str!("scripts/find.js"),
+    include_str!("scripts/json.js"),
+    include_str!("scripts/timing.js"),
+    include_str!("scripts/favicon.js"),
+];
+
+// Longest wait accepted by the `delay` host function
+const MAX_DELAY_MILLIS: u64 = 10_000;
+
+// Identifies a tab for the lifetime of the window, unlike its index in the tab strip
+pub type TabId = usize;
+
+// Requests from page scripts, delivered to the window owning the tab
+pub enum BridgeEvent {
+    OpenTab {
+        opener: TabId,
+        url: String,
+        foreground: bool,
+    },
+    Download {
+        url: String,
+        filename: Option<String>,
+    },
+    TabUrl {
+        tab_id: TabId,
+        reply: Sender<String>,
+    },
+    // Scrolled while split view links the page's scrolling to the other pane's
+    LinkedScroll {
+        tab_id: TabId,
+        x: f32,
+        y: f32,
+    },
+    ScrollChanged {
+        tab_id: TabId,
+        x: f32,
+        y: f32,
+    },
+    PageMetrics {
+        tab_id: TabId,
+        metrics: PageMetrics,
+    },
+    AudioChanged {
+        tab_id: TabId,
+        playing: bool,
+    },
+    VideoChanged {
+        tab_id: TabId,
+        // Whether a video could float in Picture-in-Picture, and whether one does
+        has_video: bool,
+        picture_in_picture: bool,
+    },
+    Hints {
+        tab_id: TabId,
+        hints: Vec<Hint>,
+    },
+    JsonDocument {
+        tab_id: TabId,
+        text: String,
+    },
+    FindResult {
+        tab_id: TabId,
+        // 1-based index of the current match, 0 when nothing matched
+        current: u32,
+        count: u32,
+    },
+    UserAgent {
+        tab_id: TabId,
+        user_agent: String,
+    },
+    // A form field the profile can fill got focus on a page of `origin`, where it is in CSS
+    // pixels; sent with no kinds once focus moved away
+    AutofillField {
+        tab_id: TabId,
+        origin: String,
+        kinds: Vec<FieldKind>,
+        x: f32,
+        bottom: f32,
+        width: f32,
+    },
+    AutofillSubmitted {
+        tab_id: TabId,
+        origin: String,
+        values: BTreeMap<FieldKind, String>,
+    },
+    // A sign-in field got focus on a page of `origin`, where it is in CSS pixels; sent with an
+    // empty origin once focus moved away
+    PasswordField {
+        tab_id: TabId,
+        origin: String,
+        x: f32,
+        bottom: f32,
+        width: f32,
+    },
+    PasswordSubmitted {
+        tab_id: TabId,
+        origin: String,
+        username: String,
+        password: String,
+    },
+    // The sentences read aloud would go through, in order
+    ReadAloudSentences {
+        tab_id: TabId,
+        sentences: Vec<String>,
+    },
+    // The page engine finished a sentence, `error` is empty unless it failed
+    ReadAloudSpoken {
+        tab_id: TabId,
+        index: usize,
+        error: String,
+    },
+    LoadTiming {
+        tab_id: TabId,
+        // Milliseconds since the navigation started, negative when the page can't tell
+        dom_content_loaded: f64,
+        load: f64,
+    },
+    ConsoleMessage {
+        tab_id: TabId,
+        message: ConsoleMessage,
+    },
+    HoverLink {
+        tab_id: TabId,
+        url: Option<String>,
+    },
+    // The icon `page_url` declares, `None` when it declares none
+    Favicon {
+        tab_id: TabId,
+        page_url: String,
+        icon_url: Option<String>,
+    },
+    // Answered once the user allowed the tab's site to replace the clipboard with `text`
+    ClipboardWrite {
+        tab_id: TabId,
+        text: String,
+        reply: Sender<Result<(), BridgeError>>,
+    },
+    ResourceBlocked {
+        tab_id: TabId,
+    },
+    MemoryUsage {
+        tab_id: TabId,
+        usage: TabUsage,
+    },
+    // Whether the user typed into the page since it was last submitted
+    FormInput {
+        tab_id: TabId,
+        unsaved: bool,
+    },
+    // A main-frame request is about to be sent, held until `applied` answers
+    Navigating {
+        tab_id: TabId,
+        url: String,
+        applied: Sender<()>,
+    },
+    // Answered with the clipboard text once the user allowed the tab's site to read it
+    ClipboardRead {
+        tab_id: TabId,
+        reply: Sender<Result<String, BridgeError>>,
+    },
+    FullscreenChanged {
+        tab_id: TabId,
+        fullscreen: bool,
+    },
+    Readable {
+        tab_id: TabId,
+        readable: bool,
+    },
+    ReaderContent {
+        tab_id: TabId,
+        article: Article,
+    },
+    ContextMenu {
+        tab_id: TabId,
+        frame: Frame,
+        link_url: Option<String>,
+        image_url: Option<String>,
+        selection_text: Option<String>,
+        field: Option<EditableField>,
+    },
+    // A plugin's script asked for something, answered once the window checked its permissions
+    Plugin {
+        tab_id: TabId,
+        token: String,
+        request: PluginRequest,
+        reply: Sender<Result<(), BridgeError>>,
+    },
+}
+
+/// Context of a host function call: the frame that called it and the tab it belongs to.
+///
+/// Handlers opt in by being registered through [`ContextFactory::handler`], which builds it
+/// from the calling frame and passes it in. Plain typed handlers are unaffected.
+#[derive(Clone)]
+pub struct CallContext {
+    pub frame: Frame,
+    pub tab_id: TabId,
+    events: Sender<BridgeEvent>,
+}
+
+impl CallContext {
+    // Emit an event back to the calling frame only
+    pub fn emit<T: Serialize>(&self, message: T) {
+        self.frame.emit(message);
+    }
+
+    // Current URL of the calling tab, as shown in the address bar
+    pub async fn tab_url(&self) -> Option<String> {
+        let (reply, response) = flume::bounded(1);
+        self.events
+            .send(BridgeEvent::TabUrl {
+                tab_id: self.tab_id,
+                reply,
+            })
+            .ok()?;
+        response.recv_async().await.ok()
+    }
+}
+
+// Creates call contexts for the tab a registry was built for
+#[derive(Clone)]
+pub struct ContextFactory {
+    tab_id: TabId,
+    events: Sender<BridgeEvent>,
+}
+
+impl ContextFactory {
+    fn context(&self, frame: Frame) -> CallContext {
+        CallContext {
+            frame,
+            tab_id: self.tab_id,
+            events: self.events.clone(),
+        }
+    }
+
+    // Turn a handler taking a `CallContext` into one the registry calls with the frame,
+    // sync or async alike: `.register("emit", contexts.handler(|context| ...))`
+    pub fn handler<R>(
+        &self,
+        f: impl Fn(CallContext) -> R + Send + Sync + 'static,
+    ) -> impl Fn(Frame) -> R + Send + Sync + 'static {
+        let contexts = self.clone();
+        move |frame: Frame| f(contexts.context(frame))
+    }
+}
+
+/// Event pushed by the host to pages, independent of any call they made.
+///
+/// Pages subscribe with `hostEvents.on(event, (data) => ...)`, which works in iframes too.
+/// Top frames also see it as `{ event, data }` through `jsBridge.addEventListener`.
+#[derive(Debug, Clone, Serialize)]
+pub struct HostEvent {
+    pub event: String,
+    pub data: Value,
+}
+
+impl HostEvent {
+    /// Fails when `payload` can't be represented as JSON, e.g. a map with non-string keys.
+    pub fn new<T: Serialize>(event: &str, payload: &T) -> serde_json::Result<Self> {
+        Ok(Self {
+            event: event.to_string(),
+            data: serde_json::to_value(payload)?,
+        })
+    }
+}
+
+/// Error returned to page scripts by fallible host functions.
+#[derive(Debug, Clone, Serialize)]
+pub struct BridgeError {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl BridgeError {
+    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// Envelope for the result of a fallible host function, sync or async.
+///
+/// Pages receive `{ status: "ok", value }` or `{ status: "err", error: { code, message } }`.
+/// Calling through `hostCall(name, ...args)` instead of `jsBridge[name]` unwraps it, so the
+/// promise resolves with `value` or rejects with an `Error` whose `message` and `code` are
+/// those of the [`BridgeError`].
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum Reply<T> {
+    Ok { value: T },
+    Err { error: BridgeError },
+}
+
+impl<T, E: Into<BridgeError>> From<Result<T, E>> for Reply<T> {
+    fn from(result: Result<T, E>) -> Self {
+        match result {
+            Ok(value) => Reply::Ok { value },
+            Err(error) => Reply::Err {
+                error: error.into(),
+            },
+        }
+    }
+}
+
+impl From<ParseIntError> for BridgeError {
+    fn from(err: ParseIntError) -> Self {
+        BridgeError::new("invalid_number", err.to_string())
+    }
+}
+
+// Streams pages in a tab can start, kept by the tab so they're cancelled when it navigates
+pub fn stream_registry(background_executor: BackgroundExecutor) -> StreamRegistry {
+    StreamRegistry::builder(background_executor)
+        .register_stream("countdown", countdown)
+        .register_bytes("loadImageBytes", load_image_bytes)
+        .build()
+}
+
+// Build the functions exposed to pages in the tab `tab_id` on `window.jsBridge`
+pub fn func_registry(
+    tab_id: TabId,
+    events: Sender<BridgeEvent>,
+    streams: StreamRegistry,
+    background_executor: BackgroundExecutor,
+) -> FuncRegistry {
+    let contexts = ContextFactory {
+        tab_id,
+        events: events.clone(),
+    };
+
+    FuncRegistry::builder()
+        .with_spawner(move |fut| {
+            background_executor.spawn(fut).detach();
+        })
+        .register("toUppercase", |value: String| value.to_uppercase())
+        .register("addInt", |a: i32, b: i32| a + b)
+        // `await hostCall("clipboardWrite", "hello")`, `const text = await hostCall("clipboardRead")`.
+        // Both ask as the tab's site, so iframes are rejected rather than borrowing its grant.
+        .register_async("clipboardWrite", {
+            let events = events.clone();
+            move |frame: Frame, text: String| {
+                let events = events.clone();
+                async move {
+                    if !frame.is_main() {
+                        return Reply::from(Err(clipboard_frame_error()));
+                    }
+                    let (reply, response) = flume::bounded(1);
+                    _ = events.send(BridgeEvent::ClipboardWrite {
+                        tab_id,
+                        text,
+                        reply,
+                    });
+                    Reply::from(response.recv_async().await.unwrap_or_else(|_| {
+                        Err(BridgeError::new("unavailable", "The tab was closed"))
+                    }))
+                }
+            }
+        })
+        .register_async("clipboardRead", {
+            let events = events.clone();
+            move |frame: Frame| {
+                let events = events.clone();
+                async move {
+                    if !frame.is_main() {
+                        return Reply::from(Err(clipboard_frame_error()));
+                    }
+                    let (reply, response) = flume::bounded(1);
+                    _ = events.send(BridgeEvent::ClipboardRead { tab_id, reply });
+                    Reply::from(response.recv_async().await.unwrap_or_else(|_| {
+                        Err(BridgeError::new("unavailable", "The tab was closed"))
+                    }))
+                }
+            }
+        })
+        // rejects with the parse error's message, as it always has
+        .register("parseInt", |value: String| value.parse::<i32>())
+        // `await hostCall("tryParseInt", " 42 ")`, rejecting with `code: "invalid_number"`
+        .register("tryParseInt", |value: String| {
+            Reply::from(value.trim().parse::<i32>())
+        })
+        .register_async("sleep", |millis: u64| async move {
+            Timer::after(Duration::from_millis(millis)).await;
+            "ok"
+        })
+        .register_async("delay", |millis: u64| async move {
+            if millis > MAX_DELAY_MILLIS {
+                return Reply::from(Err(BridgeError::new(
+                    "invalid_argument",
+                    format!("delay must be at most {}ms", MAX_DELAY_MILLIS),
+                )));
+            }
+
+            Timer::after(Duration::from_millis(millis)).await;
+            Reply::from(Ok::<_, BridgeError>(millis))
+        })
+        .register(
+            "emit",
+            contexts.handler(|context: CallContext| {
+                #[derive(Debug, Serialize)]
+                struct Message {
+                    event: String,
+                    data: String,
+                }
+
+                context.emit(Message {
+                    event: "custom".to_string(),
+                    data: "ok".to_string(),
+                });
+            }),
+        )
+        .register_async(
+            "whoAmI",
+            contexts.handler(|context: CallContext| async move {
+                context.tab_url().await.unwrap_or_default()
+            }),
+        )
+        .register("startStream", {
+            let streams = streams.clone();
+            move |frame: Frame, id: String, name: String, arg: Value| {
+                Reply::from(
+                    streams
+                        .start(frame, id, &name, arg)
+                        .map_err(|err| BridgeError::new("stream_failed", err)),
+                )
+            }
+        })
+        .register("cancelStream", move |id: String| streams.cancel(&id))
+        .register("openTab", {
+            let events = events.clone();
+            move |url: String, foreground: bool| {
+                _ = events.send(BridgeEvent::OpenTab {
+                    opener: tab_id,
+                    url,
+                    foreground,
+                });
+            }
+        })
+        .register("reportScroll", {
+            let events = events.clone();
+            move |x: f32, y: f32| {
+                _ = events.send(BridgeEvent::ScrollChanged { tab_id, x, y });
+            }
+        })
+        .register("linkedScroll", {
+            let events = events.clone();
+            move |x: f32, y: f32| {
+                _ = events.send(BridgeEvent::LinkedScroll { tab_id, x, y });
+            }
+        })
+        .register("reportPageMetrics", {
+            let events = events.clone();
+            move |scroll_height: f32, viewport_height: f32, scroll_y: f32| {
+                _ = events.send(BridgeEvent::PageMetrics {
+                    tab_id,
+                    metrics: PageMetrics {
+                        scroll_height,
+                        viewport_height,
+                        scroll_y,
+                    },
+                });
+            }
+        })
+        .register("reportAudio", {
+            let events = events.clone();
+            move |playing: bool| {
+                _ = events.send(BridgeEvent::AudioChanged { tab_id, playing });
+            }
+        })
+        .register("reportVideo", {
+            let events = events.clone();
+            move |has_video: bool, picture_in_picture: bool| {
+                _ = events.send(BridgeEvent::VideoChanged {
+                    tab_id,
+                    has_video,
+                    picture_in_picture,
+                });
+            }
+        })
+        .register("reportJson", {
+            let events = events.clone();
+            move |text: String| {
+                _ = events.send(BridgeEvent::JsonDocument { tab_id, text });
+            }
+        })
+        .register("findResult", {
+            let events = events.clone();
+            move |current: u32, count: u32| {
+                _ = events.send(BridgeEvent::FindResult {
+                    tab_id,
+                    current,
+                    count,
+                });
+            }
+        })
+        .register("reportLoadTiming", {
+            let events = events.clone();
+            move |dom_content_loaded: f64, load: f64| {
+                _ = events.send(BridgeEvent::LoadTiming {
+                    tab_id,
+                    dom_content_loaded,
+                    load,
+                });
+            }
+        })
+        // like logins, profile data is only for the top frame, under the origin it is on
+        .register("autofillField", {
+            let events = events.clone();
+            move |frame: Frame, kinds: Vec<FieldKind>, x: f32, bottom: f32, width: f32| {
+                if !frame.is_main() {
+                    return;
+                }
+                _ = events.send(BridgeEvent::AutofillField {
+                    tab_id,
+                    origin: permissions::origin_of(&frame.url()),
+                    kinds,
+                    x,
+                    bottom,
+                    width,
+                });
+            }
+        })
+        .register("autofillSubmitted", {
+            let events = events.clone();
+            move |frame: Frame, values: BTreeMap<FieldKind, String>| {
+                if !frame.is_main() {
+                    return;
+                }
+                _ = events.send(BridgeEvent::AutofillSubmitted {
+                    tab_id,
+                    origin: permissions::origin_of(&frame.url()),
+                    values,
+                });
+            }
+        })
+        // logins are only for the top frame, under the origin it is really on rather than the
+        // one a script says
+        .register("passwordField", {
+            let events = events.clone();
+            move |frame: Frame, focused: bool, x: f32, bottom: f32, width: f32| {
+                if !frame.is_main() {
+                    return;
+                }
+                let origin = if focused {
+                    permissions::origin_of(&frame.url())
+                } else {
+                    String::new()
+                };
+                _ = events.send(BridgeEvent::PasswordField {
+                    tab_id,
+                    origin,
+                    x,
+                    bottom,
+                    width,
+                });
+            }
+        })
+        .register("passwordSubmitted", {
+            let events = events.clone();
+            move |frame: Frame, username: String, password: String| {
+                if !frame.is_main() {
+                    return;
+                }
+                _ = events.send(BridgeEvent::PasswordSubmitted {
+                    tab_id,
+                    origin: permissions::origin_of(&frame.url()),
+                    username,
+                    password,
+                });
+            }
+        })
+        .register("ttsSentences", {
+            let events = events.clone();
+            move |sentences: Vec<String>| {
+                _ = events.send(BridgeEvent::ReadAloudSentences { tab_id, sentences });
+            }
+        })
+        .register("ttsSpoken", {
+            let events = events.clone();
+            move |index: usize, error: String| {
+                _ = events.send(BridgeEvent::ReadAloudSpoken {
+                    tab_id,
+                    index,
+                    error,
+                });
+            }
+        })
+        .register("reportHints", {
+            let events = events.clone();
+            move |hints: Vec<Hint>| {
+                _ = events.send(BridgeEvent::Hints { tab_id, hints });
+            }
+        })
+        .register("reportUserAgent", {
+            let events = events.clone();
+            move |user_agent: String| {
+                _ = events.send(BridgeEvent::UserAgent { tab_id, user_agent });
+            }
+        })
+        .register("consoleMessage", {
+            let events = events.clone();
+            move |level: ConsoleLevel, message: String, source: String, line: u32| {
+                _ = events.send(BridgeEvent::ConsoleMessage {
+                    tab_id,
+                    message: ConsoleMessage {
+                        level,
+                        message,
+                        source,
+                        line,
+                    },
+                });
+            }
+        })
+        .register("hoverLink", {
+            let events = events.clone();
+            move |url: String| {
+                _ = events.send(BridgeEvent::HoverLink {
+                    tab_id,
+                    url: Some(url).filter(|url| !url.is_empty()),
+                });
+            }
+        })
+        .register("reportFavicon", {
+            let events = events.clone();
+            move |page_url: String, icon_url: String| {
+                _ = events.send(BridgeEvent::Favicon {
+                    tab_id,
+                    page_url,
+                    icon_url: Some(icon_url).filter(|url| !url.is_empty()),
+                });
+            }
+        })
+        .register("reportMemory", {
+            let events = events.clone();
+            move |js_heap_used: f64, dom_nodes: u32| {
+                _ = events.send(BridgeEvent::MemoryUsage {
+                    tab_id,
+                    usage: TabUsage::new(js_heap_used, dom_nodes),
+                });
+            }
+        })
+        .register("reportFormInput", {
+            let events = events.clone();
+            move |unsaved: bool| {
+                _ = events.send(BridgeEvent::FormInput { tab_id, unsaved });
+            }
+        })
+        .register("reportFullscreen", {
+            let events = events.clone();
+            move |fullscreen: bool| {
+                _ = events.send(BridgeEvent::FullscreenChanged { tab_id, fullscreen });
+            }
+        })
+        .register("reportReadable", {
+            let events = events.clone();
+            move |readable: bool| {
+                _ = events.send(BridgeEvent::Readable { tab_id, readable });
+            }
+        })
+        .register("readerContent", {
+            let events = events.clone();
+            move |article: Article| {
+                _ = events.send(BridgeEvent::ReaderContent { tab_id, article });
+            }
+        })
+        .register("contextMenu", {
+            let events = events.clone();
+            move |frame: Frame,
+                  link: String,
+                  image: String,
+                  selection: String,
+                  field: Option<EditableField>| {
+                _ = events.send(BridgeEvent::ContextMenu {
+                    tab_id,
+                    frame,
+                    link_url: Some(link).filter(|link| !link.is_empty()),
+                    image_url: Some(image).filter(|image| !image.is_empty()),
+                    selection_text: Some(selection).filter(|text| !text.trim().is_empty()),
+                    field,
+                });
+            }
+        })
+        // host API of plugins, see `scripts/plugins.js`
+        .register_async("pluginNavigate", {
+            let events = events.clone();
+            move |token: String, url: String| {
+                plugin_request(&events, tab_id, token, PluginRequest::Navigate(url))
+            }
+        })
+        .register_async("pluginInject", {
+            let events = events.clone();
+            move |token: String, code: String| {
+                plugin_request(&events, tab_id, token, PluginRequest::InjectScript(code))
+            }
+        })
+        .register_async("pluginEmit", {
+            let events = events.clone();
+            move |token: String, event: String, detail: Value| {
+                plugin_request(
+                    &events,
+                    tab_id,
+                    token,
+                    PluginRequest::Emit { event, detail },
+                )
+            }
+        })
+        .register_async("pluginNotify", {
+            let events = events.clone();
+            move |token: String, message: String| {
+                plugin_request(&events, tab_id, token, PluginRequest::Notify(message))
+            }
+        })
+        .register_async(
+            "pluginCurrentUrl",
+            contexts.handler(|context: CallContext| async move {
+                context.tab_url().await.unwrap_or_default()
+            }),
+        )
+        .register("download", move |url: String, filename: String| {
+            _ = events.send(BridgeEvent::Download {
+                url,
+                filename: Some(filename).filter(|filename| !filename.is_empty()),
+            });
+        })
+        .build()
+}
+
+// Emit `n`, `n - 1`, ... `0`, one value per second
+fn countdown(n: u32) -> impl Stream<Item = u32> {
+    stream::once(async move { n }).chain(
+        Timer::interval(Duration::from_secs(1))
+            .take(n as usize)
+            .enumerate()
+            .map(move |(elapsed, _)| n - 1 - elapsed as u32),
+    )
+}
+
+// Contents of a bundled icon, e.g. `await hostBytes("loadImageBytes", "globe.svg")`. The name
+// comes from the page, so only files at the top of the asset folder are served, by their exact
+// names: nothing else can be reached, and with `dev-assets` the cache of files read only ever
+// holds the folder's own.
+fn load_image_bytes(name: String) -> anyhow::Result<Vec<u8>> {
+    let assets = assets::assets();
+    let listed =
+        is_file_name(&name) && assets.list("")?.iter().any(|file| &**file == name.as_str());
+    if !listed {
+        return Err(anyhow::anyhow!("no image named {}", name));
+    }
+    assets
+        .load(&name)?
+        .map(|bytes| bytes.into_owned())
+        .ok_or_else(|| anyhow::anyhow!("no image named {}", name))
+}
+
+// A single relative path component, e.g. `globe.svg` but not `../globe.svg` or `icons/globe.svg`
+fn is_file_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) && !name.contains(['/', '\\'])
+}
+
+// Clipboard calls from iframes, which can't ask as the tab's site
+fn clipboard_frame_error() -> BridgeError {
+    BridgeError::new(
+        "permission_denied",
+        "Only the top frame may use the clipboard",
+    )
+}
+
+// Hand a plugin's request to the window, resolving with its answer
+fn plugin_request(
+    events: &Sender<BridgeEvent>,
+    tab_id: TabId,
+    token: String,
+    request: PluginRequest,
+) -> impl Future<Output = Reply<()>> + 'static {
+    let (reply, response) = flume::bounded(1);
+    _ = events.send(BridgeEvent::Plugin {
+        tab_id,
+        token,
+        request,
+        reply,
+    });
+    async move {
+        Reply::from(
+            response
+                .recv_async()
+                .await
+                .unwrap_or_else(|_| Err(BridgeError::new("unavailable", "The tab was closed"))),
+        )
+    }
+}
+
+pub fn inject_document_start_scripts(frame: &Frame) {
+    for script in DOCUMENT_START_SCRIPTS {
+        frame.execute_javascript(script);
+    }
+}
+
+pub fn inject_page_scripts(frame: &Frame) {
+    for script in PAGE_SCRIPTS {
+        frame.execute_javascript(script);
+    }
+}
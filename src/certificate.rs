@@ -0,0 +1,243 @@
+use gpui::{div, prelude::*, px, svg, white, App, Global, IntoElement, Rgba, Window};
+use gpui_webview::events::CertificateCallback;
+use std::collections::HashSet;
+
+use crate::bridge::TabId;
+use crate::theme::Theme;
+
+// What's wrong with a site's certificate, from Chromium's net error code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CertificateProblem {
+    NameMismatch,
+    Expired,
+    Untrusted,
+    Other,
+}
+
+impl CertificateProblem {
+    pub fn from_code(code: i32) -> Self {
+        match code {
+            -200 => CertificateProblem::NameMismatch,
+            -201 => CertificateProblem::Expired,
+            -202 => CertificateProblem::Untrusted,
+            _ => CertificateProblem::Other,
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            CertificateProblem::NameMismatch => {
+                "The certificate was issued for a different site, so this may not be the site you meant to visit."
+            }
+            CertificateProblem::Expired => {
+                "The certificate has expired or isn't valid yet. Check that your computer's clock is right."
+            }
+            CertificateProblem::Untrusted => {
+                "The certificate isn't issued by an authority this browser trusts, it may be self-signed."
+            }
+            CertificateProblem::Other => "The certificate couldn't be verified.",
+        }
+    }
+}
+
+// Hosts whose certificate errors the user chose to ignore until the browser quits
+#[derive(Default)]
+pub struct CertificateExceptions(pub HashSet<String>);
+
+impl Global for CertificateExceptions {}
+
+// A request held back until the user decides whether to trust the site's certificate
+pub struct CertificateError {
+    pub tab_id: TabId,
+    pub url: String,
+    pub host: String,
+    pub code: i32,
+    pub problem: CertificateProblem,
+    pub remember: bool,
+    pub callback: CertificateCallback,
+}
+
+// Warning covering the page in place of a site with a bad certificate
+pub fn render_interstitial(
+    error: &CertificateError,
+    theme: &Theme,
+    on_back: impl Fn(&mut Window, &mut App) + 'static,
+    on_toggle_remember: impl Fn(&mut Window, &mut App) + 'static,
+    on_proceed: impl Fn(&mut Window, &mut App) + 'static,
+) -> impl IntoElement {
+    div()
+        .absolute()
+        .inset_0()
+        .occlude()
+        .flex()
+        .flex_col()
+        .items_center()
+        .justify_center()
+        .gap_3()
+        .bg(theme.page)
+        .child(
+            svg()
+                .path("globe.svg")
+                .size(px(32.))
+                .text_color(theme.danger),
+        )
+        .child(
+            div()
+                .text_lg()
+                .text_color(theme.text)
+                .child("Your connection is not private"),
+        )
+        .child(
+            div()
+                .max_w(px(480.))
+                .text_sm()
+                .text_center()
+                .text_color(theme.text_dim)
+                .child(format!(
+                    "Someone may be trying to intercept your connection to {}. {}",
+                    error.host,
+                    error.problem.description()
+                )),
+        )
+        .child(
+            div()
+                .max_w(px(480.))
+                .truncate()
+                .text_xs()
+                .text_color(theme.text_muted)
+                .child(format!("{} (error {})", error.url, error.code)),
+        )
+        .child(
+            div()
+                .id("back-to-safety")
+                .mt_2()
+                .px_3()
+                .py_1()
+                .rounded_md()
+                .bg(theme.accent)
+                .text_xs()
+                .text_color(white())
+                .cursor_pointer()
+                .hover(|this| this.opacity(0.85))
+                .on_click(move |_, window, cx| on_back(window, cx))
+                .child("Back to safety"),
+        )
+        .child(
+            div()
+                .mt_4()
+                .flex()
+                .items_center()
+                .gap_3()
+                .child(
+                    div()
+                        .id("remember-certificate-exception")
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .text_xs()
+                        .text_color(theme.text_muted)
+                        .cursor_pointer()
+                        .on_click(move |_, window, cx| on_toggle_remember(window, cx))
+                        .child(
+                            div()
+                                .size(px(12.))
+                                .rounded_sm()
+                                .border_1()
+                                .border_color(theme.text_muted)
+                                .when(error.remember, |this| {
+                                    this.bg(theme.danger).border_color(theme.danger)
+                                }),
+                        )
+                        .child("Don't warn again until I quit"),
+                )
+                .child(
+                    div()
+                        .id("proceed-unsafe")
+                        .text_xs()
+                        .text_color(theme.danger)
+                        .cursor_pointer()
+                        .hover(|this| this.underline())
+                        .on_click(move |_, window, cx| on_proceed(window, cx))
+                        .child(format!("Proceed to {} (unsafe)", error.host)),
+                ),
+        )
+}
+
+// How safely the page in a tab was reached, shown by the icon at the start of the address bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityState {
+    Secure,
+    Insecure,
+    // The certificate was refused, or let through by the user
+    CertificateError,
+    // Browser and local pages that never touch the network
+    Local,
+}
+
+impl SecurityState {
+    pub fn of(url: &str, certificate_error: bool, cx: &App) -> Self {
+        if certificate_error {
+            return SecurityState::CertificateError;
+        }
+
+        let Ok(url) = url::Url::parse(url) else {
+            return SecurityState::Local;
+        };
+        match url.scheme() {
+            "https" | "wss" => {
+                let excepted = url.host_str().is_some_and(|host| {
+                    cx.try_global::<CertificateExceptions>()
+                        .is_some_and(|exceptions| exceptions.0.contains(host))
+                });
+                if excepted {
+                    SecurityState::CertificateError
+                } else {
+                    SecurityState::Secure
+                }
+            }
+            "http" | "ws" | "ftp" => SecurityState::Insecure,
+            _ => SecurityState::Local,
+        }
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            SecurityState::Secure => "lock.svg",
+            SecurityState::Insecure => "lock-open.svg",
+            SecurityState::CertificateError => "shield-alert.svg",
+            SecurityState::Local => "globe.svg",
+        }
+    }
+
+    pub fn color(self, theme: &Theme) -> Rgba {
+        match self {
+            SecurityState::Secure => theme.success,
+            SecurityState::Insecure | SecurityState::Local => theme.text_muted,
+            SecurityState::CertificateError => theme.warning,
+        }
+    }
+
+    pub fn summary(self) -> &'static str {
+        match self {
+            SecurityState::Secure => "Connection is secure",
+            SecurityState::Insecure => "Connection is not secure",
+            SecurityState::CertificateError => "Certificate is not valid",
+            SecurityState::Local => "Local page",
+        }
+    }
+
+    pub fn details(self) -> &'static str {
+        match self {
+            SecurityState::Secure => {
+                "Information you send to this site, like passwords or card numbers, is encrypted."
+            }
+            SecurityState::Insecure => {
+                "Don't enter passwords or card numbers, they can be read by others on the network."
+            }
+            SecurityState::CertificateError => {
+                "The site's identity couldn't be verified. Someone may be intercepting the connection."
+            }
+            SecurityState::Local => "This page wasn't loaded over the network.",
+        }
+    }
+}
@@ -0,0 +1,1124 @@
+use gpui::{
+    anchored, deferred, div, linear_color_stop, linear_gradient, prelude::*, px, rgb, rgba, svg,
+    App, Context, ExternalPaths, IntoElement, MouseButton, MouseDownEvent, Pixels, Point, Render,
+    SharedString, Task, Timer, Window,
+};
+use gpui_component::input::{self, TextInput};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::address;
+use crate::bookmarks::Bookmarks;
+use crate::bridge::TabId;
+use crate::certificate::SecurityState;
+use crate::context_menu;
+use crate::downloads::DownloadManager;
+use crate::settings::BrowserSettings;
+use crate::theme::Theme;
+use crate::webview_ext::WebViewExt;
+use crate::widgets::svg_button;
+use crate::{favicon_icon, Main};
+
+// Narrowest a tab chip gets before further tabs move into the overflow menu
+const MIN_TAB_WIDTH: f32 = 96.;
+// Pinned tabs only show their favicon
+const PINNED_TAB_WIDTH: f32 = 32.;
+// Horizontal space of the tab strip taken by its right padding and the overflow button
+const TAB_STRIP_CHROME_WIDTH: f32 = 10. + 32.;
+// How long the pointer rests on a tab chip before its preview shows
+const PREVIEW_DELAY: Duration = Duration::from_millis(500);
+
+// Left padding of the tab strip, clearing the macOS traffic lights. They are hidden while
+// the window is fullscreen, so the tabs can start at the edge.
+fn traffic_light_inset(window: &Window) -> f32 {
+    if window.is_fullscreen() {
+        10.
+    } else {
+        84.
+    }
+}
+
+// Tab chip that was right-clicked, and where
+pub struct TabMenu {
+    tab_id: TabId,
+    position: Point<Pixels>,
+}
+
+// Tab chip under the pointer, previewed once `PREVIEW_DELAY` has passed
+pub struct TabPreview {
+    tab_id: TabId,
+    shown: bool,
+    _delay: Task<()>,
+}
+
+// "5 min ago" and the like, for when a sleeping tab was last shown
+pub(crate) fn format_elapsed(elapsed: Duration) -> String {
+    let minutes = elapsed.as_secs() / 60;
+    match minutes {
+        0 => "just now".to_string(),
+        1..60 => format!("{} min ago", minutes),
+        60..1440 => format!("{} h ago", minutes / 60),
+        _ => format!("{} days ago", minutes / 1440),
+    }
+}
+
+// A tab chip being dragged to a new place in the strip, drawn under the cursor
+#[derive(Clone)]
+pub struct DraggedTab {
+    tab_id: TabId,
+    // Index when the drag started, to tell which side of a chip it would land on
+    ix: usize,
+    pinned: bool,
+    title: SharedString,
+    favicon: Option<Arc<gpui::Image>>,
+}
+
+impl Render for DraggedTab {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        div()
+            .flex()
+            .items_center()
+            .gap_2()
+            .h_7()
+            .px_2()
+            .map(|this| {
+                if self.pinned {
+                    this.w(px(PINNED_TAB_WIDTH)).justify_center()
+                } else {
+                    this.w(px(160.))
+                }
+            })
+            .rounded_md()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.surface)
+            .opacity(0.9)
+            .child(favicon_icon(self.favicon.clone(), 10.0))
+            .when(!self.pinned, |this| {
+                this.child(
+                    div()
+                        .flex_1()
+                        .min_w_0()
+                        .truncate()
+                        .text_xs()
+                        .text_color(theme.text)
+                        .child(self.title.clone()),
+                )
+            })
+    }
+}
+
+// Browser chrome above the page: the tab strip, and the toolbar with the address bar
+impl Main {
+    // Indices of the tabs shown in the strip, the rest are listed in the overflow menu.
+    // Pinned tabs are always shown. So is the active tab, scrolling the window of other
+    // visible tabs just far enough to reach it.
+    fn visible_tabs(&self, window: &Window) -> Vec<usize> {
+        let pinned = self.pinned_count();
+        // tabs are separated by a 4px gap
+        let available = f32::from(window.viewport_size().width)
+            - traffic_light_inset(window)
+            - TAB_STRIP_CHROME_WIDTH
+            - pinned as f32 * (PINNED_TAB_WIDTH + 4.);
+        let capacity = ((available + 4.) / (MIN_TAB_WIDTH + 4.)).floor().max(1.) as usize;
+
+        let unpinned = if self.tabs.len() - pinned <= capacity {
+            pinned..self.tabs.len()
+        } else {
+            let active = self.active_tab.saturating_sub(pinned);
+            let start = pinned + (active + 1).saturating_sub(capacity);
+            start..start + capacity
+        };
+        (0..pinned).chain(unpinned).collect()
+    }
+
+    // Start the preview delay when the pointer enters a chip, and drop the preview as soon as
+    // it leaves
+    fn hover_tab(&mut self, tab_id: TabId, hovered: bool, cx: &mut Context<Self>) {
+        if !hovered {
+            if self
+                .tab_preview
+                .as_ref()
+                .is_some_and(|preview| preview.tab_id == tab_id)
+            {
+                self.tab_preview = None;
+                cx.notify();
+            }
+            return;
+        }
+
+        let delay = cx.spawn(async move |this, cx| {
+            Timer::after(PREVIEW_DELAY).await;
+            _ = this.update(cx, |this, cx| {
+                if let Some(preview) = &mut this.tab_preview {
+                    preview.shown = true;
+                    cx.notify();
+                }
+            });
+        });
+        self.tab_preview = Some(TabPreview {
+            tab_id,
+            shown: false,
+            _delay: delay,
+        });
+    }
+
+    // Title and address of a hovered tab. Sleeping tabs show what was stored when they were
+    // last loaded, without loading them.
+    fn render_tab_preview(&self, ix: usize, cx: &App) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let tab = &self.tabs[ix];
+        let asleep = tab.webview.is_none();
+
+        div()
+            .absolute()
+            .top(px(32.))
+            .left_0()
+            .w(px(240.))
+            .flex()
+            .flex_col()
+            .gap_1()
+            .p_2()
+            .rounded_md()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.surface)
+            .text_xs()
+            .child(
+                div()
+                    .line_clamp(2)
+                    .text_color(theme.text)
+                    .child(if tab.title.is_empty() {
+                        tab.url.clone()
+                    } else {
+                        tab.title.clone()
+                    }),
+            )
+            .child(
+                div()
+                    .truncate()
+                    .text_color(theme.text_muted)
+                    .child(tab.url.clone()),
+            )
+            .when(asleep, |this| {
+                this.child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_1()
+                        .text_color(theme.text_dim)
+                        .child(
+                            svg()
+                                .path("moon.svg")
+                                .size(px(10.0))
+                                .text_color(theme.text_dim),
+                        )
+                        .child(format!(
+                            "Asleep, shown {}",
+                            format_elapsed(tab.last_active.elapsed())
+                        )),
+                )
+            })
+    }
+
+    fn open_tab_overflow(&mut self, cx: &mut Context<Self>) {
+        self.show_tab_overflow = true;
+        cx.notify();
+    }
+
+    // Horizontal strip of tab chips shown above the toolbar
+    pub fn render_tab_strip(&self, window: &Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let visible = self.visible_tabs(window);
+        let overflowing = visible.len() < self.tabs.len();
+
+        div()
+            .when(self.is_fullscreen, |this| this.hidden())
+            .when(self.private, |this| {
+                this.pb(px(6.)).rounded_t_xl().bg(if theme.dark {
+                    rgba(0x000000a6)
+                } else {
+                    rgba(0x0000002e)
+                })
+            })
+            .flex()
+            .items_center()
+            .gap_1()
+            .pl(px(traffic_light_inset(window)))
+            .pr(px(10.))
+            .pt(px(8.))
+            .children(visible.into_iter().map(|ix| {
+                let tab = &self.tabs[ix];
+                let active = ix == self.active_tab;
+                let tab_id = tab.id;
+                let pinned = tab.pinned;
+                let title = if tab.title.is_empty() {
+                    tab.url.clone()
+                } else {
+                    tab.title.clone()
+                };
+
+                div()
+                    .id(("tab", ix))
+                    .relative()
+                    .flex()
+                    .map(|this| {
+                        if tab.pinned {
+                            this.flex_none().w(px(PINNED_TAB_WIDTH)).justify_center()
+                        } else {
+                            this.flex_1().min_w_0().max_w(px(180.))
+                        }
+                    })
+                    .h_7()
+                    .px_2()
+                    .gap_2()
+                    .items_center()
+                    .rounded_md()
+                    .border_1()
+                    .cursor_pointer()
+                    .map(|this| {
+                        if active {
+                            this.border_color(theme.border).bg(theme.selected)
+                        } else {
+                            this.border_color(rgba(0x00000000))
+                                .hover(|this| this.bg(theme.hover_subtle))
+                        }
+                    })
+                    // restored but not shown yet, or discarded
+                    .when(tab.webview.is_none(), |this| this.opacity(0.6))
+                    .on_hover(cx.listener(move |this, hovered: &bool, _, cx| {
+                        this.hover_tab(tab_id, *hovered, cx)
+                    }))
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        this.tab_preview = None;
+                        this.activate_tab(ix, window, cx)
+                    }))
+                    .on_drag(
+                        DraggedTab {
+                            tab_id,
+                            ix,
+                            pinned,
+                            title: title.clone(),
+                            favicon: tab.favicon.clone(),
+                        },
+                        |dragged, _, _, cx| cx.new(|_| dragged.clone()),
+                    )
+                    // the dragged tab lands at this chip's place, so the edge it will
+                    // appear on is highlighted. Pinned and other tabs don't mix.
+                    .drag_over::<DraggedTab>(move |style, dragged, _, _| {
+                        if dragged.pinned != pinned || dragged.ix == ix {
+                            style
+                        } else if dragged.ix < ix {
+                            style.border_r_2().border_color(theme.accent)
+                        } else {
+                            style.border_l_2().border_color(theme.accent)
+                        }
+                    })
+                    // files dropped on a chip open in that tab
+                    .drag_over::<ExternalPaths>(move |style, _, _, _| {
+                        style.border_1().border_color(theme.accent)
+                    })
+                    .on_drop(cx.listener(move |this, paths: &ExternalPaths, window, cx| {
+                        this.drop_paths_on_tab(tab_id, paths, window, cx)
+                    }))
+                    .on_drop(cx.listener(move |this, dragged: &DraggedTab, _, cx| {
+                        if let Some(from) =
+                            this.tabs.iter().position(|tab| tab.id == dragged.tab_id)
+                        {
+                            this.move_tab(from, ix, cx);
+                        }
+                    }))
+                    .on_mouse_down(
+                        MouseButton::Right,
+                        cx.listener(move |this, event: &MouseDownEvent, _, cx| {
+                            this.tab_preview = None;
+                            this.tab_menu = Some(TabMenu {
+                                tab_id,
+                                position: event.position,
+                            });
+                            cx.notify();
+                        }),
+                    )
+                    .child(favicon_icon(tab.favicon.clone(), 10.0))
+                    .when(!tab.pinned, |this| {
+                        this.child(
+                            div()
+                                .flex_1()
+                                .min_w_0()
+                                .truncate()
+                                .text_xs()
+                                .text_color(if active { theme.text } else { theme.text_dim })
+                                .child(title),
+                        )
+                    })
+                    .when(!tab.pinned && tab.webview.is_none(), |this| {
+                        this.child(
+                            svg()
+                                .flex_none()
+                                .path("moon.svg")
+                                .size(px(10.0))
+                                .text_color(theme.icon_muted),
+                        )
+                    })
+                    .when(!tab.pinned && (tab.audio_playing || tab.muted), |this| {
+                        this.child(
+                            div()
+                                .id(("mute-tab", ix))
+                                .flex_none()
+                                .rounded_md()
+                                .hover(|this| this.bg(theme.hover))
+                                .on_click(cx.listener(move |this, _, _, cx| {
+                                    cx.stop_propagation();
+                                    this.toggle_mute(ix, cx)
+                                }))
+                                .child(
+                                    svg()
+                                        .path(if tab.muted {
+                                            "volume-x.svg"
+                                        } else {
+                                            "volume-2.svg"
+                                        })
+                                        .size(px(10.0))
+                                        .text_color(theme.icon_muted),
+                                ),
+                        )
+                    })
+                    .when(!tab.pinned, |this| {
+                        this.child(
+                            div()
+                                .id(("close-tab", ix))
+                                .flex_none()
+                                .rounded_md()
+                                .hover(|this| this.bg(theme.hover))
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    cx.stop_propagation();
+                                    this.close_tab(ix, window, cx)
+                                }))
+                                .child(
+                                    svg()
+                                        .path("close.svg")
+                                        .size(px(10.0))
+                                        .text_color(theme.icon_muted),
+                                ),
+                        )
+                    })
+                    .when(
+                        self.tab_preview
+                            .as_ref()
+                            .is_some_and(|preview| preview.tab_id == tab_id && preview.shown),
+                        |this| this.child(deferred(self.render_tab_preview(ix, cx))),
+                    )
+            }))
+            .when(overflowing, |this| {
+                this.child(
+                    div()
+                        .relative()
+                        .flex_none()
+                        .child(
+                            div()
+                                .id("tab-overflow")
+                                .flex()
+                                .items_center()
+                                .justify_center()
+                                .size(px(24.))
+                                .rounded_md()
+                                .cursor_pointer()
+                                .hover(|this| this.bg(theme.hover))
+                                .when(self.show_tab_overflow, |this| this.bg(theme.selected))
+                                // while open, pressing here is a press outside the menu, which
+                                // closes it; toggling on the click would open it right again
+                                .when(!self.show_tab_overflow, |this| {
+                                    this.on_click(
+                                        cx.listener(|this, _, _, cx| this.open_tab_overflow(cx)),
+                                    )
+                                })
+                                .child(
+                                    svg()
+                                        .path("chevron-down.svg")
+                                        .size(px(12.0))
+                                        .text_color(theme.text),
+                                ),
+                        )
+                        .when(self.show_tab_overflow, |this| {
+                            this.child(deferred(self.render_tab_overflow(cx)))
+                        }),
+                )
+            })
+            .child(
+                div()
+                    .relative()
+                    .flex_none()
+                    .child(
+                        div()
+                            .id("recently-closed")
+                            .flex()
+                            .items_center()
+                            .justify_center()
+                            .size(px(24.))
+                            .rounded_md()
+                            .cursor_pointer()
+                            .hover(|this| this.bg(theme.hover))
+                            .when(self.show_recently_closed, |this| this.bg(theme.selected))
+                            // the open menu closes on this press already, like the tab overflow
+                            .when(!self.show_recently_closed, |this| {
+                                this.on_click(
+                                    cx.listener(|this, _, _, cx| this.toggle_recently_closed(cx)),
+                                )
+                            })
+                            .child(
+                                svg()
+                                    .path("history.svg")
+                                    .size(px(12.0))
+                                    .text_color(theme.text),
+                            ),
+                    )
+                    .when(self.show_recently_closed, |this| {
+                        this.child(deferred(self.render_recently_closed(cx)))
+                    }),
+            )
+    }
+
+    // Every open tab, so tabs pushed out of the strip can still be reached
+    fn render_tab_overflow(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        div()
+            .id("tab-overflow-menu")
+            .occlude()
+            .absolute()
+            .top(px(28.))
+            .right_0()
+            .w(px(280.))
+            .max_h(px(400.))
+            .overflow_y_scroll()
+            .flex()
+            .flex_col()
+            .py_1()
+            .rounded_md()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.surface)
+            .on_mouse_down_out(cx.listener(|this, _, _, cx| {
+                this.show_tab_overflow = false;
+                cx.notify();
+            }))
+            .children(self.tabs.iter().enumerate().map(|(ix, tab)| {
+                let active = ix == self.active_tab;
+                let title = if tab.title.is_empty() {
+                    tab.url.clone()
+                } else {
+                    tab.title.clone()
+                };
+
+                div()
+                    .id(("overflow-tab", ix))
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_3()
+                    .py_1()
+                    .cursor_pointer()
+                    .when(active, |this| this.bg(theme.selected))
+                    .hover(|this| this.bg(theme.hover))
+                    .on_click(
+                        cx.listener(move |this, _, window, cx| this.activate_tab(ix, window, cx)),
+                    )
+                    .child(favicon_icon(tab.favicon.clone(), 10.0))
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w_0()
+                            .truncate()
+                            .text_xs()
+                            .text_color(if active { theme.text } else { theme.text_dim })
+                            .child(title),
+                    )
+                    .when(!tab.pinned, |this| {
+                        this.child(
+                            div()
+                                .id(("close-overflow-tab", ix))
+                                .flex_none()
+                                .rounded_md()
+                                .hover(|this| this.bg(theme.hover))
+                                .on_click(cx.listener(move |this, _, window, cx| {
+                                    cx.stop_propagation();
+                                    this.close_tab(ix, window, cx)
+                                }))
+                                .child(
+                                    svg()
+                                        .path("close.svg")
+                                        .size(px(10.0))
+                                        .text_color(theme.icon_muted),
+                                ),
+                        )
+                    })
+            }))
+    }
+
+    // Right-click menu of a tab chip
+    pub fn render_tab_menu(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let theme = *cx.global::<Theme>();
+        let menu = self.tab_menu.as_ref()?;
+        let tab_id = menu.tab_id;
+        let tab = self.tabs.iter().find(|tab| tab.id == tab_id)?;
+        let tab_ix = self.tabs.iter().position(|tab| tab.id == tab_id)?;
+        let this = cx.entity();
+        let closable = |range: std::ops::Range<usize>| {
+            self.tabs[range]
+                .iter()
+                .any(|other| other.id != tab_id && !other.pinned)
+        };
+        let all_muted = self.tabs.iter().all(|tab| tab.muted);
+        // whether the tab is one of the two split view shows
+        let split_pair = self
+            .split_view
+            .as_ref()
+            .is_some_and(|split| split.tab_id == tab_id || self.tabs[self.active_tab].id == tab_id);
+
+        // Each entry closes the menu and then runs against the tab, wherever it is by then
+        let item =
+            |id, label, enabled, action: fn(&mut Main, usize, &mut Window, &mut Context<Main>)| {
+                let this = this.clone();
+                context_menu::menu_item(id, label, enabled, move |window, cx| {
+                    this.update(cx, |this, cx| {
+                        this.tab_menu = None;
+                        if let Some(ix) = this.tabs.iter().position(|tab| tab.id == tab_id) {
+                            action(this, ix, window, cx);
+                        }
+                        cx.notify();
+                    })
+                })
+            };
+
+        Some(deferred(
+            anchored().position(menu.position).child(
+                div()
+                    .id("tab-menu")
+                    .occlude()
+                    .w(px(200.))
+                    .py_1()
+                    .flex()
+                    .flex_col()
+                    .rounded_md()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .on_mouse_down_out(cx.listener(|this, _, _, cx| {
+                        this.tab_menu = None;
+                        cx.notify();
+                    }))
+                    .child(item(
+                        "pin-tab",
+                        if tab.pinned { "Unpin Tab" } else { "Pin Tab" },
+                        true,
+                        |this, ix, _, cx| this.toggle_pin(ix, cx),
+                    ))
+                    .child(item(
+                        "mute-tab",
+                        if tab.muted { "Unmute Tab" } else { "Mute Tab" },
+                        true,
+                        |this, ix, _, cx| this.toggle_mute(ix, cx),
+                    ))
+                    .child(item(
+                        "mute-all-tabs",
+                        if all_muted {
+                            "Unmute All Tabs"
+                        } else {
+                            "Mute All Tabs"
+                        },
+                        true,
+                        |this, _, _, cx| this.toggle_mute_all(cx),
+                    ))
+                    .map(|this| {
+                        if split_pair {
+                            this.child(item(
+                                "exit-split-view",
+                                "Exit Split View",
+                                true,
+                                |this, _, _, cx| this.exit_split_view(cx),
+                            ))
+                        } else {
+                            this.child(item(
+                                "split-view",
+                                "Show Beside Current Tab",
+                                tab_ix != self.active_tab,
+                                |this, ix, window, cx| this.split_with(ix, window, cx),
+                            ))
+                        }
+                    })
+                    .child(item(
+                        "reload-all-tabs",
+                        "Reload All Tabs",
+                        true,
+                        |this, _, _, cx| this.reload_all(cx),
+                    ))
+                    .child(context_menu::separator())
+                    .child(item(
+                        "close-tab",
+                        "Close Tab",
+                        !tab.pinned,
+                        |this, ix, window, cx| this.close_tab(ix, window, cx),
+                    ))
+                    .child(item(
+                        "close-other-tabs",
+                        "Close Other Tabs",
+                        closable(0..self.tabs.len()),
+                        |this, ix, window, cx| this.close_other_tabs(ix, window, cx),
+                    ))
+                    .child(item(
+                        "close-tabs-right",
+                        "Close Tabs to the Right",
+                        closable(tab_ix + 1..self.tabs.len()),
+                        |this, ix, window, cx| this.close_tabs(ix, |other| other > ix, window, cx),
+                    ))
+                    .child(item(
+                        "close-tabs-left",
+                        "Close Tabs to the Left",
+                        closable(0..tab_ix),
+                        |this, ix, window, cx| this.close_tabs(ix, |other| other < ix, window, cx),
+                    )),
+            ),
+        ))
+    }
+
+    // Editing entries for the address bar, plus opening the clipboard's text right away
+    pub fn render_address_menu(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let theme = *cx.global::<Theme>();
+        let position = self.address_menu?;
+        let this = cx.entity();
+        let clipboard = cx
+            .read_from_clipboard()
+            .and_then(|item| item.text())
+            .filter(|text| !text.trim().is_empty());
+        // the same test the address bar makes on Enter
+        let engine = cx.global::<BrowserSettings>().search_engine();
+        let is_search = clipboard.as_deref().is_some_and(|text| {
+            address::normalize_input(text, &engine) == address::search_url(&engine, text.trim())
+        });
+
+        // Each entry closes the menu and then acts on the focused address bar
+        let item = |id, label, enabled, action: fn(&mut Main, &mut Window, &mut Context<Main>)| {
+            let this = this.clone();
+            context_menu::menu_item(id, label, enabled, move |window, cx| {
+                this.update(cx, |this, cx| {
+                    this.address_menu = None;
+                    action(this, window, cx);
+                    cx.notify();
+                })
+            })
+        };
+
+        Some(deferred(
+            anchored().position(position).child(
+                div()
+                    .id("address-menu")
+                    .occlude()
+                    .w(px(180.))
+                    .py_1()
+                    .flex()
+                    .flex_col()
+                    .rounded_md()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .on_mouse_down_out(cx.listener(|this, _, _, cx| {
+                        this.address_menu = None;
+                        cx.notify();
+                    }))
+                    .child(item("address-cut", "Cut", true, |_, window, cx| {
+                        window.dispatch_action(Box::new(input::Cut), cx)
+                    }))
+                    .child(item("address-copy", "Copy", true, |_, window, cx| {
+                        window.dispatch_action(Box::new(input::Copy), cx)
+                    }))
+                    .child(item(
+                        "address-paste",
+                        "Paste",
+                        clipboard.is_some(),
+                        |_, window, cx| window.dispatch_action(Box::new(input::Paste), cx),
+                    ))
+                    .child(item(
+                        "address-paste-and-go",
+                        if is_search {
+                            "Paste and Search"
+                        } else {
+                            "Paste and Go"
+                        },
+                        clipboard.is_some(),
+                        |this, window, cx| this.paste_and_go(window, cx),
+                    ))
+                    .child(context_menu::separator())
+                    .child(item(
+                        "address-select-all",
+                        "Select All",
+                        true,
+                        |_, window, cx| window.dispatch_action(Box::new(input::SelectAll), cx),
+                    )),
+            ),
+        ))
+    }
+
+    // Navigation buttons, the address bar and page actions, with the downloads panel
+    pub fn render_toolbar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let this = cx.entity();
+        let tab = &self.tabs[self.active_tab];
+        let webview = self.active_webview().read(cx);
+        let can_go_back = webview.can_go_back();
+        let can_go_forward = webview.can_go_forward();
+        let is_loading = tab.is_loading;
+        let is_bookmarked = cx.global::<Bookmarks>().is_bookmarked(&tab.url);
+        let active_downloads = DownloadManager::global(cx)
+            .read(cx)
+            .active_count(self.private);
+        let blocking = tab.blocker.is_enabled();
+
+        div()
+            .when(self.is_fullscreen, |this| this.hidden())
+            .relative()
+            .px(px(12.))
+            .py(px(10.))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        // Back button
+                        svg_button(
+                            "back.svg",
+                            14.0,
+                            if can_go_back {
+                                theme.text
+                            } else {
+                                theme.border
+                            },
+                            can_go_back,
+                            {
+                                let this = this.clone();
+                                move |_, cx| this.update(cx, |this, cx| this.go_back(cx))
+                            },
+                        ),
+                    )
+                    .child(
+                        // Forward button
+                        svg_button(
+                            "forward.svg",
+                            14.0,
+                            if can_go_forward {
+                                theme.text
+                            } else {
+                                theme.border
+                            },
+                            can_go_forward,
+                            {
+                                let this = this.clone();
+                                move |_, cx| this.update(cx, |this, cx| this.go_forward(cx))
+                            },
+                        ),
+                    )
+                    .child(
+                        // Refresh button, or stop while loading
+                        if is_loading {
+                            svg_button("x.svg", 12.0, theme.text, true, {
+                                let this = this.clone();
+                                move |_, cx| this.update(cx, |this, cx| this.stop_loading(cx))
+                            })
+                            .into_any_element()
+                        } else {
+                            svg_button("rotate-cw.svg", 12.0, theme.text, true, {
+                                let this = this.clone();
+                                move |window, cx| {
+                                    let hard = window.modifiers().shift;
+                                    this.update(cx, |this, cx| this.reload_with_toast(hard, cx))
+                                }
+                            })
+                            .into_any_element()
+                        },
+                    )
+                    .child(
+                        // Home button
+                        svg_button("home.svg", 12.0, theme.text, true, {
+                            let this = this.clone();
+                            move |_, cx| this.update(cx, |this, cx| this.go_home(cx))
+                        }),
+                    )
+                    .child(self.render_address_bar(cx))
+                    .child(
+                        // Ads and trackers blocked on the page; clicking turns blocking off
+                        // or back on for the site
+                        div()
+                            .relative()
+                            .child(svg_button(
+                                "shield.svg",
+                                12.0,
+                                if blocking { theme.text } else { theme.border },
+                                true,
+                                {
+                                    let this = this.clone();
+                                    move |_, cx| {
+                                        this.update(cx, |this, cx| this.toggle_site_blocking(cx))
+                                    }
+                                },
+                            ))
+                            .when(blocking && tab.blocked_count > 0, |this| {
+                                this.child(
+                                    div()
+                                        .absolute()
+                                        .top(px(-6.))
+                                        .left(px(7.))
+                                        .px(px(3.))
+                                        .rounded_full()
+                                        .bg(theme.accent)
+                                        .text_size(px(8.))
+                                        .text_color(theme.text)
+                                        .child(if tab.blocked_count > 99 {
+                                            "99+".to_string()
+                                        } else {
+                                            tab.blocked_count.to_string()
+                                        }),
+                                )
+                            }),
+                    )
+                    .when(tab.has_video || tab.picture_in_picture, |toolbar| {
+                        // Picture-in-Picture, lit while the page's video floats
+                        toolbar.child(svg_button(
+                            "picture-in-picture.svg",
+                            12.0,
+                            if tab.picture_in_picture {
+                                theme.accent
+                            } else {
+                                theme.text
+                            },
+                            true,
+                            {
+                                let this = this.clone();
+                                move |_, cx| {
+                                    this.update(cx, |this, cx| this.toggle_picture_in_picture(cx))
+                                }
+                            },
+                        ))
+                    })
+                    .when(tab.readable, |toolbar| {
+                        // Reader mode, offered only on article-like pages
+                        toolbar.child(svg_button(
+                            "book-open.svg",
+                            12.0,
+                            if tab.reader.is_some() {
+                                theme.accent
+                            } else {
+                                theme.text
+                            },
+                            true,
+                            {
+                                let this = this.clone();
+                                move |_, cx| this.update(cx, |this, cx| this.toggle_reader(cx))
+                            },
+                        ))
+                    })
+                    .children(self.render_plugin_buttons(cx))
+                    .child(
+                        // Bookmark star
+                        svg_button(
+                            if is_bookmarked {
+                                "star-filled.svg"
+                            } else {
+                                "star.svg"
+                            },
+                            12.0,
+                            theme.text,
+                            !self.private,
+                            {
+                                let this = this.clone();
+                                move |_, cx| this.update(cx, |this, cx| this.toggle_bookmark(cx))
+                            },
+                        ),
+                    )
+                    .child(
+                        // Downloads button, with a dot while downloads are running
+                        div()
+                            .relative()
+                            .child(svg_button("download.svg", 12.0, theme.text, true, {
+                                let this = this.clone();
+                                move |_, cx| this.update(cx, |this, cx| this.toggle_downloads(cx))
+                            }))
+                            .when(active_downloads > 0, |this| {
+                                this.child(
+                                    div()
+                                        .absolute()
+                                        .top(px(-2.))
+                                        .right(px(-2.))
+                                        .size(px(5.))
+                                        .rounded_full()
+                                        .bg(theme.accent),
+                                )
+                            }),
+                    )
+                    .child(
+                        // New tab button
+                        div()
+                            .id("new-tab")
+                            .cursor_pointer()
+                            .on_click(
+                                cx.listener(|this, _, window, cx| this.open_new_tab(window, cx)),
+                            )
+                            .px_1()
+                            .py_1()
+                            .bg(linear_gradient(
+                                150.,
+                                linear_color_stop(theme.field[0], 0.05), // transparent
+                                linear_color_stop(theme.field[1], 0.85), // Very dark/black
+                            ))
+                            .border_1()
+                            .border_color(theme.border)
+                            .rounded_md()
+                            .items_center()
+                            .justify_center()
+                            .child(svg().path("plus.svg").size(px(12.0)).text_color(theme.text)),
+                    ),
+            )
+            // deferred so the panel paints above the page
+            .children(self.render_downloads_panel(cx).map(deferred))
+    }
+
+    // Connection security icon, site favicon, the URL input and the zoom badge, with
+    // suggestion and site info popovers
+    fn render_address_bar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let tab = &self.tabs[self.active_tab];
+        let favicon = tab.favicon.clone();
+        let security = SecurityState::of(&tab.url, tab.certificate_failed(), cx);
+        let zoom_badge = self
+            .zoom_badge_task
+            .is_some()
+            .then(|| format!("{}%", (tab.zoom_level * 100.).round()));
+
+        div()
+            .flex()
+            .border_1()
+            .border_color(theme.border)
+            .rounded_md()
+            .relative()
+            .h_8()
+            .w_64()
+            .items_center()
+            .capture_key_down(cx.listener(Self::on_address_key_down))
+            .on_mouse_down(
+                MouseButton::Right,
+                cx.listener(|this, event: &MouseDownEvent, window, cx| {
+                    window.focus(&this.address_state.focus_handle(cx));
+                    this.address_menu = Some(event.position);
+                    cx.notify();
+                }),
+            )
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_3()
+                    .h_full()
+                    .w_full()
+                    .child(
+                        // Site info popover
+                        div()
+                            .id("site-data")
+                            .flex_none()
+                            .flex()
+                            .items_center()
+                            .gap_1()
+                            .cursor_pointer()
+                            .on_click(cx.listener(|this, _, _, cx| this.toggle_site_data(cx)))
+                            .child(
+                                svg()
+                                    .path(security.icon())
+                                    .size(px(10.0))
+                                    .text_color(security.color(&theme)),
+                            )
+                            .child(favicon_icon(favicon, 10.0)),
+                    )
+                    .child(
+                        TextInput::new(&self.address_state)
+                            .text_color(theme.text_secondary)
+                            .text_xs()
+                            .border_0(),
+                    )
+                    .when(!tab.javascript_enabled, |this| {
+                        // the page was loaded without scripts
+                        this.child(
+                            div()
+                                .id("javascript-off")
+                                .flex_none()
+                                .px_1()
+                                .rounded_md()
+                                .bg(rgba(0xff9f0a33))
+                                .text_xs()
+                                .text_color(rgb(0xff9f0a))
+                                .cursor_pointer()
+                                .on_click(cx.listener(|this, _, _, cx| this.toggle_site_data(cx)))
+                                .child("JS off"),
+                        )
+                    })
+                    .children(zoom_badge.map(|zoom| {
+                        // Transient zoom level badge
+                        div()
+                            .flex_none()
+                            .px_1()
+                            .rounded_md()
+                            .bg(theme.hover)
+                            .text_xs()
+                            .text_color(theme.text_secondary)
+                            .child(zoom)
+                    }))
+                    .child(
+                        svg()
+                            .path("close.svg")
+                            .size(px(10.0))
+                            .text_color(theme.icon_muted),
+                    ),
+            )
+            .children(
+                // deferred so the dropdown paints above the page
+                self.render_suggestions(cx).map(deferred),
+            )
+            .children(self.render_site_data(cx).map(deferred))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+
+    #[test]
+    fn formats_time_since_shown() {
+        assert_eq!(format_elapsed(Duration::from_secs(59)), "just now");
+        assert_eq!(format_elapsed(Duration::from_secs(5 * 60)), "5 min ago");
+        assert_eq!(
+            format_elapsed(Duration::from_secs(2 * 3600 + 59)),
+            "2 h ago"
+        );
+        assert_eq!(format_elapsed(Duration::from_secs(3 * 86400)), "3 days ago");
+    }
+
+    // The chrome that can be drawn without a `Main`, whose tabs would start CEF
+    #[gpui::test]
+    fn renders_a_dragged_tab(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            cx.set_global(BrowserSettings::default());
+            Theme::init(cx);
+        });
+        for pinned in [false, true] {
+            let (_, cx) = cx.add_window_view(|_, _| DraggedTab {
+                tab_id: 1,
+                ix: 0,
+                pinned,
+                title: "Example Domain".into(),
+                favicon: None,
+            });
+            cx.update(|window, cx| {
+                let _ = window.draw(cx);
+            });
+        }
+    }
+}
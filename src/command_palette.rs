@@ -0,0 +1,314 @@
+use gpui::{
+    div, prelude::*, px, Action, App, Context, Entity, FocusHandle, Global, IntoElement,
+    KeyDownEvent, Render, SharedString, Subscription, Window,
+};
+use gpui_component::{
+    input::{InputEvent, InputState, TextInput},
+    ContextModal,
+};
+
+use crate::theme::Theme;
+use crate::{
+    CaptureRegion, CaptureScreenshot, CloseTab, CopyUrl, DuplicateTab, ExportPdf, FindInPage,
+    FocusAddressBar, HardReload, Main, NewPrivateWindow, NewTab, NewWindow, NextTab, OpenFile,
+    OpenPreferences, PasteAndGo, PreviousTab, Reload, ReopenClosedTab, ResetZoom, ToggleBookmark,
+    ToggleCommandPalette, ToggleConsole, ToggleDevTools, ToggleFullscreen, ToggleMute,
+    TogglePictureInPicture, ToggleReadAloud, ToggleRecentlyClosed, ToggleSplitView,
+    ToggleTabSearch, ToggleTaskManager, ZoomIn, ZoomOut,
+};
+
+// A named action the palette can run, see `Commands::register`
+pub struct Command {
+    pub name: SharedString,
+    pub action: Box<dyn Action>,
+}
+
+// Everything the command palette lists, in the order shown for an empty query
+pub struct Commands(Vec<Command>);
+
+impl Global for Commands {}
+
+impl Commands {
+    pub fn init(cx: &mut App) {
+        let mut commands = Self(Vec::new());
+        commands.register("New Tab", NewTab);
+        commands.register("New Window", NewWindow);
+        commands.register("New Private Window", NewPrivateWindow);
+        commands.register("Duplicate Tab", DuplicateTab);
+        commands.register("Close Tab", CloseTab);
+        commands.register("Reopen Closed Tab", ReopenClosedTab);
+        commands.register("Show Recently Closed", ToggleRecentlyClosed);
+        commands.register("Next Tab", NextTab);
+        commands.register("Previous Tab", PreviousTab);
+        commands.register("Search Tabs", ToggleTabSearch);
+        commands.register("Reload", Reload);
+        commands.register("Hard Reload", HardReload);
+        commands.register("Focus Address Bar", FocusAddressBar);
+        commands.register("Copy URL", CopyUrl);
+        commands.register("Paste and Go", PasteAndGo);
+        commands.register("Bookmark Page", ToggleBookmark);
+        commands.register("Find in Page", FindInPage);
+        commands.register("Toggle Split View", ToggleSplitView);
+        commands.register("Read Aloud", ToggleReadAloud);
+        commands.register("Zoom In", ZoomIn);
+        commands.register("Zoom Out", ZoomOut);
+        commands.register("Reset Zoom", ResetZoom);
+        commands.register("Mute Tab", ToggleMute);
+        commands.register("Toggle Fullscreen", ToggleFullscreen);
+        commands.register("Picture in Picture", TogglePictureInPicture);
+        commands.register("Take Screenshot", CaptureScreenshot);
+        commands.register("Take Screenshot of Area", CaptureRegion);
+        commands.register("Export as PDF", ExportPdf);
+        commands.register("Open File", OpenFile);
+        commands.register("Toggle Developer Tools", ToggleDevTools);
+        commands.register("Toggle Console", ToggleConsole);
+        commands.register("Task Manager", ToggleTaskManager);
+        commands.register("Open Preferences", OpenPreferences);
+        cx.set_global(commands);
+    }
+
+    pub fn register(&mut self, name: impl Into<SharedString>, action: impl Action) {
+        self.0.push(Command {
+            name: name.into(),
+            action: Box::new(action),
+        });
+    }
+}
+
+// Scores `name` against the typed letters in order, higher for runs of letters and for
+// matches at the start of words, `None` when some letter is missing
+fn fuzzy_score(name: &str, query: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut chars = name.char_indices();
+    let mut previous: Option<usize> = None;
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let (at, _) = chars
+            .by_ref()
+            .find(|(_, c)| c.to_lowercase().eq(wanted.to_lowercase()))?;
+        let word_start = at == 0 || name[..at].ends_with(' ');
+        score += 1;
+        if word_start {
+            score += 4;
+        }
+        if previous.is_some_and(|previous| previous + 1 == at) {
+            score += 2;
+        }
+        previous = Some(at);
+    }
+    // shorter names win ties
+    Some(score * 100 - name.len() as i32)
+}
+
+// A command as listed, with the keys bound to it where the palette was opened
+struct Entry {
+    name: SharedString,
+    shortcut: Option<SharedString>,
+    action: Box<dyn Action>,
+}
+
+pub struct CommandPalette {
+    query: Entity<InputState>,
+    entries: Vec<Entry>,
+    // Indices into `entries` matching the query, best first
+    matches: Vec<usize>,
+    selected: usize,
+    // Where the command runs, focused again once the palette closes
+    target: FocusHandle,
+    _subscription: Subscription,
+}
+
+impl CommandPalette {
+    fn new(target: FocusHandle, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let entries: Vec<_> = cx
+            .global::<Commands>()
+            .0
+            .iter()
+            .map(|command| Entry {
+                name: command.name.clone(),
+                shortcut: window
+                    .bindings_for_action_in(&*command.action, &target)
+                    .last()
+                    .map(|binding| {
+                        binding
+                            .keystrokes()
+                            .iter()
+                            .map(|keystroke| keystroke.to_string())
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                            .into()
+                    }),
+                action: command.action.boxed_clone(),
+            })
+            .collect();
+        let query = cx.new(|cx| InputState::new(window, cx).placeholder("Type a command"));
+        let subscription = cx.subscribe_in(&query, window, |this, _, event: &InputEvent, _, cx| {
+            if let InputEvent::Change(_) = event {
+                this.update_matches(cx);
+            }
+        });
+
+        Self {
+            query,
+            matches: (0..entries.len()).collect(),
+            entries,
+            selected: 0,
+            target,
+            _subscription: subscription,
+        }
+    }
+
+    fn update_matches(&mut self, cx: &mut Context<Self>) {
+        let query = self.query.read(cx).value();
+        let mut scored: Vec<_> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, entry)| Some((fuzzy_score(&entry.name, &query)?, ix)))
+            .collect();
+        // stable, so equal scores keep the registry order
+        scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        self.matches = scored.into_iter().map(|(_, ix)| ix).collect();
+        self.selected = 0;
+        cx.notify();
+    }
+
+    fn run(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.matches.get(ix).map(|&ix| &self.entries[ix]) else {
+            return;
+        };
+        let action = entry.action.boxed_clone();
+        window.close_modal(cx);
+        window.focus(&self.target);
+        window.dispatch_action(action, cx);
+    }
+
+    fn on_key_down(&mut self, event: &KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let last = self.matches.len() - 1;
+        match event.keystroke.key.as_str() {
+            "down" => {
+                self.selected = if self.selected < last {
+                    self.selected + 1
+                } else {
+                    0
+                }
+            }
+            "up" => {
+                self.selected = if self.selected > 0 {
+                    self.selected - 1
+                } else {
+                    last
+                }
+            }
+            "enter" => self.run(self.selected, window, cx),
+            _ => return,
+        }
+        cx.stop_propagation();
+        cx.notify();
+    }
+}
+
+impl Render for CommandPalette {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .capture_key_down(cx.listener(Self::on_key_down))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .h_8()
+                    .px_3()
+                    .border_1()
+                    .border_color(theme.border)
+                    .rounded_md()
+                    .child(
+                        TextInput::new(&self.query)
+                            .text_color(theme.text_secondary)
+                            .text_xs()
+                            .border_0(),
+                    ),
+            )
+            .child(
+                div()
+                    .id("command-palette-matches")
+                    .max_h(px(320.))
+                    .flex()
+                    .flex_col()
+                    .overflow_y_scroll()
+                    .when(self.matches.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .px_3()
+                                .py_1()
+                                .text_xs()
+                                .text_color(theme.text_muted)
+                                .child("No matching commands"),
+                        )
+                    })
+                    .children(self.matches.iter().enumerate().map(|(ix, &entry_ix)| {
+                        let entry = &self.entries[entry_ix];
+                        div()
+                            .id(("command", entry_ix))
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .px_3()
+                            .py_1()
+                            .rounded_md()
+                            .text_xs()
+                            .cursor_pointer()
+                            .when(ix == self.selected, |this| this.bg(theme.hover))
+                            .hover(|this| this.bg(theme.hover))
+                            .on_click(
+                                cx.listener(move |this, _, window, cx| this.run(ix, window, cx)),
+                            )
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .truncate()
+                                    .text_color(theme.text)
+                                    .child(entry.name.clone()),
+                            )
+                            .children(entry.shortcut.clone().map(|shortcut| {
+                                div()
+                                    .flex_none()
+                                    .text_color(theme.text_muted)
+                                    .child(shortcut)
+                            }))
+                    })),
+            )
+    }
+}
+
+impl Main {
+    pub(crate) fn open_command_palette(
+        &mut self,
+        _: &ToggleCommandPalette,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if window.has_active_modal(cx) {
+            window.close_modal(cx);
+            return;
+        }
+
+        let target = window
+            .focused(cx)
+            .unwrap_or_else(|| self.focus_handle.clone());
+        let palette = cx.new(|cx| CommandPalette::new(target, window, cx));
+        window.open_modal(cx, {
+            let palette = palette.clone();
+            move |modal, _, _| modal.width(px(420.)).child(palette.clone())
+        });
+        // the modal takes focus as it opens
+        let query = palette.read(cx).query.focus_handle(cx);
+        window.defer(cx, move |window, _| window.focus(&query));
+    }
+}
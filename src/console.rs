@@ -0,0 +1,200 @@
+use gpui::{div, prelude::*, px, App, Entity, IntoElement, Rgba, SharedString, Window};
+use gpui_component::input::{InputState, TextInput};
+use serde::Deserialize;
+use std::collections::VecDeque;
+
+use crate::theme::{with_alpha, Theme};
+
+// Messages kept per tab, older ones are dropped first
+const MAX_MESSAGES: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleLevel {
+    Debug,
+    Log,
+    Info,
+    Warn,
+    Error,
+}
+
+impl ConsoleLevel {
+    fn color(self, theme: &Theme) -> Rgba {
+        match self {
+            ConsoleLevel::Debug => theme.text_muted,
+            ConsoleLevel::Log => theme.text_secondary,
+            ConsoleLevel::Info => theme.accent,
+            ConsoleLevel::Warn => theme.warning,
+            ConsoleLevel::Error => theme.danger,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            ConsoleLevel::Debug => "debug",
+            ConsoleLevel::Log => "log",
+            ConsoleLevel::Info => "info",
+            ConsoleLevel::Warn => "warn",
+            ConsoleLevel::Error => "error",
+        }
+    }
+}
+
+// A console call or uncaught error, with where in the page's scripts it came from
+#[derive(Debug, Clone)]
+pub struct ConsoleMessage {
+    pub level: ConsoleLevel,
+    pub message: String,
+    pub source: String,
+    pub line: u32,
+}
+
+// Recent console output of a tab
+#[derive(Default)]
+pub struct ConsoleLog {
+    messages: VecDeque<ConsoleMessage>,
+}
+
+impl ConsoleLog {
+    pub fn push(&mut self, message: ConsoleMessage) {
+        if self.messages.len() == MAX_MESSAGES {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message);
+    }
+
+    pub fn clear(&mut self) {
+        self.messages.clear();
+    }
+
+    // Messages containing `query`, ignoring case, oldest first
+    pub fn filtered<'a>(&'a self, query: &str) -> impl Iterator<Item = &'a ConsoleMessage> {
+        let query = query.to_lowercase();
+        self.messages
+            .iter()
+            .filter(move |message| message.message.to_lowercase().contains(&query))
+    }
+}
+
+// Panel along the bottom of the page listing the tab's console output
+pub fn render_console(
+    log: &ConsoleLog,
+    filter: &Entity<InputState>,
+    cx: &App,
+    on_clear: impl Fn(&mut Window, &mut App) + 'static,
+    on_close: impl Fn(&mut Window, &mut App) + 'static,
+) -> impl IntoElement {
+    let query = filter.read(cx).value().trim().to_string();
+    let mut messages = log.filtered(&query).peekable();
+    let empty = messages.peek().is_none();
+    let theme = *cx.global::<Theme>();
+
+    div()
+        .absolute()
+        .bottom_0()
+        .left_0()
+        .right_0()
+        .h(px(220.))
+        .flex()
+        .flex_col()
+        .border_t_1()
+        .border_color(theme.border)
+        .bg(theme.surface)
+        .occlude()
+        .child(
+            div()
+                .flex()
+                .items_center()
+                .gap_3()
+                .px_3()
+                .h_8()
+                .flex_none()
+                .border_b_1()
+                .border_color(theme.border)
+                .text_xs()
+                .text_color(theme.text)
+                .child("Console")
+                .child(
+                    div().flex_1().child(
+                        TextInput::new(filter)
+                            .text_color(theme.text_secondary)
+                            .text_xs()
+                            .border_0(),
+                    ),
+                )
+                .child(
+                    control("console-clear", "Clear", &theme)
+                        .on_click(move |_, window, cx| on_clear(window, cx)),
+                )
+                .child(
+                    control("console-close", "Close", &theme)
+                        .on_click(move |_, window, cx| on_close(window, cx)),
+                ),
+        )
+        .child(
+            div()
+                .id("console-messages")
+                .flex_1()
+                .flex()
+                .flex_col()
+                .overflow_y_scroll()
+                .font_family("monospace")
+                .text_xs()
+                .when(empty, |this| {
+                    this.child(div().px_3().py_2().text_color(theme.text_muted).child(
+                        if query.is_empty() {
+                            "No console messages"
+                        } else {
+                            "No matching messages"
+                        },
+                    ))
+                })
+                .children(messages.map(|message| {
+                    // the file name is enough to tell scripts apart
+                    let file = message.source.rsplit('/').next().unwrap_or_default();
+                    let location = if file.is_empty() {
+                        String::new()
+                    } else {
+                        format!("{}:{}", file, message.line)
+                    };
+
+                    div()
+                        .flex()
+                        .gap_3()
+                        .px_3()
+                        .py_1()
+                        .border_b_1()
+                        // fainter than the panel's own borders
+                        .border_color(with_alpha(theme.border, theme.border.a / 2.))
+                        .text_color(message.level.color(&theme))
+                        .when(message.level == ConsoleLevel::Error, |this| {
+                            this.bg(with_alpha(theme.danger, 0.08))
+                        })
+                        .when(message.level == ConsoleLevel::Warn, |this| {
+                            this.bg(with_alpha(theme.warning, 0.05))
+                        })
+                        .child(div().w(px(36.)).flex_none().child(message.level.label()))
+                        .child(
+                            div()
+                                .flex_1()
+                                .min_w_0()
+                                .child(SharedString::from(message.message.clone())),
+                        )
+                        .child(
+                            div()
+                                .flex_none()
+                                .text_color(theme.text_muted)
+                                .child(location),
+                        )
+                })),
+        )
+}
+
+fn control(id: &'static str, label: &'static str, theme: &Theme) -> gpui::Stateful<gpui::Div> {
+    div()
+        .id(id)
+        .cursor_pointer()
+        .text_color(theme.text_muted)
+        .hover(|this| this.text_color(theme.text))
+        .child(label)
+}
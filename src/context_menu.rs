@@ -0,0 +1,94 @@
+use gpui::{div, prelude::*, px, App, ElementId, IntoElement, Pixels, Point, SharedString, Window};
+use gpui_webview::wef::Frame;
+use serde::Deserialize;
+
+use crate::bridge::TabId;
+use crate::theme::Theme;
+
+// What was under the cursor when the page was right-clicked
+pub struct ContextMenu {
+    pub tab_id: TabId,
+    // Window coordinates the menu is anchored at
+    pub position: Point<Pixels>,
+    // Frame that was clicked, where paste is inserted
+    pub frame: Frame,
+    pub link_url: Option<String>,
+    // Source of the image that was clicked
+    pub image_url: Option<String>,
+    pub selection_text: Option<String>,
+    pub editable: bool,
+    // Whether the clicked field has spell checking on
+    pub spellcheck: bool,
+    pub misspelling: Option<Misspelling>,
+}
+
+// The text field that was right-clicked, as the page reports it
+#[derive(Debug, Clone, Deserialize)]
+pub struct EditableField {
+    // Word under the cursor, empty between words
+    pub word: String,
+    pub spellcheck: bool,
+}
+
+pub struct Misspelling {
+    pub word: String,
+    pub suggestions: Vec<String>,
+}
+
+pub fn menu_item(
+    id: impl Into<ElementId>,
+    label: impl Into<SharedString>,
+    enabled: bool,
+    on_click: impl Fn(&mut Window, &mut App) + 'static,
+) -> impl IntoElement {
+    MenuItem {
+        id: id.into(),
+        label: label.into(),
+        enabled,
+        on_click: Box::new(on_click),
+    }
+}
+
+pub fn separator() -> impl IntoElement {
+    Separator
+}
+
+// Menu rows draw in the theme's colors, which they read when rendered
+#[derive(IntoElement)]
+struct MenuItem {
+    id: ElementId,
+    label: SharedString,
+    enabled: bool,
+    on_click: Box<dyn Fn(&mut Window, &mut App)>,
+}
+
+impl RenderOnce for MenuItem {
+    fn render(self, _: &mut Window, cx: &mut App) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let on_click = self.on_click;
+        div()
+            .id(self.id)
+            .mx_1()
+            .px_2()
+            .py(px(5.))
+            .rounded_sm()
+            .text_xs()
+            .text_color(theme.text)
+            .when(self.enabled, |this| {
+                this.cursor_pointer()
+                    .hover(|this| this.bg(theme.hover))
+                    .on_click(move |_, window, cx| on_click(window, cx))
+            })
+            .when(!self.enabled, |this| this.opacity(0.4))
+            .child(self.label)
+    }
+}
+
+#[derive(IntoElement)]
+struct Separator;
+
+impl RenderOnce for Separator {
+    fn render(self, _: &mut Window, cx: &mut App) -> impl IntoElement {
+        div().my_1().h(px(1.)).bg(cx.global::<Theme>().border)
+    }
+}
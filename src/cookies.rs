@@ -0,0 +1,93 @@
+use futures_util::StreamExt;
+use gpui_webview::wef::{self, CookieManager, RequestContext};
+use std::future::Future;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    // Unix time in seconds, `None` for session cookies
+    pub expires: Option<u64>,
+}
+
+impl From<&wef::Cookie> for Cookie {
+    fn from(cookie: &wef::Cookie) -> Self {
+        Self {
+            name: cookie.name().to_string(),
+            value: cookie.value().to_string(),
+            domain: cookie.domain().to_string(),
+            path: cookie.path().to_string(),
+            expires: cookie.expires().map(|expires| expires.max(0) as u64),
+        }
+    }
+}
+
+// Private windows have their own cookie jar, everything else shares the global one
+fn manager(request_context: Option<&RequestContext>) -> CookieManager {
+    match request_context {
+        Some(request_context) => request_context.cookie_manager(),
+        None => CookieManager::global(),
+    }
+}
+
+// Every cookie that would be sent to `url`, including HTTP-only ones
+//
+// Resolves once CEF has visited all of them.
+pub fn list_cookies(
+    request_context: Option<&RequestContext>,
+    url: &str,
+) -> impl Future<Output = Vec<Cookie>> + 'static {
+    let (tx, rx) = flume::unbounded();
+    manager(request_context).visit_url_cookies(url, true, move |cookie| {
+        _ = tx.send(Cookie::from(cookie));
+        true
+    });
+
+    // CEF drops the visitor, closing the channel, after the last cookie
+    rx.into_stream().collect()
+}
+
+// Delete the cookies for `url`, resolving to how many were removed
+pub fn delete_cookies(
+    request_context: Option<&RequestContext>,
+    url: &str,
+) -> impl Future<Output = usize> + 'static {
+    let (tx, rx) = flume::bounded(1);
+    manager(request_context).delete_cookies(url, "", move |deleted| {
+        _ = tx.send(deleted);
+    });
+    async move { rx.recv_async().await.unwrap_or(0) }
+}
+
+// Delete every cookie in the profile
+pub fn clear_all_cookies(
+    request_context: Option<&RequestContext>,
+) -> impl Future<Output = usize> + 'static {
+    delete_cookies(request_context, "")
+}
+
+// Short relative description of when a cookie expires
+pub fn format_expiry(expires: Option<u64>) -> String {
+    let Some(expires) = expires else {
+        return "Session".to_string();
+    };
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default();
+    let Some(left) = expires.checked_sub(now) else {
+        return "Expired".to_string();
+    };
+
+    const HOUR: u64 = 60 * 60;
+    const DAY: u64 = 24 * HOUR;
+    match left {
+        left if left < HOUR => "Expires within the hour".to_string(),
+        left if left < DAY => format!("Expires in {}h", left / HOUR),
+        left => format!("Expires in {}d", left / DAY),
+    }
+}
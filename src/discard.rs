@@ -0,0 +1,135 @@
+use gpui::{Context, Timer};
+use std::time::Duration;
+
+use crate::settings::BrowserSettings;
+use crate::Main;
+
+// How often each window looks for tabs to discard
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+impl Main {
+    // Check this window's tabs against the discard settings for as long as it's open
+    pub(crate) fn watch_inactive_tabs(cx: &mut Context<Self>) {
+        cx.spawn(async move |this, cx| loop {
+            Timer::after(CHECK_INTERVAL).await;
+            if this
+                .update(cx, |this, cx| this.discard_inactive_tabs(cx))
+                .is_err()
+            {
+                break;
+            }
+        })
+        .detach();
+    }
+
+    // Whether the tab at `ix` can lose its webview without the user losing anything but its
+    // navigation history
+    fn can_discard(&self, ix: usize) -> bool {
+        let tab = &self.tabs[ix];
+        ix != self.active_tab
+            && tab.webview.is_some()
+            && !tab.pinned
+            && !tab.is_loading
+            && !tab.audio_playing
+            && !tab.picture_in_picture
+            && !tab.unsaved_input
+            // a new webview wouldn't get the tab's own user agent
+            && tab.user_agent.is_none()
+            && tab.certificate_error.is_none()
+            && self
+                .split_view
+                .as_ref()
+                .is_none_or(|split| split.tab_id != tab.id)
+            && self
+                .read_aloud
+                .as_ref()
+                .is_none_or(|read_aloud| read_aloud.tab_id != tab.id)
+            && !self
+                .permission_prompts
+                .iter()
+                .any(|prompt| prompt.tab_id == tab.id)
+            && self
+                .auth_prompt
+                .as_ref()
+                .is_none_or(|prompt| prompt.request.tab_id != tab.id)
+    }
+
+    // Discard tabs left alone longer than the idle timeout, then the least recently used ones
+    // while the pages' reported memory is over the limit
+    fn discard_inactive_tabs(&mut self, cx: &mut Context<Self>) {
+        let settings = cx.global::<BrowserSettings>();
+        let idle_timeout = settings
+            .discard_after_minutes
+            .map(|minutes| Duration::from_secs(u64::from(minutes) * 60));
+        let memory_limit = settings
+            .discard_memory_limit_mb
+            .map(|megabytes| megabytes * 1024 * 1024);
+
+        if let Some(idle_timeout) = idle_timeout {
+            for ix in 0..self.tabs.len() {
+                if self.can_discard(ix) && self.tabs[ix].last_active.elapsed() >= idle_timeout {
+                    self.discard_tab(ix, cx);
+                }
+            }
+        }
+
+        if let Some(memory_limit) = memory_limit {
+            let reported = |tab: &crate::TabState| {
+                tab.usage
+                    .and_then(|usage| usage.js_heap_used)
+                    .unwrap_or_default()
+            };
+            let mut total: u64 = self
+                .tabs
+                .iter()
+                .filter(|tab| tab.webview.is_some())
+                .map(reported)
+                .sum();
+            let mut candidates: Vec<_> = (0..self.tabs.len())
+                .filter(|ix| self.can_discard(*ix))
+                .collect();
+            candidates.sort_by_key(|ix| self.tabs[*ix].last_active);
+            for ix in candidates {
+                if total <= memory_limit {
+                    break;
+                }
+                total -= reported(&self.tabs[ix]);
+                self.discard_tab(ix, cx);
+            }
+            // fresh numbers for the next check
+            self.sample_tab_usage(cx);
+        }
+    }
+
+    // Drop the tab's webview, keeping what's needed to load it again where it was left. It
+    // becomes a placeholder like a restored tab, reloaded by `ensure_loaded` when activated.
+    pub(crate) fn discard_tab(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let tab = &mut self.tabs[ix];
+        if tab.webview.take().is_none() {
+            return;
+        }
+        log::debug!("Discarding inactive tab {}", tab.url);
+        tab._subscriptions.clear();
+        if let Some(streams) = tab.streams.take() {
+            streams.cancel_all();
+        }
+        tab.main_frame = None;
+        tab.pending_scroll = Some(tab.scroll_position).filter(|position| *position != (0., 0.));
+        // the new webview starts with an empty history, at the default zoom with scripts on
+        tab.history_index = 0;
+        tab.scroll_positions.clear();
+        tab.pending_history_step = None;
+        tab.zoom_level = 1.0;
+        tab.zoom_host = None;
+        tab.javascript_enabled = true;
+        tab.usage = None;
+        tab.readable = false;
+        tab.reader = None;
+        tab.console.clear();
+        tab.blocked_count = 0;
+        tab.plugins.clear();
+        // the page is loaded again, certificate check and all
+        tab.certificate_bypassed = None;
+        cx.notify();
+    }
+}
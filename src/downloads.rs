@@ -0,0 +1,715 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use futures_util::AsyncReadExt;
+use gpui::http_client::{AsyncBody, HttpClient};
+use gpui::{App, AppContext, Context, Entity, Global, Task};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use url::Url;
+
+use crate::settings::BrowserSettings;
+use crate::storage;
+use crate::toast::{toast_error, toast_success};
+
+const DOWNLOADS_FILE: &str = "downloads.json";
+
+pub type DownloadId = usize;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum DownloadState {
+    InProgress,
+    Completed,
+    Cancelled,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Download {
+    pub id: DownloadId,
+    pub url: String,
+    pub filename: String,
+    pub path: PathBuf,
+    pub bytes_received: u64,
+    // `None` when the server didn't report a content length
+    pub total_bytes: Option<u64>,
+    pub state: DownloadState,
+    // Content type the server sent, e.g. "application/pdf"
+    #[serde(default)]
+    pub mime_type: Option<String>,
+    // Started in a private window: listed only in private windows and never saved
+    #[serde(skip)]
+    pub private: bool,
+}
+
+impl Download {
+    // Fraction downloaded, `None` while the total size is unknown
+    pub fn progress(&self) -> Option<f32> {
+        match self.state {
+            DownloadState::Completed => Some(1.0),
+            _ => self
+                .total_bytes
+                .filter(|total| *total > 0)
+                .map(|total| (self.bytes_received as f32 / total as f32).min(1.0)),
+        }
+    }
+
+    // Programs and scripts, which are never opened automatically
+    pub fn is_executable(&self) -> bool {
+        is_executable(&self.path, self.mime_type.as_deref())
+    }
+
+    // Size so far, or why the download stopped
+    pub fn status(&self) -> String {
+        match &self.state {
+            DownloadState::InProgress => match self.total_bytes {
+                Some(total) => format!(
+                    "{} of {}",
+                    format_bytes(self.bytes_received),
+                    format_bytes(total)
+                ),
+                None => format_bytes(self.bytes_received),
+            },
+            DownloadState::Completed => format_bytes(self.bytes_received),
+            DownloadState::Cancelled => "Cancelled".to_string(),
+            DownloadState::Failed(err) => format!("Failed: {}", err),
+        }
+    }
+}
+
+// Progress reported by the background transfer
+enum TransferUpdate {
+    Started {
+        total_bytes: Option<u64>,
+        mime_type: Option<String>,
+    },
+    Received(u64),
+}
+
+struct GlobalDownloads(Entity<DownloadManager>);
+
+impl Global for GlobalDownloads {}
+
+// Downloads shared by all windows, with completed ones kept across runs
+pub struct DownloadManager {
+    downloads: Vec<Download>,
+    next_id: DownloadId,
+    tasks: Vec<(DownloadId, Task<()>)>,
+}
+
+impl DownloadManager {
+    pub fn init(cx: &mut App) {
+        let downloads = storage::load_json::<Vec<Download>>(DOWNLOADS_FILE)
+            .unwrap_or_else(|err| {
+                log::error!("Failed to load downloads: {}", err);
+                None
+            })
+            .unwrap_or_default();
+        let next_id = downloads.iter().map(|download| download.id + 1).max();
+
+        let manager = cx.new(|_| Self {
+            next_id: next_id.unwrap_or_default(),
+            downloads,
+            tasks: Vec::new(),
+        });
+        cx.set_global(GlobalDownloads(manager));
+    }
+
+    pub fn global(cx: &App) -> Entity<Self> {
+        cx.global::<GlobalDownloads>().0.clone()
+    }
+
+    // Downloads of private windows when `private`, of the others otherwise, most recent first
+    pub fn downloads(&self, private: bool) -> impl Iterator<Item = &Download> {
+        self.downloads
+            .iter()
+            .rev()
+            .filter(move |download| download.private == private)
+    }
+
+    pub fn active_count(&self, private: bool) -> usize {
+        self.downloads(private)
+            .filter(|download| download.state == DownloadState::InProgress)
+            .count()
+    }
+
+    // Start downloading `url` into the downloads directory
+    pub fn start(
+        &mut self,
+        url: &str,
+        suggested_name: Option<&str>,
+        private: bool,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(dir) = cx.global::<BrowserSettings>().download_dir() else {
+            log::warn!("No download directory available for {}", url);
+            return;
+        };
+
+        // the transfer creates the file later, so names of running downloads are taken too
+        let reserved: Vec<&Path> = self
+            .downloads
+            .iter()
+            .filter(|download| download.state == DownloadState::InProgress)
+            .map(|download| download.path.as_path())
+            .collect();
+        let path = unique_path_excluding(&dir, &filename_for(url, suggested_name), &reserved);
+        self.save_as(url, path, private, cx);
+    }
+
+    // Download `url` into `path`, the file picked in a save dialog
+    pub fn save_as(&mut self, url: &str, path: PathBuf, private: bool, cx: &mut Context<Self>) {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "download".to_string());
+        // an embedded file would be stored whole in the downloads list
+        let recorded_url = match url.split_once(',') {
+            Some((header, _)) if url.starts_with("data:") => format!("{},…", header),
+            _ => url.to_string(),
+        };
+        self.downloads.push(Download {
+            id,
+            url: recorded_url,
+            filename: filename.clone(),
+            path: path.clone(),
+            bytes_received: 0,
+            total_bytes: None,
+            state: DownloadState::InProgress,
+            mime_type: None,
+            private,
+        });
+
+        let (tx, rx) = flume::unbounded();
+        let transfer = cx.background_spawn(transfer(cx.http_client(), url.to_string(), path, tx));
+        let task = cx.spawn(async move |this, cx| {
+            while let Ok(update) = rx.recv_async().await {
+                let updated = this.update(cx, |this, cx| {
+                    this.update_download(id, cx, |download| match update {
+                        TransferUpdate::Started {
+                            total_bytes,
+                            mime_type,
+                        } => {
+                            download.total_bytes = total_bytes;
+                            download.mime_type = mime_type;
+                        }
+                        TransferUpdate::Received(bytes) => download.bytes_received += bytes,
+                    })
+                });
+                if updated.is_err() {
+                    return;
+                }
+            }
+
+            let result = transfer.await;
+            this.update(cx, |this, cx| {
+                this.tasks.retain(|(task_id, _)| *task_id != id);
+                match &result {
+                    Ok(()) => toast_success(cx, format!("Downloaded {}", filename)),
+                    Err(_) => toast_error(cx, format!("Download of {} failed", filename)),
+                }
+                this.update_download(id, cx, |download| {
+                    download.state = match result {
+                        Ok(()) => DownloadState::Completed,
+                        Err(err) => DownloadState::Failed(err.to_string()),
+                    }
+                });
+                this.save();
+                this.run_completion_action(id, cx);
+            })
+            .ok();
+        });
+        self.tasks.push((id, task));
+        cx.notify();
+    }
+
+    // Open the finished file or its folder, as the download rules say for its type
+    fn run_completion_action(&self, id: DownloadId, cx: &mut Context<Self>) {
+        let Some(download) = self
+            .downloads
+            .iter()
+            .find(|download| download.id == id && download.state == DownloadState::Completed)
+        else {
+            return;
+        };
+        let rules = &cx.global::<BrowserSettings>().download_rules;
+        match completion_action(rules, download) {
+            DownloadAction::Nothing => {}
+            DownloadAction::Open => cx.open_with_system(&download.path),
+            DownloadAction::ShowInFolder => cx.reveal_path(&download.path),
+        }
+    }
+
+    // Stop an in-progress download and remove its partial file
+    pub fn cancel(&mut self, id: DownloadId, cx: &mut Context<Self>) {
+        // dropping the task cancels the transfer
+        self.tasks.retain(|(task_id, _)| *task_id != id);
+        self.update_download(id, cx, |download| {
+            if download.state == DownloadState::InProgress {
+                download.state = DownloadState::Cancelled;
+                _ = std::fs::remove_file(&download.path);
+            }
+        });
+        self.save();
+    }
+
+    // Forget finished downloads of private windows, or of the others, keeping the files on disk
+    pub fn clear_finished(&mut self, private: bool, cx: &mut Context<Self>) {
+        self.downloads.retain(|download| {
+            download.private != private || download.state == DownloadState::InProgress
+        });
+        self.save();
+        cx.notify();
+    }
+
+    fn update_download(
+        &mut self,
+        id: DownloadId,
+        cx: &mut Context<Self>,
+        f: impl FnOnce(&mut Download),
+    ) {
+        if let Some(download) = self.downloads.iter_mut().find(|download| download.id == id) {
+            f(download);
+            cx.notify();
+        }
+    }
+
+    // Persist finished downloads, in-progress ones can't be resumed and private ones stay off
+    // the disk
+    fn save(&self) {
+        let finished: Vec<_> = self
+            .downloads
+            .iter()
+            .filter(|download| download.state != DownloadState::InProgress && !download.private)
+            .collect();
+        if let Err(err) = storage::save_json(DOWNLOADS_FILE, &finished) {
+            log::error!("Failed to save downloads: {}", err);
+        }
+    }
+}
+
+// Stream the response body for `url` into `path`, reporting progress as it goes
+async fn transfer(
+    client: Arc<dyn HttpClient>,
+    url: String,
+    path: PathBuf,
+    updates: flume::Sender<TransferUpdate>,
+) -> Result<()> {
+    // the bytes are in the URL itself, e.g. images embedded in the page
+    if url.starts_with("data:") {
+        let bytes = decode_data_url(&url)?;
+        let mime_type = url
+            .strip_prefix("data:")
+            .and_then(|rest| rest.split([';', ',']).next())
+            .filter(|mime| !mime.is_empty())
+            .map(str::to_string);
+        _ = updates.send(TransferUpdate::Started {
+            total_bytes: Some(bytes.len() as u64),
+            mime_type,
+        });
+        std::fs::write(&path, &bytes)?;
+        _ = updates.send(TransferUpdate::Received(bytes.len() as u64));
+        return Ok(());
+    }
+
+    let mut response = client.get(&url, AsyncBody::empty(), true).await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("server returned {}", response.status()));
+    }
+
+    let total_bytes = response
+        .headers()
+        .get("content-length")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok());
+    let mime_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase())
+        .filter(|mime| !mime.is_empty());
+    _ = updates.send(TransferUpdate::Started {
+        total_bytes,
+        mime_type,
+    });
+
+    let mut file = File::create(&path)?;
+    let body = response.body_mut();
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        let read = body.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read])?;
+        _ = updates.send(TransferUpdate::Received(read as u64));
+    }
+    file.flush()?;
+    Ok(())
+}
+
+// What happens once a download of some type finishes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadAction {
+    #[default]
+    Nothing,
+    Open,
+    ShowInFolder,
+}
+
+// A download rule, matching a file extension like "pdf", or a MIME type like
+// "application/pdf" or "image/*"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadRule {
+    pub pattern: String,
+    pub action: DownloadAction,
+}
+
+impl DownloadRule {
+    // `None` for patterns that are neither an extension nor a MIME type
+    pub fn new(pattern: &str, action: DownloadAction) -> Option<Self> {
+        let pattern = pattern
+            .trim()
+            .trim_start_matches("*.")
+            .trim_start_matches('.');
+        let valid = !pattern.is_empty()
+            && pattern
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "/*+-._".contains(c));
+        valid.then(|| Self {
+            pattern: pattern.to_ascii_lowercase(),
+            action,
+        })
+    }
+
+    fn matches(&self, path: &Path, mime_type: Option<&str>) -> bool {
+        match self.pattern.split_once('/') {
+            Some((kind, "*")) => mime_type
+                .and_then(|mime| mime.split_once('/'))
+                .is_some_and(|(mime_kind, _)| mime_kind == kind),
+            Some(_) => mime_type == Some(self.pattern.as_str()),
+            None => extension(path).as_deref() == Some(self.pattern.as_str()),
+        }
+    }
+}
+
+// Extensions of programs, installers, desktop launchers and scripts the system would run when
+// opened, plus pages like HTML and SVG whose scripts run in the browser that opens them
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "app",
+    "appimage",
+    "applescript",
+    "apk",
+    "appx",
+    "bash",
+    "bat",
+    "bin",
+    "cmd",
+    "com",
+    "command",
+    "cpl",
+    "deb",
+    "desktop",
+    "dmg",
+    "exe",
+    "fish",
+    "hta",
+    "htm",
+    "html",
+    "jar",
+    "js",
+    "jse",
+    "ksh",
+    "lnk",
+    "mht",
+    "mhtml",
+    "msi",
+    "msix",
+    "php",
+    "pkg",
+    "pl",
+    "ps1",
+    "psm1",
+    "py",
+    "pyw",
+    "rb",
+    "reg",
+    "rpm",
+    "run",
+    "scpt",
+    "scr",
+    "sh",
+    "shtml",
+    "svg",
+    "svgz",
+    "url",
+    "vbe",
+    "vbs",
+    "ws",
+    "wsf",
+    "xht",
+    "xhtml",
+    "zsh",
+];
+const EXECUTABLE_MIME_TYPES: &[&str] = &[
+    "application/vnd.microsoft.portable-executable",
+    "application/x-apple-diskimage",
+    "application/x-msdownload",
+    "application/x-msi",
+    "application/x-executable",
+    "application/x-sh",
+    "application/x-shellscript",
+    "application/x-desktop",
+    "application/x-perl",
+    "application/x-python",
+    "text/x-python",
+    "text/html",
+    "application/xhtml+xml",
+    "image/svg+xml",
+    "application/java-archive",
+    "application/vnd.android.package-archive",
+];
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_ascii_lowercase())
+}
+
+pub fn is_executable(path: &Path, mime_type: Option<&str>) -> bool {
+    extension(path).is_some_and(|extension| EXECUTABLE_EXTENSIONS.contains(&extension.as_str()))
+        || mime_type.is_some_and(|mime| EXECUTABLE_MIME_TYPES.contains(&mime))
+}
+
+// The first matching rule's action, nothing for types without one, and never opening
+// executables whatever the rules say
+pub fn completion_action(rules: &[DownloadRule], download: &Download) -> DownloadAction {
+    let action = rules
+        .iter()
+        .find(|rule| rule.matches(&download.path, download.mime_type.as_deref()))
+        .map_or(DownloadAction::Nothing, |rule| rule.action);
+    match action {
+        DownloadAction::Open if download.is_executable() => DownloadAction::Nothing,
+        action => action,
+    }
+}
+
+// Contents of a `data:` URL, base64 or percent-encoded
+fn decode_data_url(url: &str) -> Result<Vec<u8>> {
+    let (header, data) = url
+        .strip_prefix("data:")
+        .and_then(|rest| rest.split_once(','))
+        .ok_or_else(|| anyhow!("malformed data URL"))?;
+    let data = percent_decode(data);
+    if header.ends_with(";base64") {
+        // line breaks and spaces are allowed in the encoded data
+        let data: Vec<u8> = data
+            .into_iter()
+            .filter(|byte| !byte.is_ascii_whitespace())
+            .collect();
+        Ok(BASE64.decode(data)?)
+    } else {
+        Ok(data)
+    }
+}
+
+fn percent_decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+// `image.png` and the like for a `data:` URL, which has no path to take a name from
+fn data_url_filename(url: &str) -> Option<String> {
+    let mime = url.strip_prefix("data:")?.split([';', ',']).next()?;
+    let extension = match mime {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        "text/plain" => "txt",
+        "text/html" => "html",
+        "application/pdf" => "pdf",
+        _ => "bin",
+    };
+    let stem = mime.split('/').next().filter(|stem| !stem.is_empty());
+    Some(format!("{}.{}", stem.unwrap_or("download"), extension))
+}
+
+// Suggested name, else the last URL path segment, stripped of path separators
+pub fn filename_for(url: &str, suggested_name: Option<&str>) -> String {
+    let from_url = Url::parse(url).ok().and_then(|url| {
+        url.path_segments()
+            .and_then(|mut segments| segments.next_back().map(str::to_string))
+    });
+    let from_url = data_url_filename(url).or(from_url);
+
+    suggested_name
+        .map(str::to_string)
+        .filter(|name| !name.trim().is_empty())
+        .or(from_url.filter(|name| !name.is_empty()))
+        .map(|name| name.replace(['/', '\\'], "_"))
+        .unwrap_or_else(|| "download".to_string())
+}
+
+// `dir/name`, or `dir/name (n).ext` if that file already exists
+pub fn unique_path(dir: &Path, filename: &str) -> PathBuf {
+    unique_path_excluding(dir, filename, &[])
+}
+
+// Like `unique_path`, also skipping `reserved` paths that don't exist yet
+fn unique_path_excluding(dir: &Path, filename: &str, reserved: &[&Path]) -> PathBuf {
+    let is_free = |path: &Path| !path.exists() && !reserved.contains(&path);
+    let path = dir.join(filename);
+    if is_free(&path) {
+        return path;
+    }
+
+    let (stem, extension) = match filename.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+        _ => (filename, None),
+    };
+    (1..)
+        .map(|n| match extension {
+            Some(extension) => dir.join(format!("{} ({}).{}", stem, n, extension)),
+            None => dir.join(format!("{} ({})", stem, n)),
+        })
+        .find(|path| is_free(path))
+        .unwrap_or(path)
+}
+
+// e.g. "1.4 MB"
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn download(filename: &str, mime_type: Option<&str>) -> Download {
+        Download {
+            id: 0,
+            url: format!("https://example.com/{}", filename),
+            filename: filename.to_string(),
+            path: PathBuf::from("/tmp").join(filename),
+            bytes_received: 0,
+            total_bytes: None,
+            state: DownloadState::Completed,
+            mime_type: mime_type.map(str::to_string),
+            private: false,
+        }
+    }
+
+    fn rule(pattern: &str, action: DownloadAction) -> DownloadRule {
+        DownloadRule::new(pattern, action).unwrap()
+    }
+
+    #[test]
+    fn nothing_without_a_matching_rule() {
+        let rules = [rule("pdf", DownloadAction::Open)];
+        let zip = download("archive.zip", Some("application/zip"));
+        assert_eq!(completion_action(&rules, &zip), DownloadAction::Nothing);
+        assert_eq!(completion_action(&[], &zip), DownloadAction::Nothing);
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules = [
+            rule("image/*", DownloadAction::ShowInFolder),
+            rule("png", DownloadAction::Open),
+        ];
+        let png = download("photo.PNG", Some("image/png"));
+        assert_eq!(
+            completion_action(&rules, &png),
+            DownloadAction::ShowInFolder
+        );
+    }
+
+    #[test]
+    fn matches_extensions_and_mime_types() {
+        let rules = [
+            rule("*.pdf", DownloadAction::Open),
+            rule("text/csv", DownloadAction::ShowInFolder),
+        ];
+        let pdf = download("paper.pdf", None);
+        let csv = download("export", Some("text/csv"));
+        assert_eq!(completion_action(&rules, &pdf), DownloadAction::Open);
+        assert_eq!(
+            completion_action(&rules, &csv),
+            DownloadAction::ShowInFolder
+        );
+    }
+
+    #[test]
+    fn never_opens_executables() {
+        for (filename, mime_type, pattern) in [
+            ("setup.exe", None, "exe"),
+            ("run.desktop", None, "desktop"),
+            ("tool.py", None, "py"),
+            ("page.html", None, "html"),
+            ("logo.svg", Some("image/svg+xml"), "image/*"),
+        ] {
+            let rules = [rule(pattern, DownloadAction::Open)];
+            let file = download(filename, mime_type);
+            assert_eq!(
+                completion_action(&rules, &file),
+                DownloadAction::Nothing,
+                "{}",
+                filename
+            );
+        }
+    }
+
+    #[test]
+    fn executables_may_still_be_shown_in_folder() {
+        let rules = [rule("exe", DownloadAction::ShowInFolder)];
+        let exe = download("setup.exe", None);
+        assert_eq!(
+            completion_action(&rules, &exe),
+            DownloadAction::ShowInFolder
+        );
+    }
+
+    #[test]
+    fn executable_mime_type_blocks_opening() {
+        let rules = [rule("application/*", DownloadAction::Open)];
+        let script = download("install", Some("application/x-sh"));
+        assert_eq!(completion_action(&rules, &script), DownloadAction::Nothing);
+    }
+}
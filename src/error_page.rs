@@ -0,0 +1,105 @@
+use gpui::{div, prelude::*, px, rgb, rgba, svg, App, IntoElement, SharedString, Window};
+
+// Chromium's net error for a navigation cancelled by the user or replaced by another
+pub const ERR_ABORTED: i32 = -3;
+// Chromium's net error for a server that wasn't given acceptable credentials
+pub const ERR_INVALID_AUTH_CREDENTIALS: i32 = -338;
+
+// A failed main-frame navigation
+#[derive(Debug, Clone)]
+pub struct LoadError {
+    pub url: SharedString,
+    pub code: i32,
+    pub reason: SharedString,
+}
+
+impl LoadError {
+    pub fn new(url: &str, code: i32, error_text: &str) -> Self {
+        let reason = friendly_reason(code)
+            .map(SharedString::from)
+            .unwrap_or_else(|| SharedString::from(error_text.to_string()));
+
+        Self {
+            url: SharedString::from(url.to_string()),
+            code,
+            reason,
+        }
+    }
+}
+
+// Human-readable text for common Chromium net error codes
+fn friendly_reason(code: i32) -> Option<&'static str> {
+    Some(match code {
+        -6 => "The file could not be found.",
+        -7 | -118 => "The server took too long to respond.",
+        -21 => "The network connection changed while loading.",
+        -100 | -101 | -324 => "The server closed the connection unexpectedly.",
+        -102 => "The server refused the connection.",
+        -105 | -137 => "The server's address could not be found.",
+        -106 => "You are offline. Check your internet connection.",
+        -109 => "The server address is unreachable.",
+        -200..=-299 => "The site's security certificate is not trusted.",
+        -300 => "The address is not valid.",
+        -310 => "The page redirected too many times.",
+        -338 => "The site requires you to sign in.",
+        _ => return None,
+    })
+}
+
+// Full-area error page rendered in place of the webview
+pub fn render_error_page(
+    error: &LoadError,
+    on_retry: impl Fn(&mut Window, &mut App) + 'static,
+) -> impl IntoElement {
+    div()
+        .absolute()
+        .inset_0()
+        .flex()
+        .flex_col()
+        .items_center()
+        .justify_center()
+        .gap_3()
+        .bg(rgb(0x0a0a0a))
+        .child(
+            svg()
+                .path("globe.svg")
+                .size(px(32.))
+                .text_color(rgba(0xffffff66)),
+        )
+        .child(
+            div()
+                .text_lg()
+                .text_color(rgb(0xf2f2f2))
+                .child("This page can't be reached"),
+        )
+        .child(
+            div()
+                .text_sm()
+                .text_color(rgb(0xa1a1a1))
+                .child(error.reason.clone()),
+        )
+        .child(
+            div()
+                .max_w(px(480.))
+                .truncate()
+                .text_xs()
+                .text_color(rgb(0x7a7a7a))
+                .child(format!("{} (error {})", error.url, error.code)),
+        )
+        .child(
+            div()
+                .id("retry")
+                .mt_2()
+                .px_3()
+                .py_1()
+                .rounded_md()
+                .border_1()
+                .border_color(rgba(0xd3d9d92b))
+                .text_xs()
+                .text_color(rgb(0xf2f2f2))
+                .cursor_pointer()
+                .hover(|this| this.bg(rgba(0xffffff10)))
+                .on_click(move |_, window, cx| on_retry(window, cx))
+                .child("Try again"),
+        )
+}
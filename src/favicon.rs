@@ -0,0 +1,76 @@
+use anyhow::{anyhow, Result};
+use futures_util::AsyncReadExt;
+use gpui::http_client::{AsyncBody, HttpClient};
+use std::io::Cursor;
+use std::sync::Arc;
+use url::Url;
+
+// Icons are downscaled to this size before being handed to gpui
+const ICON_SIZE: u32 = 32;
+
+// A fetched icon, cached by icon URL
+#[derive(Clone)]
+pub enum FaviconState {
+    // Being downloaded, so other tabs showing it don't fetch it again
+    Loading,
+    Loaded(Arc<gpui::Image>),
+    // Failed to download or decode
+    Missing,
+}
+
+impl FaviconState {
+    pub fn image(&self) -> Option<Arc<gpui::Image>> {
+        match self {
+            FaviconState::Loaded(image) => Some(image.clone()),
+            FaviconState::Loading | FaviconState::Missing => None,
+        }
+    }
+}
+
+// Host of `page_url` together with the conventional favicon location for it
+pub fn favicon_url(page_url: &str) -> Option<(String, String)> {
+    page_icon_url(page_url, None)
+}
+
+// Host of `page_url` and the icon it declared, falling back to `/favicon.ico` when it declared
+// none or one that can't be downloaded
+pub fn page_icon_url(page_url: &str, declared: Option<&str>) -> Option<(String, String)> {
+    let url = Url::parse(page_url).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+
+    let host = url.host_str()?.to_string();
+    let icon_url = declared
+        .and_then(|declared| url.join(declared).ok())
+        .filter(|icon| matches!(icon.scheme(), "http" | "https"))
+        .or_else(|| url.join("/favicon.ico").ok())?;
+    Some((host, icon_url.to_string()))
+}
+
+// Whether `page_url` is on `host`, so an icon fetched for a page there still belongs to it
+pub fn is_on_host(page_url: &str, host: &str) -> bool {
+    Url::parse(page_url)
+        .ok()
+        .is_some_and(|url| url.host_str() == Some(host))
+}
+
+// Download and decode a favicon (ico, png, ...) into a PNG gpui image
+pub async fn fetch(client: Arc<dyn HttpClient>, icon_url: String) -> Result<Arc<gpui::Image>> {
+    let mut response = client.get(&icon_url, AsyncBody::empty(), true).await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("{} returned {}", icon_url, response.status()));
+    }
+
+    let mut bytes = Vec::new();
+    response.body_mut().read_to_end(&mut bytes).await?;
+
+    let icon = image::load_from_memory(&bytes)?.thumbnail(ICON_SIZE, ICON_SIZE);
+    let mut png = Vec::new();
+    icon.write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)?;
+
+    Ok(Arc::new(gpui::Image::from_bytes(
+        gpui::ImageFormat::Png,
+        png,
+    )))
+}
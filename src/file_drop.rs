@@ -0,0 +1,97 @@
+use gpui::{div, prelude::*, px, Context, ExternalPaths, IntoElement, Window};
+
+use crate::address;
+use crate::bridge::TabId;
+use crate::theme::Theme;
+use crate::Main;
+
+fn file_urls(paths: &ExternalPaths) -> Vec<String> {
+    paths
+        .paths()
+        .iter()
+        .filter_map(|path| address::file_url(path))
+        .collect()
+}
+
+impl Main {
+    // Open each dropped file or folder in a new tab, showing the first one
+    pub(crate) fn open_dropped_paths(
+        &mut self,
+        paths: &ExternalPaths,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let mut first = None;
+        for url in file_urls(paths) {
+            let ix = self.insert_tab(self.tabs.len(), &url, window, cx);
+            first.get_or_insert(ix);
+        }
+        if let Some(ix) = first {
+            self.activate_tab(ix, window, cx);
+        }
+    }
+
+    // A drop onto a tab chip loads the first file in that tab, the others open after it
+    pub(crate) fn drop_paths_on_tab(
+        &mut self,
+        tab_id: TabId,
+        paths: &ExternalPaths,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ix) = self.tabs.iter().position(|tab| tab.id == tab_id) else {
+            return;
+        };
+        let mut urls = file_urls(paths).into_iter();
+        let Some(first) = urls.next() else {
+            return;
+        };
+        for (offset, url) in urls.enumerate() {
+            self.insert_tab(ix + 1 + offset, &url, window, cx);
+        }
+        let ix = self
+            .tabs
+            .iter()
+            .position(|tab| tab.id == tab_id)
+            .unwrap_or(ix);
+        self.activate_tab(ix, window, cx);
+        self.navigate(&first, window, cx);
+    }
+
+    // Covers the page while files are dragged over the window. It takes no mouse events of its
+    // own, so it's only visible and only receives the drop during a file drag.
+    pub(crate) fn render_drop_zone(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+
+        div()
+            .id("drop-zone")
+            .group("drop-zone")
+            .absolute()
+            .inset_0()
+            .drag_over::<ExternalPaths>(move |style, _, _, _| {
+                style
+                    .bg(theme.surface)
+                    .border_2()
+                    .border_color(theme.accent)
+            })
+            .on_drop(cx.listener(|this, paths: &ExternalPaths, window, cx| {
+                this.open_dropped_paths(paths, window, cx)
+            }))
+            .flex()
+            .items_center()
+            .justify_center()
+            .child(
+                div()
+                    .invisible()
+                    .group_drag_over::<ExternalPaths>("drop-zone", |style| style.visible())
+                    .px_4()
+                    .py_2()
+                    .rounded_md()
+                    .bg(theme.hover)
+                    .text_sm()
+                    .text_color(theme.text)
+                    .max_w(px(320.))
+                    .child("Drop to open in a new tab"),
+            )
+    }
+}
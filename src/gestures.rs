@@ -0,0 +1,214 @@
+use gpui::{
+    div, prelude::*, px, rgb, rgba, svg, App, IntoElement, ScrollDelta, ScrollWheelEvent,
+    TouchPhase, Window,
+};
+use std::time::{Duration, Instant};
+
+use crate::widgets::svg_button;
+
+// Horizontal travel of a two-finger swipe that navigates
+const SWIPE_THRESHOLD: f32 = 120.;
+// Swipes are ignored for this long after one navigated, so trailing events of the same
+// gesture don't go back twice
+const SWIPE_COOLDOWN: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Back,
+    Forward,
+}
+
+struct Swipe {
+    // Horizontal travel so far, positive towards going back
+    delta: f32,
+    // Moved more vertically than horizontally, so it's scrolling the page
+    vertical: bool,
+    navigated: bool,
+}
+
+// Follows trackpad scroll gestures and turns horizontal ones into back/forward navigation
+#[derive(Default)]
+pub struct SwipeTracker {
+    swipe: Option<Swipe>,
+    last_navigation: Option<Instant>,
+}
+
+impl SwipeTracker {
+    // Feed a scroll event, returning the direction to navigate once a swipe goes far enough.
+    // Directions there's no history for are never returned nor shown.
+    pub fn on_scroll(
+        &mut self,
+        event: &ScrollWheelEvent,
+        can_go_back: bool,
+        can_go_forward: bool,
+    ) -> Option<SwipeDirection> {
+        // mouse wheels scroll by lines and never swipe
+        let ScrollDelta::Pixels(delta) = event.delta else {
+            return None;
+        };
+
+        match event.touch_phase {
+            TouchPhase::Started => {
+                self.swipe = Some(Swipe {
+                    delta: 0.,
+                    vertical: false,
+                    navigated: false,
+                });
+                None
+            }
+            TouchPhase::Ended => {
+                self.swipe = None;
+                None
+            }
+            TouchPhase::Moved => {
+                // momentum events after the fingers lifted don't belong to a swipe
+                let swipe = self.swipe.as_mut()?;
+                let (dx, dy) = (f32::from(delta.x), f32::from(delta.y));
+                if swipe.delta == 0. && dy.abs() > dx.abs() {
+                    swipe.vertical = true;
+                }
+                if swipe.vertical || swipe.navigated {
+                    return None;
+                }
+
+                swipe.delta += dx;
+                let direction = if swipe.delta > 0. {
+                    SwipeDirection::Back
+                } else {
+                    SwipeDirection::Forward
+                };
+                let allowed = match direction {
+                    SwipeDirection::Back => can_go_back,
+                    SwipeDirection::Forward => can_go_forward,
+                };
+                let cooling_down = self
+                    .last_navigation
+                    .is_some_and(|at| at.elapsed() < SWIPE_COOLDOWN);
+                if !allowed {
+                    swipe.delta = 0.;
+                    return None;
+                }
+                if swipe.delta.abs() < SWIPE_THRESHOLD || cooling_down {
+                    return None;
+                }
+
+                swipe.navigated = true;
+                self.last_navigation = Some(Instant::now());
+                Some(direction)
+            }
+        }
+    }
+
+    // Direction and progress towards the threshold of the swipe in progress, for the indicator
+    pub fn progress(&self) -> Option<(SwipeDirection, f32)> {
+        let swipe = self.swipe.as_ref().filter(|swipe| swipe.delta != 0.)?;
+        let direction = if swipe.delta > 0. {
+            SwipeDirection::Back
+        } else {
+            SwipeDirection::Forward
+        };
+        let progress = if swipe.navigated {
+            1.
+        } else {
+            (swipe.delta.abs() / SWIPE_THRESHOLD).min(1.)
+        };
+        Some((direction, progress))
+    }
+}
+
+// Arrow sliding in from the edge the page is being swiped away from
+pub fn render_swipe_indicator(direction: SwipeDirection, progress: f32) -> impl IntoElement {
+    const SIZE: f32 = 40.;
+    let offset = px(-SIZE + (SIZE + 16.) * progress);
+    let (icon, color) = if progress >= 1. {
+        (rgb(0x0a0a0a), rgb(0xf2f2f2))
+    } else {
+        (rgb(0xf2f2f2), rgba(0x181818e6))
+    };
+
+    div()
+        .absolute()
+        .top_0()
+        .bottom_0()
+        .flex()
+        .items_center()
+        .map(|this| match direction {
+            SwipeDirection::Back => this.left(offset),
+            SwipeDirection::Forward => this.right(offset),
+        })
+        .child(
+            div()
+                .size(px(SIZE))
+                .flex()
+                .items_center()
+                .justify_center()
+                .rounded_full()
+                .border_1()
+                .border_color(rgba(0xd3d9d92b))
+                .bg(color)
+                .opacity(0.4 + 0.6 * progress)
+                .child(
+                    svg()
+                        .path(match direction {
+                            SwipeDirection::Back => "back.svg",
+                            SwipeDirection::Forward => "forward.svg",
+                        })
+                        .size(px(16.))
+                        .text_color(icon),
+                ),
+        )
+}
+
+// Hover zone along the page's left or right edge, showing a round back or forward button
+// while the pointer is inside. Only the shown button occludes, so the page still gets clicks
+// and hovers along the edge.
+pub fn render_edge_button(
+    direction: SwipeDirection,
+    shown: bool,
+    on_hover: impl Fn(&bool, &mut Window, &mut App) + 'static,
+    on_click: impl Fn(&mut Window, &mut App) + 'static,
+) -> impl IntoElement {
+    const ZONE_WIDTH: f32 = 56.;
+
+    div()
+        .id(match direction {
+            SwipeDirection::Back => "edge-back",
+            SwipeDirection::Forward => "edge-forward",
+        })
+        .absolute()
+        .top_0()
+        .bottom_0()
+        .w(px(ZONE_WIDTH))
+        .map(|this| match direction {
+            SwipeDirection::Back => this.left_0(),
+            SwipeDirection::Forward => this.right_0(),
+        })
+        .flex()
+        .items_center()
+        .justify_center()
+        .on_hover(on_hover)
+        .when(shown, |this| {
+            this.child(
+                div()
+                    .occlude()
+                    .size(px(40.))
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .rounded_full()
+                    .border_1()
+                    .border_color(rgba(0xd3d9d92b))
+                    .bg(rgba(0x181818b3))
+                    .child(svg_button(
+                        match direction {
+                            SwipeDirection::Back => "back.svg",
+                            SwipeDirection::Forward => "forward.svg",
+                        },
+                        16.,
+                        rgb(0xf2f2f2),
+                        true,
+                        on_click,
+                    )),
+            )
+        })
+}
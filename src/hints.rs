@@ -0,0 +1,105 @@
+use gpui::{div, prelude::*, px, rgb, rgba, IntoElement};
+use serde::Deserialize;
+
+use crate::bridge::TabId;
+
+// A label placed over a clickable element, at its top-left corner in CSS pixels
+#[derive(Debug, Clone, Deserialize)]
+pub struct Hint {
+    pub label: String,
+    pub x: f32,
+    pub y: f32,
+}
+
+// Hint mode in a tab: the labels on screen and what has been typed so far
+pub struct LinkHints {
+    pub tab_id: TabId,
+    pub hints: Vec<Hint>,
+    pub typed: String,
+}
+
+// What typing a key in hint mode leads to
+pub enum HintKey {
+    // Narrowed down to the labels starting with what's typed
+    Pending,
+    // Typed a complete label
+    Activate(String),
+    // Nothing matches any more
+    Cancel,
+}
+
+impl LinkHints {
+    pub fn new(tab_id: TabId, hints: Vec<Hint>) -> Self {
+        Self {
+            tab_id,
+            hints,
+            typed: String::new(),
+        }
+    }
+
+    pub fn type_key(&mut self, key: &str) -> HintKey {
+        self.typed.push_str(&key.to_lowercase());
+        if self.hints.iter().any(|hint| hint.label == self.typed) {
+            return HintKey::Activate(self.typed.clone());
+        }
+        if self
+            .hints
+            .iter()
+            .any(|hint| hint.label.starts_with(&self.typed))
+        {
+            HintKey::Pending
+        } else {
+            HintKey::Cancel
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        self.typed.pop();
+    }
+}
+
+pub fn activate_script(label: &str) -> String {
+    format!(
+        "window.__browserHints && window.__browserHints.activate({})",
+        serde_json::to_string(label).unwrap_or_default()
+    )
+}
+
+pub const STOP_SCRIPT: &str = "window.__browserHints && window.__browserHints.stop()";
+
+// Badges drawn over the page for labels still matching the typed prefix. `zoom` converts the
+// page's CSS pixels to window pixels.
+pub fn render_hints(hints: &LinkHints, zoom: f32) -> impl IntoElement {
+    let typed = hints.typed.as_str();
+
+    div().absolute().inset_0().children(
+        hints
+            .hints
+            .iter()
+            .filter(|hint| hint.label.starts_with(typed))
+            .map(|hint| {
+                div()
+                    .absolute()
+                    .left(px(hint.x * zoom))
+                    .top(px(hint.y * zoom))
+                    .flex()
+                    .px(px(3.))
+                    .rounded_sm()
+                    .border_1()
+                    .border_color(rgba(0x0000004d))
+                    .bg(rgb(0xffd60a))
+                    .text_xs()
+                    .font_weight(gpui::FontWeight::BOLD)
+                    .child(
+                        div()
+                            .text_color(rgba(0x00000066))
+                            .child(hint.label[..typed.len()].to_uppercase()),
+                    )
+                    .child(
+                        div()
+                            .text_color(rgb(0x000000))
+                            .child(hint.label[typed.len()..].to_uppercase()),
+                    )
+            }),
+    )
+}
@@ -0,0 +1,121 @@
+use gpui::Global;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::storage;
+
+const HISTORY_FILE: &str = "history.jsonl";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub url: String,
+    pub title: String,
+    // Seconds since the unix epoch
+    pub visited_at: u64,
+    // Visits so far, 0 for entries saved before visits were counted
+    #[serde(default)]
+    pub visit_count: u32,
+}
+
+// Visited pages, one entry per URL ordered from oldest to most recent visit
+pub struct History {
+    entries: Vec<HistoryEntry>,
+}
+
+impl Global for History {}
+
+impl History {
+    // Load history from disk, collapsing repeated visits into the latest one
+    pub fn load() -> Self {
+        let records =
+            storage::load_json_lines::<HistoryEntry>(HISTORY_FILE).unwrap_or_else(|err| {
+                log::error!("Failed to load history: {}", err);
+                Vec::new()
+            });
+
+        // later records for a URL supersede earlier ones
+        let record_count = records.len();
+        let mut latest = HashMap::new();
+        for (order, entry) in records.into_iter().enumerate() {
+            latest.insert(entry.url.clone(), (order, entry));
+        }
+        let mut entries: Vec<_> = latest.into_values().collect();
+        entries.sort_by_key(|(order, _)| *order);
+        let history = Self {
+            entries: entries.into_iter().map(|(_, entry)| entry).collect(),
+        };
+
+        // compact the append-only log once duplicates pile up
+        if history.entries.len() < record_count {
+            if let Err(err) = storage::save_json_lines(HISTORY_FILE, &history.entries) {
+                log::error!("Failed to compact history: {}", err);
+            }
+        }
+
+        history
+    }
+
+    pub fn record_visit(&mut self, url: &str, title: &str) {
+        if !should_record(url) {
+            return;
+        }
+
+        let visit_count = self
+            .entries
+            .iter()
+            .find(|entry| entry.url == url)
+            .map_or(0, |entry| entry.visit_count.max(1));
+        let entry = HistoryEntry {
+            url: url.to_string(),
+            title: title.to_string(),
+            visited_at: now(),
+            visit_count: visit_count + 1,
+        };
+        self.append(entry);
+    }
+
+    // Attach a page title to the most recent visit of `url`
+    pub fn set_title(&mut self, url: &str, title: &str) {
+        let Some(entry) = self.entries.iter().rev().find(|entry| entry.url == url) else {
+            return;
+        };
+        if entry.title == title {
+            return;
+        }
+
+        let entry = HistoryEntry {
+            title: title.to_string(),
+            ..entry.clone()
+        };
+        self.append(entry);
+    }
+
+    // Oldest visit first
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    fn append(&mut self, entry: HistoryEntry) {
+        if let Err(err) = storage::append_json_line(HISTORY_FILE, &entry) {
+            log::error!("Failed to save history: {}", err);
+        }
+        self.insert(entry);
+    }
+
+    fn insert(&mut self, entry: HistoryEntry) {
+        self.entries.retain(|existing| existing.url != entry.url);
+        self.entries.push(entry);
+    }
+}
+
+fn should_record(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://") || url.starts_with("file://")
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
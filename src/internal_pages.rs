@@ -0,0 +1,625 @@
+use gpui::{div, prelude::*, px, AnyElement, ClipboardItem, Context, IntoElement, SharedString};
+use std::fs;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+use crate::bookmarks::Bookmarks;
+use crate::downloads::{self, DownloadManager, DownloadState};
+use crate::history::History;
+use crate::passwords::Passwords;
+use crate::proxy::{self, ActiveProxy, ProxyMode};
+use crate::settings::{BLANK_PAGE_URL, NEW_TAB_URL};
+use crate::theme::Theme;
+use crate::toast::toast_info;
+use crate::widgets::{svg_button, warning_badge};
+use crate::{download_progress_bar, Main};
+
+// History entries listed on `about:history`, most recent first
+const MAX_HISTORY_ROWS: usize = 200;
+// Fragment of the blank page standing in for a local directory, followed by its file URL
+const DIRECTORY_FRAGMENT: &str = "#directory:";
+
+/// `about:` pages drawn by the browser itself instead of loaded from the web.
+///
+/// A tab on one keeps the `about:` URL while its webview shows `about:blank#<name>`, so each
+/// visit is an entry in the tab's own history and back and forward move between internal and
+/// web pages like between any others.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InternalPage {
+    NewTab,
+    Settings,
+    History,
+    Bookmarks,
+    Downloads,
+    Passwords,
+    Proxy,
+    About,
+}
+
+impl InternalPage {
+    const ALL: [InternalPage; 8] = [
+        InternalPage::NewTab,
+        InternalPage::Settings,
+        InternalPage::History,
+        InternalPage::Bookmarks,
+        InternalPage::Downloads,
+        InternalPage::Passwords,
+        InternalPage::Proxy,
+        InternalPage::About,
+    ];
+
+    pub fn from_url(url: &str) -> Option<Self> {
+        let name = url.strip_prefix("about:")?;
+        Self::ALL
+            .into_iter()
+            .find(|page| page.name().eq_ignore_ascii_case(name))
+    }
+
+    // The page a webview URL stands for, e.g. `about:blank#history`
+    fn from_webview_url(url: &str) -> Option<Self> {
+        let name = url.strip_prefix(BLANK_PAGE_URL)?.strip_prefix('#')?;
+        Self::ALL.into_iter().find(|page| page.name() == name)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            InternalPage::NewTab => "newtab",
+            InternalPage::Settings => "settings",
+            InternalPage::History => "history",
+            InternalPage::Bookmarks => "bookmarks",
+            InternalPage::Downloads => "downloads",
+            InternalPage::Passwords => "passwords",
+            InternalPage::Proxy => "proxy",
+            InternalPage::About => "about",
+        }
+    }
+
+    pub fn url(self) -> String {
+        match self {
+            InternalPage::NewTab => NEW_TAB_URL.to_string(),
+            page => format!("about:{}", page.name()),
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            InternalPage::NewTab => "New Tab",
+            InternalPage::Settings => "Settings",
+            InternalPage::History => "History",
+            InternalPage::Bookmarks => "Bookmarks",
+            InternalPage::Downloads => "Downloads",
+            InternalPage::Passwords => "Passwords",
+            InternalPage::Proxy => "Proxy",
+            InternalPage::About => "About Pages",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            InternalPage::NewTab => "Search box and the sites you visit most",
+            InternalPage::Settings => "Browser preferences",
+            InternalPage::History => "Pages you visited",
+            InternalPage::Bookmarks => "Pages you bookmarked",
+            InternalPage::Downloads => "Files you downloaded",
+            InternalPage::Passwords => "Logins you saved",
+            InternalPage::Proxy => "How pages reach the network",
+            InternalPage::About => "This list",
+        }
+    }
+}
+
+// The local directory a `file:` URL ending in a slash lists
+pub fn directory_path(url: &str) -> Option<PathBuf> {
+    if !url.starts_with("file://") || !url.ends_with('/') {
+        return None;
+    }
+    Url::parse(url).ok()?.to_file_path().ok()
+}
+
+// Whether a tab on `url` shows a page drawn by the browser
+pub fn is_internal(url: &str) -> bool {
+    InternalPage::from_url(url).is_some() || directory_path(url).is_some()
+}
+
+// Tab title of an internal page, the blank page's own would be its URL
+pub fn page_title(url: &str) -> Option<String> {
+    if let Some(page) = InternalPage::from_url(url) {
+        return Some(page.title().to_string());
+    }
+    directory_path(url).map(|path| format!("Index of {}", path.display()))
+}
+
+// What the webview of a tab on `url` loads
+pub fn webview_url(url: &str) -> String {
+    if directory_path(url).is_some() {
+        return format!("{}{}{}", BLANK_PAGE_URL, DIRECTORY_FRAGMENT, url);
+    }
+    match InternalPage::from_url(url) {
+        Some(page) => format!("{}#{}", BLANK_PAGE_URL, page.name()),
+        None => url.to_string(),
+    }
+}
+
+// The URL a tab shows for the address its webview reports
+pub fn tab_url(webview_url: &str) -> String {
+    if let Some(url) = webview_url
+        .strip_prefix(BLANK_PAGE_URL)
+        .and_then(|fragment| fragment.strip_prefix(DIRECTORY_FRAGMENT))
+    {
+        return url.to_string();
+    }
+    match InternalPage::from_webview_url(webview_url) {
+        Some(page) => page.url(),
+        None => webview_url.to_string(),
+    }
+}
+
+fn page(theme: &Theme, title: impl Into<SharedString>) -> gpui::Stateful<gpui::Div> {
+    div()
+        .id("internal-page")
+        .absolute()
+        .inset_0()
+        .flex()
+        .flex_col()
+        .items_center()
+        .overflow_y_scroll()
+        .bg(theme.page)
+        .child(
+            div()
+                .w(px(640.))
+                .pt_10()
+                .pb_4()
+                .text_lg()
+                .text_color(theme.text)
+                .child(title.into()),
+        )
+}
+
+fn empty(theme: &Theme, text: impl Into<SharedString>) -> impl IntoElement {
+    div()
+        .w(px(640.))
+        .text_xs()
+        .text_color(theme.text_muted)
+        .child(text.into())
+}
+
+// A clickable row with a title over a dimmer line
+fn row(
+    theme: &Theme,
+    id: impl Into<gpui::ElementId>,
+    title: impl Into<SharedString>,
+    detail: impl Into<SharedString>,
+) -> gpui::Stateful<gpui::Div> {
+    div()
+        .id(id)
+        .w(px(640.))
+        .flex()
+        .items_center()
+        .gap_3()
+        .px_3()
+        .py_2()
+        .rounded_md()
+        .cursor_pointer()
+        .hover(|this| this.bg(theme.hover_subtle))
+        .child(
+            div()
+                .flex()
+                .flex_col()
+                .flex_1()
+                .min_w_0()
+                .gap_1()
+                .child(
+                    div()
+                        .truncate()
+                        .text_sm()
+                        .text_color(theme.text)
+                        .child(title.into()),
+                )
+                .child(
+                    div()
+                        .truncate()
+                        .text_xs()
+                        .text_color(theme.text_muted)
+                        .child(detail.into()),
+                ),
+        )
+}
+
+impl Main {
+    // Native page drawn over the blank webview of a tab on an internal page
+    pub(crate) fn render_internal_page(
+        &self,
+        page: InternalPage,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        match page {
+            InternalPage::NewTab => self.render_new_tab_page(cx).into_any_element(),
+            InternalPage::Settings => div()
+                .absolute()
+                .inset_0()
+                .children(self.settings_page.clone())
+                .into_any_element(),
+            InternalPage::History => self.render_history_page(cx).into_any_element(),
+            InternalPage::Bookmarks => self.render_bookmarks_page(cx).into_any_element(),
+            InternalPage::Downloads => self.render_downloads_page(cx).into_any_element(),
+            InternalPage::Passwords => self.render_passwords_page(cx).into_any_element(),
+            InternalPage::Proxy => self.render_proxy_page(cx).into_any_element(),
+            InternalPage::About => page_list(cx).into_any_element(),
+        }
+    }
+
+    fn render_history_page(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let entries: Vec<_> = cx
+            .global::<History>()
+            .entries()
+            .iter()
+            .rev()
+            .filter(|entry| InternalPage::from_url(&entry.url).is_none())
+            .take(MAX_HISTORY_ROWS)
+            .cloned()
+            .collect();
+
+        page(&theme, "History")
+            .when(entries.is_empty(), |this| {
+                this.child(empty(&theme, "Pages you visit will show up here"))
+            })
+            .children(entries.into_iter().enumerate().map(|(ix, entry)| {
+                let title = if entry.title.is_empty() {
+                    entry.url.clone()
+                } else {
+                    entry.title.clone()
+                };
+                let url = entry.url;
+                row(&theme, ("history-entry", ix), title, url.clone()).on_click(
+                    cx.listener(move |this, _, window, cx| this.navigate(&url, window, cx)),
+                )
+            }))
+    }
+
+    fn render_bookmarks_page(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let bookmarks = cx.global::<Bookmarks>().all().to_vec();
+
+        page(&theme, "Bookmarks")
+            .when(bookmarks.is_empty(), |this| {
+                this.child(empty(
+                    &theme,
+                    "Bookmark pages with the star in the address bar",
+                ))
+            })
+            .children(bookmarks.into_iter().enumerate().map(|(ix, bookmark)| {
+                let title = if bookmark.title.is_empty() {
+                    bookmark.url.clone()
+                } else {
+                    bookmark.title.clone()
+                };
+                let url = bookmark.url;
+                row(&theme, ("bookmark", ix), title, url.clone())
+                    .on_click(cx.listener({
+                        let url = url.clone();
+                        move |this, _, window, cx| this.navigate(&url, window, cx)
+                    }))
+                    .child(svg_button(
+                        "close.svg",
+                        10.0,
+                        theme.icon_muted,
+                        true,
+                        move |_, cx| {
+                            cx.update_global::<Bookmarks, _>(|bookmarks, _| {
+                                bookmarks.remove_bookmark(&url)
+                            });
+                            cx.refresh_windows();
+                        },
+                    ))
+            }))
+    }
+
+    // Locked until asked, so leaving the page open doesn't show passwords to passers-by
+    fn render_passwords_page(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let saved = cx.global::<Passwords>().logins().len();
+        let Some(unlocked) = &self.unlocked_passwords else {
+            return page(&theme, "Passwords")
+                .when(saved == 0, |this| {
+                    this.child(empty(
+                        &theme,
+                        "Passwords you save when signing in will show up here",
+                    ))
+                })
+                .when(saved > 0, |this| {
+                    this.child(
+                        row(
+                            &theme,
+                            "unlock-passwords",
+                            "Unlock to see saved passwords",
+                            match saved {
+                                1 => "1 saved login".to_string(),
+                                count => format!("{} saved logins", count),
+                            },
+                        )
+                        .on_click(cx.listener(|this, _, _, cx| this.unlock_passwords(cx))),
+                    )
+                });
+        };
+
+        page(&theme, "Passwords")
+            .child(
+                row(&theme, "lock-passwords", "Lock", "Hide the passwords again")
+                    .on_click(cx.listener(|this, _, _, cx| this.lock_passwords(cx))),
+            )
+            .children(unlocked.iter().enumerate().map(|(ix, unlocked)| {
+                let login = unlocked.login.clone();
+                let username = if login.username.is_empty() {
+                    "No username".to_string()
+                } else {
+                    login.username.clone()
+                };
+                let password = match &unlocked.password {
+                    None => "Not in the keychain".to_string(),
+                    Some(password) if unlocked.shown => password.clone(),
+                    Some(_) => "••••••••".to_string(),
+                };
+                let copied = unlocked.password.clone();
+                // buttons on a row that itself toggles the password
+                let button = |id: &'static str| {
+                    div()
+                        .id((id, ix))
+                        .px_2()
+                        .text_xs()
+                        .text_color(theme.text_dim)
+                        .cursor_pointer()
+                        .hover(|this| this.text_color(theme.text))
+                };
+                row(
+                    &theme,
+                    ("saved-login", ix),
+                    login.origin.clone(),
+                    format!("{} · {}", username, password),
+                )
+                // clicking the row shows or hides its password
+                .on_click(cx.listener(move |this, _, _, cx| {
+                    if let Some(unlocked) = this
+                        .unlocked_passwords
+                        .as_mut()
+                        .and_then(|unlocked| unlocked.get_mut(ix))
+                    {
+                        unlocked.shown = !unlocked.shown;
+                        cx.notify();
+                    }
+                }))
+                .children(copied.map(|password| {
+                    button("copy-password")
+                        .on_click(move |_, _, cx| {
+                            cx.stop_propagation();
+                            cx.write_to_clipboard(ClipboardItem::new_string(password.clone()));
+                            toast_info(cx, "Password copied");
+                        })
+                        .child("Copy")
+                }))
+                .child(
+                    button("delete-password")
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            cx.stop_propagation();
+                            Passwords::remove(cx, &login);
+                            if let Some(unlocked) = &mut this.unlocked_passwords {
+                                unlocked.retain(|unlocked| unlocked.login != login);
+                            }
+                            cx.notify();
+                        }))
+                        .child("Delete"),
+                )
+            }))
+    }
+
+    // What the profile was last switched to, which isn't always what preferences show while
+    // a field is being edited
+    fn render_proxy_page(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let active = cx.global::<ActiveProxy>();
+        let settings = &active.settings;
+        let manual = settings.mode == ProxyMode::Manual && settings.server().is_some();
+        let status = match &active.error {
+            Some(err) => format!("Not applied: {}", err),
+            None if self.private => {
+                "Applied, this private window's own profile included".to_string()
+            }
+            None => "Applied".to_string(),
+        };
+        let mut rows = vec![
+            ("Mode", settings.mode.label().to_string()),
+            ("Connects through", proxy::describe(settings)),
+        ];
+        if manual {
+            rows.push((
+                "Reached directly",
+                if settings.bypass_list.is_empty() {
+                    "No hosts".to_string()
+                } else {
+                    settings.bypass_list.join(", ")
+                },
+            ));
+            rows.push((
+                "Signs in as",
+                if settings.username.is_empty() {
+                    "Asks when the proxy wants a password".to_string()
+                } else {
+                    settings.username.clone()
+                },
+            ));
+        }
+        rows.push(("Status", status));
+
+        page(&theme, "Proxy")
+            .children(
+                rows.into_iter()
+                    .enumerate()
+                    .map(|(ix, (title, detail))| row(&theme, ("proxy-row", ix), title, detail)),
+            )
+            .child(
+                row(
+                    &theme,
+                    "proxy-settings",
+                    "Change proxy settings",
+                    "Opens preferences",
+                )
+                .on_click(cx.listener(|this, _, window, cx| {
+                    this.navigate(&InternalPage::Settings.url(), window, cx)
+                })),
+            )
+    }
+
+    fn render_downloads_page(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let manager = DownloadManager::global(cx);
+        let downloads: Vec<_> = manager.read(cx).downloads(self.private).cloned().collect();
+
+        page(&theme, "Downloads")
+            .when(downloads.is_empty(), |this| {
+                this.child(empty(&theme, "Files you download will show up here"))
+            })
+            .children(downloads.into_iter().map(|download| {
+                let id = download.id;
+                row(
+                    &theme,
+                    ("download", id),
+                    download.filename.clone(),
+                    download.status(),
+                )
+                .when(download.is_executable(), |this| {
+                    this.child(warning_badge("Program"))
+                })
+                .when(download.state == DownloadState::InProgress, |this| {
+                    this.child(div().w(px(120.)).child(download_progress_bar(
+                        id,
+                        download.progress(),
+                        theme,
+                    )))
+                    .child(svg_button(
+                        "close.svg",
+                        10.0,
+                        theme.icon_muted,
+                        true,
+                        {
+                            let manager = manager.clone();
+                            move |_, cx| {
+                                manager.update(cx, |downloads, cx| downloads.cancel(id, cx))
+                            }
+                        },
+                    ))
+                })
+                // finished files open in the file manager
+                .when(download.state == DownloadState::Completed, |this| {
+                    let path = download.path.clone();
+                    this.on_click(move |_, _, cx| cx.reveal_path(&path))
+                })
+            }))
+    }
+}
+
+// A local directory's contents, read when the tab navigates to it
+pub struct DirectoryListing {
+    path: PathBuf,
+    entries: Vec<DirectoryEntry>,
+    error: Option<String>,
+}
+
+struct DirectoryEntry {
+    name: String,
+    url: String,
+    is_dir: bool,
+    size: u64,
+}
+
+impl DirectoryListing {
+    // Folders first, then files, both by name
+    pub fn read(path: PathBuf) -> Self {
+        let (mut entries, error) = match fs::read_dir(&path) {
+            Ok(entries) => (
+                entries
+                    .filter_map(|entry| DirectoryEntry::new(&entry.ok()?.path()))
+                    .collect::<Vec<_>>(),
+                None,
+            ),
+            Err(err) => (Vec::new(), Some(err.to_string())),
+        };
+        entries.sort_by_cached_key(|entry| (!entry.is_dir, entry.name.to_lowercase()));
+        Self {
+            path,
+            entries,
+            error,
+        }
+    }
+}
+
+impl DirectoryEntry {
+    // Symlinks are followed, so a link to a folder opens as one
+    fn new(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let url = if metadata.is_dir() {
+            Url::from_directory_path(path)
+        } else {
+            Url::from_file_path(path)
+        };
+        Some(Self {
+            name: path.file_name()?.to_string_lossy().into_owned(),
+            url: url.ok()?.to_string(),
+            is_dir: metadata.is_dir(),
+            size: metadata.len(),
+        })
+    }
+}
+
+impl Main {
+    pub(crate) fn render_directory_page(
+        &self,
+        listing: &DirectoryListing,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let parent = listing
+            .path
+            .parent()
+            .and_then(|parent| Url::from_directory_path(parent).ok())
+            .map(String::from);
+
+        page(&theme, format!("Index of {}", listing.path.display()))
+            .children(parent.map(|url| {
+                row(&theme, "parent-directory", "..", "Parent folder").on_click(
+                    cx.listener(move |this, _, window, cx| this.navigate(&url, window, cx)),
+                )
+            }))
+            .children(listing.error.clone().map(|error| empty(&theme, error)))
+            .when(
+                listing.error.is_none() && listing.entries.is_empty(),
+                |this| this.child(empty(&theme, "This folder is empty")),
+            )
+            .children(listing.entries.iter().enumerate().map(|(ix, entry)| {
+                let (name, detail) = if entry.is_dir {
+                    (format!("{}/", entry.name), "Folder".to_string())
+                } else {
+                    (entry.name.clone(), downloads::format_bytes(entry.size))
+                };
+                let url = entry.url.clone();
+                row(&theme, ("directory-entry", ix), name, detail).on_click(
+                    cx.listener(move |this, _, window, cx| this.navigate(&url, window, cx)),
+                )
+            }))
+    }
+}
+
+fn page_list(cx: &mut Context<Main>) -> impl IntoElement {
+    let theme = *cx.global::<Theme>();
+    page(&theme, "About Pages").children(InternalPage::ALL.into_iter().enumerate().map(
+        |(ix, internal_page)| {
+            let url = internal_page.url();
+            row(
+                &theme,
+                ("about-page", ix),
+                url.clone(),
+                internal_page.description(),
+            )
+            .on_click(cx.listener(move |this, _, window, cx| this.navigate(&url, window, cx)))
+        },
+    ))
+}
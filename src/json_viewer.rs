@@ -0,0 +1,310 @@
+use gpui::{div, prelude::*, px, rgb, rgba, uniform_list, Context, IntoElement, SharedString};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::ops::Range;
+
+use crate::bridge::TabId;
+use crate::Main;
+
+// Indent per nesting level
+const INDENT: f32 = 16.;
+// Longest string shown in full before it's cut off in its row
+const MAX_STRING_CHARS: usize = 500;
+
+const KEY_COLOR: u32 = 0x7dd3fc;
+const STRING_COLOR: u32 = 0x9ece6a;
+const NUMBER_COLOR: u32 = 0xff9f0a;
+const LITERAL_COLOR: u32 = 0xbb9af7;
+const MUTED_COLOR: u32 = 0x7a7a7a;
+
+enum NodeValue {
+    Scalar(SharedString, u32),
+    Array(usize),
+    Object(usize),
+}
+
+// One row of the tree, in document order
+struct JsonNode {
+    depth: usize,
+    // Object key or array index, none for the root
+    key: Option<SharedString>,
+    value: NodeValue,
+    // Index past the node's last descendant
+    end: usize,
+}
+
+// A JSON document shown as a tree of collapsible rows over the tab's raw text
+pub struct JsonView {
+    nodes: Vec<JsonNode>,
+    collapsed: HashSet<usize>,
+    // Indices of the nodes not inside a collapsed one, what the list draws
+    visible: Vec<usize>,
+    // Showing the page's own text instead of the tree
+    pub raw: bool,
+}
+
+impl JsonView {
+    // `None` when `text` isn't JSON, leaving the page as it is
+    pub fn parse(text: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(text).ok()?;
+        let mut nodes = Vec::new();
+        push_node(&mut nodes, 0, None, &value);
+        let mut view = Self {
+            nodes,
+            collapsed: HashSet::new(),
+            visible: Vec::new(),
+            raw: false,
+        };
+        view.layout();
+        Some(view)
+    }
+
+    fn toggle(&mut self, ix: usize) {
+        if !self.collapsed.remove(&ix) {
+            self.collapsed.insert(ix);
+        }
+        self.layout();
+    }
+
+    // Collapse or expand everything below the root
+    fn set_all_collapsed(&mut self, collapsed: bool) {
+        self.collapsed.clear();
+        if collapsed {
+            self.collapsed.extend(
+                self.nodes
+                    .iter()
+                    .enumerate()
+                    .skip(1)
+                    .filter(|(ix, node)| node.end > ix + 1)
+                    .map(|(ix, _)| ix),
+            );
+        }
+        self.layout();
+    }
+
+    fn layout(&mut self) {
+        self.visible.clear();
+        let mut ix = 0;
+        while ix < self.nodes.len() {
+            self.visible.push(ix);
+            ix = if self.collapsed.contains(&ix) {
+                self.nodes[ix].end
+            } else {
+                ix + 1
+            };
+        }
+    }
+}
+
+fn push_node(nodes: &mut Vec<JsonNode>, depth: usize, key: Option<SharedString>, value: &Value) {
+    let ix = nodes.len();
+    let value_node = match value {
+        Value::Null => NodeValue::Scalar("null".into(), LITERAL_COLOR),
+        Value::Bool(value) => NodeValue::Scalar(value.to_string().into(), LITERAL_COLOR),
+        Value::Number(number) => NodeValue::Scalar(number.to_string().into(), NUMBER_COLOR),
+        Value::String(text) => {
+            let quoted = if text.chars().count() > MAX_STRING_CHARS {
+                let cut: String = text.chars().take(MAX_STRING_CHARS).collect();
+                format!("{}…", serde_json::to_string(&cut).unwrap_or_default())
+            } else {
+                serde_json::to_string(text).unwrap_or_default()
+            };
+            NodeValue::Scalar(quoted.into(), STRING_COLOR)
+        }
+        Value::Array(items) => NodeValue::Array(items.len()),
+        Value::Object(map) => NodeValue::Object(map.len()),
+    };
+    nodes.push(JsonNode {
+        depth,
+        key,
+        value: value_node,
+        end: ix + 1,
+    });
+
+    match value {
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                push_node(nodes, depth + 1, Some(index.to_string().into()), item);
+            }
+        }
+        Value::Object(map) => {
+            for (key, item) in map {
+                push_node(nodes, depth + 1, Some(key.clone().into()), item);
+            }
+        }
+        _ => {}
+    }
+    nodes[ix].end = nodes.len();
+}
+
+fn control(id: &'static str, label: &'static str, active: bool) -> gpui::Stateful<gpui::Div> {
+    div()
+        .id(id)
+        .px_2()
+        .py_1()
+        .rounded_md()
+        .border_1()
+        .border_color(rgba(0xd3d9d92b))
+        .text_xs()
+        .text_color(rgb(0xf2f2f2))
+        .cursor_pointer()
+        .when(active, |this| this.bg(rgba(0xffffff1a)))
+        .hover(|this| this.bg(rgba(0xffffff10)))
+        .child(label)
+}
+
+impl Main {
+    fn update_json_view(
+        &mut self,
+        tab_id: TabId,
+        cx: &mut Context<Self>,
+        update: impl FnOnce(&mut JsonView),
+    ) {
+        if let Some(view) = self
+            .tabs
+            .iter_mut()
+            .find(|tab| tab.id == tab_id)
+            .and_then(|tab| tab.json.as_mut())
+        {
+            update(view);
+            cx.notify();
+        }
+    }
+
+    // The tree covering the page, or just the button back to it while the raw text shows
+    pub(crate) fn render_json_view(
+        &self,
+        tab_id: TabId,
+        view: &JsonView,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let controls = div()
+            .flex()
+            .justify_end()
+            .gap_2()
+            .p_3()
+            .when(!view.raw, |this| {
+                this.child(
+                    control("json-collapse-all", "Collapse All", false).on_click(cx.listener(
+                        move |this, _, _, cx| {
+                            this.update_json_view(tab_id, cx, |view| view.set_all_collapsed(true))
+                        },
+                    )),
+                )
+                .child(
+                    control("json-expand-all", "Expand All", false).on_click(cx.listener(
+                        move |this, _, _, cx| {
+                            this.update_json_view(tab_id, cx, |view| view.set_all_collapsed(false))
+                        },
+                    )),
+                )
+            })
+            .child(control("json-raw", "Raw", view.raw).on_click(cx.listener(
+                move |this, _, _, cx| {
+                    this.update_json_view(tab_id, cx, |view| view.raw = !view.raw)
+                },
+            )));
+
+        if view.raw {
+            return div().absolute().top_0().right_0().child(controls);
+        }
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .flex_col()
+            .bg(rgb(0x0a0a0a))
+            .child(controls)
+            .child(
+                uniform_list(
+                    "json-tree",
+                    view.visible.len(),
+                    cx.processor(move |this, range: Range<usize>, _, cx| {
+                        let Some(view) = this
+                            .tabs
+                            .iter()
+                            .find(|tab| tab.id == tab_id)
+                            .and_then(|tab| tab.json.as_ref())
+                        else {
+                            return Vec::new();
+                        };
+                        range
+                            .filter_map(|row| view.visible.get(row).copied())
+                            .map(|ix| render_row(tab_id, view, ix, cx))
+                            .collect()
+                    }),
+                )
+                .flex_1()
+                .px_3()
+                .pb_3(),
+            )
+    }
+}
+
+fn render_row(
+    tab_id: TabId,
+    view: &JsonView,
+    ix: usize,
+    cx: &mut Context<Main>,
+) -> gpui::Stateful<gpui::Div> {
+    let node = &view.nodes[ix];
+    let collapsed = view.collapsed.contains(&ix);
+    let expandable = node.end > ix + 1;
+    let summary = match &node.value {
+        NodeValue::Scalar(text, value_color) => {
+            div().text_color(rgb(*value_color)).child(text.clone())
+        }
+        NodeValue::Array(len) => div()
+            .text_color(rgb(MUTED_COLOR))
+            .child(match (collapsed, len) {
+                (_, 0) => "[]".to_string(),
+                (true, len) => format!("[…] {} items", len),
+                (false, _) => "[".to_string(),
+            }),
+        NodeValue::Object(len) => {
+            div()
+                .text_color(rgb(MUTED_COLOR))
+                .child(match (collapsed, len) {
+                    (_, 0) => "{}".to_string(),
+                    (true, len) => format!("{{…}} {} keys", len),
+                    (false, _) => "{".to_string(),
+                })
+        }
+    };
+
+    div()
+        .id(("json-node", ix))
+        .flex()
+        .items_center()
+        .gap_1()
+        .h(px(20.))
+        .pl(px(node.depth as f32 * INDENT))
+        .text_xs()
+        .font_family("monospace")
+        .whitespace_nowrap()
+        .overflow_hidden()
+        .child(
+            div()
+                .flex_none()
+                .w(px(12.))
+                .text_color(rgb(MUTED_COLOR))
+                .when(expandable, |this| {
+                    this.child(if collapsed { "▸" } else { "▾" })
+                }),
+        )
+        .children(node.key.clone().map(|key| {
+            div()
+                .flex_none()
+                .text_color(rgb(KEY_COLOR))
+                .child(format!("{}:", key))
+        }))
+        .child(summary.truncate())
+        .when(expandable, |this| {
+            this.cursor_pointer()
+                .hover(|this| this.bg(rgba(0xffffff08)))
+                .on_click(cx.listener(move |this, _, _, cx| {
+                    this.update_json_view(tab_id, cx, |view| view.toggle(ix))
+                }))
+        })
+}
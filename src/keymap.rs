@@ -0,0 +1,431 @@
+use gpui::{Action, App, Global, KeyBinding, Keystroke, NoAction};
+use std::collections::BTreeMap;
+
+use crate::storage;
+use crate::{
+    ActivateLastTab, ActivateTab1, ActivateTab2, ActivateTab3, ActivateTab4, ActivateTab5,
+    ActivateTab6, ActivateTab7, ActivateTab8, CaptureRegion, CaptureScreenshot, CloseTab, CopyUrl,
+    DuplicateTab, ExportPdf, FindInPage, FocusAddressBar, HardReload, NewPrivateWindow, NewTab,
+    NewWindow, NextTab, OpenFile, OpenPreferences, PasteAndGo, PreviousTab, Reload,
+    ReopenClosedTab, ResetZoom, ToggleBookmark, ToggleCommandPalette, ToggleConsole,
+    ToggleDevTools, ToggleFullscreen, ToggleMute, TogglePictureInPicture, ToggleReadAloud,
+    ToggleRecentlyClosed, ToggleSplitView, ToggleTabSearch, ToggleTaskManager, ZoomIn, ZoomOut,
+};
+
+const KEYMAP_FILE: &str = "keymap.json";
+// Key context of the browser window the bindings apply in
+const CONTEXT: &str = "Browser";
+
+// Chrome's shortcut for DevTools besides F12
+const DEVTOOLS_KEY: &str = if cfg!(target_os = "macos") {
+    "cmd-alt-i"
+} else {
+    "ctrl-shift-i"
+};
+// Chrome's shortcut for the DevTools console
+const CONSOLE_KEY: &str = if cfg!(target_os = "macos") {
+    "cmd-alt-j"
+} else {
+    "ctrl-shift-j"
+};
+
+// Bindings made outside the keymap, which can't be changed here but still take chords
+const FIXED_BINDINGS: &[(&str, &str)] = &[
+    #[cfg(feature = "dev-assets")]
+    ("Reload assets", crate::assets::RELOAD_ASSETS_KEY),
+];
+
+// An action that can be bound, with the chords it has out of the box. `secondary` is Cmd on
+// macOS and Ctrl elsewhere.
+pub struct KeymapEntry {
+    pub label: &'static str,
+    // The action's name, e.g. `browser::NewTab`, which the keymap file is keyed by
+    pub name: &'static str,
+    pub defaults: Vec<&'static str>,
+    bind: fn(&str) -> KeyBinding,
+}
+
+fn entry<A: Action + Default>(label: &'static str, defaults: &[&'static str]) -> KeymapEntry {
+    KeymapEntry {
+        label,
+        name: A::default().name(),
+        defaults: defaults.to_vec(),
+        bind: |keys| KeyBinding::new(keys, A::default(), Some(CONTEXT)),
+    }
+}
+
+fn default_entries() -> Vec<KeymapEntry> {
+    vec![
+        entry::<FocusAddressBar>("Focus address bar", &["secondary-l", "alt-d"]),
+        entry::<NewTab>("New tab", &["secondary-t"]),
+        entry::<DuplicateTab>("Duplicate tab", &["secondary-shift-d"]),
+        entry::<CloseTab>("Close tab", &["secondary-w"]),
+        entry::<ReopenClosedTab>("Reopen closed tab", &["secondary-shift-t"]),
+        // next to reopening the last one with shift-t
+        entry::<ToggleRecentlyClosed>("Recently closed", &["secondary-alt-t"]),
+        entry::<Reload>("Reload", &["secondary-r", "f5"]),
+        entry::<HardReload>("Hard reload", &["secondary-shift-r", "shift-f5"]),
+        entry::<NextTab>("Next tab", &["ctrl-tab"]),
+        entry::<PreviousTab>("Previous tab", &["ctrl-shift-tab"]),
+        entry::<ActivateTab1>("Tab 1", &["secondary-1"]),
+        entry::<ActivateTab2>("Tab 2", &["secondary-2"]),
+        entry::<ActivateTab3>("Tab 3", &["secondary-3"]),
+        entry::<ActivateTab4>("Tab 4", &["secondary-4"]),
+        entry::<ActivateTab5>("Tab 5", &["secondary-5"]),
+        entry::<ActivateTab6>("Tab 6", &["secondary-6"]),
+        entry::<ActivateTab7>("Tab 7", &["secondary-7"]),
+        entry::<ActivateTab8>("Tab 8", &["secondary-8"]),
+        entry::<ActivateLastTab>("Last tab", &["secondary-9"]),
+        entry::<CopyUrl>("Copy URL", &["secondary-shift-c"]),
+        entry::<PasteAndGo>("Paste and go", &[]),
+        entry::<ToggleDevTools>("Developer tools", &["f12", DEVTOOLS_KEY]),
+        entry::<ToggleConsole>("Console", &[CONSOLE_KEY]),
+        // Chrome's task manager shortcut
+        entry::<ToggleTaskManager>("Task manager", &["shift-escape"]),
+        // Firefox's Picture-in-Picture shortcut
+        entry::<TogglePictureInPicture>("Picture in Picture", &["secondary-shift-]"]),
+        entry::<CaptureScreenshot>("Take screenshot", &["secondary-shift-s"]),
+        entry::<CaptureRegion>("Take screenshot of area", &["secondary-shift-x"]),
+        entry::<ExportPdf>("Export as PDF", &["secondary-p"]),
+        entry::<OpenFile>("Open file", &["secondary-o"]),
+        entry::<NewWindow>("New window", &["secondary-n"]),
+        entry::<NewPrivateWindow>("New private window", &["secondary-shift-n"]),
+        entry::<OpenPreferences>("Preferences", &["secondary-,"]),
+        entry::<ToggleMute>("Mute tab", &["secondary-m"]),
+        entry::<ToggleFullscreen>("Fullscreen", &["f11"]),
+        entry::<FindInPage>("Find in page", &["secondary-f"]),
+        // Edge's read aloud shortcut
+        entry::<ToggleReadAloud>("Read aloud", &["secondary-shift-u"]),
+        entry::<ToggleBookmark>("Bookmark page", &["secondary-d"]),
+        entry::<ToggleSplitView>("Split view", &[]),
+        // VS Code's command palette shortcut
+        entry::<ToggleCommandPalette>("Command palette", &["secondary-shift-p"]),
+        // Chrome's tab search shortcut
+        entry::<ToggleTabSearch>("Search tabs", &["secondary-shift-a"]),
+        entry::<ZoomIn>("Zoom in", &["secondary-=", "secondary-+"]),
+        entry::<ZoomOut>("Zoom out", &["secondary--"]),
+        entry::<ResetZoom>("Reset zoom", &["secondary-0"]),
+    ]
+}
+
+// Whether gpui can parse `chord`, e.g. `secondary-k` or the sequence `ctrl-k ctrl-s`
+pub fn is_valid_chord(chord: &str) -> bool {
+    !chord.trim().is_empty()
+        && chord
+            .split_whitespace()
+            .all(|key| Keystroke::parse(key).is_ok())
+}
+
+// `chord` with `secondary` resolved for the platform and modifiers in one order, so that
+// `secondary-l` and `cmd-l` on macOS, or `ctrl-l` elsewhere, come out the same
+fn normalize_chord(chord: &str) -> String {
+    chord
+        .split_whitespace()
+        .map(|key| {
+            let Ok(keystroke) = Keystroke::parse(key) else {
+                return key.to_string();
+            };
+            let modifiers = keystroke.modifiers;
+            let mut parts: Vec<&str> = [
+                (modifiers.control, "ctrl"),
+                (modifiers.alt, "alt"),
+                (modifiers.shift, "shift"),
+                (modifiers.platform, "cmd"),
+                (modifiers.function, "fn"),
+            ]
+            .into_iter()
+            .filter_map(|(pressed, name)| pressed.then_some(name))
+            .collect();
+            parts.push(&keystroke.key);
+            parts.join("-")
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// The browser's key bindings: the defaults with the chords from `keymap.json` in place of
+// those of any action listed there. The file only holds actions that were rebound.
+pub struct Keymap {
+    entries: Vec<KeymapEntry>,
+    custom: BTreeMap<String, Vec<String>>,
+}
+
+impl Global for Keymap {}
+
+impl Keymap {
+    pub fn init(cx: &mut App) {
+        let custom = storage::load_json::<BTreeMap<String, Vec<String>>>(KEYMAP_FILE)
+            .unwrap_or_else(|err| {
+                log::error!("Failed to load the keymap: {}", err);
+                None
+            })
+            .unwrap_or_default();
+        let keymap = Self {
+            entries: default_entries(),
+            custom,
+        };
+        for name in keymap.custom.keys() {
+            if !keymap.entries.iter().any(|entry| entry.name == name) {
+                log::warn!("Keymap binds unknown action {}", name);
+            }
+        }
+        keymap.warn_conflicts();
+
+        cx.bind_keys(keymap.entries.iter().flat_map(|entry| {
+            keymap
+                .chords(entry.name)
+                .into_iter()
+                .filter_map(|chord| keymap.binding(entry, &chord))
+                .collect::<Vec<_>>()
+        }));
+        cx.set_global(keymap);
+    }
+
+    pub fn entries(&self) -> &[KeymapEntry] {
+        &self.entries
+    }
+
+    // Chords bound to the action named `name`
+    pub fn chords(&self, name: &str) -> Vec<String> {
+        if let Some(chords) = self.custom.get(name) {
+            return chords.clone();
+        }
+        self.entries
+            .iter()
+            .find(|entry| entry.name == name)
+            .map(|entry| {
+                entry
+                    .defaults
+                    .iter()
+                    .map(|chord| chord.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn is_custom(&self, name: &str) -> bool {
+        self.custom.contains_key(name)
+    }
+
+    fn binding(&self, entry: &KeymapEntry, chord: &str) -> Option<KeyBinding> {
+        if is_valid_chord(chord) {
+            Some((entry.bind)(chord))
+        } else {
+            log::warn!(
+                "Ignoring invalid key binding {:?} for {}",
+                chord,
+                entry.name
+            );
+            None
+        }
+    }
+
+    // Chords bound to more than one action, with the labels of those actions. Chords are
+    // compared as gpui reads them, and include the bindings made outside the keymap.
+    pub fn conflicts(&self) -> Vec<(String, Vec<&'static str>)> {
+        let bindings = self
+            .entries
+            .iter()
+            .flat_map(|entry| {
+                self.chords(entry.name)
+                    .into_iter()
+                    .map(|chord| (chord, entry.label))
+            })
+            .chain(
+                FIXED_BINDINGS
+                    .iter()
+                    .map(|(label, chord)| (chord.to_string(), *label)),
+            );
+        // by normalized chord, with the first spelling of it that was bound
+        let mut actions: BTreeMap<String, (String, Vec<&'static str>)> = BTreeMap::new();
+        for (chord, label) in bindings {
+            let (_, labels) = actions
+                .entry(normalize_chord(&chord))
+                .or_insert_with(|| (chord, Vec::new()));
+            if !labels.contains(&label) {
+                labels.push(label);
+            }
+        }
+        actions
+            .into_values()
+            .filter(|(_, labels)| labels.len() > 1)
+            .collect()
+    }
+
+    fn warn_conflicts(&self) {
+        for (chord, labels) in self.conflicts() {
+            log::warn!(
+                "{} is bound to more than one action: {}",
+                chord,
+                labels.join(", ")
+            );
+        }
+    }
+
+    // Bind `chords` to the action named `name` from now on and remember them, or go back to
+    // its defaults with `None`
+    pub fn set(cx: &mut App, name: &str, chords: Option<Vec<String>>) {
+        cx.update_global::<Self, _>(|keymap, cx| {
+            let Some(ix) = keymap.entries.iter().position(|entry| entry.name == name) else {
+                return;
+            };
+            let old = keymap.chords(name);
+            match chords {
+                Some(chords) => {
+                    let chords: Vec<_> = chords
+                        .into_iter()
+                        .filter(|chord| is_valid_chord(chord))
+                        .collect();
+                    keymap.custom.insert(name.to_string(), chords);
+                }
+                None => {
+                    keymap.custom.remove(name);
+                }
+            }
+            let new = keymap.chords(name);
+
+            // gpui can't remove a single binding, so chords going away are bound to nothing,
+            // which wins over the earlier binding, and then again to other actions sharing them
+            let removed: Vec<_> = old
+                .into_iter()
+                .filter(|chord| !new.contains(chord) && is_valid_chord(chord))
+                .collect();
+            let mut bindings: Vec<_> = removed
+                .iter()
+                .map(|chord| KeyBinding::new(chord, NoAction, Some(CONTEXT)))
+                .collect();
+            for other in keymap.entries.iter().filter(|other| other.name != name) {
+                for chord in keymap.chords(other.name) {
+                    if removed.contains(&chord) {
+                        bindings.extend(keymap.binding(other, &chord));
+                    }
+                }
+            }
+            let entry = &keymap.entries[ix];
+            bindings.extend(new.iter().filter_map(|chord| keymap.binding(entry, chord)));
+            cx.bind_keys(bindings);
+
+            keymap.warn_conflicts();
+            keymap.save();
+        });
+    }
+
+    pub fn reset_all(cx: &mut App) {
+        let names: Vec<_> = cx.global::<Self>().custom.keys().cloned().collect();
+        for name in names {
+            Self::set(cx, &name, None);
+        }
+    }
+
+    fn save(&self) {
+        if let Err(err) = storage::save_json(KEYMAP_FILE, &self.custom) {
+            log::error!("Failed to save the keymap: {}", err);
+        }
+    }
+}
+
+// `chord` the way the platform writes shortcuts, e.g. ⌘⇧T on macOS
+pub fn display_chord(chord: &str) -> String {
+    chord
+        .split_whitespace()
+        .map(|key| {
+            Keystroke::parse(key)
+                .map(|keystroke| keystroke.to_string())
+                .unwrap_or_else(|_| key.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keymap(custom: &[(&str, &[&str])]) -> Keymap {
+        Keymap {
+            entries: default_entries(),
+            custom: custom
+                .iter()
+                .map(|(name, chords)| {
+                    let chords = chords.iter().map(|chord| chord.to_string()).collect();
+                    (name.to_string(), chords)
+                })
+                .collect(),
+        }
+    }
+
+    fn name<A: Action + Default>() -> &'static str {
+        A::default().name()
+    }
+
+    #[test]
+    fn defaults_dont_conflict() {
+        assert!(keymap(&[]).conflicts().is_empty());
+    }
+
+    #[test]
+    fn same_chord_conflicts() {
+        let keymap = keymap(&[(name::<NewTab>(), &["secondary-l"])]);
+        assert_eq!(
+            keymap.conflicts(),
+            vec![(
+                "secondary-l".to_string(),
+                vec!["Focus address bar", "New tab"]
+            )]
+        );
+    }
+
+    #[test]
+    fn secondary_conflicts_with_the_platform_modifier() {
+        let chord = if cfg!(target_os = "macos") {
+            "cmd-l"
+        } else {
+            "ctrl-l"
+        };
+        let keymap = keymap(&[(name::<NewTab>(), &[chord])]);
+        assert_eq!(
+            keymap.conflicts(),
+            vec![(
+                "secondary-l".to_string(),
+                vec!["Focus address bar", "New tab"]
+            )]
+        );
+    }
+
+    #[test]
+    fn modifier_order_doesnt_matter() {
+        assert_eq!(
+            normalize_chord("shift-secondary-t"),
+            normalize_chord("secondary-shift-t")
+        );
+        assert_eq!(
+            normalize_chord("alt-ctrl-k  ctrl-s"),
+            normalize_chord("ctrl-alt-k ctrl-s")
+        );
+        assert_ne!(normalize_chord("ctrl-k"), normalize_chord("alt-k"));
+    }
+
+    #[test]
+    fn one_action_bound_twice_isnt_a_conflict() {
+        let chord = if cfg!(target_os = "macos") {
+            "cmd-t"
+        } else {
+            "ctrl-t"
+        };
+        let keymap = keymap(&[(name::<NewTab>(), &["secondary-t", chord])]);
+        assert!(keymap.conflicts().is_empty());
+    }
+
+    #[cfg(feature = "dev-assets")]
+    #[test]
+    fn fixed_bindings_conflict() {
+        let keymap = keymap(&[(name::<NewTab>(), &["secondary-alt-r"])]);
+        assert_eq!(
+            keymap.conflicts(),
+            vec![(
+                "secondary-alt-r".to_string(),
+                vec!["New tab", "Reload assets"]
+            )]
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use gpui::{div, prelude::*, IntoElement};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::theme::Theme;
+
+// Loads kept per tab, the oldest are dropped first
+const MAX_TIMINGS: usize = 5;
+
+// One main frame navigation, timed by the host from load start to end and by the page itself
+pub struct LoadTiming {
+    url: String,
+    started: Instant,
+    // Until the page finished loading, `None` while it still is or if it never did
+    finished: Option<Duration>,
+    // Until it failed or another navigation took over
+    stopped: Option<Duration>,
+    dom_content_loaded: Option<Duration>,
+    load: Option<Duration>,
+}
+
+#[derive(Default)]
+pub struct LoadTimings(VecDeque<LoadTiming>);
+
+impl LoadTimings {
+    pub fn start(&mut self, url: String) {
+        self.stop();
+        self.0.push_back(LoadTiming {
+            url,
+            started: Instant::now(),
+            finished: None,
+            stopped: None,
+            dom_content_loaded: None,
+            load: None,
+        });
+        if self.0.len() > MAX_TIMINGS {
+            self.0.pop_front();
+        }
+    }
+
+    pub fn finish(&mut self) {
+        if let Some(timing) = self.pending() {
+            timing.finished = Some(timing.started.elapsed());
+        }
+    }
+
+    // The latest load ended without finishing
+    pub fn stop(&mut self) {
+        if let Some(timing) = self.pending() {
+            timing.stopped = Some(timing.started.elapsed());
+        }
+    }
+
+    fn pending(&mut self) -> Option<&mut LoadTiming> {
+        self.0
+            .back_mut()
+            .filter(|timing| timing.finished.is_none() && timing.stopped.is_none())
+    }
+
+    // What the page measured for the latest load
+    pub fn report(&mut self, dom_content_loaded: f64, load: f64) {
+        let millis = |value: f64| (value >= 0.).then(|| Duration::from_secs_f64(value / 1000.));
+        if let Some(timing) = self.0.back_mut() {
+            timing.dom_content_loaded = millis(dom_content_loaded);
+            timing.load = millis(load);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    // Most recent first, for the site info popover
+    pub fn render(&self, theme: Theme) -> impl IntoElement {
+        div()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .px_3()
+            .pb_2()
+            .text_xs()
+            .child(div().text_color(theme.text_dim).child("Page loads"))
+            .children(self.0.iter().rev().map(|timing| {
+                let summary = match (timing.finished, timing.stopped) {
+                    (Some(finished), _) => {
+                        let mut parts = Vec::new();
+                        if let Some(dom_content_loaded) = timing.dom_content_loaded {
+                            parts.push(format!(
+                                "DOMContentLoaded {}",
+                                format_duration(dom_content_loaded)
+                            ));
+                        }
+                        // the host's measure when the page didn't report one
+                        let load = timing.load.unwrap_or(finished);
+                        parts.push(format!("load {}", format_duration(load)));
+                        parts.join(" · ")
+                    }
+                    (None, Some(stopped)) => {
+                        format!("didn't finish, stopped after {}", format_duration(stopped))
+                    }
+                    (None, None) => format!(
+                        "still loading, {} so far",
+                        format_duration(timing.started.elapsed())
+                    ),
+                };
+                div()
+                    .flex()
+                    .gap_2()
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w_0()
+                            .truncate()
+                            .text_color(theme.text_muted)
+                            .child(timing.url.clone()),
+                    )
+                    .child(div().flex_none().text_color(theme.text).child(summary))
+            }))
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    if duration < Duration::from_secs(1) {
+        format!("{} ms", duration.as_millis())
+    } else {
+        format!("{:.2} s", duration.as_secs_f64())
+    }
+}
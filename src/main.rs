@@ -1,325 +1,4657 @@
-use anyhow::Result;
-use std::fs;
-use std::path::PathBuf;
-use std::time::Duration;
+mod address;
+mod assets;
+mod auth;
+mod autofill;
+mod blocker;
+mod bookmarks;
+mod bridge;
+mod certificate;
+mod chrome;
+mod command_palette;
+mod console;
+mod context_menu;
+mod cookies;
+mod discard;
+mod downloads;
+mod error_page;
+mod favicon;
+mod file_drop;
+mod gestures;
+mod hints;
+mod history;
+mod internal_pages;
+mod json_viewer;
+mod keymap;
+mod load_timing;
+mod message_pump;
+mod new_tab;
+mod passwords;
+mod permissions;
+mod plugins;
+mod preferences;
+mod proxy;
+mod reader;
+mod recently_closed;
+mod region_capture;
+mod screenshot;
+mod session;
+mod settings;
+mod spellcheck;
+mod split_view;
+mod storage;
+mod streams;
+mod suggestions;
+mod tab_search;
+mod task_manager;
+mod theme;
+mod toast;
+mod tts;
+mod user_agent;
+mod userscripts;
+mod webview_ext;
+mod widgets;
+mod window_state;
 
-use futures_util::StreamExt;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use auth::{AuthPrompt, AuthRequest, Credentials};
+use autofill::Autofill;
+use blocker::{ContentBlocker, TabBlocker};
+use bookmarks::Bookmarks;
+use bridge::{BridgeError, BridgeEvent, HostEvent, TabId};
+use certificate::{CertificateError, CertificateExceptions, CertificateProblem, SecurityState};
+use chrome::{TabMenu, TabPreview};
+use command_palette::Commands;
+use console::ConsoleLog;
+use context_menu::{ContextMenu, Misspelling};
+use cookies::Cookie;
+use downloads::{DownloadManager, DownloadState};
+use error_page::LoadError;
+use favicon::FaviconState;
+use gestures::{SwipeDirection, SwipeTracker};
 use gpui::{
-    div, linear_color_stop, linear_gradient, point, prelude::*, px, rgb, rgba, size, svg, App,
-    AppContext, Application, AssetSource, Bounds, Context, Entity, IntoElement, ParentElement,
-    Render, SharedString, Styled, Timer, Window, WindowBounds, WindowOptions,
+    actions, anchored, deferred, div, img, linear_color_stop, linear_gradient, point, prelude::*,
+    px, relative, rgb, rgba, svg, Animation, AnimationExt, AnyView, App, AppContext, Application,
+    ClipboardItem, Context, Entity, FocusHandle, Focusable, FontWeight, HighlightStyle,
+    IntoElement, KeyDownEvent, MouseButton, MouseDownEvent, NavigationDirection, ParentElement,
+    PathPromptOptions, Render, ScrollWheelEvent, SharedString, Styled, StyledText, Subscription,
+    Task, Timer, Window, WindowOptions,
 };
 use gpui_component::{
-    input::{InputEvent, InputState, TextInput},
+    input::{InputEvent, InputState, SelectAll, TextInput},
     Root,
 };
 use gpui_webview::{
-    events::TitleChangedEvent,
-    wef::{self, Frame, FuncRegistry, Settings},
+    events::{
+        AddressChangedEvent, AuthRequiredEvent, CertificateErrorEvent, LoadEndEvent,
+        LoadErrorEvent, LoadStartEvent, LoadingProgressChangedEvent, LoadingStateChangedEvent,
+        PermissionRequestEvent, TitleChangedEvent,
+    },
+    wef::{self, Frame, RequestContext, Settings},
     WebView,
 };
+use hints::{HintKey, LinkHints};
+use history::History;
+use internal_pages::{DirectoryListing, InternalPage};
+use json_viewer::JsonView;
+use keymap::Keymap;
+use load_timing::LoadTimings;
+use message_pump::MessagePump;
+use new_tab::NewTabTiles;
+use passwords::Passwords;
+use permissions::{Decision, Permission, PermissionPrompt, PermissionResponder, SitePermissions};
+use plugins::Plugins;
+use proxy::ActiveProxy;
+use recently_closed::{ClosedItem, RecentlyClosed};
+use screenshot::{CaptureArea, PageMetrics};
 use serde::Serialize;
+use session::{SessionState, SessionTab, SessionWindows};
+use settings::{BrowserSettings, StartupPage};
+use spellcheck::SpellChecker;
+use streams::StreamRegistry;
+use suggestions::{Suggestion, SuggestionKind};
+use tab_search::OpenWindows;
+use task_manager::{TabUsage, TaskManager};
+use theme::Theme;
+use toast::{toast_error, toast_info, toast_success, ToastManager};
+use user_agent::{BuiltinUserAgent, UserAgentPreset};
+use userscripts::{RunAt, UserScripts};
+use webview_ext::{PdfOptions, WebViewExt};
+use widgets::{svg_button, warning_badge};
+use window_state::WindowState;
+
+actions!(
+    browser,
+    [
+        FindInPage,
+        ZoomIn,
+        ZoomOut,
+        ResetZoom,
+        FocusAddressBar,
+        Reload,
+        HardReload,
+        NewTab,
+        DuplicateTab,
+        CloseTab,
+        ReopenClosedTab,
+        ToggleRecentlyClosed,
+        NextTab,
+        PreviousTab,
+        ActivateTab1,
+        ActivateTab2,
+        ActivateTab3,
+        ActivateTab4,
+        ActivateTab5,
+        ActivateTab6,
+        ActivateTab7,
+        ActivateTab8,
+        ActivateLastTab,
+        CopyUrl,
+        ToggleDevTools,
+        CaptureScreenshot,
+        ExportPdf,
+        OpenFile,
+        NewWindow,
+        NewPrivateWindow,
+        OpenPreferences,
+        ToggleMute,
+        ToggleFullscreen,
+        ToggleConsole,
+        ToggleTaskManager,
+        TogglePictureInPicture,
+        ToggleBookmark,
+        ToggleCommandPalette,
+        ToggleTabSearch,
+        PasteAndGo,
+        ToggleReadAloud,
+        CaptureRegion,
+        ToggleSplitView
+    ]
+);
+
+// Closing more other tabs than this at once asks first
+const CLOSE_TABS_WITHOUT_ASKING: usize = 3;
+// Typing pause before address bar suggestions are looked up
+const SUGGEST_DEBOUNCE: Duration = Duration::from_millis(80);
+// Longest a main-frame request waits for the window to apply the site's JavaScript setting
+const NAVIGATING_TIMEOUT: Duration = Duration::from_millis(250);
+
+// A tab's find in page search, kept while other tabs are shown. Matching and highlighting
+// happen in the page, see `scripts/find.js`.
+struct FindSession {
+    query: String,
+    // 1-based index of the current match, 0 when nothing matched
+    current: u32,
+    count: u32,
+}
+
+// A single browser tab and the subscriptions to its webview
+struct TabState {
+    id: TabId,
+    // Created when the tab is first activated, so restored tabs cost nothing until shown
+    webview: Option<Entity<WebView>>,
+    // Host streams started by the tab's pages, created with its webview
+    streams: Option<StreamRegistry>,
+    title: SharedString,
+    url: SharedString,
+    is_loading: bool,
+    // Load progress in 0..=1, and a counter identifying the current load
+    progress: f32,
+    load_id: usize,
+    favicon: Option<Arc<gpui::Image>>,
+    // Icon the page asked for, so one fetched for a page the tab has left isn't shown
+    favicon_url: Option<String>,
+    zoom_level: f32,
+    // Host whose saved zoom was last applied, so it's looked up again only on a new site
+    zoom_host: Option<String>,
+    // Set when the last main-frame navigation failed
+    error: Option<LoadError>,
+    // Last scroll offset reported by the page, and one to restore once it loads
+    scroll_position: (f32, f32),
+    pending_scroll: Option<(f32, f32)>,
+    // Position in the tab's navigation history, counted by the loads seen so far, and the
+    // scroll offset each entry was left at
+    history_index: usize,
+    scroll_positions: HashMap<usize, (f32, f32)>,
+    // Set by back, forward and reload, so the next load moves by this many entries
+    pending_history_step: Option<isize>,
+    // Main frame of the loaded page, for scripts run on the page's behalf
+    main_frame: Option<Frame>,
+    // Whether the page is playing audible media, as reported by its scripts
+    audio_playing: bool,
+    muted: bool,
+    // Whether the page has a video that can float in Picture-in-Picture, and whether one is
+    has_video: bool,
+    picture_in_picture: bool,
+    console: ConsoleLog,
+    // The last few main frame loads, for the site info popover
+    timings: LoadTimings,
+    // Shown as a favicon-only chip at the start of the strip, and can't be closed
+    pinned: bool,
+    // Navigation held back by an invalid certificate, waiting for the user
+    certificate_error: Option<CertificateError>,
+    // Host the user let through despite its certificate for this visit, until the tab
+    // navigates to another host
+    certificate_bypassed: Option<String>,
+    // Filters the tab's requests against the ad and tracker rules
+    blocker: TabBlocker,
+    // Requests blocked since the page started loading
+    blocked_count: usize,
+    // Ids of the plugins set up in the page, the only ones to hear each other's events here
+    plugins: Vec<String>,
+    // Whether the page's scripts may run, following the per-site setting
+    javascript_enabled: bool,
+    // Set when the user typed into the page since it was submitted, so it isn't discarded
+    unsaved_input: bool,
+    // When the tab was last shown, or left, for discarding tabs that haven't been in a while
+    last_active: Instant,
+    // Memory use the page reported last
+    usage: Option<TabUsage>,
+    // "Request desktop/mobile site" for this tab, over the user agent setting
+    user_agent: Option<UserAgentPreset>,
+    // Whether the page looks like an article, and its extracted content while reader mode is on
+    readable: bool,
+    reader: Option<reader::Article>,
+    // Open while the find bar is shown for this tab
+    find: Option<FindSession>,
+    // The parsed document when the page is raw JSON
+    json: Option<JsonView>,
+    // Contents of the local directory the tab is on
+    directory: Option<DirectoryListing>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl TabState {
+    // Whether the page's certificate was refused, or let through by the user
+    fn certificate_failed(&self) -> bool {
+        self.certificate_error.is_some() || self.certificate_bypassed.is_some()
+    }
+
+    // Remember the scroll offset of the entry being left, and work out which entry is loading.
+    // Back, forward and reload bring back the offset their entry was left at.
+    fn record_navigation(&mut self) {
+        self.scroll_positions
+            .insert(self.history_index, self.scroll_position);
+        self.scroll_position = (0., 0.);
+
+        match self.pending_history_step.take() {
+            Some(step) => {
+                self.history_index = self.history_index.saturating_add_signed(step);
+                if let Some(position) = self
+                    .scroll_positions
+                    .get(&self.history_index)
+                    .filter(|position| **position != (0., 0.))
+                {
+                    self.pending_scroll = Some(*position);
+                }
+            }
+            None => {
+                // a new page drops the forward entries
+                self.history_index += 1;
+                let current = self.history_index;
+                self.scroll_positions.retain(|ix, _| *ix < current);
+            }
+        }
+    }
+}
+
+// Text with the part matching the typed query in bold
+fn highlighted(text: &str, matched: Option<std::ops::Range<usize>>) -> StyledText {
+    let highlight = HighlightStyle {
+        font_weight: Some(FontWeight::BOLD),
+        color: Some(rgb(0xffffff).into()),
+        ..Default::default()
+    };
+    StyledText::new(text.to_string()).with_highlights(matched.map(|range| (range, highlight)))
+}
+
+// Page favicon, or a generic globe when none is available
+fn favicon_icon(favicon: Option<Arc<gpui::Image>>, size: f32) -> gpui::AnyElement {
+    match favicon {
+        Some(favicon) => img(favicon).flex_none().size(px(size)).into_any_element(),
+        None => svg()
+            .path("globe.svg")
+            .flex_none()
+            .size(px(size))
+            .text_color(rgb(0xfefefe))
+            .into_any_element(),
+    }
+}
+
+// Per-download progress bar, pulsing while the total size is unknown
+fn download_progress_bar(id: usize, progress: Option<f32>, theme: Theme) -> impl IntoElement {
+    div()
+        .h(px(3.))
+        .w_full()
+        .rounded_full()
+        .bg(theme.hover)
+        .child(match progress {
+            Some(progress) => div()
+                .h_full()
+                .w(relative(progress))
+                .rounded_full()
+                .bg(theme.text)
+                .into_any_element(),
+            None => div()
+                .h_full()
+                .w_full()
+                .rounded_full()
+                .bg(theme.text)
+                .with_animation(
+                    ("download-pulse", id),
+                    Animation::new(Duration::from_millis(1000)).repeat(),
+                    |this, delta| this.opacity(0.2 + 0.6 * (delta * std::f32::consts::PI).sin()),
+                )
+                .into_any_element(),
+        })
+}
+
+// Connection, permissions and cookies of the active page, shown from the address bar
+struct SiteData {
+    url: String,
+    // `None` until every cookie has been enumerated
+    cookies: Option<Vec<Cookie>>,
+    _load: Task<()>,
+}
+
+// Runs a context menu entry against the menu it was chosen from
+type ContextMenuAction = fn(&mut Main, ContextMenu, &mut Window, &mut Context<Main>);
+
+struct Main {
+    focus_handle: FocusHandle,
+    // Private windows keep cookies and cache in their own in-memory profile,
+    // and don't record history, bookmarks or the session
+    private: bool,
+    request_context: Option<RequestContext>,
+    address_state: Entity<InputState>,
+    // Search box of the new-tab page
+    new_tab_search: Entity<InputState>,
+    bridge_tx: flume::Sender<BridgeEvent>,
+    next_tab_id: TabId,
+    tabs: Vec<TabState>,
+    active_tab: usize,
+    // Decoded favicons by icon URL
+    favicons: HashMap<String, FaviconState>,
+    // Find bar input, created when the find bar is first opened and shared by all tabs
+    find_state: Option<Entity<InputState>>,
+    find_match_case: bool,
+    // Whether every match is highlighted or only the current one
+    find_highlight_all: bool,
+    _find_subscription: Option<Subscription>,
+    // Console panel filter, present while the console panel is open
+    console_filter: Option<Entity<InputState>>,
+    _console_filter_subscription: Option<Subscription>,
+    // Hides the zoom badge shortly after the last zoom change
+    zoom_badge_task: Option<Task<()>>,
+    // History matches shown under the address bar while typing
+    suggestions: Vec<Suggestion>,
+    selected_suggestion: Option<usize>,
+    // Rest of the top suggestion's URL when it starts with the typed text
+    inline_completion: Option<String>,
+    suggest_task: Option<Task<()>>,
+    show_downloads: bool,
+    // Closed tabs of a private window, others record into the `RecentlyClosed` global
+    closed_tabs: RecentlyClosed,
+    show_recently_closed: bool,
+    context_menu: Option<ContextMenu>,
+    context_menu_focus: FocusHandle,
+    // A full-page screenshot waiting for the page to report its size
+    page_metrics_request: Option<(TabId, flume::Sender<PageMetrics>)>,
+    site_data: Option<SiteData>,
+    show_tab_overflow: bool,
+    // Right-click menu of a tab chip
+    tab_menu: Option<TabMenu>,
+    // Where the address bar was right-clicked
+    address_menu: Option<gpui::Point<gpui::Pixels>>,
+    tab_preview: Option<TabPreview>,
+    // Destination of the link under the mouse in the active tab, shown in the status bar
+    hover_url: Option<SharedString>,
+    // Link hint labels shown over the active page while picking a link by keyboard
+    link_hints: Option<LinkHints>,
+    // The active page is fullscreen, so the tab strip and toolbar are hidden
+    is_fullscreen: bool,
+    // Whether the window went fullscreen for the page, and should leave it with the page
+    fullscreen_window_for_page: bool,
+    // Sign-in dialog for a server asking for credentials, one at a time
+    auth_prompt: Option<AuthPrompt>,
+    // Servers each tab was already given keychain credentials for during the current load,
+    // so credentials the server rejects aren't sent again
+    auth_attempts: HashSet<(TabId, String)>,
+    // Pages waiting for the user to allow or block a permission, each shown while its tab is
+    permission_prompts: Vec<PermissionPrompt>,
+    // Open tabs and their memory use, polled only while this is open
+    task_manager: Option<TaskManager>,
+    // Preferences shown by `about:settings` tabs, created the first time one is shown
+    settings_page: Option<AnyView>,
+    // Two-finger horizontal swipes over the page, which go back and forward
+    swipe: SwipeTracker,
+    // Page edge whose back or forward button the pointer is over
+    edge_hover: Option<SwipeDirection>,
+    // Saves the window bounds once resizing or moving settles
+    save_window_state_task: Option<Task<()>>,
+    // Last bounds of a regular window, kept with its tabs in the session
+    window_bounds: Option<WindowState>,
+    // The tab being read aloud, at most one per window
+    read_aloud: Option<tts::ReadAloud>,
+    // Menu offering to fill the focused form field from the autofill profile
+    autofill_offer: Option<autofill::AutofillOffer>,
+    // Asks whether to save what was typed into a form that was just submitted
+    autofill_save: Option<autofill::AutofillSave>,
+    // Menu offering the logins saved for the focused sign-in field's site
+    password_offer: Option<passwords::PasswordOffer>,
+    // Asks whether to save a login the page was just sent
+    password_save: Option<passwords::PasswordSave>,
+    // Saved logins with their passwords, while `about:passwords` is unlocked
+    unlocked_passwords: Option<Vec<passwords::UnlockedLogin>>,
+    // Another tab shown next to the active one
+    split_view: Option<split_view::SplitView>,
+    // Set while dragging out an area of the active page to screenshot
+    region_capture: Option<region_capture::RegionCapture>,
+}
+
+impl Main {
+    fn new(
+        session: SessionState,
+        private: bool,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Entity<Self> {
+        cx.new(|cx| {
+            // create address input
+            let address_state = cx.new(|cx| InputState::new(window, cx));
+
+            cx.subscribe_in(
+                &address_state,
+                window,
+                |this, state, event: &InputEvent, window, cx| match event {
+                    InputEvent::PressEnter { .. } => {
+                        let engine = cx.global::<BrowserSettings>().search_engine();
+                        let url = address::normalize_input(&state.read(cx).value(), &engine);
+                        this.navigate(&url, window, cx);
+                    }
+                    InputEvent::Change(_) => {
+                        if state.focus_handle(cx).is_focused(window) {
+                            let query = state.read(cx).value().to_string();
+                            this.suggest_task = Some(cx.spawn(async move |this, cx| {
+                                Timer::after(SUGGEST_DEBOUNCE).await;
+                                this.update(cx, |this, cx| this.update_suggestions(&query, cx))
+                                    .ok();
+                            }));
+                        }
+                    }
+                    InputEvent::Blur => this.clear_suggestions(cx),
+                    _ => {}
+                },
+            )
+            .detach();
+
+            let new_tab_search =
+                cx.new(|cx| InputState::new(window, cx).placeholder("Search or enter an address"));
+            cx.subscribe_in(
+                &new_tab_search,
+                window,
+                |this, state, event: &InputEvent, window, cx| {
+                    if let InputEvent::PressEnter { .. } = event {
+                        let engine = cx.global::<BrowserSettings>().search_engine();
+                        let url = address::normalize_input(&state.read(cx).value(), &engine);
+                        state.update(cx, |state, cx| state.set_value("", window, cx));
+                        this.navigate(&url, window, cx);
+                    }
+                },
+            )
+            .detach();
+
+            cx.observe(&DownloadManager::global(cx), |_, _, cx| cx.notify())
+                .detach();
+
+            // let pages follow the system theme without polling `matchMedia`
+            cx.observe_window_appearance(window, |this, window, cx| {
+                let dark = matches!(
+                    window.appearance(),
+                    gpui::WindowAppearance::Dark | gpui::WindowAppearance::VibrantDark
+                );
+                if let Err(err) =
+                    this.broadcast_event("appearanceChanged", &serde_json::json!({ "dark": dark }))
+                {
+                    log::error!("Failed to broadcast appearance change: {}", err);
+                }
+                Theme::sync(cx);
+                cx.notify();
+            })
+            .detach();
+            cx.observe_global_in::<Theme>(window, |_, window, cx| {
+                window.set_background_appearance(cx.global::<Theme>().window_background());
+                cx.notify();
+            })
+            .detach();
+
+            Self::watch_inactive_tabs(cx);
+
+            // CEF's message loop slows down once no window is focused or loading
+            cx.observe_window_activation(window, |this, window, cx| {
+                this.sync_message_pump(window, cx)
+            })
+            .detach();
+            let entity_id = cx.entity_id();
+            cx.on_release(move |_, cx| MessagePump::set_busy(entity_id, false, cx))
+                .detach();
+
+            // plugins switched on or off add or remove their toolbar buttons
+            cx.observe_global::<Plugins>(|_, cx| cx.notify()).detach();
+
+            // private windows switch their own profile's proxy along with the shared one
+            cx.observe_global::<ActiveProxy>(|this, cx| {
+                if let Some(request_context) = &this.request_context {
+                    _ = proxy::apply(request_context, &cx.global::<ActiveProxy>().settings);
+                }
+                cx.notify();
+            })
+            .detach();
+
+            // allowlisting a site in one window stops blocking on it in the others
+            cx.observe_global::<BrowserSettings>(|this, cx| {
+                for ix in 0..this.tabs.len() {
+                    this.sync_content_blocking(ix, cx);
+                }
+            })
+            .detach();
+
+            // zooming a site in one tab zooms its other tabs, and resetting all zoom levels
+            // in preferences resets open tabs. Private windows keep their one-off zoom.
+            if !private {
+                cx.observe_global::<BrowserSettings>(|this, cx| {
+                    for ix in 0..this.tabs.len() {
+                        if this.tabs[ix].zoom_host.is_some() {
+                            this.apply_site_zoom(ix, cx);
+                        }
+                    }
+                })
+                .detach();
+            }
+
+            if !private {
+                cx.observe_window_bounds(window, |this, window, cx| {
+                    let state = WindowState::from_window(window);
+                    this.window_bounds = Some(state);
+                    this.save_window_state_task = Some(cx.background_spawn(async move {
+                        Timer::after(Duration::from_millis(500)).await;
+                        state.save();
+                    }));
+                })
+                .detach();
+            }
+
+            // page scripts talk to this window through the bridge channel
+            let (bridge_tx, bridge_rx) = flume::unbounded();
+            cx.spawn_in(window, async move |this, cx| {
+                while let Ok(event) = bridge_rx.recv_async().await {
+                    let handled = this.update_in(cx, |this, window, cx| {
+                        this.handle_bridge_event(event, window, cx)
+                    });
+                    if handled.is_err() {
+                        break;
+                    }
+                }
+            })
+            .detach();
+
+            // an empty cache path keeps the profile in memory, so it's discarded on close
+            let request_context = private.then(|| {
+                let request_context = RequestContext::in_memory();
+                _ = proxy::apply(&request_context, &cx.global::<ActiveProxy>().settings);
+                request_context
+            });
+
+            let mut this = Self {
+                focus_handle: cx.focus_handle(),
+                private,
+                request_context,
+                address_state,
+                new_tab_search,
+                bridge_tx,
+                next_tab_id: 0,
+                tabs: Vec::new(),
+                active_tab: 0,
+                favicons: HashMap::new(),
+                find_state: None,
+                find_match_case: false,
+                find_highlight_all: true,
+                _find_subscription: None,
+                console_filter: None,
+                _console_filter_subscription: None,
+                zoom_badge_task: None,
+                suggestions: Vec::new(),
+                selected_suggestion: None,
+                inline_completion: None,
+                suggest_task: None,
+                show_downloads: false,
+                closed_tabs: RecentlyClosed::in_memory(),
+                show_recently_closed: false,
+                context_menu: None,
+                context_menu_focus: cx.focus_handle(),
+                page_metrics_request: None,
+                site_data: None,
+                show_tab_overflow: false,
+                tab_menu: None,
+                address_menu: None,
+                tab_preview: None,
+                hover_url: None,
+                link_hints: None,
+                is_fullscreen: false,
+                fullscreen_window_for_page: false,
+                swipe: SwipeTracker::default(),
+                edge_hover: None,
+                auth_prompt: None,
+                auth_attempts: HashSet::new(),
+                permission_prompts: Vec::new(),
+                task_manager: None,
+                settings_page: None,
+                save_window_state_task: None,
+                window_bounds: (!private).then(|| WindowState::from_window(window)),
+                read_aloud: None,
+                region_capture: None,
+                split_view: None,
+                autofill_offer: None,
+                autofill_save: None,
+                password_offer: None,
+                password_save: None,
+                unlocked_passwords: None,
+            };
+            // restored tabs start unloaded, only the active one creates its webview here
+            for tab in &session.tabs {
+                let ix = this.insert_unloaded_tab(
+                    this.tabs.len(),
+                    &tab.url,
+                    tab.title.clone().into(),
+                    tab.favicon_image(),
+                    cx,
+                );
+                if this.tabs[ix].favicon.is_none() {
+                    this.update_favicon(ix, &tab.url, None, cx);
+                }
+            }
+            for tab in this.tabs.iter_mut().take(session.pinned) {
+                tab.pinned = true;
+            }
+            if cx.global::<BrowserSettings>().load_tabs_on_startup {
+                for ix in 0..this.tabs.len() {
+                    this.ensure_loaded(ix, window, cx);
+                }
+            }
+            this.activate_tab(session.active_tab, window, cx);
+            this
+        })
+    }
+
+    // Push `event` to every loaded page in this window. The top frame of each tab relays it to
+    // its iframes, see `hostEvents` in `scripts/bridge.js`.
+    fn broadcast_event<T: Serialize>(&self, event: &str, payload: &T) -> serde_json::Result<()> {
+        let message = HostEvent::new(event, payload)?;
+        for frame in self.tabs.iter().filter_map(|tab| tab.main_frame.as_ref()) {
+            frame.emit(message.clone());
+        }
+        Ok(())
+    }
+
+    // Create a webview for `url`, append it as a tab and make it active
+    fn new_tab(&mut self, url: &str, window: &mut Window, cx: &mut Context<Self>) {
+        self.insert_tab(self.tabs.len(), url, window, cx);
+        self.activate_tab(self.tabs.len() - 1, window, cx);
+    }
+
+    // Create a webview for `url` as a tab at `ix` without activating it. New tabs go after the
+    // pinned ones, returns where it ended up.
+    fn insert_tab(
+        &mut self,
+        ix: usize,
+        url: &str,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> usize {
+        let ix = self.insert_unloaded_tab(ix, url, SharedString::default(), None, cx);
+        self.ensure_loaded(ix, window, cx);
+        ix
+    }
+
+    // Add a tab for `url` at `ix` that shows its title and favicon in the strip but has no webview
+    // until it's first activated, see `ensure_loaded`. Returns where it ended up.
+    fn insert_unloaded_tab(
+        &mut self,
+        ix: usize,
+        url: &str,
+        title: SharedString,
+        favicon: Option<Arc<gpui::Image>>,
+        cx: &mut Context<Self>,
+    ) -> usize {
+        let id = self.next_tab_id;
+        self.next_tab_id += 1;
+
+        let blocker = cx
+            .global::<ContentBlocker>()
+            .tab_blocker(id, self.bridge_tx.clone());
+        blocker.set_enabled(cx.global::<BrowserSettings>().blocking_enabled(url));
+
+        let ix = ix.min(self.tabs.len()).max(self.pinned_count());
+        if ix <= self.active_tab && !self.tabs.is_empty() {
+            self.active_tab += 1;
+        }
+        self.tabs.insert(
+            ix,
+            TabState {
+                id,
+                webview: None,
+                streams: None,
+                title,
+                url: SharedString::from(url.to_string()),
+                is_loading: false,
+                progress: 0.0,
+                load_id: 0,
+                favicon,
+                favicon_url: None,
+                zoom_level: 1.0,
+                zoom_host: None,
+                error: None,
+                scroll_position: (0., 0.),
+                pending_scroll: None,
+                history_index: 0,
+                scroll_positions: HashMap::new(),
+                pending_history_step: None,
+                main_frame: None,
+                audio_playing: false,
+                has_video: false,
+                picture_in_picture: false,
+                muted: false,
+                console: ConsoleLog::default(),
+                timings: LoadTimings::default(),
+                user_agent: None,
+                readable: false,
+                reader: None,
+                find: None,
+                json: None,
+                directory: None,
+                pinned: false,
+                certificate_error: None,
+                certificate_bypassed: None,
+                blocker,
+                blocked_count: 0,
+                plugins: Vec::new(),
+                javascript_enabled: true,
+                unsaved_input: false,
+                last_active: Instant::now(),
+                usage: None,
+                _subscriptions: Vec::new(),
+            },
+        );
+        if url == settings::NEW_TAB_URL {
+            self.load_tile_favicons(cx);
+        }
+        ix
+    }
+
+    // Create the webview of the tab at `ix` and load its page, unless that already happened
+    fn ensure_loaded(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let tab = &self.tabs[ix];
+        if tab.webview.is_some() {
+            return;
+        }
+        let id = tab.id;
+        let url = tab.url.to_string();
+        let blocker = tab.blocker.clone();
+
+        let streams = bridge::stream_registry(cx.background_executor().clone());
+        let func_registry = bridge::func_registry(
+            id,
+            self.bridge_tx.clone(),
+            streams.clone(),
+            cx.background_executor().clone(),
+        );
+        let webview_url = internal_pages::webview_url(&url);
+        let webview = match &self.request_context {
+            Some(request_context) => WebView::with_request_context(
+                &webview_url,
+                func_registry,
+                request_context.clone(),
+                window,
+                cx,
+            ),
+            None => WebView::with_func_registry(&webview_url, func_registry, window, cx),
+        };
+        webview.read(cx).set_before_resource_load({
+            let events = self.bridge_tx.clone();
+            move |request| {
+                // the request waits until the window switched JavaScript for the site, so the
+                // new page's scripts start under its setting. A window too busy to answer in
+                // time lets the page load under the previous one.
+                if request.resource_type() == wef::ResourceType::MainFrame {
+                    let (applied, response) = flume::bounded(1);
+                    if events
+                        .send(BridgeEvent::Navigating {
+                            tab_id: id,
+                            url: request.url().to_string(),
+                            applied,
+                        })
+                        .is_ok()
+                    {
+                        _ = response.recv_timeout(NAVIGATING_TIMEOUT);
+                    }
+                }
+                blocker.should_load(request)
+            }
+        });
+
+        let subscriptions = vec![
+            cx.subscribe_in(
+                &webview,
+                window,
+                |this, webview, event: &TitleChangedEvent, window, cx| {
+                    if let Some(ix) = this.tab_index(webview) {
+                        let tab = &mut this.tabs[ix];
+                        let url = tab.url.clone();
+                        // the blank page's title would be its URL
+                        let internal_title = internal_pages::page_title(&url);
+                        let title = internal_title.as_deref().unwrap_or(event.title.as_str());
+                        tab.title = title.to_string().into();
+                        if !this.private && internal_title.is_none() {
+                            cx.update_global::<History, _>(|history, _| {
+                                history.set_title(&url, title)
+                            });
+                        }
+                        if ix == this.active_tab {
+                            window.set_window_title(title);
+                        }
+                        cx.notify();
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &webview,
+                window,
+                |this, webview, event: &AddressChangedEvent, window, cx| {
+                    if !event.frame.is_main() {
+                        return;
+                    }
+                    if let Some(ix) = this.tab_index(webview) {
+                        // the blank page under an internal page, before its fragment is set
+                        if event.url == settings::BLANK_PAGE_URL
+                            && internal_pages::is_internal(&this.tabs[ix].url)
+                        {
+                            return;
+                        }
+                        let url = internal_pages::tab_url(&event.url);
+                        let internal_title = internal_pages::page_title(&url);
+                        this.tabs[ix].url = SharedString::from(url.clone());
+                        if let Some(title) = &internal_title {
+                            this.tabs[ix].title = title.clone().into();
+                        }
+                        this.tabs[ix].directory =
+                            internal_pages::directory_path(&url).map(DirectoryListing::read);
+                        let host = settings::zoom_host(&url);
+                        if this.tabs[ix].zoom_host != host {
+                            this.tabs[ix].zoom_host = host;
+                            this.apply_site_zoom(ix, cx);
+                        }
+                        this.sync_content_blocking(ix, cx);
+                        this.apply_javascript_setting(ix, &url, cx);
+                        if !this.private && internal_title.is_none() {
+                            cx.update_global::<History, _>(|history, _| {
+                                history.record_visit(&url, "")
+                            });
+                        }
+                        // pages changing their address from script stay loaded, so their
+                        // plugins hear of it here; new documents' plugins once they've loaded
+                        plugins::dispatch(
+                            &event.frame,
+                            "navigate",
+                            &serde_json::json!({ "url": url }),
+                            None,
+                        );
+                        this.sync_split_address(window, cx);
+                        if ix == this.active_tab {
+                            this.sync_address_bar(window, cx);
+                            if InternalPage::from_url(&url) != Some(InternalPage::Passwords) {
+                                this.lock_passwords(cx);
+                            }
+                        }
+                        cx.notify();
+                    }
+                },
+            ),
+            // re-render when the back/forward stack changes
+            cx.subscribe_in(
+                &webview,
+                window,
+                |this, _, _: &LoadingStateChangedEvent, window, cx| {
+                    this.sync_message_pump(window, cx);
+                    cx.notify();
+                },
+            ),
+            // track main frame loading to swap refresh/stop
+            cx.subscribe_in(
+                &webview,
+                window,
+                |this, webview, event: &LoadStartEvent, window, cx| {
+                    bridge::inject_document_start_scripts(&event.frame);
+                    userscripts::inject(&event.frame, RunAt::DocumentStart, cx);
+                    if let Some(ix) = this.tab_index(webview).filter(|_| event.frame.is_main()) {
+                        // before the page's scripts, which therefore can't get in their way
+                        let plugins = if this.private {
+                            Vec::new()
+                        } else {
+                            plugins::inject(&event.frame, cx)
+                        };
+                        let tab = &mut this.tabs[ix];
+                        tab.plugins = plugins;
+                        tab.is_loading = true;
+                        tab.progress = 0.0;
+                        tab.load_id += 1;
+                        tab.error = None;
+                        // streams would keep emitting into the new document
+                        if let Some(streams) = &tab.streams {
+                            streams.cancel_all();
+                        }
+                        tab.record_navigation();
+                        tab.timings.start(event.frame.url());
+                        // the new page reports again once it starts playing
+                        tab.audio_playing = false;
+                        tab.has_video = false;
+                        tab.picture_in_picture = false;
+                        tab.readable = false;
+                        tab.reader = None;
+                        tab.json = None;
+                        // the new document has none of the old matches
+                        if let Some(find) = &mut tab.find {
+                            find.current = 0;
+                            find.count = 0;
+                        }
+                        tab.console.clear();
+                        tab.blocked_count = 0;
+                        tab.unsaved_input = false;
+                        // navigated somewhere else while the warning was up
+                        if let Some(error) = tab.certificate_error.take() {
+                            error.callback.cancel();
+                        }
+                        // a certificate let through only covers its own host
+                        let host = url::Url::parse(&event.frame.url())
+                            .ok()
+                            .and_then(|url| url.host_str().map(str::to_string));
+                        tab.certificate_bypassed
+                            .take_if(|bypassed| host.as_deref() != Some(bypassed.as_str()));
+                        if ix == this.active_tab {
+                            this.hover_url = None;
+                            this.link_hints = None;
+                        }
+                        let tab_id = this.tabs[ix].id;
+                        this.autofill_offer.take_if(|offer| offer.tab_id == tab_id);
+                        this.password_offer.take_if(|offer| offer.tab_id == tab_id);
+                        this.dismiss_permission_prompts(tab_id);
+                        if this
+                            .read_aloud
+                            .as_ref()
+                            .is_some_and(|read_aloud| read_aloud.tab_id == tab_id)
+                        {
+                            this.stop_read_aloud(cx);
+                        }
+                        this.sync_message_pump(window, cx);
+                        cx.notify();
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &webview,
+                window,
+                |this, webview, event: &LoadEndEvent, window, cx| {
+                    bridge::inject_page_scripts(&event.frame);
+                    userscripts::inject(&event.frame, RunAt::DocumentEnd, cx);
+                    if let Some(ix) = this.tab_index(webview).filter(|_| event.frame.is_main()) {
+                        let tab = &mut this.tabs[ix];
+                        if !tab.plugins.is_empty() {
+                            plugins::start(&event.frame);
+                        }
+                        tab.is_loading = false;
+                        tab.progress = 1.0;
+                        tab.main_frame = Some(event.frame.clone());
+                        tab.timings.finish();
+                        let tab_id = tab.id;
+                        this.auth_attempts.retain(|(id, _)| *id != tab_id);
+                        if let Some((x, y)) = tab.pending_scroll.take() {
+                            event.frame.execute_javascript(&format!(
+                                "window.__browserRestoreScroll({}, {})",
+                                x, y
+                            ));
+                        }
+                        this.apply_linked_scroll(cx);
+                        // pages report the icon they declare, except with scripts off
+                        if !this.tabs[ix].javascript_enabled {
+                            this.update_favicon(ix, &event.frame.url(), None, cx);
+                        }
+                        this.sync_message_pump(window, cx);
+                        cx.notify();
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &webview,
+                window,
+                |this, webview, event: &LoadErrorEvent, _, cx| {
+                    if !event.frame.is_main() {
+                        return;
+                    }
+                    // stopped loads end here too, with ERR_ABORTED
+                    let ix = this.tab_index(webview);
+                    if let Some(ix) = ix {
+                        this.tabs[ix].timings.stop();
+                    }
+                    if event.error_code == error_page::ERR_ABORTED {
+                        return;
+                    }
+                    if let Some(ix) = ix {
+                        this.tabs[ix].error = Some(LoadError::new(
+                            &event.failed_url,
+                            event.error_code,
+                            &event.error_text,
+                        ));
+                        cx.notify();
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &webview,
+                window,
+                |this, webview, event: &CertificateErrorEvent, _, cx| {
+                    let Some(ix) = this.tab_index(webview) else {
+                        event.callback.cancel();
+                        return;
+                    };
+
+                    let host = url::Url::parse(&event.url)
+                        .ok()
+                        .and_then(|url| url.host_str().map(str::to_string))
+                        .unwrap_or_else(|| event.url.clone());
+                    if cx.global::<CertificateExceptions>().0.contains(&host) {
+                        event.callback.proceed();
+                        return;
+                    }
+
+                    let error = CertificateError {
+                        tab_id: this.tabs[ix].id,
+                        url: event.url.clone(),
+                        host,
+                        code: event.error_code,
+                        problem: CertificateProblem::from_code(event.error_code),
+                        remember: false,
+                        callback: event.callback.clone(),
+                    };
+                    if let Some(previous) = this.tabs[ix].certificate_error.replace(error) {
+                        previous.callback.cancel();
+                    }
+                    cx.notify();
+                },
+            ),
+            cx.subscribe_in(
+                &webview,
+                window,
+                |this, webview, event: &AuthRequiredEvent, window, cx| match this.tab_index(webview)
+                {
+                    Some(ix) => this.request_credentials(ix, event, window, cx),
+                    None => event.callback.cancel(),
+                },
+            ),
+            cx.subscribe_in(
+                &webview,
+                window,
+                |this, webview, event: &PermissionRequestEvent, _, cx| match this.tab_index(webview)
+                {
+                    Some(ix) => this.request_permissions(ix, event, cx),
+                    None => event.callback.deny(),
+                },
+            ),
+            cx.subscribe_in(
+                &webview,
+                window,
+                |this, webview, event: &LoadingProgressChangedEvent, _, cx| {
+                    if let Some(ix) = this.tab_index(webview) {
+                        this.tabs[ix].progress = event.progress.clamp(0.0, 1.0);
+                        cx.notify();
+                    }
+                },
+            ),
+        ];
+
+        let tab = &mut self.tabs[ix];
+        // muted from the tab menu before it was loaded
+        if tab.muted {
+            webview.read(cx).set_muted(true);
+        }
+        tab.webview = Some(webview);
+        tab.streams = Some(streams);
+        tab._subscriptions = subscriptions;
+    }
+
+    fn handle_bridge_event(
+        &mut self,
+        event: BridgeEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            BridgeEvent::OpenTab {
+                opener,
+                url,
+                foreground,
+            } => self.open_link_in_tab(opener, &url, foreground, window, cx),
+            BridgeEvent::Download { url, filename } => {
+                DownloadManager::global(cx).update(cx, |downloads, cx| {
+                    downloads.start(&url, filename.as_deref(), self.private, cx)
+                });
+                self.show_downloads = true;
+                cx.notify();
+            }
+            BridgeEvent::ScrollChanged { tab_id, x, y } => {
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == tab_id) {
+                    tab.scroll_position = (x, y);
+                }
+                // the menus would be left behind by their field
+                let autofill = self.autofill_offer.take_if(|offer| offer.tab_id == tab_id);
+                let password = self.password_offer.take_if(|offer| offer.tab_id == tab_id);
+                if autofill.is_some() || password.is_some() {
+                    cx.notify();
+                }
+            }
+            BridgeEvent::AutofillField {
+                tab_id,
+                origin,
+                kinds,
+                x,
+                bottom,
+                width,
+            } => {
+                // nothing is offered to a page the tab has since moved away from
+                let kinds = if self.tab_on_origin(tab_id, &origin) {
+                    kinds
+                } else {
+                    Vec::new()
+                };
+                self.offer_autofill(tab_id, kinds, x, bottom, width, cx)
+            }
+            BridgeEvent::AutofillSubmitted {
+                tab_id,
+                origin,
+                values,
+            } => self.autofill_submitted(tab_id, origin, values, cx),
+            BridgeEvent::LinkedScroll { tab_id, x, y } => self.mirror_split_scroll(tab_id, x, y),
+            BridgeEvent::PasswordField {
+                tab_id,
+                origin,
+                x,
+                bottom,
+                width,
+            } => self.offer_passwords(tab_id, origin, x, bottom, width, cx),
+            BridgeEvent::PasswordSubmitted {
+                tab_id,
+                origin,
+                username,
+                password,
+            } => self.password_submitted(tab_id, origin, username, password, cx),
+            BridgeEvent::AudioChanged { tab_id, playing } => {
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == tab_id) {
+                    tab.audio_playing = playing;
+                    // background audio keeps playing smoothly while the window is inactive
+                    self.sync_message_pump(window, cx);
+                    cx.notify();
+                }
+            }
+            BridgeEvent::VideoChanged {
+                tab_id,
+                has_video,
+                picture_in_picture,
+            } => {
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == tab_id) {
+                    tab.has_video = has_video;
+                    tab.picture_in_picture = picture_in_picture;
+                    // the floating video keeps playing while the window is in the background
+                    self.sync_message_pump(window, cx);
+                    cx.notify();
+                }
+            }
+            BridgeEvent::JsonDocument { tab_id, text } => {
+                let Some(load_id) = self
+                    .tabs
+                    .iter()
+                    .find(|tab| tab.id == tab_id)
+                    .map(|tab| tab.load_id)
+                else {
+                    return;
+                };
+                // large documents take a while to parse and lay out
+                let parse = cx.background_spawn(async move { JsonView::parse(&text) });
+                cx.spawn(async move |this, cx| {
+                    let Some(view) = parse.await else {
+                        return;
+                    };
+                    this.update(cx, |this, cx| {
+                        if let Some(tab) = this
+                            .tabs
+                            .iter_mut()
+                            .find(|tab| tab.id == tab_id && tab.load_id == load_id)
+                        {
+                            tab.json = Some(view);
+                            cx.notify();
+                        }
+                    })
+                    .ok();
+                })
+                .detach();
+            }
+            BridgeEvent::ReadAloudSentences { tab_id, sentences } => {
+                self.start_read_aloud(tab_id, sentences, cx)
+            }
+            BridgeEvent::ReadAloudSpoken {
+                tab_id,
+                index,
+                error,
+            } => self.read_aloud_spoken(tab_id, index, error, cx),
+            BridgeEvent::LoadTiming {
+                tab_id,
+                dom_content_loaded,
+                load,
+            } => {
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == tab_id) {
+                    tab.timings.report(dom_content_loaded, load);
+                    cx.notify();
+                }
+            }
+            BridgeEvent::FindResult {
+                tab_id,
+                current,
+                count,
+            } => {
+                if let Some(find) = self
+                    .tabs
+                    .iter_mut()
+                    .find(|tab| tab.id == tab_id)
+                    .and_then(|tab| tab.find.as_mut())
+                {
+                    find.current = current;
+                    find.count = count;
+                    cx.notify();
+                }
+            }
+            BridgeEvent::Hints { tab_id, hints } => {
+                if self.tabs[self.active_tab].id != tab_id {
+                    return;
+                }
+                if hints.is_empty() {
+                    self.cancel_link_hints(cx);
+                    toast_info(cx, "No links on screen");
+                    return;
+                }
+
+                // refreshed after a scroll or page change, keep what's typed if it still matches
+                let mut link_hints = LinkHints::new(tab_id, hints);
+                if let Some(previous) = self
+                    .link_hints
+                    .take()
+                    .filter(|hints| hints.tab_id == tab_id)
+                {
+                    if link_hints
+                        .hints
+                        .iter()
+                        .any(|hint| hint.label.starts_with(&previous.typed))
+                    {
+                        link_hints.typed = previous.typed;
+                    }
+                }
+                self.link_hints = Some(link_hints);
+                cx.notify();
+            }
+            BridgeEvent::ClipboardWrite {
+                tab_id,
+                text,
+                reply,
+            } => self.write_clipboard_for(tab_id, text, reply, cx),
+            BridgeEvent::ClipboardRead { tab_id, reply } => {
+                self.read_clipboard_for(tab_id, reply, cx)
+            }
+            BridgeEvent::ConsoleMessage { tab_id, message } => {
+                if let Some(ix) = self.tabs.iter().position(|tab| tab.id == tab_id) {
+                    self.tabs[ix].console.push(message);
+                    if ix == self.active_tab && self.console_filter.is_some() {
+                        cx.notify();
+                    }
+                }
+            }
+            BridgeEvent::UserAgent { tab_id, user_agent } => {
+                let overridden = self
+                    .tabs
+                    .iter()
+                    .any(|tab| tab.id == tab_id && tab.user_agent.is_some());
+                let customized = cx.global::<BrowserSettings>().user_agent.is_some();
+                if !overridden && !customized && !cx.has_global::<BuiltinUserAgent>() {
+                    cx.set_global(BuiltinUserAgent(user_agent));
+                }
+            }
+            BridgeEvent::Navigating {
+                tab_id,
+                url,
+                applied,
+            } => {
+                if let Some(ix) = self.tabs.iter().position(|tab| tab.id == tab_id) {
+                    self.apply_javascript_setting(ix, &url, cx);
+                }
+                _ = applied.send(());
+            }
+            BridgeEvent::ResourceBlocked { tab_id } => {
+                if let Some(ix) = self.tabs.iter().position(|tab| tab.id == tab_id) {
+                    self.tabs[ix].blocked_count += 1;
+                    if ix == self.active_tab {
+                        cx.notify();
+                    }
+                }
+            }
+            BridgeEvent::MemoryUsage { tab_id, usage } => {
+                self.report_tab_usage(tab_id, usage, cx);
+            }
+            BridgeEvent::FormInput { tab_id, unsaved } => {
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == tab_id) {
+                    tab.unsaved_input = unsaved;
+                }
+            }
+            BridgeEvent::Favicon {
+                tab_id,
+                page_url,
+                icon_url,
+            } => {
+                // a page the tab has already left may still report
+                let host = favicon::favicon_url(&page_url).map(|(host, _)| host);
+                if let Some(ix) = self.tabs.iter().position(|tab| {
+                    tab.id == tab_id
+                        && host
+                            .as_ref()
+                            .is_none_or(|host| favicon::is_on_host(&tab.url, host))
+                }) {
+                    self.update_favicon(ix, &page_url, icon_url.as_deref(), cx);
+                    cx.notify();
+                }
+            }
+            BridgeEvent::HoverLink { tab_id, url } => {
+                if self.tabs[self.active_tab].id == tab_id {
+                    self.hover_url = url.map(SharedString::from);
+                    cx.notify();
+                }
+            }
+            BridgeEvent::FullscreenChanged { tab_id, fullscreen } => {
+                if self.tabs[self.active_tab].id == tab_id {
+                    self.set_page_fullscreen(fullscreen, window);
+                    cx.notify();
+                }
+            }
+            BridgeEvent::Readable { tab_id, readable } => {
+                if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == tab_id) {
+                    tab.readable = readable;
+                    cx.notify();
+                }
+            }
+            BridgeEvent::ReaderContent { tab_id, article } => {
+                if article.blocks.is_empty() {
+                    toast_info(cx, "Couldn't find an article on this page");
+                } else if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == tab_id) {
+                    tab.reader = Some(article);
+                    cx.notify();
+                }
+            }
+            BridgeEvent::PageMetrics { tab_id, metrics } => {
+                if let Some((_, reply)) = self
+                    .page_metrics_request
+                    .take_if(|(requester, _)| *requester == tab_id)
+                {
+                    _ = reply.send(metrics);
+                }
+            }
+            BridgeEvent::ContextMenu {
+                tab_id,
+                frame,
+                link_url,
+                image_url,
+                selection_text,
+                field,
+            } => {
+                if self.tabs[self.active_tab].id != tab_id {
+                    return;
+                }
+
+                let spellcheck = field.as_ref().is_some_and(|field| field.spellcheck);
+                let checker = cx.global::<SpellChecker>();
+                let misspelling = field
+                    .as_ref()
+                    .filter(|field| field.spellcheck && !checker.is_correct(&field.word))
+                    .map(|field| Misspelling {
+                        word: field.word.clone(),
+                        suggestions: checker.suggestions(&field.word),
+                    });
+
+                // the page doesn't report where it was clicked, the cursor hasn't moved far since
+                self.context_menu = Some(ContextMenu {
+                    tab_id,
+                    position: window.mouse_position(),
+                    frame,
+                    link_url,
+                    image_url,
+                    selection_text,
+                    editable: field.is_some(),
+                    spellcheck,
+                    misspelling,
+                });
+                window.focus(&self.context_menu_focus);
+                cx.notify();
+            }
+            BridgeEvent::Plugin {
+                tab_id,
+                token,
+                request,
+                reply,
+            } => self.handle_plugin_request(tab_id, &token, request, reply, cx),
+            BridgeEvent::TabUrl { tab_id, reply } => {
+                if let Some(tab) = self.tabs.iter().find(|tab| tab.id == tab_id) {
+                    _ = reply.send(tab.url.to_string());
+                }
+            }
+        }
+    }
+
+    // Open a link next to the tab it came from, in the foreground or background
+    fn open_link_in_tab(
+        &mut self,
+        opener: TabId,
+        url: &str,
+        foreground: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let ix = self
+            .tabs
+            .iter()
+            .position(|tab| tab.id == opener)
+            .map_or(self.tabs.len(), |ix| ix + 1);
+        let ix = self.insert_tab(ix, url, window, cx);
+        if foreground {
+            self.activate_tab(ix, window, cx);
+        } else {
+            cx.notify();
+        }
+    }
+
+    // Open the active page again in a new tab right after it. CEF can't copy a browser's
+    // navigation history, so the copy's history starts at the current page.
+    fn duplicate_tab(&mut self, _: &DuplicateTab, window: &mut Window, cx: &mut Context<Self>) {
+        let url = self.tabs[self.active_tab].url.to_string();
+        let ix = self.insert_tab(self.active_tab + 1, &url, window, cx);
+        self.activate_tab(ix, window, cx);
+    }
+
+    // Keep CEF's message loop at its active rate while this window is focused, loading or
+    // playing media
+    fn sync_message_pump(&self, window: &Window, cx: &mut Context<Self>) {
+        let busy = window.is_window_active()
+            || self
+                .tabs
+                .iter()
+                .any(|tab| tab.is_loading || tab.picture_in_picture || tab.audio_playing);
+        MessagePump::set_busy(cx.entity_id(), busy, cx);
+    }
+
+    // Close the tab at `ix`, replacing the last tab with a blank one. Pinned tabs have to be
+    // unpinned first.
+    fn close_tab(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if ix >= self.tabs.len() {
+            return;
+        }
+        if self.tabs[ix].pinned {
+            toast_info(cx, "Unpin the tab to close it");
+            return;
+        }
+        let tab_id = self.tabs[ix].id;
+        if let Some(prompt) = self
+            .auth_prompt
+            .take_if(|prompt| prompt.request.tab_id == tab_id)
+        {
+            prompt.request.callback.cancel();
+        }
+        self.dismiss_permission_prompts(tab_id);
+        if self
+            .read_aloud
+            .as_ref()
+            .is_some_and(|read_aloud| read_aloud.tab_id == tab_id)
+        {
+            self.stop_read_aloud(cx);
+        }
+        if let Some(streams) = &self.tabs[ix].streams {
+            streams.cancel_all();
+        }
+        self.autofill_offer.take_if(|offer| offer.tab_id == tab_id);
+        self.autofill_save.take_if(|prompt| prompt.tab_id == tab_id);
+        self.password_offer.take_if(|offer| offer.tab_id == tab_id);
+        self.password_save.take_if(|prompt| prompt.tab_id == tab_id);
+        if ix == self.active_tab
+            || self
+                .split_view
+                .as_ref()
+                .is_some_and(|split| split.tab_id == tab_id)
+        {
+            self.exit_split_view(cx);
+        }
+
+        let tab = self.tabs.remove(ix);
+        if let Some(error) = &tab.certificate_error {
+            error.callback.cancel();
+        }
+        let closed = SessionTab {
+            url: tab.url.to_string(),
+            title: tab.title.to_string(),
+            favicon: None,
+        }
+        .with_favicon(tab.favicon.as_deref());
+        self.update_recently_closed(cx, |recent| {
+            recent.push(ClosedItem::tab(closed, tab.scroll_position))
+        });
+        self.sync_message_pump(window, cx);
+
+        if self.tabs.is_empty() {
+            self.open_new_tab(window, cx);
+            return;
+        }
+
+        let active_tab = if ix < self.active_tab {
+            self.active_tab - 1
+        } else {
+            self.active_tab.min(self.tabs.len() - 1)
+        };
+        self.activate_tab(active_tab, window, cx);
+    }
+
+    // Close the unpinned tabs other than `keep` for which `close` holds, switching to `keep`
+    // first when the active tab is among them
+    fn close_tabs(
+        &mut self,
+        keep: usize,
+        close: impl Fn(usize) -> bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let closing: Vec<_> = self
+            .tabs
+            .iter()
+            .enumerate()
+            .filter(|(ix, tab)| *ix != keep && !tab.pinned && close(*ix))
+            .map(|(_, tab)| tab.id)
+            .collect();
+        if closing.contains(&self.tabs[self.active_tab].id) {
+            self.activate_tab(keep, window, cx);
+        }
+        for tab_id in closing {
+            if let Some(ix) = self.tabs.iter().position(|tab| tab.id == tab_id) {
+                self.close_tab(ix, window, cx);
+            }
+        }
+    }
+
+    // Close every unpinned tab but the one at `ix`, asking first when that's more than a few
+    fn close_other_tabs(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let count = self
+            .tabs
+            .iter()
+            .enumerate()
+            .filter(|(other, tab)| *other != ix && !tab.pinned)
+            .count();
+        if count <= CLOSE_TABS_WITHOUT_ASKING {
+            self.close_tabs(ix, |_| true, window, cx);
+            return;
+        }
+
+        let tab_id = self.tabs[ix].id;
+        let answer = window.prompt(
+            gpui::PromptLevel::Warning,
+            &format!("Close {} other tabs?", count),
+            Some("Pinned tabs stay open."),
+            &["Close Tabs", "Cancel"],
+            cx,
+        );
+        cx.spawn_in(window, async move |this, cx| {
+            if answer.await.ok() != Some(0) {
+                return;
+            }
+            _ = this.update_in(cx, |this, window, cx| {
+                if let Some(ix) = this.tabs.iter().position(|tab| tab.id == tab_id) {
+                    this.close_tabs(ix, |_| true, window, cx);
+                }
+            });
+        })
+        .detach();
+    }
+
+    // Mute every tab, or unmute them all when they already are
+    fn toggle_mute_all(&mut self, cx: &mut Context<Self>) {
+        let muted = !self.tabs.iter().all(|tab| tab.muted);
+        for tab in &mut self.tabs {
+            tab.muted = muted;
+            if let Some(webview) = &tab.webview {
+                webview.read(cx).set_muted(muted);
+            }
+        }
+        cx.notify();
+    }
+
+    // Reload every tab that has a page loaded, discarded ones load fresh when shown anyway
+    fn reload_all(&mut self, cx: &mut Context<Self>) {
+        for tab in &mut self.tabs {
+            let Some(webview) = &tab.webview else {
+                continue;
+            };
+            tab.pending_history_step = Some(0);
+            if let Some(path) = internal_pages::directory_path(&tab.url) {
+                tab.directory = Some(DirectoryListing::read(path));
+            }
+            webview.read(cx).reload();
+        }
+    }
+
+    // Pinned tabs are always the first ones in the strip
+    fn pinned_count(&self) -> usize {
+        self.tabs.iter().take_while(|tab| tab.pinned).count()
+    }
+
+    // Pin or unpin the tab at `ix`, moving it to the boundary between pinned and other tabs
+    fn toggle_pin(&mut self, ix: usize, cx: &mut Context<Self>) {
+        if ix >= self.tabs.len() {
+            return;
+        }
+
+        let active_id = self.tabs[self.active_tab].id;
+        let mut tab = self.tabs.remove(ix);
+        tab.pinned = !tab.pinned;
+        let pinned = self.pinned_count();
+        self.tabs.insert(pinned, tab);
+        self.active_tab = self
+            .tabs
+            .iter()
+            .position(|tab| tab.id == active_id)
+            .unwrap_or_default();
+        cx.notify();
+    }
+
+    // Move the tab at `from` to `to`, within the pinned tabs or within the others
+    fn move_tab(&mut self, from: usize, to: usize, cx: &mut Context<Self>) {
+        if from == to
+            || from >= self.tabs.len()
+            || to >= self.tabs.len()
+            || self.tabs[from].pinned != self.tabs[to].pinned
+        {
+            return;
+        }
+
+        let active_id = self.tabs[self.active_tab].id;
+        let tab = self.tabs.remove(from);
+        self.tabs.insert(to, tab);
+        self.active_tab = self
+            .tabs
+            .iter()
+            .position(|tab| tab.id == active_id)
+            .unwrap_or_default();
+        cx.notify();
+    }
+
+    // Open a tab with the new-tab page chosen in settings
+    fn open_new_tab(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let url = cx.global::<BrowserSettings>().new_tab_url().to_string();
+        self.new_tab(&url, window, cx);
+    }
+
+    fn new_tab_action(&mut self, _: &NewTab, window: &mut Window, cx: &mut Context<Self>) {
+        self.open_new_tab(window, cx);
+        self.focus_address_bar(&FocusAddressBar, window, cx);
+    }
+
+    // Close the active tab, or the whole window when it's the last one
+    fn close_tab_action(&mut self, _: &CloseTab, window: &mut Window, cx: &mut Context<Self>) {
+        if self.tabs.len() == 1 {
+            if !self.private {
+                self.session_state().save();
+            }
+            self.remember_closed_window(cx);
+            window.remove_window();
+            return;
+        }
+
+        self.close_tab(self.active_tab, window, cx);
+    }
+
+    fn reopen_closed_tab(
+        &mut self,
+        _: &ReopenClosedTab,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(closed) = self.update_recently_closed(cx, RecentlyClosed::pop) {
+            self.reopen_closed(closed, window, cx);
+        }
+    }
+
+    // Switch tabs from the keyboard, handing focus to the page
+    fn select_tab(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if ix >= self.tabs.len() {
+            return;
+        }
+
+        self.activate_tab(ix, window, cx);
+        window.focus(&self.active_webview().focus_handle(cx));
+    }
+
+    fn next_tab(&mut self, _: &NextTab, window: &mut Window, cx: &mut Context<Self>) {
+        let ix = (self.active_tab + 1) % self.tabs.len();
+        self.select_tab(ix, window, cx);
+    }
+
+    fn previous_tab(&mut self, _: &PreviousTab, window: &mut Window, cx: &mut Context<Self>) {
+        let ix = (self.active_tab + self.tabs.len() - 1) % self.tabs.len();
+        self.select_tab(ix, window, cx);
+    }
+
+    fn activate_last_tab(
+        &mut self,
+        _: &ActivateLastTab,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.select_tab(self.tabs.len() - 1, window, cx);
+    }
+
+    fn activate_tab(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if ix >= self.tabs.len() {
+            return;
+        }
+        self.exit_page_fullscreen(window);
+        self.cancel_link_hints(cx);
+        self.region_capture = None;
+        // unlocked again when coming back to it
+        self.lock_passwords(cx);
+        self.ensure_loaded(ix, window, cx);
+        let now = Instant::now();
+        if let Some(tab) = self.tabs.get_mut(self.active_tab) {
+            tab.last_active = now;
+        }
+        self.tabs[ix].last_active = now;
+        self.follow_split_view(ix);
+        let tab = &self.tabs[ix];
+
+        self.active_tab = ix;
+        self.context_menu = None;
+        self.site_data = None;
+        self.show_tab_overflow = false;
+        self.hover_url = None;
+        let url = tab.url.clone();
+        let title = tab.title.clone();
+        self.address_state
+            .update(cx, |state, cx| state.set_value(url, window, cx));
+        if !title.is_empty() {
+            window.set_window_title(&title);
+        }
+        self.sync_find_bar(window, cx);
+        self.sync_split_address(window, cx);
+        self.apply_linked_scroll(cx);
+        cx.notify();
+    }
+
+    // Show the icon `page_url` declared, or the host's `/favicon.ico`, fetching it on first use
+    fn update_favicon(
+        &mut self,
+        ix: usize,
+        page_url: &str,
+        declared: Option<&str>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((host, icon_url)) = favicon::page_icon_url(page_url, declared) else {
+            let tab = &mut self.tabs[ix];
+            tab.favicon = None;
+            tab.favicon_url = None;
+            return;
+        };
+
+        let tab = &mut self.tabs[ix];
+        tab.favicon_url = Some(icon_url.clone());
+        match self.favicons.get(&icon_url) {
+            Some(FaviconState::Loaded(icon)) => {
+                tab.favicon = Some(icon.clone());
+                return;
+            }
+            Some(FaviconState::Missing) => {
+                tab.favicon = None;
+                return;
+            }
+            // the fetch under way fills in every tab still asking for it
+            Some(FaviconState::Loading) => return,
+            None => {}
+        }
+        self.favicons
+            .insert(icon_url.clone(), FaviconState::Loading);
+
+        let client = cx.http_client();
+        cx.spawn(async move |this, cx| {
+            let icon = cx
+                .background_spawn(favicon::fetch(client, icon_url.clone()))
+                .await
+                .map_err(|err| log::error!("Failed to load favicon for {}: {}", host, err))
+                .ok();
+
+            this.update(cx, |this, cx| {
+                // only tabs still on the host and asking for this icon, loaded or not
+                for tab in &mut this.tabs {
+                    if tab.favicon_url.as_deref() == Some(icon_url.as_str())
+                        && favicon::is_on_host(&tab.url, &host)
+                    {
+                        tab.favicon = icon.clone();
+                    }
+                }
+                let state = match icon {
+                    Some(icon) => FaviconState::Loaded(icon),
+                    None => FaviconState::Missing,
+                };
+                this.favicons.insert(icon_url, state);
+                cx.notify();
+            })
+        })
+        .detach();
+    }
+
+    fn open_find_bar(&mut self, _: &FindInPage, window: &mut Window, cx: &mut Context<Self>) {
+        let find_state = match &self.find_state {
+            Some(find_state) => find_state.clone(),
+            None => {
+                let find_state =
+                    cx.new(|cx| InputState::new(window, cx).placeholder("Find in page"));
+                self._find_subscription = Some(cx.subscribe_in(
+                    &find_state,
+                    window,
+                    |this, _, event: &InputEvent, window, cx| match event {
+                        InputEvent::Change(_) => this.find(0, cx),
+                        InputEvent::PressEnter { .. } => {
+                            let step = if window.modifiers().shift { -1 } else { 1 };
+                            this.find(step, cx)
+                        }
+                        _ => {}
+                    },
+                ));
+                self.find_state = Some(find_state.clone());
+                find_state
+            }
+        };
+
+        // a tab without a search starts with the last query
+        let tab = &mut self.tabs[self.active_tab];
+        if tab.find.is_none() {
+            tab.find = Some(FindSession {
+                query: String::new(),
+                current: 0,
+                count: 0,
+            });
+        }
+        self.find(0, cx);
+        window.focus(&find_state.focus_handle(cx));
+        cx.notify();
+    }
+
+    fn close_find_bar(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let tab = &mut self.tabs[self.active_tab];
+        if tab.find.take().is_some() {
+            if let Some(frame) = &tab.main_frame {
+                frame.execute_javascript("window.__browserStopFind && window.__browserStopFind()");
+            }
+        }
+        window.focus(&self.active_webview().focus_handle(cx));
+        cx.notify();
+    }
+
+    // Search the active page for the find bar's query. `step` moves to the next (1) or
+    // previous (-1) match, wrapping around, 0 searches again from the current one.
+    fn find(&mut self, step: i32, cx: &mut Context<Self>) {
+        let Some(find_state) = &self.find_state else {
+            return;
+        };
+        let query = find_state.read(cx).value().to_string();
+        let tab = &mut self.tabs[self.active_tab];
+        let Some(find) = &mut tab.find else {
+            return;
+        };
+
+        if let Some(frame) = &tab.main_frame {
+            frame.execute_javascript(&format!(
+                "window.__browserFind && window.__browserFind({}, {}, {}, {})",
+                serde_json::to_string(&query).unwrap_or_default(),
+                self.find_match_case,
+                self.find_highlight_all,
+                step
+            ));
+        }
+        find.query = query;
+    }
+
+    // Put the active tab's query back in the find bar, which all tabs share
+    fn sync_find_bar(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let (Some(find), Some(find_state)) = (&self.tabs[self.active_tab].find, &self.find_state)
+        else {
+            return;
+        };
+        let query = find.query.clone();
+        find_state.update(cx, |state, cx| state.set_value(query, window, cx));
+    }
+
+    fn toggle_find_match_case(&mut self, cx: &mut Context<Self>) {
+        self.find_match_case = !self.find_match_case;
+        self.find(0, cx);
+        cx.notify();
+    }
+
+    fn toggle_find_highlight_all(&mut self, cx: &mut Context<Self>) {
+        self.find_highlight_all = !self.find_highlight_all;
+        self.find(0, cx);
+        cx.notify();
+    }
+
+    // Floating find bar over the top right of the page
+    fn render_find_bar(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let theme = *cx.global::<Theme>();
+        let find = self.tabs[self.active_tab].find.as_ref()?;
+        let find_state = self.find_state.as_ref()?;
+        let this = cx.entity();
+        let toggle = |id: &'static str, label: &'static str, on: bool| {
+            div()
+                .id(id)
+                .px_1()
+                .rounded_md()
+                .text_xs()
+                .cursor_pointer()
+                .text_color(if on { theme.text } else { theme.text_muted })
+                .when(on, |this| this.bg(theme.hover))
+                .hover(|this| this.bg(theme.hover_subtle))
+                .child(label)
+        };
+
+        Some(
+            div()
+                .absolute()
+                .top_2()
+                .right_4()
+                .flex()
+                .items_center()
+                .gap_2()
+                .px_3()
+                .h_8()
+                .w(px(340.))
+                .rounded_md()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.surface)
+                .capture_key_down(cx.listener(|this, event: &KeyDownEvent, window, cx| {
+                    if event.keystroke.key == "escape" {
+                        cx.stop_propagation();
+                        this.close_find_bar(window, cx);
+                    }
+                }))
+                .child(
+                    TextInput::new(find_state)
+                        .text_color(theme.text_secondary)
+                        .text_xs()
+                        .border_0(),
+                )
+                .when(!find.query.is_empty(), |this| {
+                    this.child(
+                        div()
+                            .flex_none()
+                            .text_xs()
+                            .text_color(if find.count == 0 {
+                                rgb(0xff453a)
+                            } else {
+                                theme.text_muted
+                            })
+                            .child(format!("{}/{}", find.current, find.count)),
+                    )
+                })
+                .child(
+                    toggle("find-match-case", "Aa", self.find_match_case)
+                        .on_click(cx.listener(|this, _, _, cx| this.toggle_find_match_case(cx))),
+                )
+                .child(
+                    toggle("find-highlight-all", "All", self.find_highlight_all)
+                        .on_click(cx.listener(|this, _, _, cx| this.toggle_find_highlight_all(cx))),
+                )
+                .child(svg_button("chevron-up.svg", 12.0, theme.text, true, {
+                    let this = this.clone();
+                    move |_, cx| this.update(cx, |this, cx| this.find(-1, cx))
+                }))
+                .child(svg_button("chevron-down.svg", 12.0, theme.text, true, {
+                    let this = this.clone();
+                    move |_, cx| this.update(cx, |this, cx| this.find(1, cx))
+                }))
+                .child(svg_button("close.svg", 10.0, theme.icon_muted, true, {
+                    move |window, cx| this.update(cx, |this, cx| this.close_find_bar(window, cx))
+                })),
+        )
+    }
+
+    fn zoom_in(&mut self, _: &ZoomIn, _: &mut Window, cx: &mut Context<Self>) {
+        let zoom_level = self.tabs[self.active_tab].zoom_level;
+        let zoom = self.active_webview().read(cx).zoom_in(zoom_level);
+        self.set_active_zoom(zoom, cx);
+    }
+
+    fn zoom_out(&mut self, _: &ZoomOut, _: &mut Window, cx: &mut Context<Self>) {
+        let zoom_level = self.tabs[self.active_tab].zoom_level;
+        let zoom = self.active_webview().read(cx).zoom_out(zoom_level);
+        self.set_active_zoom(zoom, cx);
+    }
+
+    fn reset_zoom(&mut self, _: &ResetZoom, _: &mut Window, cx: &mut Context<Self>) {
+        let zoom = self.active_webview().read(cx).reset_zoom();
+        self.set_active_zoom(zoom, cx);
+    }
+
+    // Record the active tab's zoom and flash the zoom badge
+    // Private windows zoom just the tab, other windows remember the zoom for the whole site
+    fn set_active_zoom(&mut self, zoom: f32, cx: &mut Context<Self>) {
+        let tab = &mut self.tabs[self.active_tab];
+        tab.zoom_level = zoom;
+        if !self.private {
+            let url = tab.url.clone();
+            cx.update_global::<BrowserSettings, _>(|settings, _| {
+                settings.set_site_zoom(&url, zoom);
+                settings.save();
+            });
+        }
+        self.zoom_badge_task = Some(cx.spawn(async move |this, cx| {
+            Timer::after(Duration::from_millis(1500)).await;
+            this.update(cx, |this, cx| {
+                this.zoom_badge_task = None;
+                cx.notify();
+            })
+            .ok();
+        }));
+        cx.notify();
+    }
+
+    // Show the tab at `ix` at the zoom saved for its site
+    fn apply_site_zoom(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let tab = &mut self.tabs[ix];
+        let Some(webview) = &tab.webview else {
+            return;
+        };
+        let zoom = cx.global::<BrowserSettings>().site_zoom(&tab.url);
+        if (zoom - tab.zoom_level).abs() > f32::EPSILON {
+            webview.read(cx).set_zoom(zoom);
+            tab.zoom_level = zoom;
+            cx.notify();
+        }
+    }
+
+    fn session_state(&self) -> SessionState {
+        SessionState {
+            tabs: self
+                .tabs
+                .iter()
+                .map(|tab| {
+                    SessionTab {
+                        url: tab.url.to_string(),
+                        title: tab.title.to_string(),
+                        favicon: None,
+                    }
+                    .with_favicon(tab.favicon.as_deref())
+                })
+                .collect(),
+            active_tab: self.active_tab,
+            pinned: self.pinned_count(),
+            bounds: self.window_bounds,
+        }
+    }
+
+    // Show the active tab's URL, unless the user is typing in the address bar
+    fn sync_address_bar(&self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.address_state.focus_handle(cx).is_focused(window) {
+            return;
+        }
+
+        let mut url = self.tabs[self.active_tab].url.clone();
+        // leave the address bar empty to type into
+        if url == settings::NEW_TAB_URL {
+            url = SharedString::default();
+        }
+        self.address_state
+            .update(cx, |state, cx| state.set_value(url, window, cx));
+    }
+
+    fn tab_index(&self, webview: &Entity<WebView>) -> Option<usize> {
+        self.tabs
+            .iter()
+            .position(|tab| tab.webview.as_ref() == Some(webview))
+    }
+
+    // `activate_tab` loads every tab it shows, so the active one always has a webview
+    fn active_webview(&self) -> &Entity<WebView> {
+        self.tabs[self.active_tab]
+            .webview
+            .as_ref()
+            .expect("the active tab is loaded")
+    }
+
+    fn go_home(&mut self, cx: &mut App) {
+        let home_url = cx.global::<BrowserSettings>().home_url.clone();
+        self.load_url(&home_url, cx);
+    }
+
+    fn set_home_page(&mut self, cx: &mut Context<Self>) {
+        let url = self.tabs[self.active_tab].url.clone();
+        cx.update_global::<BrowserSettings, _>(|settings, _| settings.set_home_url(&url));
+        toast_success(cx, "Set as home page");
+    }
+
+    fn load_url(&mut self, url: &str, cx: &mut App) {
+        let url = InternalPage::from_url(url).map_or_else(|| url.to_string(), InternalPage::url);
+        self.active_webview()
+            .read(cx)
+            .browser()
+            .load_url(&internal_pages::webview_url(&url));
+        self.tabs[self.active_tab].url = SharedString::from(url);
+    }
+
+    // Load a normalized URL from the address bar and hand focus to the page
+    fn navigate(&mut self, url: &str, window: &mut Window, cx: &mut Context<Self>) {
+        self.clear_suggestions(cx);
+        if url.is_empty() {
+            return;
+        }
+
+        self.load_url(url, cx);
+        // focus the page so navigation updates reach the address bar
+        window.focus(&self.active_webview().focus_handle(cx));
+    }
+
+    fn update_suggestions(&mut self, query: &str, cx: &mut Context<Self>) {
+        self.suggestions = suggestions::suggest(query, cx);
+        self.selected_suggestion = None;
+        self.inline_completion = self
+            .suggestions
+            .first()
+            .and_then(|suggestion| suggestion.inline_completion(query));
+        cx.notify();
+    }
+
+    fn clear_suggestions(&mut self, cx: &mut Context<Self>) {
+        self.suggestions.clear();
+        self.selected_suggestion = None;
+        self.inline_completion = None;
+        self.suggest_task = None;
+        cx.notify();
+    }
+
+    // Fill in the rest of the top suggestion's URL
+    fn accept_inline_completion(&mut self, window: &mut Window, cx: &mut Context<Self>) -> bool {
+        let Some(completion) = self.inline_completion.take() else {
+            return false;
+        };
+
+        let value = format!(
+            "{}{}",
+            self.address_state.read(cx).value().trim(),
+            completion
+        );
+        self.address_state
+            .update(cx, |state, cx| state.set_value(value, window, cx));
+        cx.notify();
+        true
+    }
+
+    // Tab or Right accepts the inline completion,
+    // arrow keys move through suggestions, Enter opens the selected one,
+    // Escape closes suggestions and then abandons the edit
+    fn on_address_key_down(
+        &mut self,
+        event: &KeyDownEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if event.keystroke.key == "escape" {
+            cx.stop_propagation();
+            if self.suggestions.is_empty() {
+                self.cancel_address_edit(window, cx);
+            } else {
+                self.clear_suggestions(cx);
+            }
+            return;
+        }
+
+        if matches!(event.keystroke.key.as_str(), "tab" | "right")
+            && self.accept_inline_completion(window, cx)
+        {
+            cx.stop_propagation();
+            return;
+        }
+
+        if self.suggestions.is_empty() {
+            return;
+        }
+
+        let last = self.suggestions.len() - 1;
+        match event.keystroke.key.as_str() {
+            "down" => {
+                self.selected_suggestion = Some(match self.selected_suggestion {
+                    Some(ix) if ix < last => ix + 1,
+                    _ => 0,
+                });
+            }
+            "up" => {
+                self.selected_suggestion = Some(match self.selected_suggestion {
+                    Some(ix) if ix > 0 => ix - 1,
+                    _ => last,
+                });
+            }
+            "enter" => {
+                let Some(ix) = self.selected_suggestion else {
+                    return;
+                };
+                let url = self.suggestions[ix].url.clone();
+                self.navigate(&url, window, cx);
+            }
+            _ => return,
+        }
+
+        cx.stop_propagation();
+        cx.notify();
+    }
+
+    // Focus the address bar with its contents selected, ready to type a new address
+    fn focus_address_bar(
+        &mut self,
+        _: &FocusAddressBar,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.focus(&self.address_state.focus_handle(cx));
+        window.dispatch_action(Box::new(SelectAll), cx);
+    }
+
+    // Open the clipboard's text as typed into the address bar, without waiting for Enter
+    fn paste_and_go(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+            return;
+        };
+        let engine = cx.global::<BrowserSettings>().search_engine();
+        let url = address::normalize_input(&text, &engine);
+        if !url.is_empty() {
+            self.navigate(&url, window, cx);
+        }
+    }
+
+    // Restore the current page URL and give focus back to the page
+    fn cancel_address_edit(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        window.focus(&self.active_webview().focus_handle(cx));
+        self.sync_address_bar(window, cx);
+    }
+
+    // History dropdown under the address bar
+    fn render_suggestions(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let theme = *cx.global::<Theme>();
+        if self.suggestions.is_empty() {
+            return None;
+        }
+
+        Some(
+            div()
+                .absolute()
+                .top(px(34.))
+                .left_0()
+                .w_full()
+                .py_1()
+                .rounded_md()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.surface)
+                .children(self.suggestions.iter().enumerate().map(|(ix, suggestion)| {
+                    let url = suggestion.url.clone();
+                    let selected = self.selected_suggestion == Some(ix);
+                    let completes = ix == 0 && self.inline_completion.is_some();
+
+                    div()
+                        .id(("suggestion", ix))
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .px_3()
+                        .py_1()
+                        .cursor_pointer()
+                        .when(selected, |this| this.bg(theme.hover))
+                        .hover(|this| this.bg(theme.hover_subtle))
+                        // mouse down, since the input blurs and clears suggestions before a click
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(move |this, _, window, cx| this.navigate(&url, window, cx)),
+                        )
+                        .when(suggestion.kind == SuggestionKind::Bookmark, |this| {
+                            this.child(
+                                svg()
+                                    .path("star.svg")
+                                    .flex_none()
+                                    .size(px(10.))
+                                    .text_color(theme.text_muted),
+                            )
+                        })
+                        .child(
+                            div()
+                                .flex()
+                                .flex_col()
+                                .flex_1()
+                                .min_w_0()
+                                .when(!suggestion.title.is_empty(), |this| {
+                                    this.child(
+                                        div().truncate().text_xs().text_color(theme.text).child(
+                                            highlighted(
+                                                &suggestion.title,
+                                                suggestion.title_match.clone(),
+                                            ),
+                                        ),
+                                    )
+                                })
+                                .child(
+                                    div()
+                                        .truncate()
+                                        .text_xs()
+                                        .text_color(theme.text_muted)
+                                        .child(highlighted(
+                                            &suggestion.url,
+                                            suggestion.url_match.clone(),
+                                        )),
+                                ),
+                        )
+                        .when(completes, |this| {
+                            // Tab or Right fills in this URL
+                            this.child(
+                                div()
+                                    .flex_none()
+                                    .px_1()
+                                    .rounded_md()
+                                    .bg(theme.hover)
+                                    .text_xs()
+                                    .text_color(theme.text_secondary)
+                                    .child("Tab"),
+                            )
+                        })
+                })),
+        )
+    }
+
+    fn toggle_site_data(&mut self, cx: &mut Context<Self>) {
+        if self.site_data.take().is_none() {
+            let url = self.tabs[self.active_tab].url.to_string();
+            self.load_site_data(url, cx);
+        }
+        cx.notify();
+    }
+
+    fn load_site_data(&mut self, url: String, cx: &mut Context<Self>) {
+        let cookies = cookies::list_cookies(self.request_context.as_ref(), &url);
+        let load = cx.spawn(async move |this, cx| {
+            let cookies = cookies.await;
+            this.update(cx, |this, cx| {
+                if let Some(site_data) = &mut this.site_data {
+                    site_data.cookies = Some(cookies);
+                    cx.notify();
+                }
+            })
+            .ok();
+        });
+
+        self.site_data = Some(SiteData {
+            url,
+            cookies: None,
+            _load: load,
+        });
+    }
+
+    // Remove the page's cookies and web storage; CEF can't drop the HTTP cache per site
+    fn clear_site_data(&mut self, cx: &mut Context<Self>) {
+        let Some(url) = self
+            .site_data
+            .as_ref()
+            .map(|site_data| site_data.url.clone())
+        else {
+            return;
+        };
+
+        if let Some(frame) = &self.tabs[self.active_tab].main_frame {
+            frame.execute_javascript("localStorage.clear(); sessionStorage.clear();");
+        }
+        let deleted = cookies::delete_cookies(self.request_context.as_ref(), &url);
+        cx.spawn(async move |this, cx| {
+            deleted.await;
+            this.update(cx, |this, cx| {
+                this.load_site_data(url, cx);
+                toast_success(cx, "Cleared site data");
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn clear_all_cookies(&mut self, cx: &mut Context<Self>) {
+        let deleted = cookies::clear_all_cookies(self.request_context.as_ref());
+        cx.spawn(async move |this, cx| {
+            let deleted = deleted.await;
+            this.update(cx, |this, cx| {
+                if let Some(url) = this
+                    .site_data
+                    .as_ref()
+                    .map(|site_data| site_data.url.clone())
+                {
+                    this.load_site_data(url, cx);
+                }
+                toast_success(cx, format!("Deleted {} cookies", deleted));
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn render_site_data(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let theme = *cx.global::<Theme>();
+        let site_data = self.site_data.as_ref()?;
+        let host = favicon::favicon_url(&site_data.url)
+            .map(|(host, _)| host)
+            .unwrap_or_else(|| site_data.url.clone());
+        let origin = permissions::origin_of(&site_data.url);
+        let site_permissions = cx.global::<SitePermissions>().site(&origin);
+        let javascript_enabled = cx
+            .global::<BrowserSettings>()
+            .javascript_enabled(&site_data.url);
+        let tab = &self.tabs[self.active_tab];
+        let security = SecurityState::of(&site_data.url, tab.certificate_failed(), cx);
+        let link = |id: &'static str, label: &'static str| {
+            div()
+                .id(id)
+                .cursor_pointer()
+                .text_color(theme.text_muted)
+                .hover(|this| this.text_color(theme.text))
+                .child(label)
+        };
+
+        Some(
+            div()
+                .absolute()
+                .top(px(34.))
+                .left_0()
+                .w(px(300.))
+                .flex()
+                .flex_col()
+                .rounded_md()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.surface)
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .px_3()
+                        .py_2()
+                        .text_xs()
+                        .text_color(theme.text)
+                        .child(div().flex_1().truncate().child(host))
+                        .child(
+                            link("clear-site-data", "Clear site data")
+                                .on_click(cx.listener(|this, _, _, cx| this.clear_site_data(cx))),
+                        )
+                        .child(
+                            link("clear-all-cookies", "Clear all")
+                                .on_click(cx.listener(|this, _, _, cx| this.clear_all_cookies(cx))),
+                        ),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .items_start()
+                        .gap_2()
+                        .px_3()
+                        .pb_2()
+                        .text_xs()
+                        .child(
+                            svg()
+                                .flex_none()
+                                .mt(px(2.))
+                                .path(security.icon())
+                                .size(px(12.))
+                                .text_color(security.color(&theme)),
+                        )
+                        .child(
+                            div()
+                                .flex()
+                                .flex_col()
+                                .gap_1()
+                                .child(
+                                    div()
+                                        .text_color(security.color(&theme))
+                                        .child(security.summary()),
+                                )
+                                .child(
+                                    div().text_color(theme.text_muted).child(security.details()),
+                                ),
+                        ),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .px_3()
+                        .pb_2()
+                        .text_xs()
+                        .child(
+                            div()
+                                .flex_1()
+                                .text_color(theme.text_secondary)
+                                .child("JavaScript"),
+                        )
+                        .child(
+                            div()
+                                .text_color(theme.text_muted)
+                                .child(if javascript_enabled {
+                                    "Allowed"
+                                } else {
+                                    "Blocked"
+                                }),
+                        )
+                        .child(
+                            link(
+                                "toggle-javascript",
+                                if javascript_enabled { "Block" } else { "Allow" },
+                            )
+                            .on_click(
+                                cx.listener(|this, _, _, cx| this.toggle_site_javascript(cx)),
+                            ),
+                        ),
+                )
+                .when(!site_permissions.is_empty(), |this| {
+                    this.child(
+                        div()
+                            .flex()
+                            .flex_col()
+                            .gap_1()
+                            .px_3()
+                            .pb_2()
+                            .text_xs()
+                            .children(site_permissions.into_iter().enumerate().map(
+                                |(ix, (permission, decision))| {
+                                    let origin = origin.clone();
+                                    div()
+                                        .flex()
+                                        .items_center()
+                                        .gap_2()
+                                        .child(
+                                            div()
+                                                .flex_1()
+                                                .text_color(theme.text_secondary)
+                                                .child(permission.name()),
+                                        )
+                                        .child(div().text_color(theme.text_muted).child(
+                                            match decision {
+                                                Decision::Allow => "Allowed",
+                                                Decision::Block => "Blocked",
+                                            },
+                                        ))
+                                        .child(
+                                            div()
+                                                .id(("reset-permission", ix))
+                                                .cursor_pointer()
+                                                .text_color(theme.text_muted)
+                                                .hover(|this| this.text_color(theme.text))
+                                                .on_click(cx.listener(move |_, _, _, cx| {
+                                                    cx.update_global::<SitePermissions, _>(
+                                                        |permissions, _| {
+                                                            permissions.reset(&origin, permission)
+                                                        },
+                                                    );
+                                                    cx.notify();
+                                                }))
+                                                .child("Reset"),
+                                        )
+                                },
+                            )),
+                    )
+                })
+                .when(!tab.timings.is_empty(), |this| {
+                    this.child(tab.timings.render(theme))
+                })
+                .child(
+                    div()
+                        .id("cookie-list")
+                        .max_h(px(320.))
+                        .flex()
+                        .flex_col()
+                        .overflow_y_scroll()
+                        .map(|this| match &site_data.cookies {
+                            None => this.child(
+                                div()
+                                    .px_3()
+                                    .pb_3()
+                                    .text_xs()
+                                    .text_color(theme.text_muted)
+                                    .child("Loading cookies..."),
+                            ),
+                            Some(cookies) if cookies.is_empty() => this.child(
+                                div()
+                                    .px_3()
+                                    .pb_3()
+                                    .text_xs()
+                                    .text_color(theme.text_muted)
+                                    .child("No cookies for this site"),
+                            ),
+                            Some(cookies) => this.children(cookies.iter().map(|cookie| {
+                                div()
+                                    .flex()
+                                    .flex_col()
+                                    .gap_1()
+                                    .px_3()
+                                    .py_2()
+                                    .text_xs()
+                                    .child(
+                                        div()
+                                            .truncate()
+                                            .text_color(theme.text)
+                                            .child(format!("{} = {}", cookie.name, cookie.value)),
+                                    )
+                                    .child(div().truncate().text_color(theme.text_muted).child(
+                                        format!(
+                                            "{}{} · {}",
+                                            cookie.domain,
+                                            cookie.path,
+                                            cookies::format_expiry(cookie.expires)
+                                        ),
+                                    ))
+                            })),
+                        }),
+                ),
+        )
+    }
+
+    fn toggle_bookmark(&mut self, cx: &mut Context<Self>) {
+        if self.private {
+            return;
+        }
+
+        let tab = &self.tabs[self.active_tab];
+        let (url, title) = (tab.url.clone(), tab.title.clone());
+        cx.update_global::<Bookmarks, _>(|bookmarks, _| bookmarks.toggle(&url, &title));
+        cx.notify();
+    }
+
+    // Retry the failed navigation of the active tab
+    fn retry(&mut self, cx: &mut Context<Self>) {
+        if let Some(error) = self.tabs[self.active_tab].error.take() {
+            self.load_url(&error.url, cx);
+            cx.notify();
+        }
+    }
+
+    fn toggle_downloads(&mut self, cx: &mut Context<Self>) {
+        self.show_downloads = !self.show_downloads;
+        cx.notify();
+    }
+
+    // Popover listing active and finished downloads
+    fn render_downloads_panel(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let theme = *cx.global::<Theme>();
+        if !self.show_downloads {
+            return None;
+        }
+
+        let manager = DownloadManager::global(cx);
+        let state = manager.read(cx);
+
+        Some(
+            div()
+                .absolute()
+                .top(px(44.))
+                .right(px(12.))
+                .w(px(300.))
+                .max_h(px(360.))
+                .flex()
+                .flex_col()
+                .rounded_md()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.surface)
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .justify_between()
+                        .px_3()
+                        .py_2()
+                        .text_xs()
+                        .text_color(theme.text)
+                        .child("Downloads")
+                        .child(
+                            div()
+                                .id("clear-downloads")
+                                .cursor_pointer()
+                                .text_color(theme.text_muted)
+                                .hover(|this| this.text_color(theme.text))
+                                .on_click({
+                                    let manager = manager.clone();
+                                    let private = self.private;
+                                    move |_, _, cx| {
+                                        manager.update(cx, |downloads, cx| {
+                                            downloads.clear_finished(private, cx)
+                                        })
+                                    }
+                                })
+                                .child("Clear"),
+                        ),
+                )
+                .child(
+                    div()
+                        .id("downloads-list")
+                        .flex()
+                        .flex_col()
+                        .overflow_y_scroll()
+                        .when(state.downloads(self.private).next().is_none(), |this| {
+                            this.child(
+                                div()
+                                    .px_3()
+                                    .pb_3()
+                                    .text_xs()
+                                    .text_color(theme.text_muted)
+                                    .child("No downloads yet"),
+                            )
+                        })
+                        .children(state.downloads(self.private).map(|download| {
+                            let id = download.id;
+                            let status = download.status();
+
+                            // scopes the row's buttons, which share their ids with other rows
+                            div()
+                                .id(("download", id))
+                                .flex()
+                                .items_center()
+                                .gap_2()
+                                .px_3()
+                                .py_2()
+                                .child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .flex_1()
+                                        .min_w_0()
+                                        .gap_1()
+                                        .child(
+                                            div()
+                                                .flex()
+                                                .items_center()
+                                                .gap_1()
+                                                .child(
+                                                    div()
+                                                        .truncate()
+                                                        .text_xs()
+                                                        .text_color(theme.text)
+                                                        .child(download.filename.clone()),
+                                                )
+                                                // never opened automatically, see the download rules
+                                                .when(download.is_executable(), |this| {
+                                                    this.child(warning_badge("Program"))
+                                                }),
+                                        )
+                                        .when(download.state == DownloadState::InProgress, |this| {
+                                            this.child(download_progress_bar(
+                                                id,
+                                                download.progress(),
+                                                theme,
+                                            ))
+                                        })
+                                        .child(
+                                            div()
+                                                .truncate()
+                                                .text_xs()
+                                                .text_color(theme.text_muted)
+                                                .child(status),
+                                        ),
+                                )
+                                .map(|this| match download.state {
+                                    DownloadState::InProgress => this.child(svg_button(
+                                        "close.svg",
+                                        10.0,
+                                        theme.icon_muted,
+                                        true,
+                                        {
+                                            let manager = manager.clone();
+                                            move |_, cx| {
+                                                manager.update(cx, |downloads, cx| {
+                                                    downloads.cancel(id, cx)
+                                                })
+                                            }
+                                        },
+                                    )),
+                                    DownloadState::Completed => {
+                                        let path = download.path.clone();
+                                        this.child(svg_button(
+                                            "folder.svg",
+                                            12.0,
+                                            theme.text,
+                                            true,
+                                            move |_, cx| cx.reveal_path(&path),
+                                        ))
+                                    }
+                                    _ => this,
+                                })
+                        })),
+                ),
+        )
+    }
+
+    fn go_back(&mut self, cx: &mut App) {
+        let webview = self.active_webview().read(cx);
+        if webview.can_go_back() {
+            self.tabs[self.active_tab].pending_history_step = Some(-1);
+            webview.go_back();
+        }
+    }
+
+    fn go_forward(&mut self, cx: &mut App) {
+        let webview = self.active_webview().read(cx);
+        if webview.can_go_forward() {
+            self.tabs[self.active_tab].pending_history_step = Some(1);
+            webview.go_forward();
+        }
+    }
+
+    // Block requests of the tab unless its site is on the allowlist
+    fn sync_content_blocking(&self, ix: usize, cx: &App) {
+        let tab = &self.tabs[ix];
+        tab.blocker
+            .set_enabled(cx.global::<BrowserSettings>().blocking_enabled(&tab.url));
+    }
+
+    // Turn scripts on or off for the tab as it goes to `url`
+    fn apply_javascript_setting(&mut self, ix: usize, url: &str, cx: &mut Context<Self>) {
+        let enabled = cx.global::<BrowserSettings>().javascript_enabled(url);
+        let tab = &mut self.tabs[ix];
+        if tab.javascript_enabled != enabled {
+            tab.javascript_enabled = enabled;
+            if let Some(webview) = &tab.webview {
+                webview.read(cx).set_javascript_enabled(enabled);
+            }
+            cx.notify();
+        }
+    }
+
+    fn toggle_site_javascript(&mut self, cx: &mut Context<Self>) {
+        let url = self.tabs[self.active_tab].url.to_string();
+        cx.update_global::<BrowserSettings, _>(|settings, _| {
+            settings.toggle_site_javascript(&url);
+            settings.save();
+        });
+        self.apply_javascript_setting(self.active_tab, &url, cx);
+        self.reload(false, cx);
+    }
+
+    // Add or remove the active site from the blocking allowlist, reloading it to apply
+    fn toggle_site_blocking(&mut self, cx: &mut App) {
+        let url = self.tabs[self.active_tab].url.to_string();
+        cx.update_global::<BrowserSettings, _>(|settings, _| {
+            settings.toggle_site_blocking(&url);
+            settings.save();
+        });
+        self.sync_content_blocking(self.active_tab, cx);
+        self.reload(false, cx);
+    }
+
+    // Reload, or hard reload when shift is held. A hard reload revalidates everything the page
+    // loads for this one navigation, the cache itself stays as it is for other pages.
+    fn reload(&mut self, hard: bool, cx: &mut App) {
+        let tab = &mut self.tabs[self.active_tab];
+        tab.pending_history_step = Some(0);
+        if let Some(path) = internal_pages::directory_path(&tab.url) {
+            tab.directory = Some(DirectoryListing::read(path));
+        }
+        let webview = self.active_webview().read(cx);
+        if hard {
+            webview.reload_ignoring_cache();
+        } else {
+            webview.reload();
+        }
+    }
+
+    // Reload asked for from the toolbar, keyboard or menu, saying which kind it was
+    fn reload_with_toast(&mut self, hard: bool, cx: &mut App) {
+        self.reload(hard, cx);
+        if hard {
+            toast_info(cx, "Hard reload, bypassing the cache");
+        } else {
+            toast_info(cx, "Reloading");
+        }
+    }
+
+    // Answer a server asking for credentials: with the ones saved in the keychain the first
+    // time during a load, by asking the user otherwise
+    fn request_credentials(
+        &mut self,
+        ix: usize,
+        event: &AuthRequiredEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let tab_id = self.tabs[ix].id;
+        // the manual proxy's sign-in is the one set in preferences, whatever its realm
+        let proxy = &cx.global::<ActiveProxy>().settings;
+        let (key, proxy_username) = if event.is_proxy && proxy.is_server(&event.host, event.port) {
+            let username = proxy.username.trim().to_string();
+            (
+                proxy.credentials_key(),
+                Some(username).filter(|name| !name.is_empty()),
+            )
+        } else {
+            (
+                auth::credentials_key(&event.host, event.port, &event.realm),
+                None,
+            )
+        };
+        // without a saved password the dialog still starts with the proxy's user name
+        let fallback = proxy_username.map(|username| Credentials {
+            username,
+            password: String::new(),
+        });
+        let request = AuthRequest {
+            tab_id,
+            key: key.clone(),
+            server: if event.is_proxy {
+                format!("{} (proxy)", event.host)
+            } else {
+                event.host.clone()
+            },
+            realm: event.realm.clone(),
+            callback: event.callback.clone(),
+        };
+        // private windows neither use nor save keychain credentials
+        if self.private {
+            self.show_auth_prompt(request, fallback, window, cx);
+            return;
+        }
+
+        let first_attempt = self.auth_attempts.insert((tab_id, key.clone()));
+        cx.spawn_in(window, async move |this, cx| {
+            let saved = cx
+                .background_spawn(async move { auth::load_credentials(&key) })
+                .await;
+            this.update_in(cx, |this, window, cx| match saved {
+                Some(credentials) if first_attempt => request
+                    .callback
+                    .continue_with(&credentials.username, &credentials.password),
+                saved => this.show_auth_prompt(request, saved.or(fallback), window, cx),
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn show_auth_prompt(
+        &mut self,
+        request: AuthRequest,
+        saved: Option<Credentials>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        // the page asks again once it's reloaded
+        if self.auth_prompt.is_some() {
+            request.callback.cancel();
+            return;
+        }
+
+        let username = cx.new(|cx| InputState::new(window, cx));
+        let password = cx.new(|cx| InputState::new(window, cx).masked(true));
+        if let Some(saved) = &saved {
+            username.update(cx, |state, cx| {
+                state.set_value(saved.username.clone(), window, cx)
+            });
+        }
+        let subscriptions = [&username, &password]
+            .into_iter()
+            .map(|state| {
+                cx.subscribe_in(state, window, |this, _, event: &InputEvent, window, cx| {
+                    if let InputEvent::PressEnter { .. } = event {
+                        this.submit_auth(window, cx);
+                    }
+                })
+            })
+            .collect();
+
+        let focused = if saved.is_some() {
+            &password
+        } else {
+            &username
+        };
+        window.focus(&focused.focus_handle(cx));
+        self.auth_prompt = Some(AuthPrompt {
+            request,
+            username,
+            password,
+            remember: saved.is_some(),
+            _subscriptions: subscriptions,
+        });
+        cx.notify();
+    }
+
+    fn submit_auth(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(prompt) = self.auth_prompt.take() else {
+            return;
+        };
+
+        let credentials = Credentials {
+            username: prompt.username.read(cx).value().to_string(),
+            password: prompt.password.read(cx).value().to_string(),
+        };
+        prompt
+            .request
+            .callback
+            .continue_with(&credentials.username, &credentials.password);
+        if prompt.remember && !self.private {
+            let key = prompt.request.key;
+            cx.background_spawn(async move { auth::save_credentials(&key, &credentials) })
+                .detach();
+        }
+        window.focus(&self.active_webview().focus_handle(cx));
+        cx.notify();
+    }
+
+    // Turn the server down, showing the error page when it was the page itself that asked
+    fn cancel_auth(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(prompt) = self.auth_prompt.take() else {
+            return;
+        };
+
+        prompt.request.callback.cancel();
+        if let Some(tab) = self
+            .tabs
+            .iter_mut()
+            .find(|tab| tab.id == prompt.request.tab_id && tab.is_loading)
+        {
+            if let Some(webview) = &tab.webview {
+                webview.read(cx).stop_loading();
+            }
+            tab.error = Some(LoadError::new(
+                &tab.url,
+                error_page::ERR_INVALID_AUTH_CREDENTIALS,
+                "",
+            ));
+        }
+        window.focus(&self.active_webview().focus_handle(cx));
+        cx.notify();
+    }
+
+    // Answer from the remembered decision when there is one, otherwise ask
+    fn request_permissions(
+        &mut self,
+        ix: usize,
+        event: &PermissionRequestEvent,
+        cx: &mut Context<Self>,
+    ) {
+        // anything the browser doesn't prompt for is blocked
+        let Some(permissions) = event
+            .permissions
+            .iter()
+            .map(|kind| Permission::from_kind(*kind))
+            .collect::<Option<Vec<_>>>()
+            .filter(|permissions| !permissions.is_empty())
+        else {
+            event.callback.deny();
+            return;
+        };
+
+        let origin = permissions::origin_of(&event.origin);
+        match cx
+            .global::<SitePermissions>()
+            .decision(&origin, &permissions)
+        {
+            Some(Decision::Allow) => event.callback.allow(),
+            Some(Decision::Block) => event.callback.deny(),
+            None => {
+                self.permission_prompts.push(PermissionPrompt {
+                    tab_id: self.tabs[ix].id,
+                    origin,
+                    permissions,
+                    remember: false,
+                    callback: PermissionResponder::Page(event.callback.clone()),
+                });
+                cx.notify();
+            }
+        }
+    }
+
+    // Answer the active tab's prompt; `None` when it was dismissed, which blocks without
+    // remembering anything
+    fn answer_permission_prompt(&mut self, decision: Option<Decision>, cx: &mut Context<Self>) {
+        let tab_id = self.tabs[self.active_tab].id;
+        let Some(ix) = self
+            .permission_prompts
+            .iter()
+            .position(|prompt| prompt.tab_id == tab_id)
+        else {
+            return;
+        };
+
+        let prompt = self.permission_prompts.remove(ix);
+        match decision {
+            Some(Decision::Allow) => prompt.callback.allow(),
+            _ => prompt.callback.deny(),
+        }
+        if let Some(decision) = decision.filter(|_| prompt.remember && !self.private) {
+            cx.update_global::<SitePermissions, _>(|permissions, _| {
+                permissions.set(&prompt.origin, &prompt.permissions, decision)
+            });
+        }
+        cx.notify();
+    }
+
+    // Whether the tab's site may use `permission`, asking the user when it hasn't been decided.
+    // `None` once the tab is gone.
+    fn clipboard_allowed(
+        &mut self,
+        tab_id: TabId,
+        permission: Permission,
+        cx: &mut Context<Self>,
+    ) -> Option<flume::Receiver<bool>> {
+        let tab = self.tabs.iter().find(|tab| tab.id == tab_id)?;
 
-// Asset loader for SVG files
-struct Assets {
-    base: PathBuf,
-}
+        let origin = permissions::origin_of(&tab.url);
+        let permissions = vec![permission];
+        let (allowed_tx, allowed) = flume::bounded(1);
+        match cx
+            .global::<SitePermissions>()
+            .decision(&origin, &permissions)
+        {
+            Some(decision) => _ = allowed_tx.send(decision == Decision::Allow),
+            None => {
+                self.permission_prompts.push(PermissionPrompt {
+                    tab_id,
+                    origin,
+                    permissions,
+                    remember: false,
+                    callback: PermissionResponder::Host(allowed_tx),
+                });
+                cx.notify();
+            }
+        }
+        Some(allowed)
+    }
+
+    // Replace the clipboard with a page's text once its site may, asking the user first
+    fn write_clipboard_for(
+        &mut self,
+        tab_id: TabId,
+        text: String,
+        reply: flume::Sender<Result<(), BridgeError>>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(allowed) = self.clipboard_allowed(tab_id, Permission::ClipboardWrite, cx) else {
+            return;
+        };
+
+        cx.spawn(async move |_, cx| {
+            let result = if allowed.recv_async().await.unwrap_or(false) {
+                cx.update(|cx| cx.write_to_clipboard(ClipboardItem::new_string(text)))
+                    .map_err(|_| BridgeError::new("unavailable", "The window was closed"))
+            } else {
+                Err(BridgeError::new(
+                    "permission_denied",
+                    "Writing to the clipboard was blocked",
+                ))
+            };
+            _ = reply.send(result);
+        })
+        .detach();
+    }
+
+    // Hand the clipboard text to a page once its site may read it, asking the user first
+    fn read_clipboard_for(
+        &mut self,
+        tab_id: TabId,
+        reply: flume::Sender<Result<String, BridgeError>>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(allowed) = self.clipboard_allowed(tab_id, Permission::ClipboardRead, cx) else {
+            return;
+        };
 
-impl AssetSource for Assets {
-    fn load(&self, path: &str) -> Result<Option<std::borrow::Cow<'static, [u8]>>> {
-        let full_path = self.base.join(path);
+        cx.spawn(async move |_, cx| {
+            let result = if allowed.recv_async().await.unwrap_or(false) {
+                // an empty or non-text clipboard reads as ""
+                cx.update(|cx| {
+                    cx.read_from_clipboard()
+                        .and_then(|item| item.text())
+                        .unwrap_or_default()
+                })
+                .map_err(|_| BridgeError::new("unavailable", "The window was closed"))
+            } else {
+                Err(BridgeError::new(
+                    "permission_denied",
+                    "Reading the clipboard was blocked",
+                ))
+            };
+            _ = reply.send(result);
+        })
+        .detach();
+    }
 
-        match fs::read(&full_path) {
-            Ok(data) => Ok(Some(std::borrow::Cow::Owned(data))),
-            Err(err) => {
-                println!("Failed to load asset: {:?} - Error: {}", full_path, err);
-                Err(err.into())
+    fn dismiss_permission_prompts(&mut self, tab_id: TabId) {
+        self.permission_prompts.retain(|prompt| {
+            if prompt.tab_id == tab_id {
+                prompt.callback.deny();
             }
+            prompt.tab_id != tab_id
+        });
+    }
+
+    // Refuse the site's certificate and return to the previous page. Without one the
+    // failed load's error page stays.
+    fn back_to_safety(&mut self, cx: &mut Context<Self>) {
+        let Some(error) = self.tabs[self.active_tab].certificate_error.take() else {
+            return;
+        };
+
+        error.callback.cancel();
+        self.go_back(cx);
+        cx.notify();
+    }
+
+    fn proceed_despite_certificate(&mut self, cx: &mut Context<Self>) {
+        let Some(error) = self.tabs[self.active_tab].certificate_error.take() else {
+            return;
+        };
+
+        if error.remember {
+            cx.update_global::<CertificateExceptions, _>(|exceptions, _| {
+                exceptions.0.insert(error.host.clone())
+            });
+        }
+        self.tabs[self.active_tab].certificate_bypassed = Some(error.host.clone());
+        error.callback.proceed();
+        cx.notify();
+    }
+
+    fn on_scroll_wheel(
+        &mut self,
+        event: &ScrollWheelEvent,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let tab = &self.tabs[self.active_tab];
+        let webview = self.active_webview().read(cx);
+        // a page scrolled sideways scrolls back to its left edge before swiping back
+        let can_go_back = webview.can_go_back() && tab.scroll_position.0 <= 0.;
+        let can_go_forward = webview.can_go_forward();
+
+        let was_swiping = self.swipe.progress().is_some();
+        match self.swipe.on_scroll(event, can_go_back, can_go_forward) {
+            Some(SwipeDirection::Back) => self.go_back(cx),
+            Some(SwipeDirection::Forward) => self.go_forward(cx),
+            None => {}
+        }
+        if was_swiping || self.swipe.progress().is_some() {
+            cx.notify();
         }
     }
 
-    fn list(&self, path: &str) -> Result<Vec<SharedString>> {
-        let full_path = self.base.join(path);
+    // Back and forward buttons along the page's edges, for pointers without side buttons.
+    // Directions without history get no button, nor the hover zone for one.
+    fn render_edge_buttons(&self, cx: &mut Context<Self>) -> Vec<impl IntoElement> {
+        if !cx.global::<BrowserSettings>().edge_buttons {
+            return Vec::new();
+        }
+        let webview = self.active_webview().read(cx);
+        let available = [
+            (SwipeDirection::Back, webview.can_go_back()),
+            (SwipeDirection::Forward, webview.can_go_forward()),
+        ];
+        let this = cx.entity();
 
-        match fs::read_dir(&full_path) {
-            Ok(entries) => {
-                let files: Vec<SharedString> = entries
-                    .filter_map(|entry| match entry {
-                        Ok(entry) => {
-                            let file_name = entry.file_name();
-                            file_name.into_string().ok().map(SharedString::from)
+        available
+            .into_iter()
+            .filter(|(_, available)| *available)
+            .map(|(direction, _)| {
+                gestures::render_edge_button(
+                    direction,
+                    self.edge_hover == Some(direction),
+                    {
+                        let this = this.clone();
+                        move |hovered, _, cx| {
+                            this.update(cx, |this, cx| {
+                                if *hovered {
+                                    this.edge_hover = Some(direction);
+                                } else if this.edge_hover == Some(direction) {
+                                    this.edge_hover = None;
+                                }
+                                cx.notify();
+                            })
                         }
-                        Err(err) => {
-                            println!("Error reading directory entry: {}", err);
-                            None
+                    },
+                    {
+                        let this = this.clone();
+                        move |_, cx| {
+                            this.update(cx, |this, cx| {
+                                match direction {
+                                    SwipeDirection::Back => this.go_back(cx),
+                                    SwipeDirection::Forward => this.go_forward(cx),
+                                }
+                                cx.notify();
+                            })
                         }
-                    })
-                    .collect();
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn stop_loading(&self, cx: &mut App) {
+        self.active_webview().read(cx).stop_loading();
+    }
+
+    // Leave link hint mode, removing the page's labels
+    fn cancel_link_hints(&mut self, cx: &mut Context<Self>) {
+        let Some(link_hints) = self.link_hints.take() else {
+            return;
+        };
+        if let Some(frame) = self
+            .tabs
+            .iter()
+            .find(|tab| tab.id == link_hints.tab_id)
+            .and_then(|tab| tab.main_frame.as_ref())
+        {
+            frame.execute_javascript(hints::STOP_SCRIPT);
+        }
+        cx.notify();
+    }
+
+    // Keys typed in link hint mode pick a label instead of reaching the page
+    fn on_hint_key_down(&mut self, event: &KeyDownEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let keystroke = &event.keystroke;
+        if keystroke.modifiers.modified() {
+            return;
+        }
+        let Some(link_hints) = self.link_hints.as_mut() else {
+            return;
+        };
+        cx.stop_propagation();
 
-                println!("Listed {} files in directory: {:?}", files.len(), full_path);
-                Ok(files)
+        match keystroke.key.as_str() {
+            "escape" => self.cancel_link_hints(cx),
+            "backspace" => {
+                link_hints.backspace();
+                cx.notify();
             }
-            Err(err) => {
-                println!("Failed to list directory: {:?} - Error: {}", full_path, err);
-                Err(err.into())
+            key if key.len() == 1 && key.chars().all(|c| c.is_ascii_alphabetic()) => {
+                match link_hints.type_key(key) {
+                    HintKey::Pending => cx.notify(),
+                    HintKey::Activate(label) => {
+                        let tab_id = link_hints.tab_id;
+                        self.link_hints = None;
+                        if let Some(frame) = self
+                            .tabs
+                            .iter()
+                            .find(|tab| tab.id == tab_id)
+                            .and_then(|tab| tab.main_frame.as_ref())
+                        {
+                            frame.execute_javascript(&hints::activate_script(&label));
+                        }
+                        cx.notify();
+                    }
+                    HintKey::Cancel => self.cancel_link_hints(cx),
+                }
             }
+            _ => {}
         }
     }
-}
 
-// SVG button component
-fn svg_button(
-    svg_path: &str,
-    size: f32,
-    color: impl Into<gpui::Hsla>,
-    on_click: impl Fn(&mut Window, &mut App) + 'static,
-) -> impl IntoElement {
-    let svg_path = svg_path.to_string(); // Clone the string to own it
-    let color = color.into(); // Convert color to owned type
+    // Hide the browser chrome while the page is fullscreen, taking the window fullscreen too
+    fn set_page_fullscreen(&mut self, fullscreen: bool, window: &mut Window) {
+        if fullscreen == self.is_fullscreen {
+            return;
+        }
+        self.is_fullscreen = fullscreen;
 
-    div()
-        .flex()
-        .items_center()
-        .justify_center()
-        .size(px(size)) // Add padding around SVG
-        .rounded_md()
-        .cursor_pointer()
-        .hover(|this| this.bg(rgba(0x00000010))) // Light hover effect
-        .child(
-            svg()
-                .path(svg_path) // Now using owned string
-                .size(px(size))
-                .text_color(color), // Now using owned color
-        )
-}
+        if fullscreen && !window.is_fullscreen() {
+            window.toggle_fullscreen();
+            self.fullscreen_window_for_page = true;
+        } else if !fullscreen && self.fullscreen_window_for_page {
+            // leave the window as it was if the user changed it in the meantime
+            if window.is_fullscreen() {
+                window.toggle_fullscreen();
+            }
+            self.fullscreen_window_for_page = false;
+        }
+    }
 
-struct Main {
-    address_state: Entity<InputState>,
-    webview: Entity<WebView>,
-}
+    // Ask the page to leave fullscreen and bring the chrome back right away
+    fn exit_page_fullscreen(&mut self, window: &mut Window) {
+        if !self.is_fullscreen {
+            return;
+        }
+        // the active tab may just have been closed
+        if let Some(frame) = self
+            .tabs
+            .get(self.active_tab)
+            .and_then(|tab| tab.main_frame.as_ref())
+        {
+            frame.execute_javascript("document.fullscreenElement && document.exitFullscreen()");
+        }
+        self.set_page_fullscreen(false, window);
+    }
 
-impl Main {
-    fn new(window: &mut Window, cx: &mut App) -> Entity<Self> {
-        let background_executor = cx.background_executor().clone();
+    fn toggle_fullscreen(
+        &mut self,
+        _: &ToggleFullscreen,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.is_fullscreen {
+            self.exit_page_fullscreen(window);
+        } else {
+            window.toggle_fullscreen();
+        }
+        cx.notify();
+    }
+
+    // Show the active page in reader mode, or go back to the page
+    fn toggle_reader(&mut self, cx: &mut Context<Self>) {
+        let tab = &mut self.tabs[self.active_tab];
+        if tab.reader.take().is_some() {
+            cx.notify();
+        } else if let Some(frame) = &tab.main_frame {
+            // the page replies with `readerContent`
+            frame.execute_javascript(reader::EXTRACT_SCRIPT);
+        }
+    }
+
+    fn set_reader_font_size(&mut self, font_size: f32, cx: &mut Context<Self>) {
+        cx.update_global::<BrowserSettings, _>(|settings, _| {
+            settings.reader_font_size =
+                font_size.clamp(reader::MIN_FONT_SIZE, reader::MAX_FONT_SIZE);
+            settings.save();
+        });
+        cx.notify();
+    }
+
+    // Switch the active tab to a user agent preset, or back to the default when it's already
+    // using it, and reload so the site sees the change
+    fn toggle_user_agent(&mut self, preset: UserAgentPreset, cx: &mut Context<Self>) {
+        let tab = &mut self.tabs[self.active_tab];
+        tab.user_agent = (tab.user_agent != Some(preset)).then_some(preset);
+        let user_agent = tab
+            .user_agent
+            .map(|preset| preset.user_agent().to_string())
+            .or_else(|| cx.global::<BrowserSettings>().user_agent.clone());
+
+        let webview = self.active_webview().read(cx);
+        webview.set_user_agent(user_agent.as_deref());
+        webview.reload();
+        cx.notify();
+    }
+
+    fn toggle_console(&mut self, _: &ToggleConsole, window: &mut Window, cx: &mut Context<Self>) {
+        if self.console_filter.is_some() {
+            self.close_console(cx);
+            return;
+        }
+
+        let filter = cx.new(|cx| InputState::new(window, cx).placeholder("Filter"));
+        self._console_filter_subscription =
+            Some(
+                cx.subscribe_in(&filter, window, |_, _, event: &InputEvent, _, cx| {
+                    if let InputEvent::Change(_) = event {
+                        cx.notify();
+                    }
+                }),
+            );
+        self.console_filter = Some(filter);
+        cx.notify();
+    }
+
+    fn close_console(&mut self, cx: &mut Context<Self>) {
+        self.console_filter = None;
+        self._console_filter_subscription = None;
+        cx.notify();
+    }
+
+    fn clear_console(&mut self, cx: &mut Context<Self>) {
+        self.tabs[self.active_tab].console.clear();
+        cx.notify();
+    }
+
+    fn toggle_mute(&mut self, ix: usize, cx: &mut Context<Self>) {
+        let Some(tab) = self.tabs.get_mut(ix) else {
+            return;
+        };
+        tab.muted = !tab.muted;
+        if let Some(webview) = &tab.webview {
+            webview.read(cx).set_muted(tab.muted);
+        }
+        cx.notify();
+    }
+
+    // Float the active tab's video, or bring back whichever tab's video is floating
+    fn toggle_picture_in_picture(&mut self, cx: &mut Context<Self>) {
+        let active = &self.tabs[self.active_tab];
+        let tab = if active.has_video || active.picture_in_picture {
+            Some(active)
+        } else {
+            self.tabs.iter().find(|tab| tab.picture_in_picture)
+        };
+        match tab.and_then(|tab| tab.webview.as_ref()) {
+            Some(webview) => webview.read(cx).toggle_picture_in_picture(),
+            None => toast_info(cx, "No video on this page"),
+        }
+    }
 
-        let func_registry = FuncRegistry::builder()
-            .with_spawner(move |fut| {
-                background_executor.spawn(fut).detach();
+    // Link destination in the bottom-left corner of the page while a link is hovered
+    fn render_status_bar(&self, cx: &App) -> Option<impl IntoElement> {
+        let theme = *cx.global::<Theme>();
+        let url = self.hover_url.clone()?;
+
+        Some(
+            div()
+                .absolute()
+                .bottom(px(4.))
+                .left(px(4.))
+                .max_w(relative(0.5))
+                .px_2()
+                .py_1()
+                .rounded_md()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.surface)
+                .text_xs()
+                .text_color(theme.text_secondary)
+                .truncate()
+                .child(url),
+        )
+    }
+
+    // Thin load progress bar under the toolbar
+    fn render_progress_bar(&self, cx: &App) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let tab = &self.tabs[self.active_tab];
+
+        div()
+            .relative()
+            .h(px(2.))
+            .w_full()
+            .overflow_hidden()
+            .map(|this| {
+                if tab.is_loading && tab.progress <= 0.0 {
+                    // No fractional progress reported yet, show an indeterminate shimmer
+                    this.child(
+                        div()
+                            .absolute()
+                            .top_0()
+                            .h_full()
+                            .w(relative(0.3))
+                            .bg(linear_gradient(
+                                90.,
+                                linear_color_stop(rgba(0xf2f2f200), 0.),
+                                linear_color_stop(rgba(0xf2f2f2cc), 1.),
+                            ))
+                            .with_animation(
+                                ("progress-shimmer", tab.load_id),
+                                Animation::new(Duration::from_millis(1200)).repeat(),
+                                |this, delta| this.left(relative(-0.3 + 1.3 * delta)),
+                            ),
+                    )
+                } else if tab.is_loading {
+                    this.child(div().h_full().w(relative(tab.progress)).bg(theme.text))
+                } else if tab.progress > 0.0 {
+                    // Finished, fade the full bar out
+                    this.child(div().h_full().w_full().bg(theme.text).with_animation(
+                        ("progress-fade", tab.load_id),
+                        Animation::new(Duration::from_millis(300)),
+                        |this, delta| this.opacity(1.0 - delta),
+                    ))
+                } else {
+                    this
+                }
             })
-            .register("toUppercase", |value: String| value.to_uppercase())
-            .register("addInt", |a: i32, b: i32| a + b)
-            .register("parseInt", |value: String| value.parse::<i32>())
-            .register_async("sleep", |millis: u64| async move {
-                Timer::after(Duration::from_millis(millis)).await;
-                "ok"
+    }
+
+    fn new_window(&mut self, _: &NewWindow, _: &mut Window, cx: &mut Context<Self>) {
+        cx.defer(|cx| {
+            let url = cx.global::<BrowserSettings>().new_tab_url().to_string();
+            open_browser_window(cx, &url, false)
+        });
+    }
+
+    fn new_private_window(&mut self, _: &NewPrivateWindow, _: &mut Window, cx: &mut Context<Self>) {
+        cx.defer(|cx| {
+            let url = cx.global::<BrowserSettings>().new_tab_url().to_string();
+            open_browser_window(cx, &url, true)
+        });
+    }
+
+    fn open_preferences(&mut self, _: &OpenPreferences, _: &mut Window, cx: &mut Context<Self>) {
+        cx.defer(preferences::open);
+    }
+
+    fn toggle_devtools(&mut self, _: &ToggleDevTools, _: &mut Window, cx: &mut Context<Self>) {
+        self.active_webview().read(cx).toggle_devtools();
+    }
+
+    // Copy the URL the active tab is actually on, not what's typed in the address bar
+    fn copy_url(&mut self, _: &CopyUrl, _: &mut Window, cx: &mut Context<Self>) {
+        let url = self.tabs[self.active_tab].url.to_string();
+        cx.write_to_clipboard(ClipboardItem::new_string(url));
+        toast_success(cx, "Copied!");
+    }
+
+    fn capture_screenshot_action(
+        &mut self,
+        _: &CaptureScreenshot,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.capture_screenshot(CaptureArea::Viewport, cx);
+    }
+
+    // Save a screenshot of `area` of the active tab in the format picked in the settings.
+    // Regions are copied to the clipboard as well.
+    fn capture_screenshot(&mut self, area: CaptureArea, cx: &mut Context<Self>) {
+        let tab = &self.tabs[self.active_tab];
+        let Some(frame) = tab.main_frame.clone() else {
+            toast_info(cx, "Nothing to capture yet");
+            return;
+        };
+        let tab_id = tab.id;
+        let webview = self.active_webview().clone();
+        let host = favicon::favicon_url(&tab.url)
+            .map(|(host, _)| host)
+            .unwrap_or_default();
+        let settings = cx.global::<BrowserSettings>();
+        let (format, quality) = (settings.screenshot_format, settings.screenshot_quality);
+
+        let full_page = matches!(area, CaptureArea::FullPage);
+        let metrics = full_page.then(|| {
+            let (tx, rx) = flume::bounded(1);
+            self.page_metrics_request = Some((tab_id, tx));
+            frame.execute_javascript(screenshot::METRICS_SCRIPT);
+            rx
+        });
+
+        cx.spawn(async move |this, cx| {
+            let image = match metrics {
+                Some(metrics) => match screenshot::wait_for_metrics(metrics).await {
+                    Ok(metrics) => {
+                        screenshot::capture_full_page(&webview, &frame, metrics, cx).await
+                    }
+                    Err(err) => Err(err),
+                },
+                None => screenshot::capture_viewport(&webview, cx),
+            };
+            let saved = match image {
+                Ok(image) => {
+                    cx.background_spawn(async move {
+                        let image = match area {
+                            CaptureArea::Region(region) => screenshot::crop(&image, region),
+                            _ => image,
+                        };
+                        let encoded = screenshot::encode(&image, format, quality)?;
+                        let path = screenshot::save(&encoded, &host)?;
+                        anyhow::Ok((encoded, path))
+                    })
+                    .await
+                }
+                Err(err) => Err(err),
+            };
+
+            this.update(cx, |_, cx| match saved {
+                Ok((encoded, path)) => {
+                    log::info!(
+                        "Saved a {} screenshot of {} bytes",
+                        encoded.mime_type(),
+                        encoded.bytes.len()
+                    );
+                    if let CaptureArea::Region(_) = area {
+                        cx.write_to_clipboard(ClipboardItem::new_image(&gpui::Image::from_bytes(
+                            encoded.format.clipboard_format(),
+                            encoded.bytes,
+                        )));
+                        toast_success(cx, format!("Copied and saved to {}", path.display()))
+                    } else {
+                        toast_success(cx, format!("Saved to {}", path.display()))
+                    }
+                }
+                Err(err) => toast_error(cx, format!("Screenshot failed: {}", err)),
             })
-            .register("emit", |frame: Frame| {
-                #[derive(Debug, Serialize)]
-                struct Message {
-                    event: String,
-                    data: String,
+            .ok();
+        })
+        .detach();
+    }
+
+    // Pick a local file or folder and open it in the active tab
+    fn open_file(&mut self, _: &OpenFile, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(PathPromptOptions {
+            files: true,
+            directories: true,
+            multiple: false,
+            prompt: None,
+        });
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Ok(Ok(Some(paths))) = paths.await else {
+                return;
+            };
+            let Some(url) = paths.first().and_then(|path| address::file_url(path)) else {
+                return;
+            };
+            this.update_in(cx, |this, window, cx| this.navigate(&url, window, cx))
+                .ok();
+        })
+        .detach();
+    }
+
+    // Ask where to save, then print the active tab's document to PDF
+    fn export_pdf(&mut self, _: &ExportPdf, _: &mut Window, cx: &mut Context<Self>) {
+        let tab = &self.tabs[self.active_tab];
+        let webview = self.active_webview().clone();
+        let name = match favicon::favicon_url(&tab.url) {
+            Some((host, _)) => format!("{}.pdf", host),
+            None => "page.pdf".to_string(),
+        };
+        let dir = dirs::document_dir()
+            .or_else(dirs::home_dir)
+            .unwrap_or_default();
+        let path = cx.prompt_for_new_path(&dir, Some(&name));
+
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(path))) = path.await else {
+                return;
+            };
+
+            let printed = webview.read_with(cx, |webview, _| {
+                webview.print_to_pdf(&path, &PdfOptions::default())
+            });
+            let ok = match printed {
+                Ok(done) => done.recv_async().await.unwrap_or(false),
+                Err(_) => return,
+            };
+
+            this.update(cx, |_, cx| {
+                if ok {
+                    toast_success(cx, format!("Saved to {}", path.display()));
+                } else {
+                    toast_error(cx, "Couldn't export PDF");
                 }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn dismiss_context_menu(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.context_menu.take().is_some() {
+            window.focus(&self.active_webview().focus_handle(cx));
+            cx.notify();
+        }
+    }
+
+    // Insert clipboard text at the caret of the frame that was right-clicked
+    fn paste_into(&self, frame: &Frame, cx: &mut App) {
+        let Some(text) = cx.read_from_clipboard().and_then(|item| item.text()) else {
+            return;
+        };
+
+        let text = serde_json::to_string(&text).unwrap_or_default();
+        frame.execute_javascript(&format!(
+            "document.execCommand('insertText', false, {})",
+            text
+        ));
+    }
+
+    // Ask where to save `url`, then download it there without navigating to it
+    fn save_url_as(&mut self, url: String, cx: &mut Context<Self>) {
+        let dir = cx
+            .global::<BrowserSettings>()
+            .download_dir()
+            .unwrap_or_default();
+        let name = downloads::filename_for(&url, None);
+        let path = cx.prompt_for_new_path(&dir, Some(&name));
 
-                frame.emit(Message {
-                    event: "custom".to_string(),
-                    data: "ok".to_string(),
+        cx.spawn(async move |this, cx| {
+            let Ok(Ok(Some(path))) = path.await else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                let private = this.private;
+                DownloadManager::global(cx).update(cx, |downloads, cx| {
+                    downloads.save_as(&url, path, private, cx)
                 });
+                this.show_downloads = true;
+                cx.notify();
             })
-            .build();
+            .ok();
+        })
+        .detach();
+    }
 
-        cx.new(|cx| {
-            let url = "https://vercel.com";
+    // Replace the misspelled word that was right-clicked, as if it had been typed
+    fn replace_misspelling(&self, frame: &Frame, text: &str) {
+        let text = serde_json::to_string(text).unwrap_or_default();
+        frame.execute_javascript(&format!("window.__browserReplaceWord({})", text));
+    }
 
-            // create webview
-            let webview = WebView::with_func_registry(url, func_registry.clone(), window, cx);
+    fn render_context_menu(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let theme = *cx.global::<Theme>();
+        let menu = self.context_menu.as_ref()?;
+        let this = cx.entity();
+        let webview = self.active_webview().read(cx);
+        let can_go_back = webview.can_go_back();
+        let can_go_forward = webview.can_go_forward();
+        let user_agent = self.tabs[self.active_tab].user_agent;
+        let has_video = self.tabs[self.active_tab].has_video;
+        let picture_in_picture = self.tabs[self.active_tab].picture_in_picture;
 
-            window
-                .subscribe(&webview, cx, |_, event: &TitleChangedEvent, window, _| {
-                    window.set_window_title(&event.title);
+        // Each entry closes the menu and then runs against what was clicked
+        let item = |id, label, enabled, action: ContextMenuAction| {
+            let this = this.clone();
+            context_menu::menu_item(id, label, enabled, move |window, cx| {
+                this.update(cx, |this, cx| {
+                    if let Some(menu) = this.context_menu.take() {
+                        window.focus(&this.active_webview().focus_handle(cx));
+                        action(this, menu, window, cx);
+                        cx.notify();
+                    }
                 })
-                .detach();
+            })
+        };
 
-            // create address input
-            let address_state = cx.new(|cx| InputState::new(window, cx).default_value(url));
-
-            window
-                .subscribe(&address_state, cx, {
-                    let webview = webview.clone();
-                    move |state, event: &InputEvent, _, cx| {
-                        if let InputEvent::PressEnter { .. } = event {
-                            let url = state.read(cx).value();
-                            webview.read(cx).browser().load_url(url);
+        let mut items = Vec::new();
+        if let Some(misspelling) = &menu.misspelling {
+            if misspelling.suggestions.is_empty() {
+                items.push(
+                    context_menu::menu_item("no-suggestions", "No Suggestions", false, |_, _| {})
+                        .into_any_element(),
+                );
+            }
+            for (ix, suggestion) in misspelling.suggestions.iter().enumerate() {
+                let this = this.clone();
+                let suggestion = suggestion.clone();
+                items.push(
+                    context_menu::menu_item(
+                        ("spelling-suggestion", ix),
+                        suggestion.clone(),
+                        true,
+                        move |window, cx| {
+                            this.update(cx, |this, cx| {
+                                if let Some(menu) = this.context_menu.take() {
+                                    window.focus(&this.active_webview().focus_handle(cx));
+                                    this.replace_misspelling(&menu.frame, &suggestion);
+                                    cx.notify();
+                                }
+                            })
+                        },
+                    )
+                    .into_any_element(),
+                );
+            }
+            items.push(
+                item(
+                    "add-to-dictionary",
+                    "Add to Dictionary",
+                    true,
+                    |_, menu, _, cx| {
+                        if let Some(misspelling) = menu.misspelling {
+                            cx.update_global::<SpellChecker, _>(|checker, _| {
+                                checker.add_word(&misspelling.word)
+                            });
+                        }
+                    },
+                )
+                .into_any_element(),
+            );
+            items.push(context_menu::separator().into_any_element());
+        }
+        if menu.link_url.is_some() {
+            items.push(
+                item(
+                    "open-link",
+                    "Open Link in New Tab",
+                    true,
+                    |this, menu, window, cx| {
+                        if let Some(url) = menu.link_url {
+                            this.open_link_in_tab(menu.tab_id, &url, false, window, cx);
                         }
+                    },
+                )
+                .into_any_element(),
+            );
+            items.push(
+                item("copy-link", "Copy Link Address", true, |_, menu, _, cx| {
+                    if let Some(url) = menu.link_url {
+                        cx.write_to_clipboard(ClipboardItem::new_string(url));
                     }
                 })
-                .detach();
+                .into_any_element(),
+            );
+            items.push(
+                item("save-link", "Save Link As...", true, |this, menu, _, cx| {
+                    if let Some(url) = menu.link_url {
+                        this.save_url_as(url, cx);
+                    }
+                })
+                .into_any_element(),
+            );
+            items.push(context_menu::separator().into_any_element());
+        }
+        if menu.image_url.is_some() {
+            items.push(
+                item(
+                    "save-image",
+                    "Save Image As...",
+                    true,
+                    |this, menu, _, cx| {
+                        if let Some(url) = menu.image_url {
+                            this.save_url_as(url, cx);
+                        }
+                    },
+                )
+                .into_any_element(),
+            );
+            items.push(
+                item(
+                    "copy-image-url",
+                    "Copy Image Address",
+                    true,
+                    |_, menu, _, cx| {
+                        if let Some(url) = menu.image_url {
+                            cx.write_to_clipboard(ClipboardItem::new_string(url));
+                        }
+                    },
+                )
+                .into_any_element(),
+            );
+            items.push(context_menu::separator().into_any_element());
+        }
+        if has_video || picture_in_picture {
+            items.push(
+                item(
+                    "picture-in-picture",
+                    if picture_in_picture {
+                        "Exit Picture in Picture"
+                    } else {
+                        "Picture in Picture"
+                    },
+                    true,
+                    |this, _, _, cx| this.toggle_picture_in_picture(cx),
+                )
+                .into_any_element(),
+            );
+            items.push(context_menu::separator().into_any_element());
+        }
+        items.extend([
+            item("back", "Back", can_go_back, |this, _, _, cx| {
+                this.go_back(cx)
+            })
+            .into_any_element(),
+            item("forward", "Forward", can_go_forward, |this, _, _, cx| {
+                this.go_forward(cx)
+            })
+            .into_any_element(),
+            item("reload", "Reload", true, |this, _, _, cx| {
+                this.reload_with_toast(false, cx)
+            })
+            .into_any_element(),
+            item(
+                "duplicate-tab",
+                "Duplicate Tab",
+                true,
+                |this, _, window, cx| this.duplicate_tab(&DuplicateTab, window, cx),
+            )
+            .into_any_element(),
+            item(
+                "copy-url",
+                "Copy Page Address",
+                true,
+                |this, _, window, cx| this.copy_url(&CopyUrl, window, cx),
+            )
+            .into_any_element(),
+            item("set-home", "Set as Home Page", true, |this, _, _, cx| {
+                this.set_home_page(cx)
+            })
+            .into_any_element(),
+            item(
+                "desktop-site",
+                if user_agent == Some(UserAgentPreset::Desktop) {
+                    "Stop Requesting Desktop Site"
+                } else {
+                    "Request Desktop Site"
+                },
+                true,
+                |this, _, _, cx| this.toggle_user_agent(UserAgentPreset::Desktop, cx),
+            )
+            .into_any_element(),
+            item(
+                "mobile-site",
+                if user_agent == Some(UserAgentPreset::Mobile) {
+                    "Stop Requesting Mobile Site"
+                } else {
+                    "Request Mobile Site"
+                },
+                true,
+                |this, _, _, cx| this.toggle_user_agent(UserAgentPreset::Mobile, cx),
+            )
+            .into_any_element(),
+            item("screenshot", "Save Screenshot", true, |this, _, _, cx| {
+                this.capture_screenshot(CaptureArea::Viewport, cx)
+            })
+            .into_any_element(),
+            item(
+                "export-pdf",
+                "Export as PDF...",
+                true,
+                |this, _, window, cx| this.export_pdf(&ExportPdf, window, cx),
+            )
+            .into_any_element(),
+            item(
+                "full-page-screenshot",
+                "Save Full-Page Screenshot",
+                true,
+                |this, _, _, cx| this.capture_screenshot(CaptureArea::FullPage, cx),
+            )
+            .into_any_element(),
+            item(
+                "region-screenshot",
+                "Save Screenshot of Area",
+                true,
+                |this, _, window, cx| this.start_region_capture(&CaptureRegion, window, cx),
+            )
+            .into_any_element(),
+            context_menu::separator().into_any_element(),
+            item(
+                "copy",
+                "Copy",
+                menu.selection_text.is_some(),
+                |_, menu, _, cx| {
+                    if let Some(text) = menu.selection_text {
+                        cx.write_to_clipboard(ClipboardItem::new_string(text));
+                    }
+                },
+            )
+            .into_any_element(),
+            item("paste", "Paste", menu.editable, |this, menu, _, cx| {
+                this.paste_into(&menu.frame, cx)
+            })
+            .into_any_element(),
+        ]);
+        if menu.editable {
+            items.push(
+                item(
+                    "toggle-spellcheck",
+                    if menu.spellcheck {
+                        "Turn Off Spell Check"
+                    } else {
+                        "Turn On Spell Check"
+                    },
+                    true,
+                    |_, menu, _, _| {
+                        menu.frame
+                            .execute_javascript("window.__browserToggleSpellcheck()")
+                    },
+                )
+                .into_any_element(),
+            );
+        }
 
-            Self {
-                address_state,
-                webview,
-            }
-        })
+        // A full-window layer catches clicks outside the menu, painted above the webview
+        Some(deferred(
+            div()
+                .absolute()
+                .inset_0()
+                .occlude()
+                .on_mouse_down(
+                    MouseButton::Left,
+                    cx.listener(|this, _: &MouseDownEvent, window, cx| {
+                        this.dismiss_context_menu(window, cx)
+                    }),
+                )
+                .on_mouse_down(
+                    MouseButton::Right,
+                    cx.listener(|this, _: &MouseDownEvent, window, cx| {
+                        this.dismiss_context_menu(window, cx)
+                    }),
+                )
+                .child(
+                    anchored().position(menu.position).child(
+                        div()
+                            .track_focus(&self.context_menu_focus)
+                            .on_key_down(cx.listener(|this, event: &KeyDownEvent, window, cx| {
+                                if event.keystroke.key == "escape" {
+                                    this.dismiss_context_menu(window, cx);
+                                }
+                            }))
+                            .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                            .w(px(200.))
+                            .py_1()
+                            .flex()
+                            .flex_col()
+                            .rounded_md()
+                            .border_1()
+                            .border_color(theme.border)
+                            .bg(theme.surface)
+                            .children(items),
+                    ),
+                ),
+        ))
     }
 }
 
 impl Render for Main {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let this = cx.entity();
+        if self.settings_page.is_none()
+            && InternalPage::from_url(&self.tabs[self.active_tab].url)
+                == Some(InternalPage::Settings)
+        {
+            self.settings_page = Some(preferences::page(window, cx));
+        }
+        let tab = &self.tabs[self.active_tab];
+        // a background tab's sign-in waits until the tab is shown
+        let auth_prompt = self
+            .auth_prompt
+            .as_ref()
+            .filter(|prompt| prompt.request.tab_id == tab.id);
+        let auth_prompt_shown = auth_prompt.is_some();
+        let permission_prompt = self
+            .permission_prompts
+            .iter()
+            .find(|prompt| prompt.tab_id == tab.id);
+
         div()
+            .key_context("Browser")
+            .track_focus(&self.focus_handle)
+            .when(self.is_fullscreen, |this| {
+                // Escape always leaves page fullscreen, before the page sees the key
+                this.capture_key_down(cx.listener(|this, event: &KeyDownEvent, window, cx| {
+                    if event.keystroke.key == "escape" {
+                        cx.stop_propagation();
+                        this.exit_page_fullscreen(window);
+                        cx.notify();
+                    }
+                }))
+            })
+            .when(tab.certificate_error.is_some(), |this| {
+                // both keys take the safe way out, the page can't be proceeded to by accident
+                this.capture_key_down(cx.listener(|this, event: &KeyDownEvent, _, cx| {
+                    if matches!(event.keystroke.key.as_str(), "escape" | "enter") {
+                        cx.stop_propagation();
+                        this.back_to_safety(cx);
+                    }
+                }))
+            })
+            .when(auth_prompt_shown, |this| {
+                this.capture_key_down(cx.listener(|this, event: &KeyDownEvent, window, cx| {
+                    if event.keystroke.key == "escape" {
+                        cx.stop_propagation();
+                        this.cancel_auth(window, cx);
+                    }
+                }))
+            })
+            .when(permission_prompt.is_some() && !auth_prompt_shown, |this| {
+                this.capture_key_down(cx.listener(|this, event: &KeyDownEvent, _, cx| {
+                    if event.keystroke.key == "escape" {
+                        cx.stop_propagation();
+                        this.answer_permission_prompt(None, cx);
+                    }
+                }))
+            })
+            .when(self.link_hints.is_some(), |this| {
+                this.capture_key_down(cx.listener(Self::on_hint_key_down))
+            })
+            .when(self.region_capture.is_some(), |this| {
+                this.capture_key_down(cx.listener(|this, event: &KeyDownEvent, _, cx| {
+                    if event.keystroke.key == "escape" {
+                        cx.stop_propagation();
+                        this.cancel_region_capture(cx);
+                    }
+                }))
+            })
+            // mouse side buttons
+            .on_mouse_down(
+                MouseButton::Navigate(NavigationDirection::Back),
+                cx.listener(|this, _, _, cx| {
+                    this.go_back(cx);
+                    cx.notify();
+                }),
+            )
+            .on_mouse_down(
+                MouseButton::Navigate(NavigationDirection::Forward),
+                cx.listener(|this, _, _, cx| {
+                    this.go_forward(cx);
+                    cx.notify();
+                }),
+            )
+            .on_scroll_wheel(cx.listener(Self::on_scroll_wheel))
+            .on_action(cx.listener(Self::focus_address_bar))
+            .on_action(cx.listener(Self::new_tab_action))
+            .on_action(cx.listener(Self::duplicate_tab))
+            .on_action(cx.listener(Self::close_tab_action))
+            .on_action(cx.listener(Self::reopen_closed_tab))
+            .on_action(cx.listener(|this, _: &Reload, _, cx| this.reload_with_toast(false, cx)))
+            .on_action(cx.listener(|this, _: &HardReload, _, cx| this.reload_with_toast(true, cx)))
+            .on_action(cx.listener(Self::next_tab))
+            .on_action(cx.listener(Self::previous_tab))
+            .on_action(
+                cx.listener(|this, _: &ActivateTab1, window, cx| this.select_tab(0, window, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &ActivateTab2, window, cx| this.select_tab(1, window, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &ActivateTab3, window, cx| this.select_tab(2, window, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &ActivateTab4, window, cx| this.select_tab(3, window, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &ActivateTab5, window, cx| this.select_tab(4, window, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &ActivateTab6, window, cx| this.select_tab(5, window, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &ActivateTab7, window, cx| this.select_tab(6, window, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &ActivateTab8, window, cx| this.select_tab(7, window, cx)),
+            )
+            .on_action(cx.listener(Self::activate_last_tab))
+            .on_action(cx.listener(Self::copy_url))
+            .on_action(cx.listener(Self::toggle_devtools))
+            .on_action(cx.listener(Self::capture_screenshot_action))
+            .on_action(cx.listener(Self::start_region_capture))
+            .on_action(cx.listener(Self::export_pdf))
+            .on_action(cx.listener(Self::open_file))
+            .on_action(cx.listener(Self::new_window))
+            .on_action(cx.listener(Self::new_private_window))
+            .on_action(cx.listener(Self::open_preferences))
+            .on_action(cx.listener(Self::toggle_fullscreen))
+            .on_action(cx.listener(Self::toggle_console))
+            .on_action(
+                cx.listener(|this, _: &ToggleTaskManager, _, cx| this.toggle_task_manager(cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &ToggleRecentlyClosed, _, cx| {
+                    this.toggle_recently_closed(cx)
+                }),
+            )
+            .on_action(
+                cx.listener(|this, _: &ToggleMute, _, cx| this.toggle_mute(this.active_tab, cx)),
+            )
+            .on_action(cx.listener(|this, _: &TogglePictureInPicture, _, cx| {
+                this.toggle_picture_in_picture(cx)
+            }))
+            .on_action(cx.listener(|this, _: &ToggleBookmark, _, cx| this.toggle_bookmark(cx)))
+            .on_action(cx.listener(Self::open_command_palette))
+            .on_action(cx.listener(Self::open_tab_search))
+            .on_action(
+                cx.listener(|this, _: &PasteAndGo, window, cx| this.paste_and_go(window, cx)),
+            )
+            .on_action(cx.listener(Self::open_find_bar))
+            .on_action(cx.listener(Self::toggle_read_aloud))
+            .on_action(cx.listener(Self::toggle_split_view))
+            .on_action(cx.listener(Self::zoom_in))
+            .on_action(cx.listener(Self::zoom_out))
+            .on_action(cx.listener(Self::reset_zoom))
             .size_full()
+            .children(self.render_context_menu(cx))
+            .children(self.render_tab_menu(cx))
+            .children(self.render_address_menu(cx))
             .child(
                 div()
                     .border_1()
-                    .border_color(rgba(0xd3d9d92b))
+                    .border_color(theme.border)
                     .rounded_xl()
-                    .bg(rgba(0x0404055e))
+                    .bg(theme.background)
                     .size_full()
+                    .when(self.is_fullscreen, |this| this.border_0().rounded_none())
+                    .child(self.render_tab_strip(window, cx))
+                    .child(self.render_toolbar(cx))
+                    .when(!self.is_fullscreen, |this| {
+                        this.child(self.render_progress_bar(cx))
+                    })
                     .child(
                         div()
-                            .pl(px(84.)) // Left padding to clear traffic lights
-                            .py(px(10.))
-                            .child(
-                                div()
-                                    .flex()
-                                    .items_center()
-                                    .gap_2()
-                                    .child(
-                                        // Back button
-                                        svg_button("back.svg", 14.0, rgb(0xf2f2f2), |_, _| {
-                                            println!("Back clicked!")
-                                        }),
-                                    )
-                                    .child(
-                                        // Forward button
-                                        svg_button(
-                                            "forward.svg",
-                                            14.0,
-                                            rgba(0xd3d9d92b),
-                                            |_, _| println!("Forward clicked!"),
-                                        ),
-                                    )
-                                    .child(
-                                        // Refresh button
-                                        svg_button("rotate-cw.svg", 12.0, rgb(0xf2f2f2), |_, _| {
-                                            println!("Refresh clicked!")
-                                        }),
-                                    )
-                                    .child(
-                                        div()
-                                            .flex()
-                                            .border_1()
-                                            .border_color(rgba(0xd3d9d92b))
-                                            .rounded_md()
-                                            .h_8()
-                                            .w_64()
-                                            .items_center()
-                                            .child(
-                                                div()
-                                                    .flex()
-                                                    .items_center()
-                                                    .gap_2()
-                                                    .px_3()
-                                                    .h_full()
-                                                    .w_full()
-                                                    .child(
-                                                        svg()
-                                                            .path("vercel.svg")
-                                                            .size(px(10.0))
-                                                            .text_color(rgb(0xfefefe)),
-                                                    )
-                                                    .child(
-                                                        TextInput::new(&self.address_state)
-                                                            .text_color(rgb(0xd1d1d1))
-                                                            .text_xs()
-                                                            .border_0(),
-                                                    )
-                                                    .child(
-                                                        svg()
-                                                            .path("close.svg")
-                                                            .size(px(10.0))
-                                                            .text_color(rgba(0xffffffb3)),
-                                                    ),
-                                            ),
-                                    )
-                                    .child(
-                                        div()
-                                            .px_1()
-                                            .py_1()
-                                            .bg(linear_gradient(
-                                                150.,
-                                                linear_color_stop(rgba(0x2e2e2e1c), 0.05), // transparent
-                                                linear_color_stop(rgba(0x6161621c), 0.85), // Very dark/black
-                                            ))
-                                            .border_1()
-                                            .border_color(rgba(0xd3d9d92b))
-                                            .rounded_md()
-                                            .items_center()
-                                            .justify_center()
-                                            .child(
-                                                svg()
-                                                    .path("plus.svg")
-                                                    .size(px(12.0))
-                                                    .text_color(rgb(0xf2f2f2)),
-                                            ),
-                                    ),
-                            ),
-                    )
-                    .child(self.webview.clone()),
+                            .relative()
+                            .size_full()
+                            .child(self.active_webview().clone())
+                            .children(
+                                InternalPage::from_url(&tab.url)
+                                    .map(|page| self.render_internal_page(page, cx)),
+                            )
+                            .children(
+                                tab.directory
+                                    .as_ref()
+                                    .map(|listing| self.render_directory_page(listing, cx)),
+                            )
+                            .children(
+                                tab.json
+                                    .as_ref()
+                                    .map(|view| self.render_json_view(tab.id, view, cx)),
+                            )
+                            .children(tab.reader.as_ref().map(|article| {
+                                let font_size = cx.global::<BrowserSettings>().reader_font_size;
+                                reader::render_reader(
+                                    article,
+                                    font_size,
+                                    {
+                                        let this = this.clone();
+                                        move |font_size, _, cx| {
+                                            this.update(cx, |this, cx| {
+                                                this.set_reader_font_size(font_size, cx)
+                                            })
+                                        }
+                                    },
+                                    {
+                                        let this = this.clone();
+                                        move |_, cx| {
+                                            this.update(cx, |this, cx| this.toggle_reader(cx))
+                                        }
+                                    },
+                                )
+                            }))
+                            .children(tab.error.as_ref().map(|error| {
+                                let this = this.clone();
+                                error_page::render_error_page(error, move |_, cx| {
+                                    this.update(cx, |this, cx| this.retry(cx))
+                                })
+                            }))
+                            .children(tab.certificate_error.as_ref().map(|error| {
+                                certificate::render_interstitial(
+                                    error,
+                                    &theme,
+                                    {
+                                        let this = this.clone();
+                                        move |_, cx| {
+                                            this.update(cx, |this, cx| this.back_to_safety(cx))
+                                        }
+                                    },
+                                    {
+                                        let this = this.clone();
+                                        move |_, cx| {
+                                            this.update(cx, |this, cx| {
+                                                let tab = &mut this.tabs[this.active_tab];
+                                                if let Some(error) = &mut tab.certificate_error {
+                                                    error.remember = !error.remember;
+                                                }
+                                                cx.notify();
+                                            })
+                                        }
+                                    },
+                                    {
+                                        let this = this.clone();
+                                        move |_, cx| {
+                                            this.update(cx, |this, cx| {
+                                                this.proceed_despite_certificate(cx)
+                                            })
+                                        }
+                                    },
+                                )
+                            }))
+                            .children(
+                                self.link_hints.as_ref().map(|link_hints| {
+                                    hints::render_hints(link_hints, tab.zoom_level)
+                                }),
+                            )
+                            .children(self.swipe.progress().map(|(direction, progress)| {
+                                gestures::render_swipe_indicator(direction, progress)
+                            }))
+                            .children(self.render_edge_buttons(cx))
+                            .children(self.render_status_bar(cx))
+                            .children(self.console_filter.as_ref().map(|filter| {
+                                console::render_console(
+                                    &tab.console,
+                                    filter,
+                                    cx,
+                                    {
+                                        let this = this.clone();
+                                        move |_, cx| {
+                                            this.update(cx, |this, cx| this.clear_console(cx))
+                                        }
+                                    },
+                                    {
+                                        let this = this.clone();
+                                        move |_, cx| {
+                                            this.update(cx, |this, cx| this.close_console(cx))
+                                        }
+                                    },
+                                )
+                            }))
+                            .children(self.render_autofill_offer(tab.zoom_level, cx))
+                            .children(self.render_autofill_save(cx))
+                            .children(self.render_password_offer(tab.zoom_level, cx))
+                            .children(self.render_password_save(cx))
+                            .children(self.render_find_bar(cx))
+                            .children(self.render_read_aloud_bar(cx))
+                            .children(self.render_region_capture(window, cx))
+                            .children(self.render_task_manager(cx))
+                            .child(self.render_drop_zone(cx))
+                            .children(permission_prompt.map(|prompt| {
+                                permissions::render_permission_prompt(
+                                    prompt,
+                                    &theme,
+                                    !self.private,
+                                    {
+                                        let this = this.clone();
+                                        move |_, cx| {
+                                            this.update(cx, |this, cx| {
+                                                let tab_id = this.tabs[this.active_tab].id;
+                                                if let Some(prompt) = this
+                                                    .permission_prompts
+                                                    .iter_mut()
+                                                    .find(|prompt| prompt.tab_id == tab_id)
+                                                {
+                                                    prompt.remember = !prompt.remember;
+                                                }
+                                                cx.notify();
+                                            })
+                                        }
+                                    },
+                                    {
+                                        let this = this.clone();
+                                        move |_, cx| {
+                                            this.update(cx, |this, cx| {
+                                                this.answer_permission_prompt(None, cx)
+                                            })
+                                        }
+                                    },
+                                    {
+                                        let this = this.clone();
+                                        move |_, cx| {
+                                            this.update(cx, |this, cx| {
+                                                this.answer_permission_prompt(
+                                                    Some(Decision::Block),
+                                                    cx,
+                                                )
+                                            })
+                                        }
+                                    },
+                                    {
+                                        let this = this.clone();
+                                        move |_, cx| {
+                                            this.update(cx, |this, cx| {
+                                                this.answer_permission_prompt(
+                                                    Some(Decision::Allow),
+                                                    cx,
+                                                )
+                                            })
+                                        }
+                                    },
+                                )
+                            }))
+                            .children(auth_prompt.map(|prompt| {
+                                auth::render_auth_prompt(
+                                    prompt,
+                                    &theme,
+                                    {
+                                        let this = this.clone();
+                                        move |_, cx| {
+                                            this.update(cx, |this, cx| {
+                                                if let Some(prompt) = &mut this.auth_prompt {
+                                                    prompt.remember = !prompt.remember;
+                                                }
+                                                cx.notify();
+                                            })
+                                        }
+                                    },
+                                    {
+                                        let this = this.clone();
+                                        move |window, cx| {
+                                            this.update(cx, |this, cx| this.cancel_auth(window, cx))
+                                        }
+                                    },
+                                    {
+                                        let this = this.clone();
+                                        move |window, cx| {
+                                            this.update(cx, |this, cx| this.submit_auth(window, cx))
+                                        }
+                                    },
+                                )
+                            }))
+                            .map(|content| self.render_split_view(content, cx)),
+                    ),
             )
             .children(Root::render_modal_layer(window, cx))
+            .child(ToastManager::global(cx))
     }
 }
 
 fn run() {
     Application::new()
-        .with_assets(Assets {
-            base: PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("assets"),
-        })
+        .with_http_client(Arc::new(
+            reqwest_client::ReqwestClient::user_agent("browser-app").unwrap(),
+        ))
+        .with_assets(assets::assets())
         .run(|cx: &mut App| {
+            let settings = BrowserSettings::load();
+            let sessions = match settings.startup {
+                StartupPage::Blank => vec![SessionState::new(settings::BLANK_PAGE_URL)],
+                StartupPage::Home => vec![SessionState::new(&settings.home_url)],
+                StartupPage::Restore => SessionState::load_all(&settings.home_url),
+            };
+            init_globals(settings, cx);
             if cfg!(target_os = "linux") {
-                cx.spawn(async move |cx| {
-                    let (tx, rx) = flume::unbounded();
+                MessagePump::start(cx);
+            }
+            for session in sessions {
+                open_window_with_session(session, false, cx);
+            }
+            cx.activate(true);
+        });
+}
 
-                    cx.background_spawn(async move {
-                        let mut timer = Timer::interval(Duration::from_millis(1000 / 60));
-                        while timer.next().await.is_some() {
-                            _ = tx.send_async(()).await;
-                        }
-                    })
-                    .detach();
+// Load the profile and everything windows share, before the first window opens
+fn init_globals(settings: BrowserSettings, cx: &mut App) {
+    gpui_component::init(cx);
+    #[cfg(feature = "dev-assets")]
+    assets::init(cx);
+    cx.set_global(History::load());
+    cx.set_global(NewTabTiles::load());
+    cx.set_global(Bookmarks::load());
+    cx.set_global(RecentlyClosed::load());
+    cx.set_global(UserScripts::load());
+    cx.set_global(Autofill::load());
+    cx.set_global(Passwords::load());
+    cx.set_global(Plugins::load());
+    cx.set_global(SitePermissions::load());
+    cx.set_global(ContentBlocker::load());
+    DownloadManager::init(cx);
+    ToastManager::init(cx);
+    cx.set_global(CertificateExceptions::default());
+    cx.set_global(settings);
+    ActiveProxy::init(cx);
+    SpellChecker::init(cx);
+    Theme::init(cx);
+    Keymap::init(cx);
+    Commands::init(cx);
+}
+
+// Open another browser window with a single tab on `initial_url`. Windows have their own tabs
+// but share history, bookmarks and cookies, except private ones.
+fn open_browser_window(cx: &mut App, initial_url: &str, private: bool) {
+    open_window_with_session(SessionState::new(initial_url), private, cx)
+}
+
+// Open a browser window with the given tabs, isolated from the shared profile when `private`.
+// The session file holds the tabs of every regular window of the run, see `SessionWindows`.
+fn open_window_with_session(session: SessionState, private: bool, cx: &mut App) {
+    // private windows don't restore or save the window bounds
+    let window_bounds = session
+        .bounds
+        .or_else(WindowState::load)
+        .filter(|_| !private)
+        .map(|state| state.window_bounds(cx))
+        .unwrap_or_else(|| window_state::default_bounds(cx));
+    let title = if private {
+        "Private Browsing"
+    } else {
+        "Browser App"
+    };
+    cx.open_window(
+        WindowOptions {
+            window_bounds: Some(window_bounds),
+            window_background: cx.global::<Theme>().window_background(),
+            titlebar: Some(gpui::TitlebarOptions {
+                appears_transparent: true,
+                traffic_light_position: Some(point(px(16.0), px(18.0))), // Custom position
+                title: Option::Some(SharedString::from(title)),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        |window, cx| {
+            let main = Main::new(session, private, window, cx);
+            OpenWindows::add(window.window_handle(), main.downgrade(), cx);
 
-                    while rx.recv_async().await.is_ok() {
-                        wef::do_message_work();
+            // save the open tabs of every regular window and the bounds when the window closes
+            if !private {
+                window.on_window_should_close(cx, {
+                    let main = main.clone();
+                    move |window, cx| {
+                        for (handle, other) in OpenWindows::all(cx) {
+                            let other = other.read(cx);
+                            if !other.private {
+                                let session = other.session_state();
+                                SessionWindows::record(handle, session, cx);
+                            }
+                        }
+                        SessionWindows::save(cx);
+                        main.update(cx, |main, cx| main.remember_closed_window(cx));
+                        WindowState::from_window(window).save();
+                        true
                     }
-                })
-                .detach();
+                });
             }
 
-            gpui_component::init(cx);
-
-            let bounds = Bounds::centered(None, size(px(800.), px(600.0)), cx);
-            cx.open_window(
-                WindowOptions {
-                    window_bounds: Some(WindowBounds::Windowed(bounds)),
-                    window_background: gpui::WindowBackgroundAppearance::Blurred,
-                    titlebar: Some(gpui::TitlebarOptions {
-                        appears_transparent: true,
-                        traffic_light_position: Some(point(px(16.0), px(18.0))), // Custom position
-                        title: Option::Some(SharedString::from("Browser App")),
-                        ..Default::default()
-                    }),
-                    ..Default::default()
-                },
-                |window, cx| {
-                    let main = Main::new(window, cx);
-                    cx.new(|cx| Root::new(main.into(), window, cx))
-                },
-            )
-            .unwrap();
-            cx.activate(true);
-        });
+            cx.new(|cx| Root::new(main.into(), window, cx))
+        },
+    )
+    .unwrap();
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    wef::launch(Settings::new(), run);
+    env_logger::init();
+    let mut settings = Settings::new();
+    if let Some(user_agent) = BrowserSettings::load().user_agent {
+        settings = settings.user_agent(&user_agent);
+    }
+    wef::launch(settings, run);
     Ok(())
 }
@@ -0,0 +1,124 @@
+use gpui::{App, EntityId, Global, Timer};
+use gpui_webview::wef;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crate::settings::BrowserSettings;
+
+// Once the last window goes quiet, the loop keeps the active rate this long before slowing down
+const IDLE_AFTER: Duration = Duration::from_secs(3);
+// How often the time spent in CEF's work is summed up and logged
+const REPORT_INTERVAL: Duration = Duration::from_secs(5);
+
+// Time spent in `wef::do_message_work` over one report interval
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WorkStats {
+    pub calls: u32,
+    pub total: Duration,
+    pub max: Duration,
+    // Rate the loop ran at when the interval ended
+    pub fps: u32,
+}
+
+impl WorkStats {
+    pub fn average(&self) -> Duration {
+        self.total.checked_div(self.calls).unwrap_or_default()
+    }
+}
+
+/// Drives CEF's message loop on Linux, where it isn't integrated with gpui's event loop.
+///
+/// Runs at `message_loop_fps` while a window is focused or loading a page, and drops to
+/// `idle_message_loop_fps` a few seconds after the last one went quiet. Other platforms
+/// don't install it.
+pub struct MessagePump {
+    // Windows that are focused or have a tab loading, by their `Main` entity
+    busy: HashSet<EntityId>,
+    awake_until: Instant,
+    current: WorkStats,
+    last: Option<WorkStats>,
+    report_at: Instant,
+}
+
+impl Global for MessagePump {}
+
+impl MessagePump {
+    pub fn start(cx: &mut App) {
+        let now = Instant::now();
+        cx.set_global(Self {
+            busy: HashSet::new(),
+            awake_until: now + IDLE_AFTER,
+            current: WorkStats::default(),
+            last: None,
+            report_at: now + REPORT_INTERVAL,
+        });
+
+        cx.spawn(async move |cx| loop {
+            let started = Instant::now();
+            wef::do_message_work();
+            let elapsed = started.elapsed();
+
+            let Ok(interval) = cx.update(|cx| {
+                cx.update_global::<Self, _>(|pump, cx| {
+                    let fps = pump.fps(cx.global::<BrowserSettings>());
+                    pump.record(elapsed, fps);
+                    Duration::from_secs(1) / fps
+                })
+            }) else {
+                break;
+            };
+            Timer::after(interval.saturating_sub(elapsed)).await;
+        })
+        .detach();
+    }
+
+    // Whether the window owned by `id` needs the active rate; no-op where there is no pump
+    pub fn set_busy(id: EntityId, busy: bool, cx: &mut App) {
+        if !cx.has_global::<Self>() {
+            return;
+        }
+        cx.update_global::<Self, _>(|pump, _| {
+            if busy {
+                pump.busy.insert(id);
+            } else if pump.busy.remove(&id) {
+                pump.awake_until = Instant::now() + IDLE_AFTER;
+            }
+        });
+    }
+
+    // CEF's work time over the last full report interval, for tuning the rates
+    pub fn work_stats(cx: &App) -> Option<WorkStats> {
+        cx.try_global::<Self>().and_then(|pump| pump.last)
+    }
+
+    fn fps(&self, settings: &BrowserSettings) -> u32 {
+        let fps = if !self.busy.is_empty() || Instant::now() < self.awake_until {
+            settings.message_loop_fps
+        } else {
+            settings.idle_message_loop_fps
+        };
+        fps.max(1)
+    }
+
+    fn record(&mut self, elapsed: Duration, fps: u32) {
+        let stats = &mut self.current;
+        stats.calls += 1;
+        stats.total += elapsed;
+        stats.max = stats.max.max(elapsed);
+        stats.fps = fps;
+
+        let now = Instant::now();
+        if now >= self.report_at {
+            let stats = std::mem::take(&mut self.current);
+            log::debug!(
+                "CEF message loop at {} fps: {} calls, {:.2} ms average, {:.2} ms max",
+                stats.fps,
+                stats.calls,
+                stats.average().as_secs_f64() * 1000.,
+                stats.max.as_secs_f64() * 1000.
+            );
+            self.last = Some(stats);
+            self.report_at = now + REPORT_INTERVAL;
+        }
+    }
+}
@@ -0,0 +1,295 @@
+use gpui::{div, prelude::*, px, svg, Context, Global, IntoElement};
+use gpui_component::input::TextInput;
+use serde::{Deserialize, Serialize};
+
+use crate::favicon::FaviconState;
+use crate::history::History;
+use crate::settings::NEW_TAB_URL;
+use crate::storage;
+use crate::theme::Theme;
+use crate::{favicon, favicon_icon, Main};
+
+const NEW_TAB_FILE: &str = "new_tab.json";
+
+// Most-visited sites fill the grid up to this many tiles, after the pinned ones
+const MAX_TILES: usize = 8;
+const TILE_WIDTH: f32 = 112.;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tile {
+    pub url: String,
+    pub title: String,
+}
+
+// The user's changes to the new-tab grid, persisted on every change
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NewTabTiles {
+    // Always shown first, in the order they were pinned
+    pinned: Vec<Tile>,
+    // URLs kept out of the most-visited tiles
+    removed: Vec<String>,
+}
+
+impl Global for NewTabTiles {}
+
+impl NewTabTiles {
+    pub fn load() -> Self {
+        storage::load_json::<NewTabTiles>(NEW_TAB_FILE)
+            .unwrap_or_else(|err| {
+                log::error!("Failed to load new tab page: {}", err);
+                None
+            })
+            .unwrap_or_default()
+    }
+
+    // Pinned tiles, then the most visited pages, each with whether it's pinned
+    pub fn tiles(&self, history: &History) -> Vec<(Tile, bool)> {
+        let mut visited: Vec<_> = history
+            .entries()
+            .iter()
+            .filter(|entry| !self.removed.contains(&entry.url) && !self.is_pinned(&entry.url))
+            .collect();
+        visited.sort_by(|a, b| {
+            b.visit_count
+                .cmp(&a.visit_count)
+                .then(b.visited_at.cmp(&a.visited_at))
+        });
+
+        let mut tiles: Vec<_> = self
+            .pinned
+            .iter()
+            .map(|tile| (tile.clone(), true))
+            .collect();
+        let remaining = MAX_TILES.saturating_sub(tiles.len());
+        tiles.extend(visited.into_iter().take(remaining).map(|entry| {
+            let tile = Tile {
+                url: entry.url.clone(),
+                title: entry.title.clone(),
+            };
+            (tile, false)
+        }));
+        tiles
+    }
+
+    fn is_pinned(&self, url: &str) -> bool {
+        self.pinned.iter().any(|tile| tile.url == url)
+    }
+
+    pub fn toggle_pin(&mut self, tile: &Tile) {
+        if self.is_pinned(&tile.url) {
+            self.pinned.retain(|pinned| pinned.url != tile.url);
+        } else {
+            self.pinned.push(tile.clone());
+        }
+        self.save();
+    }
+
+    // Drop a tile from the grid; a pinned one is only unpinned
+    pub fn remove(&mut self, url: &str) {
+        if self.is_pinned(url) {
+            self.pinned.retain(|pinned| pinned.url != url);
+        } else if !self.removed.iter().any(|removed| removed == url) {
+            self.removed.push(url.to_string());
+        }
+        self.save();
+    }
+
+    fn save(&self) {
+        if let Err(err) = storage::save_json(NEW_TAB_FILE, self) {
+            log::error!("Failed to save new tab page: {}", err);
+        }
+    }
+}
+
+fn tile_button(
+    id: (&'static str, usize),
+    icon: &'static str,
+    theme: &Theme,
+) -> gpui::Stateful<gpui::Div> {
+    div()
+        .id(id)
+        .p_1()
+        .rounded_md()
+        .bg(theme.surface)
+        .hover(|this| this.bg(theme.hover))
+        .child(svg().path(icon).size(px(10.0)).text_color(theme.icon_muted))
+}
+
+impl Main {
+    // Fetch icons of tile sites that haven't been seen yet this session
+    pub(crate) fn load_tile_favicons(&mut self, cx: &mut Context<Self>) {
+        let tiles = cx.global::<NewTabTiles>().tiles(cx.global::<History>());
+        for (tile, _) in tiles {
+            let Some((_, icon_url)) = favicon::favicon_url(&tile.url) else {
+                continue;
+            };
+            if self.favicons.contains_key(&icon_url) {
+                continue;
+            }
+
+            self.favicons
+                .insert(icon_url.clone(), FaviconState::Loading);
+            let client = cx.http_client();
+            cx.spawn(async move |this, cx| {
+                let state = match cx
+                    .background_spawn(favicon::fetch(client, icon_url.clone()))
+                    .await
+                {
+                    Ok(icon) => FaviconState::Loaded(icon),
+                    Err(_) => FaviconState::Missing,
+                };
+                this.update(cx, |this, cx| {
+                    this.favicons.insert(icon_url, state);
+                    cx.notify();
+                })
+            })
+            .detach();
+        }
+    }
+
+    // Search box and site grid drawn over the blank webview of an `about:newtab` tab
+    pub(crate) fn render_new_tab_page(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let tiles = cx.global::<NewTabTiles>().tiles(cx.global::<History>());
+        let theme = *cx.global::<Theme>();
+
+        div()
+            .absolute()
+            .inset_0()
+            .flex()
+            .flex_col()
+            .items_center()
+            .justify_center()
+            .gap_6()
+            .bg(theme.page)
+            .child(
+                div()
+                    .w(px(480.))
+                    .h_10()
+                    .px_4()
+                    .flex()
+                    .items_center()
+                    .border_1()
+                    .border_color(theme.border)
+                    .rounded_lg()
+                    .bg(theme.hover_subtle)
+                    .child(
+                        TextInput::new(&self.new_tab_search)
+                            .text_color(theme.text_secondary)
+                            .text_sm()
+                            .border_0(),
+                    ),
+            )
+            .map(|this| {
+                if tiles.is_empty() {
+                    return this.child(
+                        div()
+                            .text_xs()
+                            .text_color(theme.text_muted)
+                            .child("Sites you visit often will show up here"),
+                    );
+                }
+
+                this.child(
+                    div()
+                        .max_w(px(TILE_WIDTH * 4. + 12. * 3.))
+                        .flex()
+                        .flex_wrap()
+                        .justify_center()
+                        .gap_3()
+                        .children(
+                            tiles
+                                .into_iter()
+                                .enumerate()
+                                .map(|(ix, (tile, pinned))| self.render_tile(ix, tile, pinned, cx)),
+                        ),
+                )
+            })
+    }
+
+    fn render_tile(
+        &self,
+        ix: usize,
+        tile: Tile,
+        pinned: bool,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let (host, icon) = match favicon::favicon_url(&tile.url) {
+            Some((host, icon_url)) => (
+                Some(host),
+                self.favicons.get(&icon_url).and_then(FaviconState::image),
+            ),
+            None => (None, None),
+        };
+        let label = if tile.title.is_empty() {
+            host.unwrap_or_else(|| tile.url.clone())
+        } else {
+            tile.title.clone()
+        };
+        let theme = *cx.global::<Theme>();
+
+        div()
+            .id(("new-tab-tile", ix))
+            .group("new-tab-tile")
+            .relative()
+            .w(px(TILE_WIDTH))
+            .h(px(96.))
+            .flex()
+            .flex_col()
+            .items_center()
+            .justify_center()
+            .gap_2()
+            .p_2()
+            .rounded_lg()
+            .border_1()
+            .border_color(theme.border)
+            .bg(theme.hover_subtle)
+            .cursor_pointer()
+            .hover(|this| this.bg(theme.hover))
+            .on_click({
+                let url = tile.url.clone();
+                cx.listener(move |this, _, window, cx| this.navigate(&url, window, cx))
+            })
+            .child(favicon_icon(icon, 24.))
+            .child(
+                div()
+                    .w_full()
+                    .text_center()
+                    .truncate()
+                    .text_xs()
+                    .text_color(theme.text_secondary)
+                    .child(label),
+            )
+            .child(
+                div()
+                    .absolute()
+                    .top_1()
+                    .right_1()
+                    .flex()
+                    .gap_1()
+                    .when(!pinned, |this| {
+                        this.invisible()
+                            .group_hover("new-tab-tile", |this| this.visible())
+                    })
+                    .child(tile_button(("pin-tile", ix), "pin.svg", &theme).on_click({
+                        let tile = tile.clone();
+                        cx.listener(move |_, _, _, cx| {
+                            cx.stop_propagation();
+                            cx.update_global::<NewTabTiles, _>(|tiles, _| tiles.toggle_pin(&tile));
+                            cx.notify();
+                        })
+                    }))
+                    .child(
+                        tile_button(("remove-tile", ix), "close.svg", &theme).on_click(
+                            cx.listener(move |_, _, _, cx| {
+                                cx.stop_propagation();
+                                cx.update_global::<NewTabTiles, _>(|tiles, _| {
+                                    tiles.remove(&tile.url)
+                                });
+                                cx.notify();
+                            }),
+                        ),
+                    ),
+            )
+    }
+}
@@ -0,0 +1,472 @@
+use gpui::{div, prelude::*, px, App, Context, Global, IntoElement, MouseButton};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{self, Credentials};
+use crate::bridge::TabId;
+use crate::permissions;
+use crate::storage;
+use crate::theme::Theme;
+use crate::toast::toast_error;
+use crate::Main;
+
+// Which logins are saved, without their passwords, which only the OS keychain holds
+const PASSWORDS_FILE: &str = "passwords.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedLogin {
+    // The page's origin, e.g. "https://accounts.example.com"; fills only happen on the same one
+    pub origin: String,
+    pub username: String,
+}
+
+impl SavedLogin {
+    fn keychain_key(&self) -> String {
+        format!("login {} {}", self.origin, self.username)
+    }
+
+    // Blocks on the OS keychain, which may ask the user to unlock it
+    pub fn load_password(&self) -> Option<String> {
+        auth::load_credentials(&self.keychain_key()).map(|credentials| credentials.password)
+    }
+
+    fn save_password(&self, password: String) {
+        auth::save_credentials(
+            &self.keychain_key(),
+            &Credentials {
+                username: self.username.clone(),
+                password,
+            },
+        );
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct PasswordsData {
+    logins: Vec<SavedLogin>,
+    // Origins the user asked never to offer saving for
+    never_saved: Vec<String>,
+}
+
+// Saved logins by origin, persisted on every change
+pub struct Passwords {
+    data: PasswordsData,
+}
+
+impl Global for Passwords {}
+
+impl Passwords {
+    pub fn load() -> Self {
+        let data = storage::load_json::<PasswordsData>(PASSWORDS_FILE)
+            .unwrap_or_else(|err| {
+                log::error!("Failed to load saved passwords: {}", err);
+                None
+            })
+            .unwrap_or_default();
+        Self { data }
+    }
+
+    pub fn logins(&self) -> &[SavedLogin] {
+        &self.data.logins
+    }
+
+    fn for_origin<'a>(&'a self, origin: &'a str) -> impl Iterator<Item = &'a SavedLogin> {
+        self.data
+            .logins
+            .iter()
+            .filter(move |login| login.origin == origin)
+    }
+
+    // Keeps the login's password in the keychain, off the main thread
+    fn add(cx: &mut App, login: SavedLogin, password: String) {
+        cx.update_global::<Self, _>(|passwords, _| {
+            if !passwords.data.logins.contains(&login) {
+                passwords.data.logins.push(login.clone());
+                passwords.save();
+            }
+        });
+        cx.background_spawn(async move { login.save_password(password) })
+            .detach();
+    }
+
+    pub fn remove(cx: &mut App, login: &SavedLogin) {
+        cx.update_global::<Self, _>(|passwords, _| {
+            passwords.data.logins.retain(|saved| saved != login);
+            passwords.save();
+        });
+        let key = login.keychain_key();
+        cx.background_spawn(async move { auth::delete_credentials(&key) })
+            .detach();
+    }
+
+    fn never_save(cx: &mut App, origin: String) {
+        cx.update_global::<Self, _>(|passwords, _| {
+            if !passwords.data.never_saved.contains(&origin) {
+                passwords.data.never_saved.push(origin);
+                passwords.save();
+            }
+        });
+    }
+
+    fn save(&self) {
+        if let Err(err) = storage::save_json(PASSWORDS_FILE, &self.data) {
+            log::error!("Failed to save the password list: {}", err);
+        }
+    }
+}
+
+// A focused sign-in field on a page with saved logins, and where it is in CSS pixels
+pub struct PasswordOffer {
+    pub tab_id: TabId,
+    origin: String,
+    x: f32,
+    bottom: f32,
+    width: f32,
+}
+
+// A login the page was just sent, waiting for the user to save it
+pub struct PasswordSave {
+    pub tab_id: TabId,
+    login: SavedLogin,
+    password: String,
+    // Whether it replaces the password saved for the same username
+    update: bool,
+}
+
+// A saved login with its password, read from the keychain while `about:passwords` is unlocked
+pub struct UnlockedLogin {
+    pub login: SavedLogin,
+    pub password: Option<String>,
+    pub shown: bool,
+}
+
+impl Main {
+    // Whether `origin` is what the tab is showing, so nothing reaches a page it wasn't for
+    pub(crate) fn tab_on_origin(&self, tab_id: TabId, origin: &str) -> bool {
+        self.tabs
+            .iter()
+            .find(|tab| tab.id == tab_id)
+            .is_some_and(|tab| permissions::origin_of(&tab.url) == origin)
+    }
+
+    // A sign-in field got focus, or lost it when `origin` is empty
+    pub(crate) fn offer_passwords(
+        &mut self,
+        tab_id: TabId,
+        origin: String,
+        x: f32,
+        bottom: f32,
+        width: f32,
+        cx: &mut Context<Self>,
+    ) {
+        let saved = cx
+            .global::<Passwords>()
+            .for_origin(&origin)
+            .next()
+            .is_some();
+        let shown =
+            saved && self.tabs[self.active_tab].id == tab_id && self.tab_on_origin(tab_id, &origin);
+        self.password_offer = shown.then(|| PasswordOffer {
+            tab_id,
+            origin,
+            x,
+            bottom,
+            width,
+        });
+        cx.notify();
+    }
+
+    fn fill_password(&mut self, login: SavedLogin, cx: &mut Context<Self>) {
+        let Some(offer) = self.password_offer.take() else {
+            return;
+        };
+        cx.spawn(async move |this, cx| {
+            let password = cx
+                .background_spawn({
+                    let login = login.clone();
+                    async move { login.load_password() }
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                let Some(password) = password else {
+                    toast_error(cx, "Couldn't read the password from the keychain");
+                    return;
+                };
+                // the tab may have moved on while the keychain was asked
+                if !this.tab_on_origin(offer.tab_id, &login.origin) {
+                    return;
+                }
+                let frame = this
+                    .tabs
+                    .iter()
+                    .find(|tab| tab.id == offer.tab_id)
+                    .and_then(|tab| tab.main_frame.as_ref());
+                if let Some(frame) = frame {
+                    let args = serde_json::to_string(&(&login.origin, &login.username, &password))
+                        .unwrap_or_default();
+                    frame.execute_javascript(&format!(
+                        "window.__browserPasswords && window.__browserPasswords.fill(...{})",
+                        args
+                    ));
+                }
+            })
+            .ok();
+        })
+        .detach();
+        cx.notify();
+    }
+
+    // The page was sent a login; ask to save it unless it's the one already saved
+    pub(crate) fn password_submitted(
+        &mut self,
+        tab_id: TabId,
+        origin: String,
+        username: String,
+        password: String,
+        cx: &mut Context<Self>,
+    ) {
+        let passwords = cx.global::<Passwords>();
+        if self.private
+            || password.is_empty()
+            || passwords.data.never_saved.contains(&origin)
+            || !self.tab_on_origin(tab_id, &origin)
+        {
+            return;
+        }
+        let login = SavedLogin { origin, username };
+        let saved = passwords.logins().contains(&login);
+        cx.spawn(async move |this, cx| {
+            let current = if saved {
+                let login = login.clone();
+                cx.background_spawn(async move { login.load_password() })
+                    .await
+            } else {
+                None
+            };
+            if current.as_ref() == Some(&password) {
+                return;
+            }
+            this.update(cx, |this, cx| {
+                this.password_save = Some(PasswordSave {
+                    tab_id,
+                    login,
+                    password,
+                    update: saved,
+                });
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    fn answer_password_save(&mut self, save: bool, cx: &mut Context<Self>) {
+        let Some(prompt) = self.password_save.take() else {
+            return;
+        };
+        if save {
+            Passwords::add(cx, prompt.login, prompt.password);
+        }
+        cx.notify();
+    }
+
+    // Read every saved password for `about:passwords`, the keychain asking the user to unlock
+    // it where it's locked
+    pub(crate) fn unlock_passwords(&mut self, cx: &mut Context<Self>) {
+        let logins = cx.global::<Passwords>().logins().to_vec();
+        cx.spawn(async move |this, cx| {
+            let unlocked = cx
+                .background_spawn(async move {
+                    logins
+                        .into_iter()
+                        .map(|login| UnlockedLogin {
+                            password: login.load_password(),
+                            login,
+                            shown: false,
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                this.unlocked_passwords = Some(unlocked);
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    pub(crate) fn lock_passwords(&mut self, cx: &mut Context<Self>) {
+        if self.unlocked_passwords.take().is_some() {
+            cx.notify();
+        }
+    }
+
+    // Menu under the focused sign-in field, one item per saved username. `zoom` converts the
+    // page's CSS pixels to window pixels.
+    pub(crate) fn render_password_offer(
+        &self,
+        zoom: f32,
+        cx: &mut Context<Self>,
+    ) -> Option<impl IntoElement> {
+        let offer = self
+            .password_offer
+            .as_ref()
+            .filter(|offer| offer.tab_id == self.tabs[self.active_tab].id)?;
+        let theme = *cx.global::<Theme>();
+        let logins: Vec<_> = cx
+            .global::<Passwords>()
+            .for_origin(&offer.origin)
+            .cloned()
+            .collect();
+
+        Some(
+            div()
+                .absolute()
+                .left(px(offer.x * zoom))
+                .top(px(offer.bottom * zoom + 2.))
+                .w(px((offer.width * zoom).max(240.)))
+                .occlude()
+                .flex()
+                .flex_col()
+                .p_1()
+                .rounded_md()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.surface)
+                .shadow_lg()
+                .children(logins.into_iter().enumerate().map(|(ix, login)| {
+                    let username = if login.username.is_empty() {
+                        "No username".to_string()
+                    } else {
+                        login.username.clone()
+                    };
+                    div()
+                        .id(("password-login", ix))
+                        .px_3()
+                        .py_1()
+                        .rounded_md()
+                        .text_xs()
+                        .cursor_pointer()
+                        .hover(|this| this.bg(theme.hover))
+                        // on mouse down, before the field's blur hides the menu
+                        .on_mouse_down(
+                            MouseButton::Left,
+                            cx.listener(move |this, _, _, cx| {
+                                this.fill_password(login.clone(), cx)
+                            }),
+                        )
+                        .child(div().truncate().text_color(theme.text).child(username))
+                        .child(div().text_color(theme.text_muted).child("••••••••"))
+                })),
+        )
+    }
+
+    // Asks whether to save the login a page was sent, over the top right of the page
+    pub(crate) fn render_password_save(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let prompt = self
+            .password_save
+            .as_ref()
+            .filter(|prompt| prompt.tab_id == self.tabs[self.active_tab].id)?;
+        let theme = *cx.global::<Theme>();
+        let button = |id: &'static str, label: &'static str, primary: bool| {
+            div()
+                .id(id)
+                .px_3()
+                .py_1()
+                .rounded_md()
+                .text_xs()
+                .cursor_pointer()
+                .when(primary, |this| {
+                    this.bg(theme.accent).text_color(gpui::white())
+                })
+                .when(!primary, |this| {
+                    this.border_1()
+                        .border_color(theme.border)
+                        .text_color(theme.text)
+                        .hover(|this| this.bg(theme.hover))
+                })
+                .child(label)
+        };
+        let origin = prompt.login.origin.clone();
+
+        Some(
+            div()
+                .absolute()
+                .top_2()
+                .right_4()
+                .w(px(320.))
+                .occlude()
+                .flex()
+                .flex_col()
+                .gap_3()
+                .p_4()
+                .rounded_lg()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.surface)
+                .shadow_lg()
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(theme.text)
+                        .child(if prompt.update {
+                            "Update saved password?"
+                        } else {
+                            "Save password?"
+                        }),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .gap_1()
+                        .text_xs()
+                        .child(
+                            div()
+                                .truncate()
+                                .text_color(theme.text_muted)
+                                .child(prompt.login.origin.clone()),
+                        )
+                        .when(!prompt.login.username.is_empty(), |this| {
+                            this.child(
+                                div()
+                                    .truncate()
+                                    .text_color(theme.text_secondary)
+                                    .child(prompt.login.username.clone()),
+                            )
+                        })
+                        .child(div().text_color(theme.text_secondary).child("••••••••")),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .justify_end()
+                        .gap_2()
+                        .when(!prompt.update, |this| {
+                            this.child(
+                                button("password-never", "Never for this site", false).on_click(
+                                    cx.listener(move |this, _, _, cx| {
+                                        Passwords::never_save(cx, origin.clone());
+                                        this.answer_password_save(false, cx)
+                                    }),
+                                ),
+                            )
+                        })
+                        .child(button("password-not-now", "Not now", false).on_click(
+                            cx.listener(|this, _, _, cx| this.answer_password_save(false, cx)),
+                        ))
+                        .child(
+                            button(
+                                "password-save",
+                                if prompt.update { "Update" } else { "Save" },
+                                true,
+                            )
+                            .on_click(
+                                cx.listener(|this, _, _, cx| this.answer_password_save(true, cx)),
+                            ),
+                        ),
+                ),
+        )
+    }
+}
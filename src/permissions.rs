@@ -0,0 +1,302 @@
+use gpui::{div, prelude::*, px, white, App, Global, IntoElement, Window};
+use gpui_webview::events::{PermissionCallback, PermissionKind};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use url::Url;
+
+use crate::bridge::TabId;
+use crate::storage;
+use crate::theme::Theme;
+
+const PERMISSIONS_FILE: &str = "permissions.json";
+
+// Capabilities a page has to ask the user for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Geolocation,
+    Camera,
+    Microphone,
+    Notifications,
+    // Reading the clipboard through the `clipboardRead` host function
+    ClipboardRead,
+    // Replacing the clipboard through the `clipboardWrite` host function
+    ClipboardWrite,
+}
+
+impl Permission {
+    // `None` for kinds the browser doesn't prompt for, which are always blocked
+    pub fn from_kind(kind: PermissionKind) -> Option<Self> {
+        Some(match kind {
+            PermissionKind::Geolocation => Permission::Geolocation,
+            PermissionKind::Camera => Permission::Camera,
+            PermissionKind::Microphone => Permission::Microphone,
+            PermissionKind::Notifications => Permission::Notifications,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Permission::Geolocation => "Location",
+            Permission::Camera => "Camera",
+            Permission::Microphone => "Microphone",
+            Permission::Notifications => "Notifications",
+            Permission::ClipboardRead => "Clipboard",
+            Permission::ClipboardWrite => "Copying",
+        }
+    }
+
+    // What the prompt says the site wants to do
+    fn request(self) -> &'static str {
+        match self {
+            Permission::Geolocation => "Know your location",
+            Permission::Camera => "Use your camera",
+            Permission::Microphone => "Use your microphone",
+            Permission::Notifications => "Show notifications",
+            Permission::ClipboardRead => "See text you copied",
+            Permission::ClipboardWrite => "Replace text you copied",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Decision {
+    Allow,
+    Block,
+}
+
+// Key decisions are remembered under, e.g. "https://meet.example.com"
+pub fn origin_of(url: &str) -> String {
+    Url::parse(url)
+        .map(|url| url.origin().ascii_serialization())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+// Decisions the user asked to be remembered, by origin, persisted on every change
+pub struct SitePermissions {
+    decisions: HashMap<String, HashMap<Permission, Decision>>,
+}
+
+impl Global for SitePermissions {}
+
+impl SitePermissions {
+    pub fn load() -> Self {
+        let decisions = storage::load_json(PERMISSIONS_FILE)
+            .unwrap_or_else(|err| {
+                log::error!("Failed to load permissions: {}", err);
+                None
+            })
+            .unwrap_or_default();
+        Self { decisions }
+    }
+
+    // The remembered answer to a request for all of `permissions`, `None` unless every one of
+    // them has been decided
+    pub fn decision(&self, origin: &str, permissions: &[Permission]) -> Option<Decision> {
+        let decisions = self.decisions.get(origin)?;
+        let mut result = Decision::Allow;
+        for permission in permissions {
+            if *decisions.get(permission)? == Decision::Block {
+                result = Decision::Block;
+            }
+        }
+        Some(result)
+    }
+
+    pub fn set(&mut self, origin: &str, permissions: &[Permission], decision: Decision) {
+        let decisions = self.decisions.entry(origin.to_string()).or_default();
+        for permission in permissions {
+            decisions.insert(*permission, decision);
+        }
+        self.save();
+    }
+
+    // Forget one decision, so the site has to ask again
+    pub fn reset(&mut self, origin: &str, permission: Permission) {
+        let Some(decisions) = self.decisions.get_mut(origin) else {
+            return;
+        };
+        decisions.remove(&permission);
+        if decisions.is_empty() {
+            self.decisions.remove(origin);
+        }
+        self.save();
+    }
+
+    pub fn site(&self, origin: &str) -> Vec<(Permission, Decision)> {
+        let mut permissions: Vec<_> = self
+            .decisions
+            .get(origin)
+            .map(|decisions| decisions.iter().map(|(p, d)| (*p, *d)).collect())
+            .unwrap_or_default();
+        permissions.sort_by_key(|(permission, _)| *permission);
+        permissions
+    }
+
+    fn save(&self) {
+        if let Err(err) = storage::save_json(PERMISSIONS_FILE, &self.decisions) {
+            log::error!("Failed to save permissions: {}", err);
+        }
+    }
+}
+
+// Who is waiting for the answer to a permission prompt
+pub enum PermissionResponder {
+    // CEF, for the page's own permission request
+    Page(PermissionCallback),
+    // A host function call, receiving whether it was allowed
+    Host(flume::Sender<bool>),
+}
+
+impl PermissionResponder {
+    pub fn allow(&self) {
+        match self {
+            PermissionResponder::Page(callback) => callback.allow(),
+            PermissionResponder::Host(reply) => _ = reply.send(true),
+        }
+    }
+
+    pub fn deny(&self) {
+        match self {
+            PermissionResponder::Page(callback) => callback.deny(),
+            PermissionResponder::Host(reply) => _ = reply.send(false),
+        }
+    }
+}
+
+// A page waiting for the user to allow or block `permissions`
+pub struct PermissionPrompt {
+    pub tab_id: TabId,
+    pub origin: String,
+    pub permissions: Vec<Permission>,
+    pub remember: bool,
+    pub callback: PermissionResponder,
+}
+
+fn button(
+    id: &'static str,
+    label: &'static str,
+    primary: bool,
+    theme: &Theme,
+) -> gpui::Stateful<gpui::Div> {
+    div()
+        .id(id)
+        .px_3()
+        .py_1()
+        .rounded_md()
+        .border_1()
+        .border_color(theme.border)
+        .text_xs()
+        .text_color(theme.text)
+        .cursor_pointer()
+        .when(primary, |this| {
+            this.bg(theme.accent)
+                .border_color(theme.accent)
+                .text_color(white())
+        })
+        .hover(|this| this.opacity(0.85))
+        .child(label)
+}
+
+// Bubble at the top left of the page, under the site it asks for. `remember` is only
+// offered where decisions are saved.
+pub fn render_permission_prompt(
+    prompt: &PermissionPrompt,
+    theme: &Theme,
+    can_remember: bool,
+    on_toggle_remember: impl Fn(&mut Window, &mut App) + 'static,
+    on_dismiss: impl Fn(&mut Window, &mut App) + 'static,
+    on_block: impl Fn(&mut Window, &mut App) + 'static,
+    on_allow: impl Fn(&mut Window, &mut App) + 'static,
+) -> impl IntoElement {
+    div()
+        .absolute()
+        .top_2()
+        .left_2()
+        .w(px(320.))
+        .occlude()
+        .flex()
+        .flex_col()
+        .gap_3()
+        .p_4()
+        .rounded_lg()
+        .border_1()
+        .border_color(theme.border)
+        .bg(theme.surface)
+        .shadow_lg()
+        .child(
+            div()
+                .flex()
+                .items_start()
+                .gap_2()
+                .child(
+                    div()
+                        .flex_1()
+                        .text_sm()
+                        .text_color(theme.text)
+                        .child(format!("{} wants to", prompt.origin)),
+                )
+                .child(
+                    div()
+                        .id("permission-dismiss")
+                        .text_xs()
+                        .text_color(theme.text_muted)
+                        .cursor_pointer()
+                        .hover(|this| this.text_color(theme.text))
+                        .on_click(move |_, window, cx| on_dismiss(window, cx))
+                        .child("✕"),
+                ),
+        )
+        .child(
+            div()
+                .flex()
+                .flex_col()
+                .gap_1()
+                .children(prompt.permissions.iter().map(|permission| {
+                    div()
+                        .text_xs()
+                        .text_color(theme.text_secondary)
+                        .child(permission.request())
+                })),
+        )
+        .when(can_remember, |this| {
+            this.child(
+                div()
+                    .id("permission-remember")
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .text_xs()
+                    .text_color(theme.text_secondary)
+                    .cursor_pointer()
+                    .on_click(move |_, window, cx| on_toggle_remember(window, cx))
+                    .child(
+                        div()
+                            .size(px(12.))
+                            .rounded_sm()
+                            .border_1()
+                            .border_color(theme.text_muted)
+                            .when(prompt.remember, |this| {
+                                this.bg(theme.accent).border_color(theme.accent)
+                            }),
+                    )
+                    .child("Remember this decision"),
+            )
+        })
+        .child(
+            div()
+                .flex()
+                .justify_end()
+                .gap_2()
+                .child(
+                    button("permission-block", "Block", false, theme)
+                        .on_click(move |_, window, cx| on_block(window, cx)),
+                )
+                .child(
+                    button("permission-allow", "Allow", true, theme)
+                        .on_click(move |_, window, cx| on_allow(window, cx)),
+                ),
+        )
+}
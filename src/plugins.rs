@@ -0,0 +1,462 @@
+use flume::Sender;
+use gpui::{div, prelude::*, px, App, Context, Global, IntoElement, SharedString};
+use gpui_webview::wef::Frame;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+use crate::bridge::{BridgeError, TabId};
+use crate::storage;
+use crate::theme::Theme;
+use crate::toast::toast_info;
+use crate::userscripts;
+use crate::Main;
+
+// Which plugins are switched on; plugins themselves live in `PLUGINS_DIR`, one per folder
+const PLUGINS_FILE: &str = "plugins.json";
+const PLUGINS_DIR: &str = "plugins";
+const MANIFEST_FILE: &str = "plugin.json";
+// Version of the `browser` object plugin scripts are handed, bumped on breaking changes
+pub const API_VERSION: u32 = 1;
+// Events the host sends, which plugins can't emit themselves
+const RESERVED_EVENTS: &[&str] = &["navigate"];
+
+const RUNTIME_SCRIPT: &str = include_str!("scripts/plugins.js");
+// Copied into an empty plugins folder, switched off, to show what a plugin looks like
+const EXAMPLE_PLUGIN: (&str, &str, &str) = (
+    "word-count",
+    include_str!("../examples/plugins/word-count/plugin.json"),
+    include_str!("../examples/plugins/word-count/plugin.js"),
+);
+
+// What a plugin may ask the host for besides reading its tab's URL, showing a notification
+// and talking to other plugins
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginPermission {
+    // Load another web page in the tab
+    Navigate,
+    // Run code in the page, outside the page's content security policy
+    InjectScript,
+}
+
+impl PluginPermission {
+    pub fn label(self) -> &'static str {
+        match self {
+            PluginPermission::Navigate => "Load pages",
+            PluginPermission::InjectScript => "Run scripts in pages",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ToolbarButton {
+    pub label: String,
+    // Function the plugin registered with `browser.register`, called in the active tab
+    pub call: String,
+}
+
+// `plugin.json` in a plugin's folder
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginManifest {
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    // Script next to the manifest, run in the top frame of matching pages once they've loaded
+    #[serde(default = "default_script")]
+    pub script: String,
+    // Pages the script runs on, in the patterns user scripts use
+    #[serde(default = "default_matches")]
+    pub matches: Vec<String>,
+    #[serde(default)]
+    pub permissions: Vec<PluginPermission>,
+    #[serde(default)]
+    pub toolbar_buttons: Vec<ToolbarButton>,
+}
+
+fn default_script() -> String {
+    "plugin.js".to_string()
+}
+
+fn default_matches() -> Vec<String> {
+    vec!["*".to_string()]
+}
+
+pub struct Plugin {
+    // Name of the plugin's folder
+    pub id: String,
+    pub manifest: PluginManifest,
+    source: String,
+    // Handed to the plugin's script only, so the host knows which plugin a call is from
+    token: String,
+}
+
+impl Plugin {
+    fn read(dir: &Path) -> anyhow::Result<Self> {
+        let id = dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| anyhow::anyhow!("unnamed plugin folder"))?
+            .to_string();
+        let manifest: PluginManifest = serde_json::from_slice(&fs::read(dir.join(MANIFEST_FILE))?)?;
+        // the script stays inside the plugin's folder
+        if Path::new(&manifest.script)
+            .components()
+            .any(|part| !matches!(part, std::path::Component::Normal(_)))
+        {
+            anyhow::bail!("script {} is outside the plugin", manifest.script);
+        }
+        let source = fs::read_to_string(dir.join(&manifest.script))?;
+        Ok(Self {
+            id,
+            manifest,
+            source,
+            token: new_token()?,
+        })
+    }
+
+    fn matches_url(&self, url: &str) -> bool {
+        self.manifest
+            .matches
+            .iter()
+            .any(|pattern| userscripts::matches_pattern(pattern, url))
+    }
+}
+
+// Whoever holds a plugin's token acts as the plugin, so it comes from the OS's random source
+fn new_token() -> anyhow::Result<String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|err| anyhow::anyhow!("no token: {}", err))?;
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct PluginsData {
+    enabled: Vec<String>,
+}
+
+// Plugins found in the plugins folder when the browser started. New ones start switched off.
+pub struct Plugins {
+    plugins: Vec<Plugin>,
+    data: PluginsData,
+}
+
+impl Global for Plugins {}
+
+impl Plugins {
+    pub fn load() -> Self {
+        let data = storage::load_json::<PluginsData>(PLUGINS_FILE)
+            .unwrap_or_else(|err| {
+                log::error!("Failed to load plugin settings: {}", err);
+                None
+            })
+            .unwrap_or_default();
+        let plugins = match plugins_dir() {
+            Some(dir) => read_plugins(&dir),
+            None => Vec::new(),
+        };
+        Self { plugins, data }
+    }
+
+    pub fn all(&self) -> &[Plugin] {
+        &self.plugins
+    }
+
+    pub fn is_enabled(&self, id: &str) -> bool {
+        self.data.enabled.iter().any(|enabled| enabled == id)
+    }
+
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) {
+        if self.is_enabled(id) == enabled {
+            return;
+        }
+        if enabled {
+            self.data.enabled.push(id.to_string());
+        } else {
+            self.data.enabled.retain(|enabled| enabled != id);
+        }
+        if let Err(err) = storage::save_json(PLUGINS_FILE, &self.data) {
+            log::error!("Failed to save plugin settings: {}", err);
+        }
+    }
+
+    fn enabled(&self) -> impl Iterator<Item = &Plugin> {
+        self.plugins
+            .iter()
+            .filter(|plugin| self.is_enabled(&plugin.id))
+    }
+
+    fn by_token(&self, token: &str) -> Option<&Plugin> {
+        self.enabled().find(|plugin| plugin.token == token)
+    }
+
+    fn get(&self, id: &str) -> Option<&Plugin> {
+        self.enabled().find(|plugin| plugin.id == id)
+    }
+}
+
+// Where plugin folders go, e.g. `~/.config/browser-app/plugins`
+pub fn plugins_dir() -> Option<PathBuf> {
+    storage::config_dir()
+        .map(|dir| dir.join(PLUGINS_DIR))
+        .map_err(|err| log::error!("No plugins folder: {}", err))
+        .ok()
+}
+
+// Every readable plugin in `dir`, sorted by name; a missing folder gets the example plugin
+fn read_plugins(dir: &Path) -> Vec<Plugin> {
+    if !dir.exists() {
+        let (id, manifest, script) = EXAMPLE_PLUGIN;
+        let example = dir.join(id);
+        let written = fs::create_dir_all(&example)
+            .and_then(|_| fs::write(example.join(MANIFEST_FILE), manifest))
+            .and_then(|_| fs::write(example.join(default_script()), script));
+        if let Err(err) = written {
+            log::error!("Failed to create the example plugin: {}", err);
+        }
+    }
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            log::error!("Failed to read {}: {}", dir.display(), err);
+            return Vec::new();
+        }
+    };
+    let mut plugins: Vec<Plugin> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter_map(|path| {
+            Plugin::read(&path)
+                .map_err(|err| log::error!("Skipping plugin {}: {}", path.display(), err))
+                .ok()
+        })
+        .collect();
+    plugins.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+    plugins
+}
+
+// Set up the enabled plugins matching a top frame as its document starts, before the page's
+// own scripts run, and return their ids. Each gets its own `browser` object, see
+// `scripts/plugins.js`; their scripts wait for `start`.
+pub fn inject(frame: &Frame, cx: &App) -> Vec<String> {
+    let Some(plugins) = cx.try_global::<Plugins>() else {
+        return Vec::new();
+    };
+    let url = frame.url();
+    let matching: Vec<&Plugin> = plugins
+        .enabled()
+        .filter(|plugin| plugin.matches_url(&url))
+        .collect();
+    if !frame.is_main() || matching.is_empty() {
+        return Vec::new();
+    }
+
+    let entries: Vec<String> = matching
+        .iter()
+        .map(|plugin| {
+            let id = serde_json::to_string(&plugin.id).unwrap_or_default();
+            let token = serde_json::to_string(&plugin.token).unwrap_or_default();
+            format!(
+                "{{ id: {}, token: {}, body: (browser) => {{\n{}\n}} }}",
+                id, token, plugin.source
+            )
+        })
+        .collect();
+    frame.execute_javascript(&format!(
+        "({})({}, [{}]);",
+        RUNTIME_SCRIPT,
+        API_VERSION,
+        entries.join(",\n")
+    ));
+    matching.iter().map(|plugin| plugin.id.clone()).collect()
+}
+
+// Run the plugins `inject` set up in a top frame that finished loading, then tell them where
+// it is
+pub fn start(frame: &Frame) {
+    frame.execute_javascript("window.__browserPlugins?.start();");
+    dispatch(
+        frame,
+        "navigate",
+        &serde_json::json!({ "url": frame.url() }),
+        None,
+    );
+}
+
+// Hand `event` to the listeners plugins in the frame added with `browser.on`
+pub fn dispatch(frame: &Frame, event: &str, detail: &Value, from: Option<&str>) {
+    let args = serde_json::to_string(&(event, detail, from)).unwrap_or_default();
+    frame.execute_javascript(&format!("window.__browserPlugins?.dispatch(...{});", args));
+}
+
+// What a plugin's script asked the host for, through its `browser` object
+pub enum PluginRequest {
+    Navigate(String),
+    InjectScript(String),
+    Emit { event: String, detail: Value },
+    Notify(String),
+}
+
+impl Main {
+    // Carry out a plugin's request for the tab `tab_id` if the plugin's manifest allows it
+    pub(crate) fn handle_plugin_request(
+        &mut self,
+        tab_id: TabId,
+        token: &str,
+        request: PluginRequest,
+        reply: Sender<Result<(), BridgeError>>,
+        cx: &mut Context<Self>,
+    ) {
+        let result = self.run_plugin_request(tab_id, token, request, cx);
+        if let Err(err) = &result {
+            log::warn!("Plugin request refused: {}", err.message);
+        }
+        _ = reply.send(result);
+    }
+
+    fn run_plugin_request(
+        &mut self,
+        tab_id: TabId,
+        token: &str,
+        request: PluginRequest,
+        cx: &mut Context<Self>,
+    ) -> Result<(), BridgeError> {
+        let plugins = cx.global::<Plugins>();
+        let plugin = plugins
+            .by_token(token)
+            .ok_or_else(|| BridgeError::new("unknown_plugin", "No enabled plugin made the call"))?;
+        let (id, name) = (plugin.id.clone(), plugin.manifest.name.clone());
+        let permissions = plugin.manifest.permissions.clone();
+        let require = |permission: PluginPermission| {
+            if permissions.contains(&permission) {
+                Ok(())
+            } else {
+                Err(BridgeError::new(
+                    "permission_denied",
+                    format!("{} may not {}", name, permission.label().to_lowercase()),
+                ))
+            }
+        };
+        let Some(ix) = self.tabs.iter().position(|tab| tab.id == tab_id) else {
+            return Err(BridgeError::new("unavailable", "The tab was closed"));
+        };
+
+        match request {
+            PluginRequest::Navigate(url) => {
+                require(PluginPermission::Navigate)?;
+                // only web pages, never internal, file or script URLs
+                let url = Url::parse(url.trim())
+                    .ok()
+                    .filter(|url| matches!(url.scheme(), "http" | "https"))
+                    .ok_or_else(|| {
+                        BridgeError::new("invalid_argument", "Only http and https URLs load")
+                    })?;
+                let tab = &mut self.tabs[ix];
+                if let Some(webview) = &tab.webview {
+                    webview.read(cx).browser().load_url(url.as_str());
+                }
+                tab.url = SharedString::from(url.to_string());
+            }
+            PluginRequest::InjectScript(code) => {
+                require(PluginPermission::InjectScript)?;
+                if let Some(frame) = &self.tabs[ix].main_frame {
+                    frame.execute_javascript(&code);
+                }
+            }
+            PluginRequest::Emit { event, detail } => {
+                if event.is_empty() || RESERVED_EVENTS.contains(&event.as_str()) {
+                    return Err(BridgeError::new(
+                        "invalid_argument",
+                        format!("Plugins can't emit \"{}\"", event),
+                    ));
+                }
+                // to every plugin in the pages of this window the sender runs in, its own
+                // copies too; other pages never hear of it
+                let frames = self
+                    .tabs
+                    .iter()
+                    .filter(|tab| tab.plugins.contains(&id))
+                    .filter_map(|tab| tab.main_frame.as_ref());
+                for frame in frames {
+                    dispatch(frame, &event, &detail, Some(&id));
+                }
+            }
+            PluginRequest::Notify(message) => {
+                let message: String = message.chars().take(200).collect();
+                toast_info(cx, format!("{}: {}", name, message));
+            }
+        }
+        cx.notify();
+        Ok(())
+    }
+
+    // Call what a plugin's toolbar button stands for in the active tab
+    fn press_plugin_button(&mut self, id: &str, call: &str, cx: &mut Context<Self>) {
+        let tab = &self.tabs[self.active_tab];
+        let Some(plugin) = cx.global::<Plugins>().get(id) else {
+            return;
+        };
+        let name = plugin.manifest.name.clone();
+        let frame = tab
+            .main_frame
+            .as_ref()
+            .filter(|_| !tab.is_loading && tab.plugins.iter().any(|loaded| loaded == id));
+        let Some(frame) = frame else {
+            toast_info(cx, format!("{} doesn't run on this page", name));
+            return;
+        };
+        let args = serde_json::to_string(&(id, call)).unwrap_or_default();
+        frame.execute_javascript(&format!("window.__browserPlugins?.call(...{});", args));
+    }
+
+    // A text button per toolbar button of the enabled plugins, left of the bookmark star
+    pub(crate) fn render_plugin_buttons(&self, cx: &mut Context<Self>) -> Vec<impl IntoElement> {
+        if self.private {
+            return Vec::new();
+        }
+        let theme = *cx.global::<Theme>();
+        let buttons: Vec<_> = cx
+            .global::<Plugins>()
+            .enabled()
+            .flat_map(|plugin| {
+                plugin
+                    .manifest
+                    .toolbar_buttons
+                    .iter()
+                    .map(|button| (plugin.id.clone(), button.clone()))
+            })
+            .collect();
+
+        buttons
+            .into_iter()
+            .enumerate()
+            .map(|(ix, (id, button))| {
+                div()
+                    .id(("plugin-button", ix))
+                    .flex_none()
+                    .max_w(px(96.))
+                    .truncate()
+                    .px_2()
+                    .py(px(2.))
+                    .rounded_md()
+                    .border_1()
+                    .border_color(theme.border)
+                    .text_xs()
+                    .text_color(theme.text)
+                    .cursor_pointer()
+                    .hover(|this| this.bg(theme.hover_subtle))
+                    .child(button.label)
+                    .on_click(cx.listener(move |this, _, _, cx| {
+                        this.press_plugin_button(&id, &button.call, cx)
+                    }))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,1375 @@
+use gpui::{
+    div, prelude::*, px, rgb, rgba, size, AnyView, AnyWindowHandle, App, AppContext, Bounds,
+    Context, Entity, FocusHandle, Global, IntoElement, KeyDownEvent, Render, SharedString,
+    Subscription, Window, WindowBounds, WindowOptions,
+};
+use gpui_component::{
+    input::{InputEvent, InputState, TextInput},
+    Root,
+};
+use std::path::PathBuf;
+
+use crate::address;
+use crate::autofill::{Autofill, FieldKind};
+use crate::downloads::{DownloadAction, DownloadRule};
+use crate::keymap::{self, Keymap};
+use crate::plugins::{self, Plugins};
+use crate::proxy::{ProxyMode, ProxyScheme};
+use crate::screenshot::{self, ScreenshotFormat};
+use crate::settings::{self, BrowserSettings, StartupPage, DEFAULT_HOME_URL};
+use crate::spellcheck::{self, SpellChecker};
+use crate::theme::{self, ThemeMode};
+use crate::toast::toast_info;
+use crate::tts::TtsEngine;
+use crate::user_agent;
+use crate::userscripts::{RunAt, UserScripts};
+use crate::widgets::slider;
+
+// The open preferences window, so a second request focuses it instead
+struct PreferencesWindow(AnyWindowHandle);
+
+impl Global for PreferencesWindow {}
+
+pub fn open(cx: &mut App) {
+    if let Some(handle) = cx.try_global::<PreferencesWindow>().map(|window| window.0) {
+        let activated = handle.update(cx, |_, window, _| window.activate_window());
+        if activated.is_ok() {
+            return;
+        }
+    }
+
+    let bounds = Bounds::centered(None, size(px(480.), px(460.)), cx);
+    let window = cx.open_window(
+        WindowOptions {
+            window_bounds: Some(WindowBounds::Windowed(bounds)),
+            titlebar: Some(gpui::TitlebarOptions {
+                title: Some(SharedString::from("Preferences")),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        |window, cx| {
+            let preferences = cx.new(|cx| Preferences::new(window, cx));
+            cx.new(|cx| Root::new(preferences.into(), window, cx))
+        },
+    );
+    match window {
+        Ok(window) => cx.set_global(PreferencesWindow(window.into())),
+        Err(err) => log::error!("Failed to open preferences: {}", err),
+    }
+}
+
+// The preferences as the content of an `about:settings` tab
+pub fn page(window: &mut Window, cx: &mut App) -> AnyView {
+    cx.new(|cx| Preferences::new(window, cx)).into()
+}
+
+// Change a setting and persist it right away
+fn update_settings(cx: &mut App, update: impl FnOnce(&mut BrowserSettings)) {
+    cx.update_global::<BrowserSettings, _>(|settings, _| {
+        update(settings);
+        settings.save();
+    });
+}
+
+// Edits the `BrowserSettings` global, which windows read whenever they need a value
+struct Preferences {
+    home_url: Entity<InputState>,
+    download_dir: Entity<InputState>,
+    user_agent: Entity<InputState>,
+    // Manual proxy, applied when a field is left or Enter is pressed rather than per keystroke
+    proxy_host: Entity<InputState>,
+    proxy_port: Entity<InputState>,
+    proxy_username: Entity<InputState>,
+    // Goes straight to the keychain and is cleared, saved passwords are never shown
+    proxy_password: Entity<InputState>,
+    proxy_bypass: Entity<InputState>,
+    // Colors of the custom theme
+    accent_color: Entity<InputState>,
+    background_color: Entity<InputState>,
+    // New custom search engine
+    engine_name: Entity<InputState>,
+    engine_template: Entity<InputState>,
+    // File type of a new download rule
+    rule_pattern: Entity<InputState>,
+    // New user script
+    script_name: Entity<InputState>,
+    script_matches: Entity<InputState>,
+    script_source: Entity<InputState>,
+    script_run_at: RunAt,
+    script_all_frames: bool,
+    // One input per autofill profile field
+    autofill_fields: Vec<(FieldKind, Entity<InputState>)>,
+    // Languages with a dictionary installed, looked up when the preferences open
+    spellcheck_languages: Vec<String>,
+    // Action whose next key press becomes one of its shortcuts
+    recording: Option<&'static str>,
+    focus_handle: FocusHandle,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl Preferences {
+    fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let home_url = cx.new(|cx| InputState::new(window, cx).placeholder(DEFAULT_HOME_URL));
+        let download_dir = cx.new(|cx| InputState::new(window, cx));
+        let user_agent = cx.new(|cx| InputState::new(window, cx).placeholder("Built-in"));
+        let proxy_host = cx.new(|cx| InputState::new(window, cx).placeholder("proxy.example.com"));
+        let proxy_port = cx.new(|cx| InputState::new(window, cx).placeholder("Port"));
+        let proxy_username = cx.new(|cx| InputState::new(window, cx).placeholder("User name"));
+        let proxy_password = cx.new(|cx| {
+            InputState::new(window, cx)
+                .masked(true)
+                .placeholder("Password")
+        });
+        let proxy_bypass = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("localhost, *.internal.example.com, 10.0.0.0/8")
+        });
+        let accent_color = cx.new(|cx| InputState::new(window, cx).placeholder("#0a84ff"));
+        let background_color = cx.new(|cx| InputState::new(window, cx).placeholder("#0404055e"));
+        let engine_name = cx.new(|cx| InputState::new(window, cx).placeholder("Name"));
+        let engine_template = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("https://example.com/search?q={query}")
+        });
+        let rule_pattern =
+            cx.new(|cx| InputState::new(window, cx).placeholder("pdf, image/*, application/zip"));
+        let script_name = cx.new(|cx| InputState::new(window, cx).placeholder("Name"));
+        let script_matches = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("*.example.com, https://example.org/docs/*")
+        });
+        let script_source = cx.new(|cx| {
+            InputState::new(window, cx)
+                .multi_line()
+                .placeholder("// JavaScript to run on matching pages")
+        });
+
+        let autofill_fields: Vec<_> = FieldKind::STORED
+            .iter()
+            .map(|&kind| {
+                let value = cx.global::<Autofill>().get(kind).to_string();
+                let state = cx.new(|cx| {
+                    let mut state = InputState::new(window, cx).placeholder(kind.label());
+                    state.set_value(value, window, cx);
+                    state
+                });
+                (kind, state)
+            })
+            .collect();
+
+        let mut subscriptions = vec![
+            cx.observe_global::<BrowserSettings>(|_, cx| cx.notify()),
+            cx.observe_global::<Autofill>(|_, cx| cx.notify()),
+            cx.observe_global::<UserScripts>(|_, cx| cx.notify()),
+            cx.observe_global::<Plugins>(|_, cx| cx.notify()),
+            cx.observe_global::<SpellChecker>(|_, cx| cx.notify()),
+            cx.observe_global::<Keymap>(|_, cx| cx.notify()),
+            cx.subscribe_in(&home_url, window, |_, state, event: &InputEvent, _, cx| {
+                if let InputEvent::Change(_) = event {
+                    let value = state.read(cx).value();
+                    update_settings(cx, |settings| {
+                        settings.home_url = if value.trim().is_empty() {
+                            DEFAULT_HOME_URL.to_string()
+                        } else {
+                            address::normalize_input(&value, &settings.search_engine())
+                        };
+                    });
+                }
+            }),
+            cx.subscribe_in(
+                &download_dir,
+                window,
+                |_, state, event: &InputEvent, _, cx| {
+                    if let InputEvent::Change(_) = event {
+                        let value = state.read(cx).value().trim().to_string();
+                        update_settings(cx, |settings| {
+                            settings.download_dir = Some(PathBuf::from(value))
+                                .filter(|dir| !dir.as_os_str().is_empty());
+                        });
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &user_agent,
+                window,
+                |_, state, event: &InputEvent, _, cx| {
+                    if let InputEvent::Change(_) = event {
+                        let value = state.read(cx).value().trim().to_string();
+                        update_settings(cx, |settings| {
+                            settings.user_agent = Some(value).filter(|value| !value.is_empty());
+                        });
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &accent_color,
+                window,
+                |_, state, event: &InputEvent, _, cx| {
+                    if let InputEvent::Change(_) = event {
+                        // half typed colors keep the last one that parsed
+                        let value = state.read(cx).value().trim().to_string();
+                        if theme::parse_color(&value).is_some() {
+                            update_settings(cx, |settings| settings.custom_theme.accent = value);
+                        }
+                    }
+                },
+            ),
+            cx.subscribe_in(
+                &background_color,
+                window,
+                |_, state, event: &InputEvent, _, cx| {
+                    if let InputEvent::Change(_) = event {
+                        let value = state.read(cx).value().trim().to_string();
+                        if theme::parse_color(&value).is_some() {
+                            update_settings(cx, |settings| {
+                                settings.custom_theme.background = value
+                            });
+                        }
+                    }
+                },
+            ),
+        ];
+        for state in [&proxy_host, &proxy_port, &proxy_username, &proxy_bypass] {
+            subscriptions.push(cx.subscribe_in(
+                state,
+                window,
+                |this, _, event: &InputEvent, _, cx| {
+                    if let InputEvent::PressEnter { .. } | InputEvent::Blur = event {
+                        this.commit_proxy(cx);
+                    }
+                },
+            ));
+        }
+        subscriptions.push(cx.subscribe_in(
+            &proxy_password,
+            window,
+            |this, _, event: &InputEvent, window, cx| {
+                if let InputEvent::PressEnter { .. } | InputEvent::Blur = event {
+                    this.save_proxy_password(window, cx);
+                }
+            },
+        ));
+        for (kind, state) in &autofill_fields {
+            let kind = *kind;
+            subscriptions.push(cx.subscribe_in(
+                state,
+                window,
+                move |_, state, event: &InputEvent, _, cx| {
+                    if let InputEvent::Change(_) = event {
+                        let value = state.read(cx).value();
+                        Autofill::set(cx, kind, &value);
+                    }
+                },
+            ));
+        }
+
+        let mut this = Self {
+            home_url,
+            download_dir,
+            user_agent,
+            proxy_host,
+            proxy_port,
+            proxy_username,
+            proxy_password,
+            proxy_bypass,
+            accent_color,
+            background_color,
+            engine_name,
+            engine_template,
+            rule_pattern,
+            script_name,
+            script_matches,
+            script_source,
+            script_run_at: RunAt::default(),
+            script_all_frames: false,
+            autofill_fields,
+            spellcheck_languages: spellcheck::available_languages(),
+            recording: None,
+            focus_handle: cx.focus_handle(),
+            _subscriptions: subscriptions,
+        };
+        this.sync_inputs(window, cx);
+        this
+    }
+
+    // Show the current settings in the text inputs
+    fn sync_inputs(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let settings = cx.global::<BrowserSettings>();
+        let home_url = settings.home_url.clone();
+        let download_dir = settings
+            .download_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        let user_agent = settings.user_agent.clone().unwrap_or_default();
+        let proxy = settings.proxy.clone();
+        let accent_color = settings.custom_theme.accent.clone();
+        let background_color = settings.custom_theme.background.clone();
+
+        self.home_url
+            .update(cx, |state, cx| state.set_value(home_url, window, cx));
+        self.download_dir
+            .update(cx, |state, cx| state.set_value(download_dir, window, cx));
+        self.user_agent
+            .update(cx, |state, cx| state.set_value(user_agent, window, cx));
+        for (state, value) in [
+            (&self.proxy_host, proxy.host),
+            (
+                &self.proxy_port,
+                proxy.port.map(|port| port.to_string()).unwrap_or_default(),
+            ),
+            (&self.proxy_username, proxy.username),
+            (&self.proxy_bypass, proxy.bypass_list.join(", ")),
+        ] {
+            state.update(cx, |state, cx| state.set_value(value, window, cx));
+        }
+        self.accent_color
+            .update(cx, |state, cx| state.set_value(accent_color, window, cx));
+        self.background_color.update(cx, |state, cx| {
+            state.set_value(background_color, window, cx)
+        });
+    }
+
+    // Store the manual proxy fields, a port that isn't a number keeps the last one
+    fn commit_proxy(&mut self, cx: &mut Context<Self>) {
+        let mut proxy = cx.global::<BrowserSettings>().proxy.clone();
+        proxy.host = self.proxy_host.read(cx).value().trim().to_string();
+        let port = self.proxy_port.read(cx).value().trim().to_string();
+        if port.is_empty() {
+            proxy.port = None;
+        } else if let Ok(port) = port.parse::<u16>() {
+            proxy.port = Some(port).filter(|port| *port != 0);
+        }
+        proxy.username = self.proxy_username.read(cx).value().trim().to_string();
+        proxy.bypass_list = self
+            .proxy_bypass
+            .read(cx)
+            .value()
+            .split([',', ';'])
+            .map(str::trim)
+            .filter(|host| !host.is_empty())
+            .map(str::to_string)
+            .collect();
+
+        if proxy != cx.global::<BrowserSettings>().proxy {
+            update_settings(cx, |settings| settings.proxy = proxy);
+        }
+    }
+
+    fn save_proxy_password(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let password = self.proxy_password.read(cx).value().to_string();
+        if password.is_empty() {
+            return;
+        }
+        self.commit_proxy(cx);
+        let proxy = cx.global::<BrowserSettings>().proxy.clone();
+        cx.background_spawn(async move { proxy.save_password(password) })
+            .detach();
+        self.proxy_password
+            .update(cx, |state, cx| state.set_value("", window, cx));
+        toast_info(cx, "Proxy password saved");
+    }
+
+    fn forget_proxy_password(&mut self, cx: &mut Context<Self>) {
+        let proxy = cx.global::<BrowserSettings>().proxy.clone();
+        cx.background_spawn(async move { proxy.forget_password() })
+            .detach();
+        toast_info(cx, "Proxy password forgotten");
+    }
+
+    fn add_search_engine(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let name = self.engine_name.read(cx).value();
+        let template = self.engine_template.read(cx).value();
+        let mut added = false;
+        update_settings(cx, |settings| {
+            added = settings.add_search_engine(&name, &template);
+        });
+
+        if added {
+            for state in [&self.engine_name, &self.engine_template] {
+                state.update(cx, |state, cx| state.set_value("", window, cx));
+            }
+        }
+    }
+
+    // Finished downloads of the typed type open, which is what a new rule is usually for
+    fn add_download_rule(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let pattern = self.rule_pattern.read(cx).value();
+        let Some(rule) = DownloadRule::new(&pattern, DownloadAction::Open) else {
+            return;
+        };
+        update_settings(cx, |settings| {
+            settings
+                .download_rules
+                .retain(|existing| existing.pattern != rule.pattern);
+            settings.download_rules.push(rule);
+        });
+        self.rule_pattern
+            .update(cx, |state, cx| state.set_value("", window, cx));
+    }
+
+    fn add_user_script(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let name = self.script_name.read(cx).value().to_string();
+        let matches = self
+            .script_matches
+            .read(cx)
+            .value()
+            .split(',')
+            .map(str::to_string)
+            .collect();
+        let source = self.script_source.read(cx).value().to_string();
+        let (run_at, all_frames) = (self.script_run_at, self.script_all_frames);
+        let added = cx.update_global::<UserScripts, _>(|scripts, _| {
+            scripts.add(&name, matches, run_at, all_frames, &source)
+        });
+
+        if added.is_some() {
+            for state in [&self.script_name, &self.script_matches, &self.script_source] {
+                state.update(cx, |state, cx| state.set_value("", window, cx));
+            }
+        }
+    }
+
+    fn clear_autofill(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        Autofill::clear(cx);
+        for (_, state) in &self.autofill_fields {
+            state.update(cx, |state, cx| state.set_value("", window, cx));
+        }
+    }
+
+    // Add the pressed chord to the action being recorded, Escape cancels
+    fn on_key_down(&mut self, event: &KeyDownEvent, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(name) = self.recording.take() else {
+            return;
+        };
+        cx.stop_propagation();
+        let chord = event.keystroke.unparse();
+        let mut chords = cx.global::<Keymap>().chords(name);
+        if event.keystroke.key != "escape" && !chords.contains(&chord) {
+            chords.push(chord);
+            Keymap::set(cx, name, Some(chords));
+        }
+        cx.notify();
+    }
+
+    fn reset_to_defaults(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        update_settings(cx, |settings| *settings = BrowserSettings::default());
+        self.sync_inputs(window, cx);
+        cx.notify();
+    }
+}
+
+fn section(label: &'static str) -> gpui::Div {
+    div()
+        .flex()
+        .flex_col()
+        .gap_1()
+        .child(div().text_xs().text_color(rgb(0xa1a1a1)).child(label))
+}
+
+fn text_field(state: &Entity<InputState>) -> impl IntoElement {
+    div()
+        .flex()
+        .items_center()
+        .h_8()
+        .px_3()
+        .border_1()
+        .border_color(rgba(0xd3d9d92b))
+        .rounded_md()
+        .child(
+            TextInput::new(state)
+                .text_color(rgb(0xd1d1d1))
+                .text_xs()
+                .border_0(),
+        )
+}
+
+// A selectable pill, highlighted when `selected`
+fn choice(
+    id: impl Into<gpui::ElementId>,
+    label: impl Into<SharedString>,
+    selected: bool,
+) -> gpui::Stateful<gpui::Div> {
+    div()
+        .id(id)
+        .px_3()
+        .py_1()
+        .rounded_md()
+        .border_1()
+        .border_color(rgba(0xd3d9d92b))
+        .text_xs()
+        .text_color(rgb(0xf2f2f2))
+        .cursor_pointer()
+        .when(selected, |this| this.bg(rgba(0xffffff1a)))
+        .hover(|this| this.bg(rgba(0xffffff10)))
+        .child(label.into())
+}
+
+impl Render for Preferences {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let settings = cx.global::<BrowserSettings>();
+        let search_engines = settings.search_engines.clone();
+        let selected_engine = settings.search_engine().name;
+        let selected_is_custom = !settings::builtin_search_engines()
+            .iter()
+            .any(|engine| engine.name == selected_engine);
+        let startup = settings.startup;
+        let tts_engine = settings.tts_engine;
+        let edge_buttons = settings.edge_buttons;
+        let screenshot_format = settings.screenshot_format;
+        let screenshot_quality = settings.screenshot_quality;
+        let proxy = settings.proxy.clone();
+        let download_rules = settings.download_rules.clone();
+        let autofill = cx.global::<Autofill>();
+        let (autofill_learn, autofill_empty) = (autofill.learn(), autofill.is_empty());
+        let theme_mode = settings.theme;
+        let custom_dark = settings.custom_theme.dark;
+        let load_tabs_on_startup = settings.load_tabs_on_startup;
+        let discard_after_minutes = settings.discard_after_minutes;
+        let zoomed_sites = settings.zoom_levels.len();
+        let idle_fps = settings.idle_message_loop_fps;
+        let selected_languages = settings.spellcheck_languages.clone();
+        let mut languages = self.spellcheck_languages.clone();
+        // keep picked languages whose dictionary went missing removable
+        for language in &selected_languages {
+            if !languages.contains(language) {
+                languages.push(language.clone());
+            }
+        }
+        let personal_words = cx.global::<SpellChecker>().personal_words();
+        let user_scripts = cx.global::<UserScripts>().all().to_vec();
+        let installed = cx.global::<Plugins>();
+        let plugins: Vec<_> = installed
+            .all()
+            .iter()
+            .map(|plugin| {
+                let manifest = &plugin.manifest;
+                let mut detail = manifest.description.clone();
+                if !manifest.permissions.is_empty() {
+                    let permissions: Vec<_> = manifest
+                        .permissions
+                        .iter()
+                        .map(|permission| permission.label())
+                        .collect();
+                    detail = [detail, permissions.join(", ")]
+                        .into_iter()
+                        .filter(|part| !part.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(" · ");
+                }
+                (
+                    plugin.id.clone(),
+                    format!("{} {}", manifest.name, manifest.version),
+                    detail,
+                    installed.is_enabled(&plugin.id),
+                )
+            })
+            .collect();
+        let bindings = cx.global::<Keymap>();
+        let shortcuts: Vec<_> = bindings
+            .entries()
+            .iter()
+            .map(|entry| {
+                (
+                    entry.name,
+                    entry.label,
+                    bindings.chords(entry.name),
+                    bindings.is_custom(entry.name),
+                )
+            })
+            .collect();
+        let conflicts = bindings.conflicts();
+        let recording = self.recording;
+        let effective_user_agent = user_agent::effective_user_agent(cx)
+            .unwrap_or_else(|| "Open a page to see the built-in user agent".to_string());
+
+        div()
+            .id("preferences")
+            .track_focus(&self.focus_handle)
+            .on_key_down(cx.listener(Self::on_key_down))
+            .size_full()
+            .flex()
+            .flex_col()
+            .gap_4()
+            .p_5()
+            .overflow_y_scroll()
+            .bg(rgb(0x0a0a0a))
+            .child(section("Home page").child(text_field(&self.home_url)))
+            .child(
+                section("Search engine")
+                    .child(
+                        div()
+                            .flex()
+                            .flex_wrap()
+                            .gap_2()
+                            .children(search_engines.into_iter().enumerate().map(|(ix, engine)| {
+                                let selected = engine.name == selected_engine;
+                                choice(("search-engine", ix), engine.name.clone(), selected)
+                                    .on_click(move |_, _, cx| {
+                                        update_settings(cx, |settings| {
+                                            settings.search_engine = engine.name.clone()
+                                        })
+                                    })
+                            }))
+                            .when(selected_is_custom, |this| {
+                                this.child(
+                                    choice("remove-search-engine", "Remove", false).on_click(
+                                        move |_, _, cx| {
+                                            update_settings(cx, |settings| {
+                                                settings.remove_search_engine(&selected_engine)
+                                            })
+                                        },
+                                    ),
+                                )
+                            }),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .gap_2()
+                            .child(div().w(px(100.)).child(text_field(&self.engine_name)))
+                            .child(div().flex_1().child(text_field(&self.engine_template)))
+                            .child(choice("add-search-engine", "Add", false).on_click(
+                                cx.listener(|this, _, window, cx| {
+                                    this.add_search_engine(window, cx)
+                                }),
+                            )),
+                    ),
+            )
+            .child(section("Download folder").child(text_field(&self.download_dir)))
+            .child(
+                section("When a download finishes")
+                    .children(download_rules.into_iter().enumerate().map(|(ix, rule)| {
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .text_xs()
+                                    .text_color(rgb(0xd1d1d1))
+                                    .child(rule.pattern.clone()),
+                            )
+                            .children(
+                                [
+                                    ("Do nothing", DownloadAction::Nothing),
+                                    ("Open", DownloadAction::Open),
+                                    ("Show in folder", DownloadAction::ShowInFolder),
+                                ]
+                                .into_iter()
+                                .enumerate()
+                                .map(
+                                    move |(action_ix, (label, action))| {
+                                        choice(
+                                            ("download-rule-action", ix * 3 + action_ix),
+                                            label,
+                                            rule.action == action,
+                                        )
+                                        .on_click(
+                                            move |_, _, cx| {
+                                                update_settings(cx, |settings| {
+                                                    if let Some(rule) =
+                                                        settings.download_rules.get_mut(ix)
+                                                    {
+                                                        rule.action = action;
+                                                    }
+                                                })
+                                            },
+                                        )
+                                    },
+                                ),
+                            )
+                            .child(
+                                choice(("remove-download-rule", ix), "Remove", false).on_click(
+                                    move |_, _, cx| {
+                                        update_settings(cx, |settings| {
+                                            if ix < settings.download_rules.len() {
+                                                settings.download_rules.remove(ix);
+                                            }
+                                        })
+                                    },
+                                ),
+                            )
+                    }))
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(div().flex_1().child(text_field(&self.rule_pattern)))
+                            .child(choice("add-download-rule", "Add", false).on_click(
+                                cx.listener(|this, _, window, cx| {
+                                    this.add_download_rule(window, cx)
+                                }),
+                            )),
+                    )
+                    .child(div().text_xs().text_color(rgb(0x7a7a7a)).child(
+                        "Other files are left alone. Programs and scripts are never opened \
+                         automatically.",
+                    )),
+            )
+            .child(
+                section("User agent")
+                    .child(text_field(&self.user_agent))
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0x7a7a7a))
+                            .child(format!("Current: {}", effective_user_agent)),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0x7a7a7a))
+                            .child("Changes apply after restarting the browser."),
+                    ),
+            )
+            .child(
+                section("Proxy")
+                    .child(
+                        div()
+                            .flex()
+                            .gap_2()
+                            .children(ProxyMode::ALL.into_iter().map(|mode| {
+                                choice(
+                                    ("proxy-mode", mode as usize),
+                                    mode.label(),
+                                    proxy.mode == mode,
+                                )
+                                .on_click(move |_, _, cx| {
+                                    update_settings(cx, |settings| settings.proxy.mode = mode)
+                                })
+                            })),
+                    )
+                    .when(proxy.mode == ProxyMode::Manual, |this| {
+                        this.child(
+                            div()
+                                .flex()
+                                .gap_2()
+                                .children(ProxyScheme::ALL.into_iter().map(|scheme| {
+                                    choice(
+                                        ("proxy-scheme", scheme as usize),
+                                        scheme.label(),
+                                        proxy.scheme == scheme,
+                                    )
+                                    .on_click(
+                                        move |_, _, cx| {
+                                            update_settings(cx, |settings| {
+                                                settings.proxy.scheme = scheme
+                                            })
+                                        },
+                                    )
+                                })),
+                        )
+                        .child(
+                            div()
+                                .flex()
+                                .gap_2()
+                                .child(div().flex_1().child(text_field(&self.proxy_host)))
+                                .child(div().w(px(80.)).child(text_field(&self.proxy_port))),
+                        )
+                        .child(
+                            div()
+                                .flex()
+                                .items_center()
+                                .gap_2()
+                                .child(div().flex_1().child(text_field(&self.proxy_username)))
+                                .child(div().flex_1().child(text_field(&self.proxy_password)))
+                                .child(
+                                    choice("forget-proxy-password", "Forget password", false)
+                                        .on_click(cx.listener(|this, _, _, cx| {
+                                            this.forget_proxy_password(cx)
+                                        })),
+                                ),
+                        )
+                        .child(text_field(&self.proxy_bypass))
+                        .child(
+                            div().text_xs().text_color(rgb(0x7a7a7a)).child(format!(
+                                "An empty port uses {}. Hosts in the last field are reached \
+                             directly. SOCKS5 proxies can't ask for a password.",
+                                proxy.scheme.default_port()
+                            )),
+                        )
+                    })
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0x7a7a7a))
+                            .child("Changes apply to new connections right away, see about:proxy."),
+                    ),
+            )
+            .child(
+                section("User scripts")
+                    .children(user_scripts.into_iter().map(|script| {
+                        let id = script.id;
+                        let enabled = script.enabled;
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .min_w_0()
+                                    .truncate()
+                                    .text_xs()
+                                    .text_color(rgb(0xf2f2f2))
+                                    .child(script.name),
+                            )
+                            .child(
+                                div()
+                                    .max_w(px(160.))
+                                    .truncate()
+                                    .text_xs()
+                                    .text_color(rgb(0x7a7a7a))
+                                    .child(script.matches.join(", ")),
+                            )
+                            .child(
+                                choice(("toggle-user-script", id as usize), "Enabled", enabled)
+                                    .on_click(move |_, _, cx| {
+                                        cx.update_global::<UserScripts, _>(|scripts, _| {
+                                            scripts.set_enabled(id, !enabled)
+                                        })
+                                    }),
+                            )
+                            .child(
+                                choice(("remove-user-script", id as usize), "Remove", false)
+                                    .on_click(move |_, _, cx| {
+                                        cx.update_global::<UserScripts, _>(|scripts, _| {
+                                            scripts.remove(id)
+                                        })
+                                    }),
+                            )
+                    }))
+                    .child(
+                        div()
+                            .flex()
+                            .gap_2()
+                            .child(div().w(px(100.)).child(text_field(&self.script_name)))
+                            .child(div().flex_1().child(text_field(&self.script_matches))),
+                    )
+                    .child(
+                        div()
+                            .h(px(96.))
+                            .px_3()
+                            .py_2()
+                            .border_1()
+                            .border_color(rgba(0xd3d9d92b))
+                            .rounded_md()
+                            .child(
+                                TextInput::new(&self.script_source)
+                                    .h_full()
+                                    .text_color(rgb(0xd1d1d1))
+                                    .text_xs()
+                                    .border_0(),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .gap_2()
+                            .child(
+                                choice(
+                                    "script-document-end",
+                                    "After load",
+                                    self.script_run_at == RunAt::DocumentEnd,
+                                )
+                                .on_click(cx.listener(
+                                    |this, _, _, cx| {
+                                        this.script_run_at = RunAt::DocumentEnd;
+                                        cx.notify();
+                                    },
+                                )),
+                            )
+                            .child(
+                                choice(
+                                    "script-document-start",
+                                    "Before page scripts",
+                                    self.script_run_at == RunAt::DocumentStart,
+                                )
+                                .on_click(cx.listener(
+                                    |this, _, _, cx| {
+                                        this.script_run_at = RunAt::DocumentStart;
+                                        cx.notify();
+                                    },
+                                )),
+                            )
+                            .child(
+                                choice(
+                                    "script-all-frames",
+                                    "Include iframes",
+                                    self.script_all_frames,
+                                )
+                                .on_click(cx.listener(
+                                    |this, _, _, cx| {
+                                        this.script_all_frames = !this.script_all_frames;
+                                        cx.notify();
+                                    },
+                                )),
+                            )
+                            .child(div().flex_1())
+                            .child(choice("add-user-script", "Add", false).on_click(
+                                cx.listener(|this, _, window, cx| this.add_user_script(window, cx)),
+                            )),
+                    ),
+            )
+            .child(
+                section("Plugins")
+                    .when(plugins.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .text_xs()
+                                .text_color(rgb(0x7a7a7a))
+                                .child("No plugins installed"),
+                        )
+                    })
+                    .children(plugins.into_iter().enumerate().map(
+                        |(ix, (id, name, detail, enabled))| {
+                            div()
+                                .flex()
+                                .items_center()
+                                .gap_2()
+                                .child(
+                                    div()
+                                        .flex()
+                                        .flex_col()
+                                        .flex_1()
+                                        .min_w_0()
+                                        .child(
+                                            div()
+                                                .truncate()
+                                                .text_xs()
+                                                .text_color(rgb(0xf2f2f2))
+                                                .child(name),
+                                        )
+                                        .child(
+                                            div()
+                                                .truncate()
+                                                .text_xs()
+                                                .text_color(rgb(0x7a7a7a))
+                                                .child(detail),
+                                        ),
+                                )
+                                .child(choice(("toggle-plugin", ix), "Enabled", enabled).on_click(
+                                    move |_, _, cx| {
+                                        cx.update_global::<Plugins, _>(|plugins, _| {
+                                            plugins.set_enabled(&id, !enabled)
+                                        })
+                                    },
+                                ))
+                        },
+                    ))
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(div().flex_1().text_xs().text_color(rgb(0x7a7a7a)).child(
+                                "Plugins are read from their folder when the browser starts and \
+                                 run on pages loaded after they're enabled, never in private \
+                                 windows.",
+                            ))
+                            .child(
+                                choice("open-plugins-folder", "Open folder", false).on_click(
+                                    |_, _, cx| {
+                                        if let Some(dir) = plugins::plugins_dir() {
+                                            cx.open_with_system(&dir);
+                                        }
+                                    },
+                                ),
+                            ),
+                    ),
+            )
+            .child(
+                section("Autofill")
+                    .children(self.autofill_fields.chunks(2).map(|row| {
+                        div().flex().gap_2().children(
+                            row.iter()
+                                .map(|(_, state)| div().flex_1().child(text_field(state))),
+                        )
+                    }))
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(
+                                choice(
+                                    "autofill-learn",
+                                    "Offer to save what I enter in forms",
+                                    autofill_learn,
+                                )
+                                .on_click(move |_, _, cx| Autofill::set_learn(cx, !autofill_learn)),
+                            )
+                            .child(div().flex_1())
+                            .when(!autofill_empty, |this| {
+                                this.child(choice("clear-autofill", "Clear", false).on_click(
+                                    cx.listener(|this, _, window, cx| {
+                                        this.clear_autofill(window, cx)
+                                    }),
+                                ))
+                            }),
+                    ),
+            )
+            .child(
+                section("Zoom").child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(div().flex_1().text_xs().text_color(rgb(0x7a7a7a)).child(
+                            match zoomed_sites {
+                                0 => "All sites use the default zoom.".to_string(),
+                                1 => "1 site has its own zoom level.".to_string(),
+                                count => format!("{} sites have their own zoom level.", count),
+                            },
+                        ))
+                        .when(zoomed_sites > 0, |this| {
+                            this.child(choice("reset-zoom-levels", "Reset all", false).on_click(
+                                |_, _, cx| {
+                                    update_settings(cx, |settings| settings.zoom_levels.clear())
+                                },
+                            ))
+                        }),
+                ),
+            )
+            .child(
+                section("Spell check languages")
+                    .child(div().flex().flex_wrap().gap_2().children(
+                        languages.into_iter().enumerate().map(|(ix, language)| {
+                            let selected = selected_languages.contains(&language);
+                            choice(("spellcheck-language", ix), language.clone(), selected)
+                                .on_click(move |_, _, cx| {
+                                    update_settings(cx, |settings| {
+                                        let languages = &mut settings.spellcheck_languages;
+                                        if languages.contains(&language) {
+                                            languages.retain(|selected| *selected != language);
+                                        } else {
+                                            languages.push(language.clone());
+                                        }
+                                    })
+                                })
+                        }),
+                    ))
+                    .when(self.spellcheck_languages.is_empty(), |this| {
+                        this.child(div().text_xs().text_color(rgb(0x7a7a7a)).child(
+                            "No dictionaries found. Add Hunspell .dic and .aff files to the \
+                             dictionaries folder in the browser's settings folder.",
+                        ))
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(div().flex_1().text_xs().text_color(rgb(0x7a7a7a)).child(
+                                match personal_words {
+                                    0 => "No words added to your dictionary.".to_string(),
+                                    1 => "1 word added to your dictionary.".to_string(),
+                                    count => {
+                                        format!("{} words added to your dictionary.", count)
+                                    }
+                                },
+                            ))
+                            .when(personal_words > 0, |this| {
+                                this.child(choice("clear-dictionary", "Clear", false).on_click(
+                                    |_, _, cx| {
+                                        cx.update_global::<SpellChecker, _>(|checker, _| {
+                                            checker.clear_personal_words()
+                                        })
+                                    },
+                                ))
+                            }),
+                    ),
+            )
+            .child(
+                section("Theme").child(
+                    div().flex().gap_2().children(
+                        [
+                            ("theme-system", "Match system", ThemeMode::System),
+                            ("theme-light", "Light", ThemeMode::Light),
+                            ("theme-dark", "Dark", ThemeMode::Dark),
+                            ("theme-custom", "Custom", ThemeMode::Custom),
+                        ]
+                        .into_iter()
+                        .map(|(id, label, mode)| {
+                            choice(id, label, theme_mode == mode).on_click(move |_, _, cx| {
+                                update_settings(cx, |settings| settings.theme = mode)
+                            })
+                        }),
+                    ),
+                ),
+            )
+            .when(theme_mode == ThemeMode::Custom, |this| {
+                this.child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .children(
+                            [
+                                ("custom-dark", "Dark", true),
+                                ("custom-light", "Light", false),
+                            ]
+                            .into_iter()
+                            .map(|(id, label, dark)| {
+                                choice(id, label, custom_dark == dark).on_click(move |_, _, cx| {
+                                    update_settings(cx, |settings| {
+                                        settings.custom_theme.dark = dark
+                                    })
+                                })
+                            }),
+                        )
+                        .child(div().w(px(100.)).child(text_field(&self.accent_color)))
+                        .child(div().flex_1().child(text_field(&self.background_color))),
+                )
+            })
+            .child(
+                section("Read aloud with").child(
+                    div().flex().gap_2().children(
+                        [
+                            ("tts-page", "The page's voice", TtsEngine::Page),
+                            ("tts-system", "The system voice", TtsEngine::System),
+                        ]
+                        .into_iter()
+                        .map(|(id, label, engine)| {
+                            choice(id, label, tts_engine == engine).on_click(move |_, _, cx| {
+                                update_settings(cx, |settings| settings.tts_engine = engine)
+                            })
+                        }),
+                    ),
+                ),
+            )
+            .child(
+                section("Navigation").child(
+                    div().flex().child(
+                        choice(
+                            "edge-buttons",
+                            "Show back and forward buttons at the page edges",
+                            edge_buttons,
+                        )
+                        .on_click(|_, _, cx| {
+                            update_settings(cx, |settings| {
+                                settings.edge_buttons = !settings.edge_buttons
+                            })
+                        }),
+                    ),
+                ),
+            )
+            .child(
+                section("Screenshots")
+                    .child(
+                        div()
+                            .flex()
+                            .gap_2()
+                            .children(ScreenshotFormat::ALL.into_iter().map(|format| {
+                                choice(
+                                    ("screenshot-format", format as usize),
+                                    format.label(),
+                                    screenshot_format == format,
+                                )
+                                .on_click(move |_, _, cx| {
+                                    update_settings(cx, |settings| {
+                                        settings.screenshot_format = format
+                                    })
+                                })
+                            })),
+                    )
+                    .when(screenshot_format == ScreenshotFormat::Jpeg, |this| {
+                        this.child(
+                            div()
+                                .flex()
+                                .items_center()
+                                .gap_2()
+                                .child(div().text_xs().text_color(rgb(0xa1a1a1)).child("Quality"))
+                                .child(div().w(px(200.)).flex().child(slider(
+                                    screenshot::quality_fraction(screenshot_quality),
+                                    rgb(0x0a84ff),
+                                    |fraction, _, cx| {
+                                        update_settings(cx, |settings| {
+                                            settings.screenshot_quality =
+                                                screenshot::quality_at(fraction)
+                                        })
+                                    },
+                                )))
+                                .child(
+                                    div()
+                                        .text_xs()
+                                        .text_color(rgb(0xd1d1d1))
+                                        .child(format!("{}%", screenshot_quality)),
+                                ),
+                        )
+                    })
+                    .child(div().text_xs().text_color(rgb(0x7a7a7a)).child(
+                        match screenshot_format {
+                            ScreenshotFormat::Png => "Lossless, largest for long pages.",
+                            ScreenshotFormat::Jpeg => {
+                                "Smallest. Transparent areas are filled with white."
+                            }
+                            ScreenshotFormat::Webp => "Lossless, usually smaller than PNG.",
+                        },
+                    )),
+            )
+            .child(
+                section("On startup").child(
+                    div().flex().gap_2().children(
+                        [
+                            ("restore-session", "Reopen last tabs", StartupPage::Restore),
+                            ("open-home", "Open the home page", StartupPage::Home),
+                            ("open-blank", "Open a blank page", StartupPage::Blank),
+                        ]
+                        .into_iter()
+                        .map(|(id, label, page)| {
+                            choice(id, label, startup == page).on_click(move |_, _, cx| {
+                                update_settings(cx, |settings| settings.startup = page)
+                            })
+                        }),
+                    ),
+                ),
+            )
+            .when(startup == StartupPage::Restore, |this| {
+                this.child(
+                    div().flex().child(
+                        choice(
+                            "load-tabs-on-startup",
+                            "Load every tab right away",
+                            load_tabs_on_startup,
+                        )
+                        .on_click(|_, _, cx| {
+                            update_settings(cx, |settings| {
+                                settings.load_tabs_on_startup = !settings.load_tabs_on_startup
+                            })
+                        }),
+                    ),
+                )
+            })
+            .child(
+                section("Discard tabs not shown for").child(
+                    div().flex().gap_2().children(
+                        [
+                            ("discard-30", "30 minutes", Some(30)),
+                            ("discard-60", "1 hour", Some(60)),
+                            ("discard-240", "4 hours", Some(240)),
+                            ("discard-never", "Never", None),
+                        ]
+                        .into_iter()
+                        .map(|(id, label, minutes)| {
+                            choice(id, label, discard_after_minutes == minutes).on_click(
+                                move |_, _, cx| {
+                                    update_settings(cx, |settings| {
+                                        settings.discard_after_minutes = minutes
+                                    })
+                                },
+                            )
+                        }),
+                    ),
+                ),
+            )
+            // elsewhere CEF runs its own message loop
+            .when(cfg!(target_os = "linux"), |this| {
+                this.child(
+                    section("Page rendering").child(
+                        div().flex().gap_2().children(
+                            [
+                                (
+                                    "message-loop-adaptive",
+                                    "Slow down when idle",
+                                    settings::DEFAULT_IDLE_MESSAGE_LOOP_FPS,
+                                ),
+                                (
+                                    "message-loop-full",
+                                    "Always full speed",
+                                    settings::DEFAULT_MESSAGE_LOOP_FPS,
+                                ),
+                            ]
+                            .into_iter()
+                            .map(|(id, label, fps)| {
+                                choice(id, label, idle_fps == fps).on_click(move |_, _, cx| {
+                                    update_settings(cx, |settings| {
+                                        settings.idle_message_loop_fps = fps
+                                    })
+                                })
+                            }),
+                        ),
+                    ),
+                )
+            })
+            .child(
+                section("Keyboard shortcuts")
+                    .children(conflicts.into_iter().map(|(chord, labels)| {
+                        div().text_xs().text_color(rgb(0xff9f0a)).child(format!(
+                            "{} is bound to {}",
+                            keymap::display_chord(&chord),
+                            labels.join(" and ")
+                        ))
+                    }))
+                    .children(shortcuts.into_iter().map(|(name, label, chords, custom)| {
+                        div()
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .child(
+                                div()
+                                    .flex_1()
+                                    .text_xs()
+                                    .text_color(rgb(0xd1d1d1))
+                                    .child(label),
+                            )
+                            // clicking a shortcut removes it
+                            .children(chords.iter().enumerate().map(|(ix, chord)| {
+                                let remaining: Vec<_> = chords
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(other, _)| *other != ix)
+                                    .map(|(_, chord)| chord.clone())
+                                    .collect();
+                                choice(
+                                    SharedString::from(format!("shortcut-{}-{}", name, ix)),
+                                    keymap::display_chord(chord),
+                                    false,
+                                )
+                                .on_click(move |_, _, cx| {
+                                    Keymap::set(cx, name, Some(remaining.clone()))
+                                })
+                            }))
+                            .child(
+                                choice(
+                                    SharedString::from(format!("record-shortcut-{}", name)),
+                                    if recording == Some(name) {
+                                        "Press keys…"
+                                    } else {
+                                        "Add"
+                                    },
+                                    recording == Some(name),
+                                )
+                                .on_click(cx.listener(
+                                    move |this, _, window, cx| {
+                                        this.recording = Some(name);
+                                        window.focus(&this.focus_handle);
+                                        cx.notify();
+                                    },
+                                )),
+                            )
+                            .when(custom, |this| {
+                                this.child(
+                                    choice(
+                                        SharedString::from(format!("reset-shortcut-{}", name)),
+                                        "Reset",
+                                        false,
+                                    )
+                                    .on_click(move |_, _, cx| Keymap::set(cx, name, None)),
+                                )
+                            })
+                    }))
+                    .child(
+                        div().flex().child(
+                            choice("reset-shortcuts", "Reset all shortcuts", false)
+                                .on_click(|_, _, cx| Keymap::reset_all(cx)),
+                        ),
+                    ),
+            )
+            .child(div().flex().justify_end().child(
+                choice("reset-settings", "Reset to defaults", false).on_click(
+                    cx.listener(|this, _, window, cx| this.reset_to_defaults(window, cx)),
+                ),
+            ))
+    }
+}
@@ -0,0 +1,186 @@
+use gpui::{App, Global};
+use gpui_webview::wef::RequestContext;
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{self, Credentials};
+use crate::settings::BrowserSettings;
+
+// How pages reach the network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyMode {
+    // Connect directly, ignoring the system's proxy
+    None,
+    #[default]
+    System,
+    Manual,
+}
+
+impl ProxyMode {
+    pub const ALL: [ProxyMode; 3] = [ProxyMode::None, ProxyMode::System, ProxyMode::Manual];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ProxyMode::None => "No proxy",
+            ProxyMode::System => "System settings",
+            ProxyMode::Manual => "Manual",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyScheme {
+    #[default]
+    Http,
+    Socks5,
+}
+
+impl ProxyScheme {
+    pub const ALL: [ProxyScheme; 2] = [ProxyScheme::Http, ProxyScheme::Socks5];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ProxyScheme::Http => "HTTP",
+            ProxyScheme::Socks5 => "SOCKS5",
+        }
+    }
+
+    fn prefix(self) -> &'static str {
+        match self {
+            ProxyScheme::Http => "http",
+            ProxyScheme::Socks5 => "socks5",
+        }
+    }
+
+    pub fn default_port(self) -> u16 {
+        match self {
+            ProxyScheme::Http => 8080,
+            ProxyScheme::Socks5 => 1080,
+        }
+    }
+}
+
+// Part of `BrowserSettings`; the password is kept in the OS keychain, not in settings.json
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxySettings {
+    pub mode: ProxyMode,
+    pub scheme: ProxyScheme,
+    pub host: String,
+    // `None` uses the scheme's usual port
+    pub port: Option<u16>,
+    // Answers the proxy's sign-in prompt, empty to always ask
+    pub username: String,
+    // Hosts reached directly, e.g. `localhost`, `*.internal.example.com` or `10.0.0.0/8`
+    pub bypass_list: Vec<String>,
+}
+
+impl ProxySettings {
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(self.scheme.default_port())
+    }
+
+    // `scheme://host:port` of a manual proxy, `None` until it has a host
+    pub fn server(&self) -> Option<String> {
+        let host = self.host.trim();
+        if self.mode != ProxyMode::Manual || host.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "{}://{}:{}",
+            self.scheme.prefix(),
+            host,
+            self.port()
+        ))
+    }
+
+    // Chromium's `proxy` preference; a manual proxy without a host connects directly
+    fn preference(&self) -> serde_json::Value {
+        match (self.mode, self.server()) {
+            (ProxyMode::System, _) => serde_json::json!({ "mode": "system" }),
+            (ProxyMode::Manual, Some(server)) => serde_json::json!({
+                "mode": "fixed_servers",
+                "server": server,
+                "bypass_list": self.bypass_list.join(";"),
+            }),
+            _ => serde_json::json!({ "mode": "direct" }),
+        }
+    }
+
+    // Whether a sign-in prompt from `host:port` comes from the manual proxy
+    pub fn is_server(&self, host: &str, port: u16) -> bool {
+        self.server().is_some()
+            && self.host.trim().eq_ignore_ascii_case(host)
+            && self.port() == port
+    }
+
+    // Keychain entry the manual proxy's credentials are kept under, the one the sign-in
+    // dialog remembers them in too
+    pub fn credentials_key(&self) -> String {
+        format!("proxy {}:{}", self.host.trim(), self.port())
+    }
+
+    // Blocks on the OS keychain
+    pub fn save_password(&self, password: String) {
+        auth::save_credentials(
+            &self.credentials_key(),
+            &Credentials {
+                username: self.username.trim().to_string(),
+                password,
+            },
+        );
+    }
+
+    pub fn forget_password(&self) {
+        auth::delete_credentials(&self.credentials_key());
+    }
+}
+
+// One line for the proxy in use, e.g. "socks5://127.0.0.1:1080"
+pub fn describe(proxy: &ProxySettings) -> String {
+    match (proxy.mode, proxy.server()) {
+        (ProxyMode::None, _) => "Direct connection".to_string(),
+        (ProxyMode::System, _) => "The system's proxy settings".to_string(),
+        (ProxyMode::Manual, Some(server)) => server,
+        (ProxyMode::Manual, None) => "Direct connection, the manual proxy has no host".to_string(),
+    }
+}
+
+// The proxy settings the browser's profile was last switched to, for `about:proxy`
+pub struct ActiveProxy {
+    pub settings: ProxySettings,
+    // Why CEF refused them, the previous ones stay in use then
+    pub error: Option<String>,
+}
+
+impl Global for ActiveProxy {}
+
+impl ActiveProxy {
+    // Apply the saved proxy now and whenever it's changed; windows observe this global to
+    // switch their own profiles
+    pub fn init(cx: &mut App) {
+        let settings = cx.global::<BrowserSettings>().proxy.clone();
+        let error = apply(&RequestContext::global(), &settings).err();
+        cx.set_global(Self { settings, error });
+        cx.observe_global::<BrowserSettings>(|cx| {
+            let settings = cx.global::<BrowserSettings>().proxy.clone();
+            if cx.global::<Self>().settings == settings {
+                return;
+            }
+            let error = apply(&RequestContext::global(), &settings).err();
+            cx.set_global(Self { settings, error });
+        })
+        .detach();
+    }
+}
+
+// Switch `request_context` to `proxy`; requests already under way finish the way they started
+pub fn apply(request_context: &RequestContext, proxy: &ProxySettings) -> Result<(), String> {
+    request_context
+        .set_preference("proxy", &proxy.preference())
+        .map_err(|err| {
+            log::error!("Failed to apply proxy settings: {}", err);
+            err.to_string()
+        })
+}
@@ -0,0 +1,135 @@
+use gpui::{div, img, prelude::*, px, rgb, rgba, App, IntoElement, SharedString, Window};
+use serde::Deserialize;
+
+// Runs the extraction defined by `scripts/reader.js`, which replies through `readerContent`
+pub const EXTRACT_SCRIPT: &str = "window.__browserReader && window.__browserReader.extract()";
+
+pub const DEFAULT_FONT_SIZE: f32 = 18.;
+pub const MIN_FONT_SIZE: f32 = 12.;
+pub const MAX_FONT_SIZE: f32 = 32.;
+// Change in font size for each press of A- or A+
+pub const FONT_SIZE_STEP: f32 = 2.;
+
+// Main content of a page, as pulled out by the reader script
+#[derive(Debug, Clone, Deserialize)]
+pub struct Article {
+    pub title: String,
+    pub byline: Option<String>,
+    pub blocks: Vec<Block>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Block {
+    Heading { level: u8, text: String },
+    Paragraph { text: String },
+    Image { src: String, alt: String },
+}
+
+fn control(id: &'static str, label: &'static str) -> gpui::Stateful<gpui::Div> {
+    div()
+        .id(id)
+        .px_2()
+        .py_1()
+        .rounded_md()
+        .border_1()
+        .border_color(rgba(0xd3d9d92b))
+        .text_xs()
+        .text_color(rgb(0xf2f2f2))
+        .cursor_pointer()
+        .hover(|this| this.bg(rgba(0xffffff10)))
+        .child(label)
+}
+
+// The article in a single readable column, covering the page
+pub fn render_reader(
+    article: &Article,
+    font_size: f32,
+    on_font_size: impl Fn(f32, &mut Window, &mut App) + Clone + 'static,
+    on_close: impl Fn(&mut Window, &mut App) + 'static,
+) -> impl IntoElement {
+    let line_height = font_size * 1.6;
+
+    div()
+        .id("reader")
+        .absolute()
+        .inset_0()
+        .overflow_y_scroll()
+        .bg(rgb(0x0a0a0a))
+        .child(
+            div()
+                .flex()
+                .justify_end()
+                .gap_2()
+                .p_3()
+                .child(control("reader-smaller", "A-").on_click({
+                    let on_font_size = on_font_size.clone();
+                    move |_, window, cx| on_font_size(font_size - FONT_SIZE_STEP, window, cx)
+                }))
+                .child(
+                    control("reader-larger", "A+").on_click(move |_, window, cx| {
+                        on_font_size(font_size + FONT_SIZE_STEP, window, cx)
+                    }),
+                )
+                .child(
+                    control("reader-close", "Close")
+                        .on_click(move |_, window, cx| on_close(window, cx)),
+                ),
+        )
+        .child(
+            div()
+                .mx_auto()
+                .max_w(px(font_size * 38.))
+                .px_6()
+                .pb(px(48.))
+                .flex()
+                .flex_col()
+                .gap(px(font_size * 0.9))
+                .text_size(px(font_size))
+                .line_height(px(line_height))
+                .text_color(rgb(0xd1d1d1))
+                .child(
+                    div()
+                        .text_size(px(font_size * 1.8))
+                        .line_height(px(font_size * 2.3))
+                        .text_color(rgb(0xf2f2f2))
+                        .child(SharedString::from(article.title.clone())),
+                )
+                .children(article.byline.clone().map(|byline| {
+                    div()
+                        .text_size(px(font_size * 0.8))
+                        .text_color(rgb(0x7a7a7a))
+                        .child(byline)
+                }))
+                .children(article.blocks.iter().map(|block| {
+                    match block {
+                        Block::Heading { level, text } => {
+                            // h1 is about 1.5x the body text, h4 and below are body sized
+                            let scale = (1.6 - *level as f32 * 0.15).max(1.);
+                            div()
+                                .pt(px(font_size * 0.5))
+                                .text_size(px(font_size * scale))
+                                .line_height(px(font_size * scale * 1.3))
+                                .text_color(rgb(0xf2f2f2))
+                                .child(text.clone())
+                                .into_any_element()
+                        }
+                        Block::Paragraph { text } => div().child(text.clone()).into_any_element(),
+                        Block::Image { src, alt } => div()
+                            .flex()
+                            .flex_col()
+                            .gap_1()
+                            .child(img(src.clone()).max_w_full().rounded_md())
+                            .when(!alt.is_empty(), |this| {
+                                this.child(
+                                    div()
+                                        .text_size(px(font_size * 0.75))
+                                        .text_color(rgb(0x7a7a7a))
+                                        .child(alt.clone()),
+                                )
+                            })
+                            .into_any_element(),
+                    }
+                })),
+        )
+}
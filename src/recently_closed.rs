@@ -0,0 +1,337 @@
+use gpui::{div, prelude::*, px, rgb, rgba, App, Context, Global, IntoElement, Window};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::chrome::format_elapsed;
+use crate::session::{SessionState, SessionTab};
+use crate::storage;
+use crate::{favicon_icon, open_window_with_session, Main};
+
+const RECENTLY_CLOSED_FILE: &str = "recently_closed.json";
+// Closed tabs and windows kept, the oldest are dropped first
+const MAX_CLOSED_ITEMS: usize = 25;
+// Entries listed in the menu
+const MAX_MENU_ITEMS: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClosedTab {
+    pub tab: SessionTab,
+    #[serde(default)]
+    pub scroll_position: (f32, f32),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ClosedKind {
+    Tab(ClosedTab),
+    Window(SessionState),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClosedItem {
+    #[serde(flatten)]
+    pub kind: ClosedKind,
+    // Seconds since the unix epoch
+    pub closed_at: u64,
+}
+
+impl ClosedItem {
+    pub fn tab(tab: SessionTab, scroll_position: (f32, f32)) -> Self {
+        Self {
+            kind: ClosedKind::Tab(ClosedTab {
+                tab,
+                scroll_position,
+            }),
+            closed_at: now(),
+        }
+    }
+
+    // A window with a single tab is remembered as that tab
+    pub fn window(mut session: SessionState) -> Self {
+        if session.tabs.len() == 1 {
+            return Self::tab(session.tabs.remove(0), (0., 0.));
+        }
+        Self {
+            kind: ClosedKind::Window(session),
+            closed_at: now(),
+        }
+    }
+
+    fn label(&self) -> String {
+        let title = |tab: &SessionTab| {
+            if tab.title.is_empty() {
+                tab.url.clone()
+            } else {
+                tab.title.clone()
+            }
+        };
+        match &self.kind {
+            ClosedKind::Tab(closed) => title(&closed.tab),
+            ClosedKind::Window(session) => format!(
+                "{} and {} more",
+                session
+                    .tabs
+                    .get(session.active_tab)
+                    .map(title)
+                    .unwrap_or_default(),
+                session.tabs.len() - 1
+            ),
+        }
+    }
+
+    fn icon_tab(&self) -> Option<&SessionTab> {
+        match &self.kind {
+            ClosedKind::Tab(closed) => Some(&closed.tab),
+            ClosedKind::Window(session) => session.tabs.get(session.active_tab),
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.closed_at))
+    }
+}
+
+// Tabs and windows closed lately, oldest first. Normal windows share one list that outlives
+// restarts, each private window keeps its own in memory.
+pub struct RecentlyClosed {
+    items: Vec<ClosedItem>,
+    persist: bool,
+}
+
+impl Global for RecentlyClosed {}
+
+impl RecentlyClosed {
+    pub fn load() -> Self {
+        let items = storage::load_json::<Vec<ClosedItem>>(RECENTLY_CLOSED_FILE)
+            .unwrap_or_else(|err| {
+                log::error!("Failed to load recently closed tabs: {}", err);
+                None
+            })
+            .unwrap_or_default();
+        Self {
+            items,
+            persist: true,
+        }
+    }
+
+    pub fn in_memory() -> Self {
+        Self {
+            items: Vec::new(),
+            persist: false,
+        }
+    }
+
+    pub fn push(&mut self, item: ClosedItem) {
+        self.items.push(item);
+        if self.items.len() > MAX_CLOSED_ITEMS {
+            self.items.remove(0);
+        }
+        self.save();
+    }
+
+    pub fn pop(&mut self) -> Option<ClosedItem> {
+        let item = self.items.pop();
+        self.save();
+        item
+    }
+
+    // Take the entry `ix` places from the most recent one
+    pub fn take(&mut self, ix: usize) -> Option<ClosedItem> {
+        let ix = self.items.len().checked_sub(ix + 1)?;
+        let item = self.items.remove(ix);
+        self.save();
+        Some(item)
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.save();
+    }
+
+    // Most recently closed first
+    pub fn recent(&self) -> impl Iterator<Item = &ClosedItem> {
+        self.items.iter().rev()
+    }
+
+    fn save(&self) {
+        if !self.persist {
+            return;
+        }
+        if let Err(err) = storage::save_json(RECENTLY_CLOSED_FILE, &self.items) {
+            log::error!("Failed to save recently closed tabs: {}", err);
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+impl Main {
+    // The list this window records into, see `RecentlyClosed`
+    pub(crate) fn recently_closed<'a>(&'a self, cx: &'a App) -> &'a RecentlyClosed {
+        if self.private {
+            &self.closed_tabs
+        } else {
+            cx.global::<RecentlyClosed>()
+        }
+    }
+
+    pub(crate) fn update_recently_closed<R>(
+        &mut self,
+        cx: &mut App,
+        update: impl FnOnce(&mut RecentlyClosed) -> R,
+    ) -> R {
+        if self.private {
+            update(&mut self.closed_tabs)
+        } else {
+            cx.update_global::<RecentlyClosed, _>(|recently_closed, _| update(recently_closed))
+        }
+    }
+
+    // Remember the whole window before it goes away; private windows take their list with them
+    pub(crate) fn remember_closed_window(&mut self, cx: &mut App) {
+        if !self.private {
+            let item = ClosedItem::window(self.session_state());
+            cx.update_global::<RecentlyClosed, _>(|recently_closed, _| recently_closed.push(item));
+        }
+    }
+
+    pub(crate) fn reopen_closed(
+        &mut self,
+        item: ClosedItem,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match item.kind {
+            ClosedKind::Tab(closed) => {
+                let ix = self.insert_unloaded_tab(
+                    self.tabs.len(),
+                    &closed.tab.url,
+                    closed.tab.title.clone().into(),
+                    closed.tab.favicon_image(),
+                    cx,
+                );
+                if closed.scroll_position != (0., 0.) {
+                    self.tabs[ix].pending_scroll = Some(closed.scroll_position);
+                }
+                self.activate_tab(ix, window, cx);
+            }
+            ClosedKind::Window(session) => {
+                cx.defer(move |cx| open_window_with_session(session, false, cx))
+            }
+        }
+    }
+
+    pub(crate) fn toggle_recently_closed(&mut self, cx: &mut Context<Self>) {
+        self.show_recently_closed = !self.show_recently_closed;
+        cx.notify();
+    }
+
+    // Dropdown under the tab strip's history button
+    pub(crate) fn render_recently_closed(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let items: Vec<_> = self
+            .recently_closed(cx)
+            .recent()
+            .take(MAX_MENU_ITEMS)
+            .cloned()
+            .collect();
+
+        div()
+            .id("recently-closed-menu")
+            .occlude()
+            .absolute()
+            .top(px(28.))
+            .right_0()
+            .w(px(280.))
+            .flex()
+            .flex_col()
+            .py_1()
+            .rounded_md()
+            .border_1()
+            .border_color(rgba(0xd3d9d92b))
+            .bg(rgba(0x181818f2))
+            .on_mouse_down_out(cx.listener(|this, _, _, cx| {
+                this.show_recently_closed = false;
+                cx.notify();
+            }))
+            .child(
+                div()
+                    .px_3()
+                    .py_1()
+                    .text_xs()
+                    .text_color(rgb(0x7a7a7a))
+                    .child("Recently closed"),
+            )
+            .when(items.is_empty(), |this| {
+                this.child(
+                    div()
+                        .px_3()
+                        .py_1()
+                        .text_xs()
+                        .text_color(rgb(0xa1a1a1))
+                        .child("Nothing closed yet"),
+                )
+            })
+            .children(items.iter().enumerate().map(|(ix, item)| {
+                div()
+                    .id(("recently-closed", ix))
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_3()
+                    .py_1()
+                    .cursor_pointer()
+                    .hover(|this| this.bg(rgba(0xffffff1a)))
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        this.show_recently_closed = false;
+                        if let Some(item) =
+                            this.update_recently_closed(cx, |recent| recent.take(ix))
+                        {
+                            this.reopen_closed(item, window, cx);
+                        }
+                        cx.notify();
+                    }))
+                    .child(favicon_icon(
+                        item.icon_tab().and_then(SessionTab::favicon_image),
+                        10.0,
+                    ))
+                    .child(
+                        div()
+                            .flex_1()
+                            .min_w_0()
+                            .truncate()
+                            .text_xs()
+                            .text_color(rgb(0xd1d1d1))
+                            .child(item.label()),
+                    )
+                    .child(
+                        div()
+                            .flex_none()
+                            .text_xs()
+                            .text_color(rgb(0x7a7a7a))
+                            .child(format_elapsed(item.elapsed())),
+                    )
+            }))
+            .when(!items.is_empty(), |this| {
+                this.child(crate::context_menu::separator())
+                    .child(crate::context_menu::menu_item(
+                        "clear-recently-closed",
+                        "Clear List",
+                        true,
+                        {
+                            let this = cx.entity();
+                            move |_, cx| {
+                                this.update(cx, |this, cx| {
+                                    this.update_recently_closed(cx, RecentlyClosed::clear);
+                                    cx.notify();
+                                })
+                            }
+                        },
+                    ))
+            })
+    }
+}
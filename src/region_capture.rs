@@ -0,0 +1,296 @@
+use gpui::{
+    canvas, div, point, prelude::*, px, rgba, App, Bounds, Context, IntoElement, MouseButton,
+    MouseDownEvent, MouseMoveEvent, Pixels, Point, Window,
+};
+
+use crate::screenshot::{self, CaptureArea, ScreenshotFormat};
+use crate::settings::BrowserSettings;
+use crate::theme::Theme;
+use crate::toast::toast_info;
+use crate::widgets::slider;
+use crate::{CaptureRegion, Main};
+
+// Drags smaller than this either way are taken as a click, not a selection
+const MIN_SELECTION: Pixels = px(4.);
+
+// An area of the page being picked for a screenshot, by dragging a rectangle over it
+#[derive(Default)]
+pub struct RegionCapture {
+    // Where the page is in the window, as last laid out
+    bounds: Bounds<Pixels>,
+    // Where the drag started and where the mouse is now, in window coordinates
+    start: Option<Point<Pixels>>,
+    end: Point<Pixels>,
+}
+
+impl RegionCapture {
+    // The dragged rectangle relative to the page, kept inside it
+    fn selection(&self) -> Option<Bounds<Pixels>> {
+        let start = self.start?;
+        let clamp = |position: Point<Pixels>| {
+            let position = position - self.bounds.origin;
+            point(
+                position.x.clamp(px(0.), self.bounds.size.width),
+                position.y.clamp(px(0.), self.bounds.size.height),
+            )
+        };
+        let (start, end) = (clamp(start), clamp(self.end));
+        Some(Bounds::from_corners(
+            point(start.x.min(end.x), start.y.min(end.y)),
+            point(start.x.max(end.x), start.y.max(end.y)),
+        ))
+    }
+}
+
+impl Main {
+    pub(crate) fn start_region_capture(
+        &mut self,
+        _: &CaptureRegion,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.tabs[self.active_tab].main_frame.is_none() {
+            toast_info(cx, "Nothing to capture yet");
+            return;
+        }
+        self.region_capture = Some(RegionCapture::default());
+        // keys go to the browser, so Escape cancels instead of reaching the page
+        window.focus(&self.focus_handle);
+        cx.notify();
+    }
+
+    pub(crate) fn cancel_region_capture(&mut self, cx: &mut Context<Self>) {
+        self.region_capture = None;
+        cx.notify();
+    }
+
+    // Screenshot the dragged area, or wait for another drag after a plain click
+    fn finish_region_capture(&mut self, cx: &mut Context<Self>) {
+        let Some(capture) = &mut self.region_capture else {
+            return;
+        };
+        let selection = capture.selection();
+        let size = capture.bounds.size;
+        let Some(selection) = selection.filter(|selection| {
+            selection.size.width >= MIN_SELECTION && selection.size.height >= MIN_SELECTION
+        }) else {
+            capture.start = None;
+            cx.notify();
+            return;
+        };
+        self.region_capture = None;
+        let fraction = |value: Pixels, total: Pixels| value / total;
+        let region = Bounds {
+            origin: point(
+                fraction(selection.origin.x, size.width),
+                fraction(selection.origin.y, size.height),
+            ),
+            size: gpui::size(
+                fraction(selection.size.width, size.width),
+                fraction(selection.size.height, size.height),
+            ),
+        };
+        self.capture_screenshot(CaptureArea::Region(region), cx);
+        cx.notify();
+    }
+
+    // Dims the page except for the area being dragged out, with its size in device pixels
+    pub(crate) fn render_region_capture(
+        &self,
+        window: &Window,
+        cx: &mut Context<Self>,
+    ) -> Option<impl IntoElement> {
+        let capture = self.region_capture.as_ref()?;
+        let theme = *cx.global::<Theme>();
+        let dim = rgba(0x00000073);
+        let this = cx.entity();
+        let label = |text: String| {
+            div()
+                .px_2()
+                .py_1()
+                .rounded_md()
+                .bg(theme.surface)
+                .border_1()
+                .border_color(theme.border)
+                .text_xs()
+                .text_color(theme.text)
+                .child(text)
+        };
+
+        let overlay = div()
+            .id("region-capture")
+            .absolute()
+            .inset_0()
+            .occlude()
+            .cursor_crosshair()
+            .child(
+                canvas(
+                    move |bounds, _, cx| {
+                        this.update(cx, |this, _| {
+                            if let Some(capture) = &mut this.region_capture {
+                                capture.bounds = bounds;
+                            }
+                        })
+                    },
+                    |_, _, _, _| {},
+                )
+                .absolute()
+                .size_full(),
+            )
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(|this, event: &MouseDownEvent, _, cx| {
+                    if let Some(capture) = &mut this.region_capture {
+                        capture.start = Some(event.position);
+                        capture.end = event.position;
+                        cx.notify();
+                    }
+                }),
+            )
+            .on_mouse_move(cx.listener(|this, event: &MouseMoveEvent, _, cx| {
+                if let Some(capture) = this
+                    .region_capture
+                    .as_mut()
+                    .filter(|capture| capture.start.is_some())
+                {
+                    capture.end = event.position;
+                    cx.notify();
+                }
+            }))
+            .on_mouse_up(
+                MouseButton::Left,
+                cx.listener(|this, _, _, cx| this.finish_region_capture(cx)),
+            )
+            // released outside the window
+            .on_mouse_up_out(
+                MouseButton::Left,
+                cx.listener(|this, _, _, cx| this.finish_region_capture(cx)),
+            );
+
+        let Some(selection) = capture.selection() else {
+            return Some(
+                overlay
+                    .bg(dim)
+                    .flex()
+                    .items_center()
+                    .justify_center()
+                    .child(label(
+                        "Drag to select an area to capture, Escape to cancel".to_string(),
+                    ))
+                    .child(self.render_capture_format(cx)),
+            );
+        };
+
+        let (left, top) = (selection.left(), selection.top());
+        let (right, bottom) = (selection.right(), selection.bottom());
+        let scale = window.scale_factor();
+        let dimensions = format!(
+            "{} × {}",
+            (f32::from(selection.size.width) * scale).round(),
+            (f32::from(selection.size.height) * scale).round()
+        );
+        let shade = || div().absolute().bg(dim);
+
+        Some(
+            overlay
+                .child(shade().left_0().top_0().w_full().h(top))
+                .child(shade().left_0().top(bottom).w_full().bottom_0())
+                .child(shade().left_0().top(top).w(left).h(bottom - top))
+                .child(shade().left(right).top(top).right_0().h(bottom - top))
+                .child(
+                    div()
+                        .absolute()
+                        .left(left)
+                        .top(top)
+                        .w(selection.size.width)
+                        .h(selection.size.height)
+                        .border_1()
+                        .border_color(theme.accent),
+                )
+                // above the selection, or inside it at the top of the page
+                .child(
+                    div()
+                        .absolute()
+                        .left(left)
+                        .top(if top >= px(28.) {
+                            top - px(28.)
+                        } else {
+                            top + px(4.)
+                        })
+                        .child(label(dimensions)),
+                )
+                .child(self.render_capture_format(cx)),
+        )
+    }
+
+    // Format picker along the bottom of the page, with JPEG's quality. Presses on it don't
+    // start a selection.
+    fn render_capture_format(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let settings = cx.global::<BrowserSettings>();
+        let (format, quality) = (settings.screenshot_format, settings.screenshot_quality);
+        let set = |cx: &mut App, update: &dyn Fn(&mut BrowserSettings)| {
+            cx.update_global::<BrowserSettings, _>(|settings, _| {
+                update(settings);
+                settings.save();
+            });
+            cx.refresh_windows();
+        };
+
+        div()
+            .absolute()
+            .bottom_4()
+            .left_0()
+            .right_0()
+            .flex()
+            .justify_center()
+            .child(
+                div()
+                    .id("capture-format")
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .px_3()
+                    .h_8()
+                    .rounded_md()
+                    .border_1()
+                    .border_color(theme.border)
+                    .bg(theme.surface)
+                    .cursor_default()
+                    .on_mouse_down(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .on_mouse_up(MouseButton::Left, |_, _, cx| cx.stop_propagation())
+                    .children(ScreenshotFormat::ALL.into_iter().map(|option| {
+                        div()
+                            .id(option.label())
+                            .px_2()
+                            .rounded_md()
+                            .text_xs()
+                            .text_color(theme.text)
+                            .cursor_pointer()
+                            .when(option == format, |this| this.bg(theme.hover_subtle))
+                            .hover(|this| this.bg(theme.hover_subtle))
+                            .child(option.label())
+                            .on_click(move |_, _, cx| {
+                                set(cx, &|settings| settings.screenshot_format = option)
+                            })
+                    }))
+                    .when(format == ScreenshotFormat::Jpeg, |this| {
+                        this.child(div().w(px(96.)).flex().child(slider(
+                            screenshot::quality_fraction(quality),
+                            theme.accent,
+                            move |fraction, _, cx| {
+                                let quality = screenshot::quality_at(fraction);
+                                set(cx, &|settings| settings.screenshot_quality = quality)
+                            },
+                        )))
+                        .child(
+                            div()
+                                .w(px(28.))
+                                .text_xs()
+                                .text_color(theme.text)
+                                .child(format!("{}%", quality)),
+                        )
+                    }),
+            )
+    }
+}
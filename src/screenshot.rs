@@ -0,0 +1,269 @@
+use anyhow::{anyhow, Result};
+use futures_util::future::{self, Either};
+use gpui::{AsyncApp, Bounds, Entity, Timer};
+use gpui_webview::{wef::Frame, WebView};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{imageops, ExtendedColorType, ImageEncoder, ImageFormat, Rgb, RgbImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::pin::pin;
+use std::time::Duration;
+
+use crate::downloads;
+use crate::webview_ext::WebViewExt;
+
+// Asks the page for its size, answered with a `PageMetrics` bridge event
+pub const METRICS_SCRIPT: &str = "jsBridge.reportPageMetrics(\
+    document.documentElement.scrollHeight, window.innerHeight, window.scrollY)";
+
+// How long the page gets to answer `METRICS_SCRIPT`, it never does with scripts off or before
+// the bridge is injected
+const METRICS_TIMEOUT: Duration = Duration::from_secs(3);
+// Time for CEF to repaint after the page is scrolled
+const SCROLL_SETTLE: Duration = Duration::from_millis(250);
+// Pages taller than this (in CSS pixels) are cut off
+const MAX_PAGE_HEIGHT: f32 = 16384.;
+// JPEG has no transparency, see-through pixels are blended onto this
+const JPEG_BACKGROUND: Rgb<u8> = Rgb([255, 255, 255]);
+pub const DEFAULT_JPEG_QUALITY: u8 = 85;
+const MIN_JPEG_QUALITY: u8 = 10;
+
+// How screenshots are encoded. PNG and WebP are lossless, JPEG is much smaller for long pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenshotFormat {
+    #[default]
+    Png,
+    Jpeg,
+    Webp,
+}
+
+impl ScreenshotFormat {
+    pub const ALL: [ScreenshotFormat; 3] = [
+        ScreenshotFormat::Png,
+        ScreenshotFormat::Jpeg,
+        ScreenshotFormat::Webp,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Png => "PNG",
+            ScreenshotFormat::Jpeg => "JPEG",
+            ScreenshotFormat::Webp => "WebP",
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Png => "image/png",
+            ScreenshotFormat::Jpeg => "image/jpeg",
+            ScreenshotFormat::Webp => "image/webp",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Png => "png",
+            ScreenshotFormat::Jpeg => "jpg",
+            ScreenshotFormat::Webp => "webp",
+        }
+    }
+
+    // What the clipboard is told the bytes are
+    pub fn clipboard_format(self) -> gpui::ImageFormat {
+        match self {
+            ScreenshotFormat::Png => gpui::ImageFormat::Png,
+            ScreenshotFormat::Jpeg => gpui::ImageFormat::Jpeg,
+            ScreenshotFormat::Webp => gpui::ImageFormat::Webp,
+        }
+    }
+}
+
+// Where `quality` sits on a quality slider, and the quality at `fraction` of it in steps of 5
+pub fn quality_fraction(quality: u8) -> f32 {
+    (quality.saturating_sub(MIN_JPEG_QUALITY)) as f32 / (100 - MIN_JPEG_QUALITY) as f32
+}
+
+pub fn quality_at(fraction: f32) -> u8 {
+    let quality =
+        MIN_JPEG_QUALITY as f32 + fraction.clamp(0., 1.) * (100 - MIN_JPEG_QUALITY) as f32;
+    ((quality / 5.).round() * 5.) as u8
+}
+
+// A screenshot ready to be saved or copied
+pub struct EncodedImage {
+    pub bytes: Vec<u8>,
+    pub format: ScreenshotFormat,
+}
+
+impl EncodedImage {
+    pub fn mime_type(&self) -> &'static str {
+        self.format.mime_type()
+    }
+}
+
+// What part of the page a screenshot covers
+#[derive(Debug, Clone, Copy)]
+pub enum CaptureArea {
+    Viewport,
+    FullPage,
+    // A rectangle of the viewport, in fractions of its width and height
+    Region(Bounds<f32>),
+}
+
+// Size of the page and viewport in CSS pixels
+#[derive(Debug, Clone, Copy)]
+pub struct PageMetrics {
+    pub scroll_height: f32,
+    pub viewport_height: f32,
+    pub scroll_y: f32,
+}
+
+// The visible viewport as last painted
+pub fn capture_viewport(webview: &Entity<WebView>, cx: &mut AsyncApp) -> Result<RgbaImage> {
+    webview
+        .read_with(cx, |webview, _| webview.capture_viewport())?
+        .ok_or_else(|| anyhow!("the page hasn't been painted yet"))
+}
+
+// The size the page reports after `METRICS_SCRIPT` ran, giving up after `METRICS_TIMEOUT`
+pub async fn wait_for_metrics(metrics: flume::Receiver<PageMetrics>) -> Result<PageMetrics> {
+    let report = pin!(metrics.recv_async());
+    let timeout = pin!(Timer::after(METRICS_TIMEOUT));
+    match future::select(report, timeout).await {
+        Either::Left((Ok(metrics), _)) => Ok(metrics),
+        Either::Left((Err(_), _)) => Err(anyhow!("the page didn't report its size")),
+        Either::Right(_) => Err(anyhow!("the page didn't report its size in time")),
+    }
+}
+
+// The whole scrollable page, captured one viewport at a time and stitched together
+//
+// CEF only ever paints the viewport, so the page is scrolled through and put back after.
+pub async fn capture_full_page(
+    webview: &Entity<WebView>,
+    frame: &Frame,
+    metrics: PageMetrics,
+    cx: &mut AsyncApp,
+) -> Result<RgbaImage> {
+    let page_height = metrics.scroll_height.min(MAX_PAGE_HEIGHT);
+    let viewport_height = metrics.viewport_height.max(1.);
+    let last_offset = (page_height - viewport_height).max(0.);
+
+    let mut frames = Vec::new();
+    let mut offset = 0.;
+    let captured = loop {
+        let y = f32::min(offset, last_offset);
+        frame.execute_javascript(&format!("window.scrollTo(0, {})", y));
+        Timer::after(SCROLL_SETTLE).await;
+
+        match capture_viewport(webview, cx) {
+            Ok(image) => frames.push((image, y)),
+            Err(err) => break Err(err),
+        }
+        if y >= last_offset {
+            break Ok(());
+        }
+        offset += viewport_height;
+    };
+    frame.execute_javascript(&format!("window.scrollTo(0, {})", metrics.scroll_y));
+    captured?;
+
+    // frames are in device pixels, offsets in CSS pixels
+    let (first, _) = frames
+        .first()
+        .ok_or_else(|| anyhow!("nothing was captured"))?;
+    let scale = first.height() as f32 / viewport_height;
+    let mut page = RgbaImage::new(first.width(), (page_height * scale).round() as u32);
+    for (image, y) in &frames {
+        imageops::replace(&mut page, image, 0, (y * scale).round() as i64);
+    }
+    Ok(page)
+}
+
+// The part of `image` covered by `region`, given in fractions of its size
+pub fn crop(image: &RgbaImage, region: Bounds<f32>) -> RgbaImage {
+    let (width, height) = (image.width() as f32, image.height() as f32);
+    let pixels = |fraction: f32, size: f32| (fraction * size).round().clamp(0., size) as u32;
+    imageops::crop_imm(
+        image,
+        pixels(region.origin.x, width),
+        pixels(region.origin.y, height),
+        pixels(region.size.width, width).max(1),
+        pixels(region.size.height, height).max(1),
+    )
+    .to_image()
+}
+
+pub fn encode_png(image: &RgbaImage) -> Result<Vec<u8>> {
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+// Encode `image` as `format`; `jpeg_quality` from 1 to 100 is used by JPEG only
+pub fn encode(
+    image: &RgbaImage,
+    format: ScreenshotFormat,
+    jpeg_quality: u8,
+) -> Result<EncodedImage> {
+    let bytes = match format {
+        ScreenshotFormat::Png => encode_png(image)?,
+        ScreenshotFormat::Jpeg => {
+            let image = flatten(image, JPEG_BACKGROUND);
+            let mut jpeg = Vec::new();
+            JpegEncoder::new_with_quality(&mut jpeg, jpeg_quality.clamp(1, 100))
+                .encode_image(&image)?;
+            jpeg
+        }
+        // the encoder only writes lossless WebP
+        ScreenshotFormat::Webp => {
+            let mut webp = Vec::new();
+            WebPEncoder::new_lossless(&mut webp).write_image(
+                image.as_raw(),
+                image.width(),
+                image.height(),
+                ExtendedColorType::Rgba8,
+            )?;
+            webp
+        }
+    };
+    Ok(EncodedImage { bytes, format })
+}
+
+// `image` blended onto an opaque `background`
+fn flatten(image: &RgbaImage, background: Rgb<u8>) -> RgbImage {
+    RgbImage::from_fn(image.width(), image.height(), |x, y| {
+        let [r, g, b, a] = image.get_pixel(x, y).0;
+        let blend = |channel: u8, background: u8| {
+            let alpha = a as u32;
+            ((channel as u32 * alpha + background as u32 * (255 - alpha) + 127) / 255) as u8
+        };
+        Rgb([
+            blend(r, background[0]),
+            blend(g, background[1]),
+            blend(b, background[2]),
+        ])
+    })
+}
+
+// Write a screenshot into the pictures folder, named after the page's host
+pub fn save(image: &EncodedImage, host: &str) -> Result<PathBuf> {
+    let dir = dirs::picture_dir()
+        .or_else(dirs::download_dir)
+        .or_else(dirs::home_dir)
+        .ok_or_else(|| anyhow!("no folder to save screenshots in"))?;
+    let extension = image.format.extension();
+    let name = if host.is_empty() {
+        format!("Screenshot.{}", extension)
+    } else {
+        format!("Screenshot {}.{}", host, extension)
+    };
+
+    let path = downloads::unique_path(&dir, &name);
+    fs::write(&path, &image.bytes)?;
+    Ok(path)
+}
@@ -0,0 +1,46 @@
+// Tell the host whether the page is playing audible media. Child frames report to the top
+// frame, which combines them so embedded players count towards the tab.
+(() => {
+  if (window.__browserAudio) return;
+  window.__browserAudio = true;
+
+  const isTop = window.top === window;
+  const media = new Set();
+  const frames = new Map();
+  let reported = false;
+
+  const audible = (element) =>
+    !element.paused && !element.ended && !element.muted && element.volume > 0;
+
+  const update = () => {
+    const playing = [...media].some(audible) || [...frames.values()].some(Boolean);
+    if (playing === reported) return;
+    reported = playing;
+    if (isTop) {
+      jsBridge.reportAudio(playing);
+    } else {
+      window.top.postMessage({ __browserAudio: playing }, "*");
+    }
+  };
+
+  for (const type of ["play", "playing", "pause", "ended", "emptied", "volumechange"]) {
+    document.addEventListener(
+      type,
+      (event) => {
+        if (event.target instanceof HTMLMediaElement) {
+          media.add(event.target);
+          update();
+        }
+      },
+      true
+    );
+  }
+
+  if (isTop) {
+    window.addEventListener("message", (event) => {
+      if (!event.data || typeof event.data.__browserAudio !== "boolean") return;
+      frames.set(event.source, event.data.__browserAudio);
+      update();
+    });
+  }
+})();
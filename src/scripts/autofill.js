@@ -0,0 +1,169 @@
+// Autofill: tell the host which profile fields the focused form has, with
+// `autofillField(kinds, x, bottom, width)`, fill them on request and report what a submitted
+// form held with `autofillSubmitted(values)`. Card, password and other sensitive fields are
+// never looked at. Only the top frame, where field positions match the host's.
+(() => {
+  if (window.top !== window || window.__browserAutofill) return;
+
+  const AUTOCOMPLETE = {
+    name: "name",
+    "given-name": "given-name",
+    "family-name": "family-name",
+    email: "email",
+    tel: "tel",
+    "tel-national": "tel",
+    organization: "organization",
+    "street-address": "street-address",
+    "address-line1": "street-address",
+    "address-level2": "address-level2",
+    "address-level1": "address-level1",
+    "postal-code": "postal-code",
+    country: "country",
+    "country-name": "country",
+  };
+  // Guesses from the field's name, id, placeholder and label, first match wins
+  const GUESSES = [
+    [/e-?mail/i, "email"],
+    [/phone|mobile|\btel\b/i, "tel"],
+    [/first.?name|given.?name|\bfname/i, "given-name"],
+    [/last.?name|family.?name|surname|\blname/i, "family-name"],
+    [/company|organi[sz]ation/i, "organization"],
+    [/full.?name|\bname\b/i, "name"],
+    [/address.?(line)?.?1|street|^address$|\baddress\b/i, "street-address"],
+    [/city|town/i, "address-level2"],
+    [/state|province|region|county/i, "address-level1"],
+    [/zip|postal|postcode/i, "postal-code"],
+    [/country/i, "country"],
+  ];
+  const SENSITIVE =
+    /card|\bcc|cvc|cvv|security.?code|expir|iban|account|routing|ssn|social.?security|password|\bpin\b/i;
+  const SKIPPED_TYPES = [
+    "button", "checkbox", "color", "date", "file", "hidden", "image", "password", "radio",
+    "range", "reset", "search", "submit",
+  ];
+
+  const classify = (element) => {
+    const field =
+      element instanceof HTMLInputElement ||
+      element instanceof HTMLSelectElement ||
+      element instanceof HTMLTextAreaElement;
+    if (!field || element.disabled || element.readOnly) return null;
+    const type = (element.getAttribute("type") || "").toLowerCase();
+    if (SKIPPED_TYPES.includes(type)) return null;
+    // sign-in forms are the password manager's
+    const root = element.form || document;
+    if (root.querySelector('input[type="password"]')) return null;
+
+    const tokens = (element.getAttribute("autocomplete") || "").toLowerCase().trim().split(/\s+/);
+    const token = tokens[tokens.length - 1];
+    if (token.startsWith("cc-") || token.includes("password")) return null;
+    if (AUTOCOMPLETE[token]) return AUTOCOMPLETE[token];
+    if (type === "email" || type === "tel") return type;
+
+    const label = element.labels && element.labels[0] ? element.labels[0].textContent : "";
+    const hint = [
+      element.name,
+      element.id,
+      element.getAttribute("placeholder"),
+      element.getAttribute("aria-label"),
+      label,
+    ].join(" ");
+    if (SENSITIVE.test(hint)) return null;
+    const guess = GUESSES.find(([pattern]) => pattern.test(hint));
+    return guess ? guess[1] : null;
+  };
+
+  // The classified fields of `form`, or with `document` those outside any form
+  const fields = (form) =>
+    [...form.querySelectorAll("input, select, textarea")]
+      .filter((field) => form !== document || !field.form)
+      .map((field) => [field, classify(field)])
+      .filter(([, kind]) => kind);
+
+  // The field the menu was last shown for
+  let focused = null;
+  let shown = false;
+  let blurTimer = null;
+
+  const hide = () => {
+    if (!shown) return;
+    shown = false;
+    jsBridge.autofillField([], 0, 0, 0);
+  };
+
+  document.addEventListener(
+    "focusin",
+    (event) => {
+      clearTimeout(blurTimer);
+      const target = event.target;
+      if (!classify(target)) {
+        focused = null;
+        hide();
+        return;
+      }
+      focused = target;
+      shown = true;
+      const kinds = [...new Set(fields(target.form || document).map(([, kind]) => kind))];
+      const rect = target.getBoundingClientRect();
+      jsBridge.autofillField(kinds, rect.left, rect.bottom, rect.width);
+    },
+    true
+  );
+  // late, so a click on the host's menu lands before it's hidden
+  document.addEventListener(
+    "focusout",
+    () => {
+      clearTimeout(blurTimer);
+      blurTimer = setTimeout(hide, 150);
+    },
+    true
+  );
+
+  // Set the value the way typing would, so frameworks watching the field see it
+  const setValue = (field, value) => {
+    if (field instanceof HTMLSelectElement) {
+      const wanted = value.toLowerCase();
+      const option = [...field.options].find(
+        (option) =>
+          option.value.toLowerCase() === wanted || option.text.trim().toLowerCase() === wanted
+      );
+      if (!option) return;
+      field.value = option.value;
+    } else {
+      const prototype = Object.getPrototypeOf(field);
+      const setter = Object.getOwnPropertyDescriptor(prototype, "value").set;
+      setter.call(field, value);
+    }
+    field.dispatchEvent(new Event("input", { bubbles: true }));
+    field.dispatchEvent(new Event("change", { bubbles: true }));
+  };
+
+  // Fill the focused form's empty fields, and the focused one, from `values` by kind
+  const fill = (values) => {
+    if (!focused || !focused.isConnected) return;
+    for (const [field, kind] of fields(focused.form || document)) {
+      const value = values[kind];
+      if (value && (field === focused || !field.value)) setValue(field, value);
+    }
+  };
+
+  document.addEventListener(
+    "submit",
+    (event) => {
+      const form = event.target;
+      if (!(form instanceof HTMLFormElement)) return;
+      const values = {};
+      for (const [field, kind] of fields(form)) {
+        const value =
+          field instanceof HTMLSelectElement && field.selectedIndex >= 0
+            ? field.options[field.selectedIndex].text.trim()
+            : field.value.trim();
+        if (value && !values[kind]) values[kind] = value;
+      }
+      if (Object.keys(values).length > 0) jsBridge.autofillSubmitted(values);
+    },
+    true
+  );
+
+  window.__browserAutofill = { fill };
+})();
@@ -0,0 +1,167 @@
+// Unwrap host replies: resolve with `value`, or reject with an Error carrying `code`
+(() => {
+  if (window.hostCall) return;
+
+  window.hostCall = async (name, ...args) => {
+    const reply = await jsBridge[name](...args);
+    if (reply && reply.status === "err") {
+      const error = new Error(reply.error.message);
+      error.code = reply.error.code;
+      throw error;
+    }
+    return reply && reply.status === "ok" ? reply.value : reply;
+  };
+})();
+
+// Async iterator over a host stream, cancelled on the host when the loop exits early
+(() => {
+  if (window.hostStream) return;
+
+  const streams = new Map();
+  let nextId = 0;
+
+  jsBridge.addEventListener((message) => {
+    if (!message || message.event !== "stream") return;
+    const stream = streams.get(message.id);
+    if (stream) stream.deliver(message);
+  });
+
+  window.hostStream = (name, arg) => {
+    const id = `${Date.now().toString(36)}-${nextId++}`;
+    const queue = [];
+    let pending = null;
+    let done = false;
+    let failure = null;
+
+    const finish = () => {
+      done = true;
+      streams.delete(id);
+      if (pending) pending.resolve({ value: undefined, done: true });
+      pending = null;
+    };
+
+    streams.set(id, {
+      deliver(message) {
+        if (message.done) return finish();
+        if (pending) {
+          pending.resolve({ value: message.value, done: false });
+          pending = null;
+        } else {
+          queue.push(message.value);
+        }
+      },
+    });
+
+    hostCall("startStream", id, name, arg === undefined ? null : arg).catch((error) => {
+      failure = error;
+      if (pending) pending.reject(error);
+      pending = null;
+      finish();
+    });
+
+    return {
+      [Symbol.asyncIterator]() {
+        return this;
+      },
+      next() {
+        if (queue.length) return Promise.resolve({ value: queue.shift(), done: false });
+        if (failure) return Promise.reject(failure);
+        if (done) return Promise.resolve({ value: undefined, done: true });
+        return new Promise((resolve, reject) => (pending = { resolve, reject }));
+      },
+      return() {
+        if (!done) jsBridge.cancelStream(id);
+        finish();
+        return Promise.resolve({ value: undefined, done: true });
+      },
+    };
+  };
+})();
+
+// Binary results of host functions, collected into one Uint8Array. They arrive as base64
+// chunks of a stream, since the bridge only carries JSON:
+//
+//   const bytes = await hostBytes("loadImageBytes", "globe.svg");
+(() => {
+  if (window.hostBytes) return;
+
+  const decode = (base64) => {
+    if (Uint8Array.fromBase64) return Uint8Array.fromBase64(base64);
+    const binary = atob(base64);
+    const bytes = new Uint8Array(binary.length);
+    for (let i = 0; i < binary.length; i++) bytes[i] = binary.charCodeAt(i);
+    return bytes;
+  };
+
+  window.hostBytes = async (name, arg) => {
+    const chunks = [];
+    let length = 0;
+    for await (const chunk of hostStream(name, arg)) {
+      if (chunk.error !== undefined) throw new Error(chunk.error);
+      const bytes = decode(chunk.data);
+      chunks.push(bytes);
+      length += bytes.length;
+    }
+
+    const result = new Uint8Array(length);
+    let offset = 0;
+    for (const bytes of chunks) {
+      result.set(bytes, offset);
+      offset += bytes.length;
+    }
+    return result;
+  };
+})();
+
+// Events pushed by the host to every page:
+//
+//   const off = hostEvents.on("appearanceChanged", ({ dark }) => ...);
+//   off(); // unsubscribe
+//
+// The host delivers them to the top frame, which relays them to its iframes, so listeners
+// work in any frame.
+(() => {
+  if (window.hostEvents) return;
+
+  const listeners = new Map();
+  const RELAY = "__browserHostEvent";
+
+  const dispatch = (message) => {
+    for (const listener of [...(listeners.get(message.event) || [])]) {
+      try {
+        listener(message.data);
+      } catch (error) {
+        console.error(error);
+      }
+    }
+    for (let i = 0; i < window.frames.length; i++) {
+      window.frames[i].postMessage({ [RELAY]: message }, "*");
+    }
+  };
+
+  // reserved for messages answering calls
+  const internal = new Set(["custom", "stream"]);
+
+  if (window.top === window) {
+    jsBridge.addEventListener((message) => {
+      if (message && typeof message.event === "string" && !internal.has(message.event)) {
+        dispatch(message);
+      }
+    });
+  } else {
+    window.addEventListener("message", (event) => {
+      // only accept what the parent frame relays
+      if (event.source === window.parent && event.data && event.data[RELAY]) {
+        dispatch(event.data[RELAY]);
+      }
+    });
+  }
+
+  window.hostEvents = {
+    on(event, listener) {
+      if (!listeners.has(event)) listeners.set(event, new Set());
+      listeners.get(event).add(listener);
+      return () => listeners.get(event).delete(listener);
+    },
+  };
+})();
@@ -0,0 +1,47 @@
+// Forward console output and uncaught errors to the host's console panel
+(() => {
+  if (window.__browserConsole) return;
+  window.__browserConsole = true;
+
+  const format = (value) => {
+    if (typeof value === "string") return value;
+    if (value instanceof Error) return value.stack || String(value);
+    try {
+      const json = JSON.stringify(value);
+      return json === undefined ? String(value) : json;
+    } catch (_) {
+      return String(value);
+    }
+  };
+
+  // script URL and line of the caller, from the third line of a fresh stack trace
+  // ("Error", this function, the wrapper, then the caller)
+  const caller = () => {
+    const line = (new Error().stack || "").split("\n")[3] || "";
+    const match = line.match(/(\S+?):(\d+):\d+\)?\s*$/);
+    return match ? [match[1].replace(/^\(/, ""), Number(match[2])] : [location.href, 0];
+  };
+
+  const report = (level, message, source, line) => {
+    try {
+      jsBridge.consoleMessage(level, message, source, line);
+    } catch (_) {}
+  };
+
+  for (const level of ["debug", "log", "info", "warn", "error"]) {
+    const original = console[level];
+    console[level] = function (...args) {
+      const [source, line] = caller();
+      report(level, args.map(format).join(" "), source, line);
+      return original.apply(this, args);
+    };
+  }
+
+  window.addEventListener("error", (event) => {
+    const message = event.error ? format(event.error) : event.message;
+    report("error", `Uncaught ${message}`, event.filename || location.href, event.lineno || 0);
+  });
+  window.addEventListener("unhandledrejection", (event) => {
+    report("error", `Uncaught (in promise) ${format(event.reason)}`, location.href, 0);
+  });
+})();
@@ -0,0 +1,77 @@
+// Replace the native context menu with the host's, unless the page handles it itself
+(() => {
+  if (window.__browserContextMenu) return;
+  window.__browserContextMenu = true;
+
+  const WORD_CHAR = /[\p{L}\p{M}'’]/u;
+  // Inputs whose text is prose worth checking
+  const TEXT_INPUT = "textarea, input:not([type]), input[type=text], input[type=search]";
+
+  // The text field that was last right-clicked and the word under the cursor in it
+  let field = null;
+
+  const wordBounds = (text, offset) => {
+    let start = offset;
+    let end = offset;
+    while (start > 0 && WORD_CHAR.test(text[start - 1])) start--;
+    while (end < text.length && WORD_CHAR.test(text[end])) end++;
+    return [start, end];
+  };
+
+  // In inputs the word at the caret, which the right-click moved to the cursor
+  const fieldAt = (target, event) => {
+    if (target.matches && target.matches(TEXT_INPUT)) {
+      const [start, end] = wordBounds(target.value, target.selectionStart ?? 0);
+      return { element: target, start, end, word: target.value.slice(start, end) };
+    }
+    if (target.matches && target.matches("input")) {
+      return { element: target, word: "" };
+    }
+
+    const range = document.caretRangeFromPoint(event.clientX, event.clientY);
+    if (!range || range.startContainer.nodeType !== Node.TEXT_NODE) {
+      return { element: target, word: "" };
+    }
+    const node = range.startContainer;
+    const [start, end] = wordBounds(node.data, range.startOffset);
+    const word = document.createRange();
+    word.setStart(node, start);
+    word.setEnd(node, end);
+    return { element: target, range: word, word: word.toString() };
+  };
+
+  // Swap the word for `text` through the editing commands, so it can be undone
+  window.__browserReplaceWord = (text) => {
+    if (!field || !field.word) return;
+    if (field.range) {
+      const selection = window.getSelection();
+      selection.removeAllRanges();
+      selection.addRange(field.range);
+    } else {
+      field.element.focus();
+      field.element.setSelectionRange(field.start, field.end);
+    }
+    document.execCommand("insertText", false, text);
+  };
+
+  window.__browserToggleSpellcheck = () => {
+    if (field) field.element.spellcheck = !field.element.spellcheck;
+  };
+
+  document.addEventListener("contextmenu", (event) => {
+    if (event.defaultPrevented) return;
+    event.preventDefault();
+
+    const target = event.target;
+    const link = target.closest && target.closest("a[href]");
+    const image = target instanceof HTMLImageElement ? target.currentSrc || target.src : "";
+    const editable = target.isContentEditable || (target.matches && target.matches("input, textarea"));
+    field = editable ? fieldAt(target, event) : null;
+    jsBridge.contextMenu(
+      link ? link.href : "",
+      image,
+      String(window.getSelection()),
+      field && { word: field.word, spellcheck: field.element.spellcheck }
+    );
+  });
+})();
@@ -0,0 +1,17 @@
+// Hand links marked for download to the host's download manager
+(() => {
+  if (window.__browserDownloads) return;
+  window.__browserDownloads = true;
+
+  document.addEventListener(
+    "click",
+    (event) => {
+      const link = event.target.closest && event.target.closest("a[download][href]");
+      if (!link || event.button !== 0 || !/^https?:/.test(link.href)) return;
+
+      event.preventDefault();
+      jsBridge.download(link.href, link.getAttribute("download") || "");
+    },
+    true
+  );
+})();
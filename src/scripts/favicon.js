@@ -0,0 +1,23 @@
+// Report the icon the page declares with `<link rel="icon">`, or "" to use `/favicon.ico`
+(() => {
+  if (window.top !== window || window.__browserFavicon) return;
+  window.__browserFavicon = true;
+
+  let current = null;
+  const report = () => {
+    const link = document.querySelector('link[rel~="icon" i][href]');
+    const href = link ? link.href : "";
+    if (href === current) return;
+    current = href;
+    jsBridge.reportFavicon(location.href, href);
+  };
+
+  report();
+  // pages that swap their icon, e.g. to show unread counts
+  new MutationObserver(report).observe(document.head || document.documentElement, {
+    childList: true,
+    subtree: true,
+    attributes: true,
+    attributeFilter: ["href", "rel"],
+  });
+})();
@@ -0,0 +1,100 @@
+// Find in page: matches are CSS highlights over ranges, so they stay put while the page
+// scrolls and the current one can be drawn differently. Reports `findResult(current, count)`.
+(() => {
+  if (window.top !== window || window.__browserFind) return;
+
+  // Past this many the page is searched no further
+  const MAX_MATCHES = 1000;
+  const SKIPPED = "script, style, noscript, template, head";
+
+  let search = null;
+
+  const style = document.createElement("style");
+  style.textContent = `
+    ::highlight(browser-find) { background-color: rgba(255, 213, 79, 0.45); }
+    ::highlight(browser-find-current) { background-color: #ff9632; color: #000; }
+  `;
+
+  const findRanges = (query, matchCase) => {
+    const needle = matchCase ? query : query.toLowerCase();
+    const ranges = [];
+    const root = document.body || document.documentElement;
+    const walker = document.createTreeWalker(root, NodeFilter.SHOW_TEXT, {
+      acceptNode: (node) =>
+        node.parentElement && !node.parentElement.closest(SKIPPED)
+          ? NodeFilter.FILTER_ACCEPT
+          : NodeFilter.FILTER_REJECT,
+    });
+    while (walker.nextNode() && ranges.length < MAX_MATCHES) {
+      const node = walker.currentNode;
+      const text = matchCase ? node.data : node.data.toLowerCase();
+      let at = text.indexOf(needle);
+      for (; at !== -1; at = text.indexOf(needle, at + needle.length)) {
+        // text in collapsed or hidden elements has no boxes to highlight
+        const range = document.createRange();
+        range.setStart(node, at);
+        range.setEnd(node, at + needle.length);
+        if (range.getClientRects().length > 0) ranges.push(range);
+        if (ranges.length === MAX_MATCHES) break;
+      }
+    }
+    return ranges;
+  };
+
+  // Ranges collapse when the text they covered changes
+  const stale = () =>
+    search.ranges.some((range) => range.collapsed || !range.startContainer.isConnected);
+
+  const scrollToCurrent = () => {
+    const range = search.ranges[search.current];
+    const rect = range.getBoundingClientRect();
+    const visible =
+      rect.top >= 0 &&
+      rect.bottom <= window.innerHeight &&
+      rect.left >= 0 &&
+      rect.right <= window.innerWidth;
+    if (!visible) {
+      range.startContainer.parentElement.scrollIntoView({ block: "center", inline: "nearest" });
+    }
+  };
+
+  const paint = () => {
+    CSS.highlights.delete("browser-find");
+    CSS.highlights.delete("browser-find-current");
+    if (!search || search.ranges.length === 0) return;
+    if (!style.isConnected) document.documentElement.appendChild(style);
+    if (search.highlightAll) {
+      CSS.highlights.set("browser-find", new Highlight(...search.ranges));
+    }
+    CSS.highlights.set("browser-find-current", new Highlight(search.ranges[search.current]));
+  };
+
+  // `step` moves the current match by one either way, wrapping around; 0 searches again
+  window.__browserFind = (query, matchCase, highlightAll, step) => {
+    if (!query) {
+      window.__browserStopFind();
+      jsBridge.findResult(0, 0);
+      return;
+    }
+    const same = search && search.query === query && search.matchCase === matchCase;
+    if (!same || step === 0 || stale()) {
+      const previous = same ? search.current : 0;
+      const ranges = findRanges(query, matchCase);
+      const current = Math.min(previous, Math.max(ranges.length - 1, 0));
+      search = { query, matchCase, highlightAll, ranges, current };
+    } else if (search.ranges.length > 0) {
+      const count = search.ranges.length;
+      search.current = (search.current + step + count) % count;
+    }
+    search.highlightAll = highlightAll;
+
+    paint();
+    if (search.ranges.length > 0) scrollToCurrent();
+    jsBridge.findResult(search.ranges.length > 0 ? search.current + 1 : 0, search.ranges.length);
+  };
+
+  window.__browserStopFind = () => {
+    search = null;
+    paint();
+  };
+})();
@@ -0,0 +1,55 @@
+// Tell the host whether the user has typed into the page since it was submitted, so the tab
+// isn't discarded with their input. Child frames report to the top frame, which combines them.
+(() => {
+  if (window.__browserForms) return;
+  window.__browserForms = true;
+
+  const isTop = window.top === window;
+  const frames = new Map();
+  let edited = false;
+  let reported = false;
+
+  const update = () => {
+    const unsaved = edited || [...frames.values()].some(Boolean);
+    if (unsaved === reported) return;
+    reported = unsaved;
+    if (isTop) {
+      jsBridge.reportFormInput(unsaved);
+    } else {
+      window.top.postMessage({ __browserForms: unsaved }, "*");
+    }
+  };
+
+  document.addEventListener(
+    "input",
+    (event) => {
+      const target = event.target;
+      if (
+        target instanceof HTMLInputElement ||
+        target instanceof HTMLTextAreaElement ||
+        target instanceof HTMLSelectElement ||
+        target.isContentEditable
+      ) {
+        edited = true;
+        update();
+      }
+    },
+    true
+  );
+  document.addEventListener(
+    "submit",
+    () => {
+      edited = false;
+      update();
+    },
+    true
+  );
+
+  if (isTop) {
+    window.addEventListener("message", (event) => {
+      if (!event.data || typeof event.data.__browserForms !== "boolean") return;
+      frames.set(event.source, event.data.__browserForms);
+      update();
+    });
+  }
+})();
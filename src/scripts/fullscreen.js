@@ -0,0 +1,9 @@
+// Tell the host when the page enters or leaves fullscreen, e.g. for a video player
+(() => {
+  if (window.top !== window || window.__browserFullscreen) return;
+  window.__browserFullscreen = true;
+
+  document.addEventListener("fullscreenchange", () => {
+    jsBridge.reportFullscreen(Boolean(document.fullscreenElement));
+  });
+})();
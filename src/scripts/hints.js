@@ -0,0 +1,111 @@
+// Link hints: press `f` outside a text field to label everything clickable on screen. The host
+// draws the labels and sends back the one typed, see `hints.rs`.
+(() => {
+  if (window.top !== window || window.__browserHints) return;
+
+  const ALPHABET = "asdfghjkl";
+  const CLICKABLE =
+    "a[href], button, input:not([type=hidden]), select, textarea, summary, [role=button], [role=link], [onclick], [contenteditable=true]";
+
+  let targets = new Map();
+  let active = false;
+  let refreshTimer = null;
+  let observer = null;
+
+  const editable = (element) =>
+    element.isContentEditable || (element.matches && element.matches("input, textarea, select"));
+
+  // Equal-length labels, so no label is a prefix of another
+  const labels = (count) => {
+    let length = 1;
+    while (ALPHABET.length ** length < count) length++;
+    return Array.from({ length: count }, (_, ix) => {
+      let label = "";
+      for (let n = ix, i = 0; i < length; i++, n = Math.floor(n / ALPHABET.length)) {
+        label = ALPHABET[n % ALPHABET.length] + label;
+      }
+      return label;
+    });
+  };
+
+  const visibleRect = (element) => {
+    for (const rect of element.getClientRects()) {
+      const onScreen =
+        rect.width > 0 &&
+        rect.height > 0 &&
+        rect.bottom > 0 &&
+        rect.right > 0 &&
+        rect.top < window.innerHeight &&
+        rect.left < window.innerWidth;
+      if (!onScreen) continue;
+
+      // skip elements covered by something else, like a modal backdrop
+      const x = Math.min(Math.max(rect.left + 1, 0), window.innerWidth - 1);
+      const y = Math.min(Math.max(rect.top + 1, 0), window.innerHeight - 1);
+      const hit = document.elementFromPoint(x, y);
+      if (hit && (hit === element || element.contains(hit) || hit.contains(element))) return rect;
+    }
+    return null;
+  };
+
+  const report = () => {
+    const found = [];
+    for (const element of document.querySelectorAll(CLICKABLE)) {
+      const rect = visibleRect(element);
+      if (rect) found.push({ element, rect });
+    }
+
+    const names = labels(found.length);
+    targets = new Map(found.map(({ element }, ix) => [names[ix], element]));
+    jsBridge.reportHints(
+      found.map(({ rect }, ix) => ({ label: names[ix], x: rect.left, y: rect.top }))
+    );
+  };
+
+  // Labels follow the page as it scrolls or changes, a little after it settles
+  const scheduleRefresh = () => {
+    clearTimeout(refreshTimer);
+    refreshTimer = setTimeout(() => active && report(), 100);
+  };
+
+  const start = () => {
+    active = true;
+    report();
+    window.addEventListener("scroll", scheduleRefresh, true);
+    window.addEventListener("resize", scheduleRefresh);
+    observer = new MutationObserver(scheduleRefresh);
+    observer.observe(document.documentElement, { childList: true, subtree: true });
+  };
+
+  const stop = () => {
+    active = false;
+    targets = new Map();
+    clearTimeout(refreshTimer);
+    window.removeEventListener("scroll", scheduleRefresh, true);
+    window.removeEventListener("resize", scheduleRefresh);
+    if (observer) observer.disconnect();
+    observer = null;
+  };
+
+  const activate = (label) => {
+    const element = targets.get(label);
+    stop();
+    if (!element) return;
+
+    element.focus();
+    if (!editable(element)) element.click();
+  };
+
+  document.addEventListener(
+    "keydown",
+    (event) => {
+      if (active || event.key !== "f" || event.metaKey || event.ctrlKey || event.altKey) return;
+      if (event.defaultPrevented || editable(event.target)) return;
+      event.preventDefault();
+      start();
+    },
+    true
+  );
+
+  window.__browserHints = { activate, stop };
+})();
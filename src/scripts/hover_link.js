@@ -0,0 +1,23 @@
+// Report the destination of the link under the mouse, for the status bar
+(() => {
+  if (window.__browserHoverLink) return;
+  window.__browserHoverLink = true;
+
+  let current = "";
+  const report = (url) => {
+    if (url === current) return;
+    current = url;
+    jsBridge.hoverLink(url);
+  };
+
+  document.addEventListener(
+    "mouseover",
+    (event) => {
+      const link = event.target.closest && event.target.closest("a[href]");
+      report(link ? link.href : "");
+    },
+    true
+  );
+  document.documentElement.addEventListener("mouseleave", () => report(""));
+  window.addEventListener("pagehide", () => report(""));
+})();
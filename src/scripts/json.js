@@ -0,0 +1,15 @@
+// Hand a raw JSON document's text to the host, which shows it as a tree over the page
+(() => {
+  if (window.top !== window || window.__browserJson) return;
+  window.__browserJson = true;
+
+  // Bigger documents are left as text rather than copied across the bridge
+  const MAX_LENGTH = 20_000_000;
+
+  if (!/^application\/([\w.-]+\+)?json$/.test(document.contentType)) return;
+  // Chromium puts the body in a <pre>, newer versions add a pretty-print toggle next to it
+  const pre = document.querySelector("body > pre");
+  const text = pre ? pre.textContent : document.body ? document.body.textContent : "";
+  if (text.length > MAX_LENGTH) return;
+  jsBridge.reportJson(text);
+})();
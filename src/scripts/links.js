@@ -0,0 +1,62 @@
+// Route new-tab link clicks and window.open through the host instead of popups. Injected as
+// the document starts loading, so clicks and popups during the load are caught too.
+(() => {
+  if (window.__browserLinks) return;
+  window.__browserLinks = true;
+
+  const openTab = (url, foreground) => jsBridge.openTab(String(url), foreground);
+  const linkFor = (event) => event.target.closest && event.target.closest("a[href]");
+
+  document.addEventListener(
+    "click",
+    (event) => {
+      const link = linkFor(event);
+      if (!link || event.button !== 0) return;
+
+      const modified = event.metaKey || event.ctrlKey;
+      if (modified || link.target === "_blank") {
+        event.preventDefault();
+        // modifier clicks open in the background unless shift is held too
+        openTab(link.href, !modified || event.shiftKey);
+      }
+    },
+    true
+  );
+
+  document.addEventListener(
+    "auxclick",
+    (event) => {
+      const link = linkFor(event);
+      if (!link || event.button !== 1) return;
+
+      event.preventDefault();
+      openTab(link.href, false);
+    },
+    true
+  );
+
+  // Stands in for the popup, which lives in a tab this page can't reach
+  const stubWindow = (href) => {
+    let closed = false;
+    return {
+      get closed() {
+        return closed;
+      },
+      close: () => {
+        closed = true;
+      },
+      focus: () => {},
+      blur: () => {},
+      postMessage: () => {},
+      opener: null,
+      location: { href },
+      document: null,
+    };
+  };
+
+  window.open = (url) => {
+    const href = url ? new URL(url, location.href).href : "about:blank";
+    if (url) openTab(href, true);
+    return stubWindow(href);
+  };
+})();
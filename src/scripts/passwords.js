@@ -0,0 +1,136 @@
+// Passwords: tell the host when a sign-in field gets focus, with
+// `passwordField(focused, x, bottom, width)`, fill a saved login on request and report what a
+// sign-in form was sent with by `passwordSubmitted(username, password)`. The host takes the
+// origin from the frame. Only the top frame, so logins are never filled into or read from a
+// frame of another origin.
+(() => {
+  if (window.top !== window || window.__browserPasswords) return;
+
+  const USERNAME_TYPES = ["", "text", "email", "tel"];
+
+  // The username and password fields of the form `field` is in, or of the fields outside any
+  // form; the username is the last text field before the first password field
+  const loginFields = (field) => {
+    const root = field.form || document;
+    const inputs = [...root.querySelectorAll("input")].filter(
+      (input) => !input.disabled && (root !== document || !input.form)
+    );
+    const passwords = inputs.filter((input) => input.type === "password");
+    if (passwords.length === 0) return null;
+    const first = passwords[0];
+    const before = inputs.filter(
+      (input) =>
+        USERNAME_TYPES.includes((input.getAttribute("type") || "").toLowerCase()) &&
+        input.compareDocumentPosition(first) & Node.DOCUMENT_POSITION_FOLLOWING
+    );
+    const username =
+      before.find((input) => /username|email/i.test(input.autocomplete)) ||
+      before[before.length - 1] ||
+      null;
+    return { username, passwords };
+  };
+
+  // The field the menu was last shown for
+  let focused = null;
+  let shown = false;
+  let blurTimer = null;
+
+  const hide = () => {
+    if (!shown) return;
+    shown = false;
+    jsBridge.passwordField(false, 0, 0, 0);
+  };
+
+  document.addEventListener(
+    "focusin",
+    (event) => {
+      clearTimeout(blurTimer);
+      const target = event.target;
+      const fields = target instanceof HTMLInputElement ? loginFields(target) : null;
+      if (!fields || (target !== fields.username && target !== fields.passwords[0])) {
+        focused = null;
+        hide();
+        return;
+      }
+      focused = target;
+      shown = true;
+      const rect = target.getBoundingClientRect();
+      jsBridge.passwordField(true, rect.left, rect.bottom, rect.width);
+    },
+    true
+  );
+  // late, so a click on the host's menu lands before it's hidden
+  document.addEventListener(
+    "focusout",
+    () => {
+      clearTimeout(blurTimer);
+      blurTimer = setTimeout(hide, 150);
+    },
+    true
+  );
+
+  const setValue = (field, value) => {
+    const setter = Object.getOwnPropertyDescriptor(HTMLInputElement.prototype, "value").set;
+    setter.call(field, value);
+    field.dispatchEvent(new Event("input", { bubbles: true }));
+    field.dispatchEvent(new Event("change", { bubbles: true }));
+  };
+
+  // Filled only while the page is still on the origin the login was saved for
+  const fill = (origin, username, password) => {
+    if (location.origin !== origin || !focused || !focused.isConnected) return;
+    const fields = loginFields(focused);
+    if (!fields) return;
+    if (fields.username) setValue(fields.username, username);
+    setValue(fields.passwords[0], password);
+  };
+
+  // Sign-up and change forms repeat the new password last
+  let reported = "";
+  const report = (field) => {
+    const fields = loginFields(field);
+    if (!fields) return;
+    const password = fields.passwords
+      .map((input) => input.value)
+      .filter(Boolean)
+      .pop();
+    if (!password) return;
+    const username = fields.username ? fields.username.value.trim() : "";
+    const key = JSON.stringify([username, password]);
+    if (key === reported) return;
+    reported = key;
+    jsBridge.passwordSubmitted(username, password);
+  };
+
+  document.addEventListener(
+    "submit",
+    (event) => {
+      const field = event.target.querySelector && event.target.querySelector("input");
+      if (field) report(field);
+    },
+    true
+  );
+  // pages that sign in from script never submit the form
+  document.addEventListener(
+    "click",
+    (event) => {
+      const button = event.target.closest && event.target.closest('button, input[type="submit"]');
+      if (!button) return;
+      const root = button.form || document;
+      const password = root.querySelector('input[type="password"]');
+      if (password) report(password);
+    },
+    true
+  );
+  document.addEventListener(
+    "keydown",
+    (event) => {
+      if (event.key === "Enter" && event.target instanceof HTMLInputElement) {
+        report(event.target);
+      }
+    },
+    true
+  );
+
+  window.__browserPasswords = { fill };
+})();
@@ -0,0 +1,77 @@
+// Tell the host whether the page has a video that can float in Picture-in-Picture, and
+// whether one is floating. Child frames report to the top frame, which combines them.
+(() => {
+  if (window.__browserPictureInPicture) return;
+  window.__browserPictureInPicture = true;
+  if (!document.pictureInPictureEnabled) return;
+
+  const isTop = window.top === window;
+  const videos = new Set();
+  const frames = new Map();
+  let reported = "";
+
+  const eligible = (video) =>
+    video.isConnected && !video.disablePictureInPicture && video.readyState > 0 && !video.ended;
+
+  const update = () => {
+    const state = {
+      video: [...videos].some(eligible) || [...frames.values()].some((frame) => frame.video),
+      active:
+        Boolean(document.pictureInPictureElement) ||
+        [...frames.values()].some((frame) => frame.active),
+    };
+    const key = JSON.stringify(state);
+    if (key === reported) return;
+    reported = key;
+    if (isTop) {
+      jsBridge.reportVideo(state.video, state.active);
+    } else {
+      window.top.postMessage({ __browserPictureInPicture: state }, "*");
+    }
+  };
+
+  for (const type of ["loadedmetadata", "play", "pause", "ended", "emptied"]) {
+    document.addEventListener(
+      type,
+      (event) => {
+        if (event.target instanceof HTMLVideoElement) {
+          videos.add(event.target);
+          update();
+        }
+      },
+      true
+    );
+  }
+  for (const type of ["enterpictureinpicture", "leavepictureinpicture"]) {
+    document.addEventListener(type, update, true);
+  }
+
+  // The playing video, or else the largest one on screen
+  const pickVideo = () => {
+    const candidates = [...document.querySelectorAll("video")].filter(eligible);
+    const area = (video) => video.clientWidth * video.clientHeight;
+    return (
+      candidates.find((video) => !video.paused) ||
+      candidates.sort((a, b) => area(b) - area(a))[0]
+    );
+  };
+
+  // Run by the host as a user gesture, which the Picture-in-Picture API requires
+  window.__browserTogglePictureInPicture = () => {
+    if (document.pictureInPictureElement) {
+      document.exitPictureInPicture().catch(() => {});
+      return;
+    }
+    const video = pickVideo();
+    if (video) video.requestPictureInPicture().catch(() => {});
+  };
+
+  if (isTop) {
+    window.addEventListener("message", (event) => {
+      const state = event.data && event.data.__browserPictureInPicture;
+      if (!state || typeof state.video !== "boolean") return;
+      frames.set(event.source, state);
+      update();
+    });
+  }
+})();
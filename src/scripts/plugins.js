@@ -0,0 +1,116 @@
+// Plugins: each plugin's script is called with its own frozen `browser` object, the only host
+// API it's given. Calls carry the plugin's token, so the host checks them against that plugin's
+// manifest; a plugin can never act on another tab. Only the top frame.
+//
+// Run as the document starts, before any of the page's scripts, with the plugins matching the
+// page: the runtime keeps the bridge functions as they are then and the tokens in its closure,
+// so a page can't stand in for it or listen in on the calls. `start` runs the plugin scripts
+// once the page has loaded.
+(apiVersion, entries) => {
+  if (window.top !== window || "__browserPlugins" in window) return;
+
+  const bridge = {};
+  const names = ["pluginNavigate", "pluginCurrentUrl", "pluginInject", "pluginEmit", "pluginNotify"];
+  for (const name of names) bridge[name] = jsBridge[name].bind(jsBridge);
+  const HostError = Error;
+
+  // The reply's `value`, or an Error carrying its `code`, as `hostCall` does
+  const unwrap = (reply) => {
+    if (reply && reply.status === "err") {
+      const error = new HostError(reply.error.message);
+      error.code = reply.error.code;
+      throw error;
+    }
+    return reply && reply.status === "ok" ? reply.value : reply;
+  };
+
+  // Registered functions and event listeners by plugin id
+  const plugins = new Map();
+  // Plugin scripts waiting for `start`, each called with its `browser` object
+  const pending = [];
+
+  const load = (id, token, body) => {
+    if (plugins.has(id)) return;
+    const state = { functions: new Map(), listeners: new Map() };
+    plugins.set(id, state);
+
+    const browser = Object.freeze({
+      apiVersion,
+      id,
+      // Load a web page in this tab, needs the "navigate" permission
+      navigate: async (url) => unwrap(await bridge.pluginNavigate(token, String(url))),
+      // The tab's URL as the address bar shows it
+      currentUrl: async () => unwrap(await bridge.pluginCurrentUrl()),
+      // Run `code` in the page outside its content security policy, needs "inject_script"
+      injectScript: async (code) => unwrap(await bridge.pluginInject(token, String(code))),
+      // Send an event to the listeners of every plugin in the pages of the window this
+      // plugin runs in
+      emit: async (event, detail) =>
+        unwrap(
+          await bridge.pluginEmit(token, String(event), detail === undefined ? null : detail)
+        ),
+      // Show a short message in the browser window
+      notify: async (message) => unwrap(await bridge.pluginNotify(token, String(message))),
+      // Make `fn` callable by the plugin's toolbar buttons
+      register: (name, fn) => {
+        if (typeof fn === "function") state.functions.set(String(name), fn);
+      },
+      // Listen to "navigate" with `{ url }`, or to events plugins emit,
+      // called with `(detail, fromPluginId)`
+      on: (event, handler) => {
+        if (typeof handler !== "function") return;
+        const name = String(event);
+        if (!state.listeners.has(name)) state.listeners.set(name, []);
+        state.listeners.get(name).push(handler);
+      },
+    });
+
+    pending.push(() => {
+      try {
+        body(browser);
+      } catch (err) {
+        console.error("Plugin", id, "failed:", err);
+      }
+    });
+  };
+
+  for (const { id, token, body } of entries) load(id, token, body);
+
+  // by index rather than iterator, which the page may have replaced by now
+  let started = false;
+  const start = () => {
+    if (started) return;
+    started = true;
+    for (let i = 0; i < pending.length; i++) pending[i]();
+  };
+
+  const dispatch = (event, detail, from) => {
+    for (const [id, state] of plugins) {
+      for (const handler of state.listeners.get(event) || []) {
+        try {
+          handler(detail, from);
+        } catch (err) {
+          console.error("Plugin", id, "failed handling", event, err);
+        }
+      }
+    }
+  };
+
+  const call = async (id, name) => {
+    const fn = plugins.has(id) && plugins.get(id).functions.get(name);
+    if (!fn) {
+      console.warn("Plugin", id, "has no function", name);
+      return;
+    }
+    try {
+      await fn();
+    } catch (err) {
+      console.error("Plugin", id, "failed in", name, err);
+    }
+  };
+
+  // neither writable nor configurable, so it stays the runtime for the life of the document
+  Object.defineProperty(window, "__browserPlugins", {
+    value: Object.freeze({ start, dispatch, call }),
+  });
+}
@@ -0,0 +1,91 @@
+// Reader mode: tell the host whether the page looks like an article, and extract it on request
+(() => {
+  if (window.top !== window || window.__browserReader) return;
+
+  // Least text in the main content before a page counts as an article
+  const MIN_ARTICLE_LENGTH = 1200;
+  const UNLIKELY = /banner|comment|cookie|footer|header|menu|nav|popup|promo|related|share|sidebar|social|sponsor|subscribe|\bads?\b|advert/i;
+  const SKIPPED = "aside, button, footer, form, header, iframe, nav, noscript, script, style, svg";
+
+  const unlikely = (element) =>
+    element.closest(SKIPPED) ||
+    UNLIKELY.test(`${element.className} ${element.id} ${element.getAttribute("role") || ""}`);
+
+  const text = (element) => element.textContent.replace(/\s+/g, " ").trim();
+
+  // Score containers by the paragraphs directly inside them, like Readability does
+  const findContent = () => {
+    const scores = new Map();
+    for (const paragraph of document.querySelectorAll("p, pre, td")) {
+      if (unlikely(paragraph)) continue;
+      const length = text(paragraph).length;
+      if (length < 25) continue;
+
+      const score = 1 + paragraph.textContent.split(",").length + Math.min(length / 100, 3);
+      const parent = paragraph.parentElement;
+      if (!parent) continue;
+      scores.set(parent, (scores.get(parent) || 0) + score);
+      const grandparent = parent.parentElement;
+      if (grandparent) scores.set(grandparent, (scores.get(grandparent) || 0) + score / 2);
+    }
+
+    let best = null;
+    let bestScore = 0;
+    for (const [element, score] of scores) {
+      if (score > bestScore && !unlikely(element)) {
+        best = element;
+        bestScore = score;
+      }
+    }
+    return best;
+  };
+
+  const isReadable = () => {
+    const content = findContent();
+    if (!content) return false;
+    const length = [...content.querySelectorAll("p")].reduce((sum, p) => sum + text(p).length, 0);
+    return length >= MIN_ARTICLE_LENGTH;
+  };
+
+  const extract = () => {
+    const content = findContent();
+    const blocks = [];
+    const walk = (element) => {
+      for (const child of element.children) {
+        if (unlikely(child)) continue;
+
+        const tag = child.tagName.toLowerCase();
+        if (/^h[1-6]$/.test(tag)) {
+          const heading = text(child);
+          if (heading) blocks.push({ kind: "heading", level: Number(tag[1]), text: heading });
+        } else if (tag === "img") {
+          const src = child.currentSrc || child.src;
+          const small = child.naturalWidth && child.naturalWidth < 100;
+          if (src && !small) blocks.push({ kind: "image", src, alt: child.alt || "" });
+        } else if (["p", "blockquote", "pre", "li", "figcaption"].includes(tag)) {
+          for (const image of child.querySelectorAll("img")) walk({ children: [image] });
+          const paragraph = text(child);
+          if (paragraph) {
+            blocks.push({ kind: "paragraph", text: tag === "li" ? `• ${paragraph}` : paragraph });
+          }
+        } else {
+          walk(child);
+        }
+      }
+    };
+    if (content) walk(content);
+
+    const heading = content && content.querySelector("h1");
+    const title = heading ? text(heading) : document.title;
+    const author = document.querySelector('meta[name="author"]');
+    jsBridge.readerContent({
+      title,
+      byline: author ? author.content : null,
+      // the title is shown above the blocks already
+      blocks: blocks.filter((block) => !(block.kind === "heading" && block.text === title)),
+    });
+  };
+
+  window.__browserReader = { extract, findContent };
+  jsBridge.reportReadable(isReadable());
+})();
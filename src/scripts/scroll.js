@@ -0,0 +1,63 @@
+// Report the page scroll offset to the host, debounced, and restore offsets it hands back.
+// Split view can also link the scrolling of two pages.
+(() => {
+  if (window.top !== window || window.__browserScroll) return;
+  window.__browserScroll = true;
+
+  let timer = null;
+  window.addEventListener(
+    "scroll",
+    () => {
+      clearTimeout(timer);
+      timer = setTimeout(() => jsBridge.reportScroll(window.scrollX, window.scrollY), 250);
+    },
+    { passive: true }
+  );
+
+  // While split view links this page's scrolling to the other pane's, every scroll is
+  // reported right away, except the ones `__browserScrollTo` made to follow the other pane
+  let linked = false;
+  let frame = null;
+  let followed = null;
+  window.addEventListener(
+    "scroll",
+    () => {
+      if (!linked || frame) return;
+      frame = requestAnimationFrame(() => {
+        frame = null;
+        const [x, y] = [window.scrollX, window.scrollY];
+        if (followed && followed[0] === x && followed[1] === y) return;
+        followed = null;
+        jsBridge.linkedScroll(x, y);
+      });
+    },
+    { passive: true }
+  );
+  window.__browserLinkScroll = (enabled) => {
+    linked = enabled;
+    followed = null;
+  };
+  window.__browserScrollTo = (x, y) => {
+    window.scrollTo({ left: x, top: y, behavior: "instant" });
+    followed = [window.scrollX, window.scrollY];
+  };
+
+  // Content often arrives after the load event, so keep trying until the page is tall
+  // enough for the offset, for a few seconds, or until the user scrolls themselves
+  window.__browserRestoreScroll = (x, y) => {
+    const deadline = Date.now() + 3000;
+    let interrupted = false;
+    const interrupt = () => (interrupted = true);
+    for (const type of ["wheel", "keydown", "mousedown", "touchstart"]) {
+      window.addEventListener(type, interrupt, { once: true, passive: true });
+    }
+
+    const attempt = () => {
+      if (interrupted) return;
+      window.scrollTo(x, y);
+      const reached = Math.abs(window.scrollY - y) <= 1 && Math.abs(window.scrollX - x) <= 1;
+      if (!reached && Date.now() < deadline) setTimeout(attempt, 100);
+    };
+    attempt();
+  };
+})();
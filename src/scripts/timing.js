@@ -0,0 +1,22 @@
+// Report the page's Navigation Timing once the load event has run, with milliseconds since
+// the navigation started: `reportLoadTiming(domContentLoaded, load)`, -1 where unknown
+(() => {
+  if (window.top !== window || window.__browserTiming) return;
+  window.__browserTiming = true;
+
+  const report = () => {
+    const [entry] = performance.getEntriesByType("navigation");
+    if (!entry) return jsBridge.reportLoadTiming(-1, -1);
+    jsBridge.reportLoadTiming(
+      entry.domContentLoadedEventEnd > 0 ? entry.domContentLoadedEventEnd : -1,
+      entry.loadEventEnd > 0 ? entry.loadEventEnd : -1
+    );
+  };
+
+  // loadEventEnd is only set once the load handlers return
+  if (document.readyState === "complete") {
+    setTimeout(report, 0);
+  } else {
+    window.addEventListener("load", () => setTimeout(report, 0), { once: true });
+  }
+})();
@@ -0,0 +1,138 @@
+// Read aloud: splits the selection, or else the article the reader script finds, into
+// sentences for the host with `ttsSentences(texts)`, and highlights the one being read. With
+// the page engine the sentences are spoken here too, reporting `ttsSpoken(index, error)`.
+(() => {
+  if (window.top !== window || window.__browserTts) return;
+
+  const SKIPPED = "aside, button, footer, form, iframe, nav, noscript, script, style, svg, template";
+  const BLOCKS = "p, li, h1, h2, h3, h4, h5, h6, blockquote, pre, td, th, dd, dt, figcaption, div";
+
+  // Ranges of the sentences and their text, as sent to the host
+  let sentences = [];
+  let texts = [];
+  // Being spoken by the page engine
+  let utterance = null;
+
+  const style = document.createElement("style");
+  style.textContent = "::highlight(browser-tts) { background-color: rgba(10, 132, 255, 0.3); }";
+
+  // Text under `root`, or only the part of it inside `range`, one run per block so sentences
+  // don't cross from one paragraph into the next
+  const textRuns = (root, range) => {
+    const runs = [];
+    let run = null;
+    const walker = document.createTreeWalker(root, NodeFilter.SHOW_TEXT, {
+      acceptNode: (node) =>
+        node.parentElement &&
+        !node.parentElement.closest(SKIPPED) &&
+        (!range || range.intersectsNode(node))
+          ? NodeFilter.FILTER_ACCEPT
+          : NodeFilter.FILTER_REJECT,
+    });
+    while (walker.nextNode()) {
+      const node = walker.currentNode;
+      // `at` is where the node's text starts in the run
+      const start = range && node === range.startContainer ? range.startOffset : 0;
+      const end = range && node === range.endContainer ? range.endOffset : node.data.length;
+      if (end <= start) continue;
+      const block = node.parentElement.closest(BLOCKS) || root;
+      if (!run || run.block !== block) {
+        run = { block, text: "", pieces: [] };
+        runs.push(run);
+      }
+      run.pieces.push({ node, start, at: run.text.length });
+      run.text += node.data.slice(start, end);
+    }
+    return runs;
+  };
+
+  // The text node and offset at `offset` into the run; an end offset between two nodes stays
+  // in the first
+  const position = (run, offset, isEnd) => {
+    let piece = run.pieces[0];
+    for (const next of run.pieces) {
+      if (isEnd ? next.at >= offset : next.at > offset) break;
+      piece = next;
+    }
+    return [piece.node, piece.start + offset - piece.at];
+  };
+
+  const collect = () => {
+    stop();
+    const selection = window.getSelection();
+    let root = null;
+    let range = null;
+    if (selection && !selection.isCollapsed && selection.rangeCount > 0) {
+      range = selection.getRangeAt(0);
+      root = range.commonAncestorContainer;
+      if (root.nodeType !== Node.ELEMENT_NODE) root = root.parentElement;
+    } else {
+      root = (window.__browserReader && window.__browserReader.findContent()) || document.body;
+    }
+
+    const segmenter = new Intl.Segmenter(undefined, { granularity: "sentence" });
+    for (const run of root ? textRuns(root, range) : []) {
+      for (const { index, segment } of segmenter.segment(run.text)) {
+        const text = segment.replace(/\s+/g, " ").trim();
+        if (!text) continue;
+        const start = index + segment.length - segment.trimStart().length;
+        const end = index + segment.trimEnd().length;
+        const sentence = document.createRange();
+        sentence.setStart(...position(run, start, false));
+        sentence.setEnd(...position(run, end, true));
+        sentences.push(sentence);
+        texts.push(text);
+      }
+    }
+    jsBridge.ttsSentences(texts);
+  };
+
+  const highlight = (index) => {
+    CSS.highlights.delete("browser-tts");
+    const sentence = sentences[index];
+    // collapsed once the text it covered changed
+    if (!sentence || sentence.collapsed) return;
+    if (!style.isConnected) document.documentElement.appendChild(style);
+    CSS.highlights.set("browser-tts", new Highlight(sentence));
+    const rect = sentence.getBoundingClientRect();
+    if (rect.top < 0 || rect.bottom > window.innerHeight) {
+      sentence.startContainer.parentElement.scrollIntoView({ block: "center", inline: "nearest" });
+    }
+  };
+
+  const speak = (index, rate) => {
+    highlight(index);
+    pause();
+    const spoken = new SpeechSynthesisUtterance(texts[index] || "");
+    spoken.rate = rate;
+    spoken.lang = document.documentElement.lang || "";
+    // cancelled ones end too, after the next sentence has started
+    spoken.onend = () => {
+      if (utterance !== spoken) return;
+      utterance = null;
+      jsBridge.ttsSpoken(index, "");
+    };
+    spoken.onerror = (event) => {
+      if (utterance !== spoken) return;
+      utterance = null;
+      jsBridge.ttsSpoken(index, event.error || "failed");
+    };
+    utterance = spoken;
+    speechSynthesis.speak(spoken);
+  };
+
+  // Stops speaking but keeps the sentences, to start again from the current one
+  const pause = () => {
+    utterance = null;
+    speechSynthesis.cancel();
+  };
+
+  const stop = () => {
+    pause();
+    CSS.highlights.delete("browser-tts");
+    sentences = [];
+    texts = [];
+  };
+
+  window.__browserTts = { collect, highlight, speak, pause, stop };
+})();
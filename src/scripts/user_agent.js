@@ -0,0 +1,7 @@
+// Tell the host the user agent pages see, so preferences can show it
+(() => {
+  if (window.top !== window || window.__browserUserAgent) return;
+  window.__browserUserAgent = true;
+
+  jsBridge.reportUserAgent(navigator.userAgent);
+})();
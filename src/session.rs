@@ -0,0 +1,165 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use gpui::{AnyWindowHandle, App, Global};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::storage;
+use crate::window_state::WindowState;
+
+const SESSION_FILE: &str = "session.json";
+
+// A tab of the last session, with what its chip shows before the tab is loaded again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "SavedTab")]
+pub struct SessionTab {
+    pub url: String,
+    pub title: String,
+    // PNG bytes, base64-encoded
+    pub favicon: Option<String>,
+}
+
+// Sessions saved before titles and favicons were kept listed bare URLs
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedTab {
+    Url(String),
+    Tab {
+        url: String,
+        #[serde(default)]
+        title: String,
+        #[serde(default)]
+        favicon: Option<String>,
+    },
+}
+
+impl From<SavedTab> for SessionTab {
+    fn from(tab: SavedTab) -> Self {
+        match tab {
+            SavedTab::Url(url) => Self::new(&url),
+            SavedTab::Tab {
+                url,
+                title,
+                favicon,
+            } => Self {
+                url,
+                title,
+                favicon,
+            },
+        }
+    }
+}
+
+impl SessionTab {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_string(),
+            title: String::new(),
+            favicon: None,
+        }
+    }
+
+    pub fn with_favicon(mut self, favicon: Option<&gpui::Image>) -> Self {
+        self.favicon = favicon.map(|image| BASE64.encode(image.bytes()));
+        self
+    }
+
+    pub fn favicon_image(&self) -> Option<Arc<gpui::Image>> {
+        let bytes = BASE64.decode(self.favicon.as_ref()?).ok()?;
+        Some(Arc::new(gpui::Image::from_bytes(
+            gpui::ImageFormat::Png,
+            bytes,
+        )))
+    }
+}
+
+// Open tabs of a window of the last session, restored on startup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionState {
+    pub tabs: Vec<SessionTab>,
+    pub active_tab: usize,
+    // How many of the first tabs are pinned
+    #[serde(default)]
+    pub pinned: usize,
+    // Where the window was, `None` to place it like a new window
+    #[serde(default)]
+    pub bounds: Option<WindowState>,
+}
+
+impl SessionState {
+    pub fn new(url: &str) -> Self {
+        Self {
+            tabs: vec![SessionTab::new(url)],
+            active_tab: 0,
+            pinned: 0,
+            bounds: None,
+        }
+    }
+
+    // Windows of the saved session, one window on `default_url` when missing or corrupt
+    pub fn load_all(default_url: &str) -> Vec<Self> {
+        let windows = match storage::load_json::<SavedSession>(SESSION_FILE) {
+            Ok(Some(SavedSession::Windows { windows })) => windows,
+            Ok(Some(SavedSession::Window(session))) => vec![session],
+            Ok(None) => Vec::new(),
+            Err(err) => {
+                log::error!("Failed to restore session: {}", err);
+                Vec::new()
+            }
+        };
+        let windows: Vec<Self> = windows
+            .into_iter()
+            .filter(|session| !session.tabs.is_empty())
+            .map(|session| Self {
+                active_tab: session.active_tab.min(session.tabs.len() - 1),
+                pinned: session.pinned.min(session.tabs.len()),
+                ..session
+            })
+            .collect();
+        if windows.is_empty() {
+            vec![Self::new(default_url)]
+        } else {
+            windows
+        }
+    }
+}
+
+// Sessions saved before several windows were kept held a single window
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedSession {
+    Windows { windows: Vec<SessionState> },
+    Window(SessionState),
+}
+
+#[derive(Serialize)]
+struct SessionFile<'a> {
+    windows: Vec<&'a SessionState>,
+}
+
+// Regular windows opened this run, in order, with their tabs as of when they or another
+// window last closed. Windows closed earlier stay in, so quitting by closing the windows one
+// by one restores all of them.
+#[derive(Default)]
+pub struct SessionWindows(Vec<(AnyWindowHandle, SessionState)>);
+
+impl Global for SessionWindows {}
+
+impl SessionWindows {
+    pub fn record(window: AnyWindowHandle, session: SessionState, cx: &mut App) {
+        let windows = &mut cx.default_global::<Self>().0;
+        match windows.iter_mut().find(|(handle, _)| *handle == window) {
+            Some((_, recorded)) => *recorded = session,
+            None => windows.push((window, session)),
+        }
+    }
+
+    pub fn save(cx: &mut App) {
+        let windows = &cx.default_global::<Self>().0;
+        let file = SessionFile {
+            windows: windows.iter().map(|(_, session)| session).collect(),
+        };
+        if let Err(err) = storage::save_json(SESSION_FILE, &file) {
+            log::error!("Failed to save session: {}", err);
+        }
+    }
+}
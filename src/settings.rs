@@ -0,0 +1,307 @@
+use gpui::Global;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use url::Url;
+
+use crate::downloads::DownloadRule;
+use crate::proxy::ProxySettings;
+use crate::reader;
+use crate::screenshot::{self, ScreenshotFormat};
+use crate::spellcheck;
+use crate::storage;
+use crate::theme::{CustomTheme, ThemeMode};
+use crate::tts::{self, TtsEngine};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+pub const DEFAULT_HOME_URL: &str = "https://vercel.com";
+pub const BLANK_PAGE_URL: &str = "about:blank";
+// Internal page with a search box and the most visited sites, drawn by the browser itself
+pub const NEW_TAB_URL: &str = "about:newtab";
+pub const DEFAULT_MESSAGE_LOOP_FPS: u32 = 60;
+pub const DEFAULT_IDLE_MESSAGE_LOOP_FPS: u32 = 10;
+
+// What a new tab shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NewTabPage {
+    Blank,
+    Home,
+    #[default]
+    Sites,
+}
+
+// What the first window opens with when the browser starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupPage {
+    Blank,
+    Home,
+    // Falls back to the home page when there is no saved session
+    #[default]
+    Restore,
+}
+
+// Placeholder in a search engine template replaced by the encoded query
+pub const QUERY_PLACEHOLDER: &str = "{query}";
+
+// Where address bar text that isn't a URL is searched
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchEngine {
+    pub name: String,
+    // Search URL with `{query}` where the terms go
+    pub query_template: String,
+}
+
+impl SearchEngine {
+    pub fn new(name: &str, query_template: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            query_template: query_template.to_string(),
+        }
+    }
+}
+
+pub fn builtin_search_engines() -> Vec<SearchEngine> {
+    vec![
+        SearchEngine::new("DuckDuckGo", "https://duckduckgo.com/?q={query}"),
+        SearchEngine::new("Google", "https://www.google.com/search?q={query}"),
+        SearchEngine::new("Bing", "https://www.bing.com/search?q={query}"),
+    ]
+}
+
+// User preferences, persisted as JSON; missing fields take their defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrowserSettings {
+    pub home_url: String,
+    pub new_tab_page: NewTabPage,
+    pub search_engines: Vec<SearchEngine>,
+    // Name of the engine in `search_engines` used by the address bar
+    pub search_engine: String,
+    // `None` uses the platform downloads folder
+    pub download_dir: Option<PathBuf>,
+    // What to do with finished downloads by file type, first match wins
+    pub download_rules: Vec<DownloadRule>,
+    pub startup: StartupPage,
+    // Create every restored tab's page right away instead of when it's first shown
+    pub load_tabs_on_startup: bool,
+    // Background tabs not shown for this long lose their page until they're shown again
+    pub discard_after_minutes: Option<u32>,
+    // Also discard the least recently used tabs while a window's pages report more JavaScript
+    // memory than this
+    pub discard_memory_limit_mb: Option<u64>,
+    // Older settings files had a flag for reopening the last session instead of `startup`
+    #[serde(skip_serializing)]
+    restore_session: Option<bool>,
+    // Text size last picked in reader mode
+    pub reader_font_size: f32,
+    // Sent instead of CEF's built-in user agent; applied when the browser starts
+    pub user_agent: Option<String>,
+    // How pages reach the network, applied to open windows right away
+    pub proxy: ProxySettings,
+    // Page zoom by host, sites without an entry are shown at 100%
+    pub zoom_levels: HashMap<String, f32>,
+    // Hosts where ads and trackers aren't blocked
+    pub blocking_allowlist: Vec<String>,
+    // Hosts whose pages load without running JavaScript
+    pub javascript_disabled: Vec<String>,
+    // Dictionaries text fields are checked against, e.g. `en_US`
+    pub spellcheck_languages: Vec<String>,
+    // Colors of the toolbar, tab strip and menus
+    pub theme: ThemeMode,
+    pub custom_theme: CustomTheme,
+    // Voice pages are read aloud with, and how fast, where 1 is the voice's normal speed
+    pub tts_engine: TtsEngine,
+    pub tts_rate: f32,
+    // Round back and forward buttons shown along the page's edges on hover
+    pub edge_buttons: bool,
+    // How screenshots are saved and copied, the quality only applies to JPEG
+    pub screenshot_format: ScreenshotFormat,
+    pub screenshot_quality: u8,
+    // Linux only: how often CEF's message loop runs while a window is focused or loading,
+    // and once the browser has been idle for a few seconds
+    pub message_loop_fps: u32,
+    pub idle_message_loop_fps: u32,
+}
+
+impl Default for BrowserSettings {
+    fn default() -> Self {
+        Self {
+            home_url: DEFAULT_HOME_URL.to_string(),
+            new_tab_page: NewTabPage::default(),
+            search_engines: builtin_search_engines(),
+            search_engine: "DuckDuckGo".to_string(),
+            download_dir: None,
+            download_rules: Vec::new(),
+            startup: StartupPage::default(),
+            load_tabs_on_startup: false,
+            discard_after_minutes: Some(60),
+            discard_memory_limit_mb: None,
+            restore_session: None,
+            reader_font_size: reader::DEFAULT_FONT_SIZE,
+            user_agent: None,
+            proxy: ProxySettings::default(),
+            zoom_levels: HashMap::new(),
+            blocking_allowlist: Vec::new(),
+            javascript_disabled: Vec::new(),
+            spellcheck_languages: vec![spellcheck::system_language()],
+            theme: ThemeMode::default(),
+            custom_theme: CustomTheme::default(),
+            tts_engine: TtsEngine::default(),
+            tts_rate: tts::DEFAULT_RATE,
+            edge_buttons: false,
+            screenshot_format: ScreenshotFormat::default(),
+            screenshot_quality: screenshot::DEFAULT_JPEG_QUALITY,
+            message_loop_fps: DEFAULT_MESSAGE_LOOP_FPS,
+            idle_message_loop_fps: DEFAULT_IDLE_MESSAGE_LOOP_FPS,
+        }
+    }
+}
+
+impl Global for BrowserSettings {}
+
+impl BrowserSettings {
+    pub fn load() -> Self {
+        match storage::load_json::<BrowserSettings>(SETTINGS_FILE) {
+            Ok(settings) => {
+                let mut settings = settings.unwrap_or_default();
+                if settings.restore_session.take() == Some(false) {
+                    settings.startup = StartupPage::Home;
+                }
+                settings
+            }
+            Err(err) => {
+                log::error!("Failed to load settings: {}", err);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        if let Err(err) = storage::save_json(SETTINGS_FILE, self) {
+            log::error!("Failed to save settings: {}", err);
+        }
+    }
+
+    // URL new tabs are opened with
+    pub fn new_tab_url(&self) -> &str {
+        match self.new_tab_page {
+            NewTabPage::Blank => BLANK_PAGE_URL,
+            NewTabPage::Home => &self.home_url,
+            NewTabPage::Sites => NEW_TAB_URL,
+        }
+    }
+
+    // The selected search engine, or the first one if it has been removed
+    pub fn search_engine(&self) -> SearchEngine {
+        self.search_engines
+            .iter()
+            .find(|engine| engine.name == self.search_engine)
+            .or_else(|| self.search_engines.first())
+            .cloned()
+            .unwrap_or_else(|| builtin_search_engines().remove(0))
+    }
+
+    // Add or replace a custom engine and select it; the template must contain `{query}`
+    pub fn add_search_engine(&mut self, name: &str, query_template: &str) -> bool {
+        let (name, query_template) = (name.trim(), query_template.trim());
+        if name.is_empty() || !query_template.contains(QUERY_PLACEHOLDER) {
+            return false;
+        }
+
+        self.search_engines.retain(|engine| engine.name != name);
+        self.search_engines
+            .push(SearchEngine::new(name, query_template));
+        self.search_engine = name.to_string();
+        true
+    }
+
+    // Built-in engines can't be removed
+    pub fn remove_search_engine(&mut self, name: &str) {
+        if builtin_search_engines()
+            .iter()
+            .any(|engine| engine.name == name)
+        {
+            return;
+        }
+
+        self.search_engines.retain(|engine| engine.name != name);
+        if self.search_engine == name {
+            self.search_engine = self.search_engine().name;
+        }
+    }
+
+    pub fn download_dir(&self) -> Option<PathBuf> {
+        self.download_dir
+            .clone()
+            .or_else(dirs::download_dir)
+            .or_else(dirs::home_dir)
+    }
+
+    // Zoom remembered for the site `url` belongs to
+    pub fn site_zoom(&self, url: &str) -> f32 {
+        zoom_host(url)
+            .and_then(|host| self.zoom_levels.get(&host).copied())
+            .unwrap_or(1.0)
+    }
+
+    // Remember `zoom` for the site `url` belongs to, forgetting it when back at 100%
+    pub fn set_site_zoom(&mut self, url: &str, zoom: f32) {
+        let Some(host) = zoom_host(url) else {
+            return;
+        };
+        if (zoom - 1.0).abs() < f32::EPSILON {
+            self.zoom_levels.remove(&host);
+        } else {
+            self.zoom_levels.insert(host, zoom);
+        }
+    }
+
+    // Whether ads and trackers are blocked on the site `url` belongs to
+    pub fn blocking_enabled(&self, url: &str) -> bool {
+        zoom_host(url).is_none_or(|host| !self.blocking_allowlist.contains(&host))
+    }
+
+    pub fn toggle_site_blocking(&mut self, url: &str) {
+        let Some(host) = zoom_host(url) else {
+            return;
+        };
+        if self.blocking_allowlist.contains(&host) {
+            self.blocking_allowlist.retain(|allowed| *allowed != host);
+        } else {
+            self.blocking_allowlist.push(host);
+        }
+    }
+
+    pub fn javascript_enabled(&self, url: &str) -> bool {
+        zoom_host(url).is_none_or(|host| !self.javascript_disabled.contains(&host))
+    }
+
+    pub fn toggle_site_javascript(&mut self, url: &str) {
+        let Some(host) = zoom_host(url) else {
+            return;
+        };
+        if self.javascript_disabled.contains(&host) {
+            self.javascript_disabled
+                .retain(|disabled| *disabled != host);
+        } else {
+            self.javascript_disabled.push(host);
+        }
+    }
+
+    pub fn set_home_url(&mut self, url: &str) {
+        self.home_url = url.to_string();
+        self.save();
+    }
+}
+
+// Key zoom levels and other per-site settings are remembered under, `None` for pages without
+// a host like about:blank
+pub fn zoom_host(url: &str) -> Option<String> {
+    Url::parse(url)
+        .ok()?
+        .host_str()
+        .map(|host| host.trim_start_matches("www.").to_string())
+}
@@ -0,0 +1,458 @@
+use gpui::{App, Global, Task};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::settings::BrowserSettings;
+use crate::storage;
+
+const DICTIONARY_FILE: &str = "dictionary.json";
+const MAX_SUGGESTIONS: usize = 5;
+// Suggestions are at most this many edits away from the misspelled word
+const MAX_DISTANCE: usize = 2;
+// Used when the environment doesn't name a locale
+const FALLBACK_LANGUAGE: &str = "en_US";
+
+// Folders searched for Hunspell dictionaries, a `<language>.dic` next to a `<language>.aff`.
+// The browser's own folder comes first so dictionaries can be added on any platform.
+fn dictionary_dirs() -> Vec<PathBuf> {
+    let mut folders = Vec::new();
+    if let Ok(dir) = storage::config_dir() {
+        folders.push(dir.join("dictionaries"));
+    }
+    if cfg!(target_os = "linux") {
+        folders.extend(
+            [
+                "/usr/share/hunspell",
+                "/usr/share/myspell",
+                "/usr/share/myspell/dicts",
+            ]
+            .map(PathBuf::from),
+        );
+    }
+    if cfg!(target_os = "macos") {
+        if let Some(home) = dirs::home_dir() {
+            folders.push(home.join("Library/Spelling"));
+        }
+        folders.push(PathBuf::from("/Library/Spelling"));
+    }
+    folders
+}
+
+fn dictionary_path(language: &str) -> Option<PathBuf> {
+    dictionary_dirs()
+        .into_iter()
+        .map(|dir| dir.join(format!("{}.dic", language)))
+        .find(|path| path.is_file() && path.with_extension("aff").is_file())
+}
+
+// Languages there is a dictionary for, e.g. `en_US`
+pub fn available_languages() -> Vec<String> {
+    let mut languages = BTreeSet::new();
+    for dir in dictionary_dirs() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_some_and(|ext| ext == "dic")
+                && path.with_extension("aff").is_file()
+            {
+                if let Some(language) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    languages.insert(language.to_string());
+                }
+            }
+        }
+    }
+    languages.into_iter().collect()
+}
+
+// The user's locale as a dictionary name, `de_DE.UTF-8` becoming `de_DE`
+pub fn system_language() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .map(|locale| {
+            locale
+                .split(['.', '@'])
+                .next()
+                .unwrap_or_default()
+                .replace('-', "_")
+        })
+        .find(|language| !language.is_empty() && language != "C" && language != "POSIX")
+        .unwrap_or_else(|| FALLBACK_LANGUAGE.to_string())
+}
+
+/// Checks words typed into pages against Hunspell dictionaries of the chosen languages.
+///
+/// Words the user adds go into a personal dictionary that is saved across runs and accepted
+/// in every language. Dictionaries load in the background; until then every word passes.
+pub struct SpellChecker {
+    // Every form of every word in the loaded dictionaries
+    words: HashSet<String>,
+    // Languages `words` was loaded for, or is being loaded for
+    languages: Vec<String>,
+    personal: BTreeSet<String>,
+    _load: Option<Task<()>>,
+}
+
+impl Global for SpellChecker {}
+
+impl SpellChecker {
+    pub fn init(cx: &mut App) {
+        let personal = storage::load_json::<BTreeSet<String>>(DICTIONARY_FILE)
+            .unwrap_or_else(|err| {
+                log::error!("Failed to load dictionary: {}", err);
+                None
+            })
+            .unwrap_or_default();
+        cx.set_global(Self {
+            words: HashSet::new(),
+            languages: Vec::new(),
+            personal,
+            _load: None,
+        });
+        Self::sync_languages(cx);
+        cx.observe_global::<BrowserSettings>(Self::sync_languages)
+            .detach();
+    }
+
+    // Load the dictionaries of the languages picked in the settings, if they changed
+    fn sync_languages(cx: &mut App) {
+        let languages = cx.global::<BrowserSettings>().spellcheck_languages.clone();
+        if cx.global::<Self>().languages == languages {
+            return;
+        }
+
+        let load = cx.spawn({
+            let languages = languages.clone();
+            async move |cx| {
+                let words = cx
+                    .background_spawn(async move { load_words(&languages) })
+                    .await;
+                _ = cx.update(|cx| cx.update_global::<Self, _>(|checker, _| checker.words = words));
+            }
+        });
+        cx.update_global::<Self, _>(|checker, _| {
+            checker.languages = languages;
+            checker._load = Some(load);
+        });
+    }
+
+    pub fn is_correct(&self, word: &str) -> bool {
+        let word = word.trim_matches(|c: char| c == '\'' || c == '’');
+        if self.words.is_empty() || word.chars().count() < 2 || word.chars().any(|c| c.is_numeric())
+        {
+            return true;
+        }
+
+        let lower = word.to_lowercase();
+        self.personal.contains(word)
+            || self.personal.contains(&lower)
+            || self.words.contains(word)
+            // sentence case and all caps of dictionary words
+            || (starts_uppercase(word) && self.words.contains(&lower))
+            || (starts_uppercase(word) && self.words.contains(&capitalize(&lower)))
+    }
+
+    // Dictionary words closest to `word`, spelled with the same leading capital
+    pub fn suggestions(&self, word: &str) -> Vec<String> {
+        let lower = word.to_lowercase();
+        let target: Vec<char> = lower.chars().collect();
+
+        let mut candidates: Vec<(usize, &String)> = self
+            .words
+            .iter()
+            // a cheap check on bytes first, a character is at most four of them
+            .filter(|candidate| candidate.len().abs_diff(lower.len()) <= MAX_DISTANCE * 4)
+            .filter_map(|candidate| {
+                let distance = edit_distance(&target, &candidate.to_lowercase());
+                (distance <= MAX_DISTANCE).then_some((distance, candidate))
+            })
+            .collect();
+        candidates.sort();
+
+        let mut suggestions: Vec<String> = Vec::new();
+        for (_, candidate) in candidates {
+            let suggestion = if starts_uppercase(word) {
+                capitalize(candidate)
+            } else {
+                candidate.clone()
+            };
+            if !suggestions.contains(&suggestion) {
+                suggestions.push(suggestion);
+            }
+            if suggestions.len() == MAX_SUGGESTIONS {
+                break;
+            }
+        }
+        suggestions
+    }
+
+    pub fn add_word(&mut self, word: &str) {
+        let word = word.trim();
+        if !word.is_empty() && self.personal.insert(word.to_string()) {
+            self.save();
+        }
+    }
+
+    pub fn personal_words(&self) -> usize {
+        self.personal.len()
+    }
+
+    pub fn clear_personal_words(&mut self) {
+        self.personal.clear();
+        self.save();
+    }
+
+    fn save(&self) {
+        if let Err(err) = storage::save_json(DICTIONARY_FILE, &self.personal) {
+            log::error!("Failed to save dictionary: {}", err);
+        }
+    }
+}
+
+fn starts_uppercase(word: &str) -> bool {
+    word.chars().next().is_some_and(char::is_uppercase)
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// Edits turning `a` into `b`, counting a swap of neighbouring letters as one
+fn edit_distance(a: &[char], b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    if a.len().abs_diff(b.len()) > MAX_DISTANCE {
+        return MAX_DISTANCE + 1;
+    }
+
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+fn load_words(languages: &[String]) -> HashSet<String> {
+    let mut words = HashSet::new();
+    for language in languages {
+        let Some(path) = dictionary_path(language) else {
+            log::warn!("No dictionary found for {}", language);
+            continue;
+        };
+        if let Err(err) = load_dictionary(&path, &mut words) {
+            log::warn!("Failed to load dictionary {}: {}", path.display(), err);
+        }
+    }
+    words
+}
+
+// How a dictionary writes the flags after a word's `/`
+#[derive(Clone, Copy)]
+enum FlagFormat {
+    // One character per flag
+    Char,
+    // Two characters per flag
+    Long,
+    // Comma-separated numbers
+    Num,
+}
+
+impl FlagFormat {
+    fn parse(self, flags: &str) -> Vec<String> {
+        match self {
+            FlagFormat::Char => flags.chars().map(String::from).collect(),
+            FlagFormat::Long => flags
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|chunk| chunk.iter().collect())
+                .collect(),
+            FlagFormat::Num => flags.split(',').map(str::to_string).collect(),
+        }
+    }
+}
+
+// One letter of an affix condition
+enum Condition {
+    Any,
+    OneOf(Vec<char>),
+    NoneOf(Vec<char>),
+}
+
+impl Condition {
+    fn parse(pattern: &str) -> Vec<Condition> {
+        let mut conditions = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            conditions.push(match c {
+                '.' => Condition::Any,
+                '[' => {
+                    let set: String = chars.by_ref().take_while(|c| *c != ']').collect();
+                    match set.strip_prefix('^') {
+                        Some(set) => Condition::NoneOf(set.chars().collect()),
+                        None => Condition::OneOf(set.chars().collect()),
+                    }
+                }
+                c => Condition::OneOf(vec![c]),
+            });
+        }
+        conditions
+    }
+
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Condition::Any => true,
+            Condition::OneOf(set) => set.contains(&c),
+            Condition::NoneOf(set) => !set.contains(&c),
+        }
+    }
+}
+
+// A prefix or suffix rule: remove `strip`, then add `add`, where the word meets `condition`
+struct Affix {
+    strip: String,
+    add: String,
+    condition: Vec<Condition>,
+}
+
+impl Affix {
+    fn apply_suffix(&self, word: &str) -> Option<String> {
+        let chars: Vec<char> = word.chars().collect();
+        let tail = chars.len().checked_sub(self.condition.len())?;
+        let matches = chars[tail..]
+            .iter()
+            .zip(&self.condition)
+            .all(|(c, condition)| condition.matches(*c));
+        let stem = word.strip_suffix(self.strip.as_str())?;
+        (matches && !stem.is_empty()).then(|| format!("{}{}", stem, self.add))
+    }
+
+    fn apply_prefix(&self, word: &str) -> Option<String> {
+        let matches = self.condition.len() <= word.chars().count()
+            && word
+                .chars()
+                .zip(&self.condition)
+                .all(|(c, condition)| condition.matches(c));
+        let stem = word.strip_prefix(self.strip.as_str())?;
+        (matches && !stem.is_empty()).then(|| format!("{}{}", self.add, stem))
+    }
+}
+
+#[derive(Default)]
+struct AffixClass {
+    // Whether the rules combine with the other kind of affix
+    cross_product: bool,
+    rules: Vec<Affix>,
+}
+
+// Add every word of a Hunspell dictionary to `words`, expanding its prefix and suffix rules.
+// Compounding, morphology and the rarer `.aff` options aren't supported, so languages that
+// rely on compound words flag more words than they should.
+fn load_dictionary(path: &Path, words: &mut HashSet<String>) -> anyhow::Result<()> {
+    let aff = String::from_utf8_lossy(&fs::read(path.with_extension("aff"))?).into_owned();
+    let dic = String::from_utf8_lossy(&fs::read(path)?).into_owned();
+
+    let mut format = FlagFormat::Char;
+    let mut prefixes: HashMap<String, AffixClass> = HashMap::new();
+    let mut suffixes: HashMap<String, AffixClass> = HashMap::new();
+    let mut hidden = HashSet::new();
+    for line in aff.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["FLAG", "long", ..] => format = FlagFormat::Long,
+            ["FLAG", "num", ..] => format = FlagFormat::Num,
+            // words with these flags aren't valid on their own
+            ["NEEDAFFIX" | "ONLYINCOMPOUND" | "FORBIDDENWORD", flag, ..] => {
+                hidden.insert(flag.to_string());
+            }
+            [kind @ ("PFX" | "SFX"), flag, cross, count] if count.parse::<usize>().is_ok() => {
+                let classes = if *kind == "PFX" {
+                    &mut prefixes
+                } else {
+                    &mut suffixes
+                };
+                classes.entry(flag.to_string()).or_default().cross_product = *cross == "Y";
+            }
+            [kind @ ("PFX" | "SFX"), flag, strip, add, condition, ..] => {
+                let classes = if *kind == "PFX" {
+                    &mut prefixes
+                } else {
+                    &mut suffixes
+                };
+                let add = add.split('/').next().unwrap_or_default();
+                classes
+                    .entry(flag.to_string())
+                    .or_default()
+                    .rules
+                    .push(Affix {
+                        strip: if *strip == "0" { "" } else { *strip }.to_string(),
+                        add: if add == "0" { "" } else { add }.to_string(),
+                        condition: Condition::parse(condition),
+                    });
+            }
+            _ => {}
+        }
+    }
+
+    // the first line is the number of words
+    for line in dic.lines().skip(1) {
+        let entry = line.split(['\t', ' ']).next().unwrap_or_default();
+        let (word, flags) = match entry.split_once('/') {
+            Some((word, flags)) => (word, format.parse(flags)),
+            None => (entry, Vec::new()),
+        };
+        if word.is_empty() {
+            continue;
+        }
+        if !flags.iter().any(|flag| hidden.contains(flag)) {
+            words.insert(word.to_string());
+        }
+
+        let mut cross_suffixed = Vec::new();
+        for class in flags.iter().filter_map(|flag| suffixes.get(flag)) {
+            for form in class
+                .rules
+                .iter()
+                .filter_map(|rule| rule.apply_suffix(word))
+            {
+                if class.cross_product {
+                    cross_suffixed.push(form.clone());
+                }
+                words.insert(form);
+            }
+        }
+        for class in flags.iter().filter_map(|flag| prefixes.get(flag)) {
+            for rule in &class.rules {
+                words.extend(rule.apply_prefix(word));
+                if class.cross_product {
+                    words.extend(
+                        cross_suffixed
+                            .iter()
+                            .filter_map(|form| rule.apply_prefix(form)),
+                    );
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,456 @@
+use gpui::{
+    canvas, div, prelude::*, px, relative, AnyElement, Bounds, Context, Div, Entity, IntoElement,
+    MouseButton, MouseMoveEvent, Pixels, Subscription, Window,
+};
+use gpui_component::input::{InputEvent, InputState, TextInput};
+
+use crate::address;
+use crate::bridge::TabId;
+use crate::internal_pages::{self, InternalPage};
+use crate::settings::BrowserSettings;
+use crate::theme::Theme;
+use crate::toast::toast_info;
+use crate::widgets::svg_button;
+use crate::{Main, ToggleSplitView};
+
+// Neither pane gets smaller than this share of the window
+const MIN_RATIO: f32 = 0.2;
+
+// A second tab shown next to the active one, after or below it
+pub struct SplitView {
+    pub tab_id: TabId,
+    vertical: bool,
+    // Share of the space the active tab's pane takes
+    ratio: f32,
+    // Whether scrolling either page scrolls the other to the same offset
+    linked_scroll: bool,
+    dragging: bool,
+    // Where both panes are in the window, as last laid out
+    bounds: Bounds<Pixels>,
+    // The second pane's own address bar
+    address: Entity<InputState>,
+    _subscription: Subscription,
+}
+
+impl Main {
+    // Split with the tab shown last before this one, or leave split view
+    pub(crate) fn toggle_split_view(
+        &mut self,
+        _: &ToggleSplitView,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.split_view.is_some() {
+            self.exit_split_view(cx);
+            return;
+        }
+        let partner = (0..self.tabs.len())
+            .filter(|ix| *ix != self.active_tab)
+            .max_by_key(|ix| self.tabs[*ix].last_active);
+        match partner {
+            Some(ix) => self.split_with(ix, window, cx),
+            None => toast_info(cx, "Open another tab to show next to this one"),
+        }
+    }
+
+    // Show the tab at `ix` next to the active one
+    pub(crate) fn split_with(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        if ix == self.active_tab {
+            return;
+        }
+        self.exit_split_view(cx);
+        self.ensure_loaded(ix, window, cx);
+
+        let address = cx.new(|cx| InputState::new(window, cx));
+        let subscription = cx.subscribe_in(
+            &address,
+            window,
+            |this, state, event: &InputEvent, window, cx| {
+                if let InputEvent::PressEnter { .. } = event {
+                    let engine = cx.global::<BrowserSettings>().search_engine();
+                    let url = address::normalize_input(&state.read(cx).value(), &engine);
+                    this.navigate_split(&url, window, cx);
+                }
+            },
+        );
+        self.split_view = Some(SplitView {
+            tab_id: self.tabs[ix].id,
+            vertical: false,
+            ratio: 0.5,
+            linked_scroll: false,
+            dragging: false,
+            bounds: Bounds::default(),
+            address,
+            _subscription: subscription,
+        });
+        self.sync_split_address(window, cx);
+        cx.notify();
+    }
+
+    pub(crate) fn exit_split_view(&mut self, cx: &mut Context<Self>) {
+        if let Some(split) = &mut self.split_view {
+            split.linked_scroll = false;
+            self.apply_linked_scroll(cx);
+            self.split_view = None;
+            cx.notify();
+        }
+    }
+
+    fn split_tab_index(&self) -> Option<usize> {
+        let split = self.split_view.as_ref()?;
+        self.tabs.iter().position(|tab| tab.id == split.tab_id)
+    }
+
+    // Activating the second pane's tab swaps the panes, so the pair stays together
+    pub(crate) fn follow_split_view(&mut self, ix: usize) {
+        let previous = self.tabs.get(self.active_tab).map(|tab| tab.id);
+        if let (Some(split), Some(previous)) = (&mut self.split_view, previous) {
+            if split.tab_id == self.tabs[ix].id {
+                split.tab_id = previous;
+            }
+        }
+    }
+
+    // Show the second pane's URL in its address bar, unless it's being typed in
+    pub(crate) fn sync_split_address(&self, window: &mut Window, cx: &mut Context<Self>) {
+        let (Some(split), Some(ix)) = (&self.split_view, self.split_tab_index()) else {
+            return;
+        };
+        if split.address.focus_handle(cx).is_focused(window) {
+            return;
+        }
+        let url = self.tabs[ix].url.clone();
+        split
+            .address
+            .update(cx, |state, cx| state.set_value(url, window, cx));
+    }
+
+    fn navigate_split(&mut self, url: &str, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(ix) = self.split_tab_index().filter(|_| !url.is_empty()) else {
+            return;
+        };
+        let url = InternalPage::from_url(url).map_or_else(|| url.to_string(), InternalPage::url);
+        let tab = &mut self.tabs[ix];
+        if let Some(webview) = &tab.webview {
+            webview
+                .read(cx)
+                .browser()
+                .load_url(&internal_pages::webview_url(&url));
+            window.focus(&webview.focus_handle(cx));
+        }
+        tab.url = url.into();
+        cx.notify();
+    }
+
+    // Back, or forward with `forward`, in the second pane
+    fn step_split_history(&mut self, forward: bool, cx: &mut Context<Self>) {
+        let Some(ix) = self.split_tab_index() else {
+            return;
+        };
+        let tab = &mut self.tabs[ix];
+        let Some(webview) = &tab.webview else {
+            return;
+        };
+        let webview = webview.read(cx);
+        if forward && webview.can_go_forward() {
+            tab.pending_history_step = Some(1);
+            webview.go_forward();
+        } else if !forward && webview.can_go_back() {
+            tab.pending_history_step = Some(-1);
+            webview.go_back();
+        }
+    }
+
+    // Tell both pages whether to report their scrolling as it happens, which they forget on
+    // every load
+    pub(crate) fn apply_linked_scroll(&self, cx: &mut Context<Self>) {
+        let Some(split) = &self.split_view else {
+            return;
+        };
+        let active = self.tabs[self.active_tab].id;
+        for tab in &self.tabs {
+            if tab.id != active && tab.id != split.tab_id {
+                continue;
+            }
+            if let Some(frame) = &tab.main_frame {
+                frame.execute_javascript(&format!(
+                    "window.__browserLinkScroll && window.__browserLinkScroll({})",
+                    split.linked_scroll
+                ));
+            }
+        }
+        cx.notify();
+    }
+
+    // One pane scrolled while scrolling is linked; move the other one along
+    pub(crate) fn mirror_split_scroll(&self, tab_id: TabId, x: f32, y: f32) {
+        let Some(split) = self.split_view.as_ref().filter(|split| split.linked_scroll) else {
+            return;
+        };
+        let active = self.tabs[self.active_tab].id;
+        let other = if tab_id == active {
+            split.tab_id
+        } else if tab_id == split.tab_id {
+            active
+        } else {
+            return;
+        };
+        if let Some(frame) = self
+            .tabs
+            .iter()
+            .find(|tab| tab.id == other)
+            .and_then(|tab| tab.main_frame.as_ref())
+        {
+            frame.execute_javascript(&format!(
+                "window.__browserScrollTo && window.__browserScrollTo({}, {})",
+                x, y
+            ));
+        }
+    }
+
+    fn drag_split_divider(&mut self, event: &MouseMoveEvent, cx: &mut Context<Self>) {
+        let Some(split) = self.split_view.as_mut().filter(|split| split.dragging) else {
+            return;
+        };
+        let bounds = split.bounds;
+        let ratio = if split.vertical {
+            (event.position.y - bounds.top()) / bounds.size.height
+        } else {
+            (event.position.x - bounds.left()) / bounds.size.width
+        };
+        if ratio.is_finite() {
+            split.ratio = ratio.clamp(MIN_RATIO, 1. - MIN_RATIO);
+            cx.notify();
+        }
+    }
+
+    // The second pane's address bar and controls
+    fn render_split_bar(&self, split: &SplitView, ix: usize, cx: &mut Context<Self>) -> Div {
+        let theme = *cx.global::<Theme>();
+        let this = cx.entity();
+        let (can_go_back, can_go_forward) = self.tabs[ix]
+            .webview
+            .as_ref()
+            .map(|webview| {
+                let webview = webview.read(cx);
+                (webview.can_go_back(), webview.can_go_forward())
+            })
+            .unwrap_or_default();
+        let control = |id: &'static str, label: &'static str| {
+            div()
+                .id(id)
+                .px_2()
+                .py_1()
+                .rounded_md()
+                .text_xs()
+                .text_color(theme.text_secondary)
+                .cursor_pointer()
+                .hover(|this| this.bg(theme.hover_subtle))
+                .child(label)
+        };
+
+        div()
+            .flex()
+            .flex_none()
+            .items_center()
+            .gap_1()
+            .h_8()
+            .px_2()
+            .border_b_1()
+            .border_color(theme.border)
+            .child(svg_button("back.svg", 12.0, theme.text, can_go_back, {
+                let this = this.clone();
+                move |_, cx| this.update(cx, |this, cx| this.step_split_history(false, cx))
+            }))
+            .child(svg_button(
+                "forward.svg",
+                12.0,
+                theme.text,
+                can_go_forward,
+                {
+                    let this = this.clone();
+                    move |_, cx| this.update(cx, |this, cx| this.step_split_history(true, cx))
+                },
+            ))
+            .child(
+                div()
+                    .flex_1()
+                    .min_w_0()
+                    .h_6()
+                    .flex()
+                    .items_center()
+                    .px_2()
+                    .rounded_md()
+                    .bg(theme.hover_subtle)
+                    .child(
+                        TextInput::new(&split.address)
+                            .text_xs()
+                            .text_color(theme.text)
+                            .border_0(),
+                    ),
+            )
+            .child(
+                control(
+                    "split-link-scroll",
+                    if split.linked_scroll {
+                        "Scrolling linked"
+                    } else {
+                        "Link scrolling"
+                    },
+                )
+                .when(split.linked_scroll, |this| {
+                    this.bg(theme.hover).text_color(theme.text)
+                })
+                .on_click(cx.listener(|this, _, _, cx| {
+                    if let Some(split) = &mut this.split_view {
+                        split.linked_scroll = !split.linked_scroll;
+                    }
+                    this.apply_linked_scroll(cx);
+                })),
+            )
+            .child(
+                control(
+                    "split-orientation",
+                    if split.vertical {
+                        "Side by side"
+                    } else {
+                        "Stacked"
+                    },
+                )
+                .on_click(cx.listener(|this, _, _, cx| {
+                    if let Some(split) = &mut this.split_view {
+                        split.vertical = !split.vertical;
+                    }
+                    cx.notify();
+                })),
+            )
+            // the second pane's tab becomes the active one, which swaps the panes
+            .child(
+                control("split-swap", "Swap").on_click(cx.listener(|this, _, window, cx| {
+                    if let Some(ix) = this.split_tab_index() {
+                        this.activate_tab(ix, window, cx);
+                    }
+                })),
+            )
+            .child(svg_button("x.svg", 12.0, theme.icon_muted, true, {
+                let this = this.clone();
+                move |_, cx| this.update(cx, |this, cx| this.exit_split_view(cx))
+            }))
+    }
+
+    // The active tab's `content` alone, or next to the paired tab with a divider between them
+    pub(crate) fn render_split_view(&self, content: Div, cx: &mut Context<Self>) -> AnyElement {
+        let (Some(split), Some(ix)) = (&self.split_view, self.split_tab_index()) else {
+            return content.into_any_element();
+        };
+        let Some(webview) = self.tabs[ix].webview.clone() else {
+            return content.into_any_element();
+        };
+        let theme = *cx.global::<Theme>();
+        let this = cx.entity();
+        let vertical = split.vertical;
+
+        div()
+            .relative()
+            .size_full()
+            .flex()
+            .when(vertical, |this| this.flex_col())
+            .child(
+                canvas(
+                    move |bounds, _, cx| {
+                        this.update(cx, |this, _| {
+                            if let Some(split) = &mut this.split_view {
+                                split.bounds = bounds;
+                            }
+                        })
+                    },
+                    |_, _, _, _| {},
+                )
+                .absolute()
+                .size_full(),
+            )
+            .child(
+                div()
+                    .relative()
+                    .flex_none()
+                    .map(|this| {
+                        if vertical {
+                            this.w_full().h(relative(split.ratio))
+                        } else {
+                            this.h_full().w(relative(split.ratio))
+                        }
+                    })
+                    .child(content),
+            )
+            .child(
+                div()
+                    .id("split-divider")
+                    .flex_none()
+                    .bg(theme.border)
+                    .hover(|this| this.bg(theme.accent))
+                    .map(|this| {
+                        if vertical {
+                            this.w_full().h(px(4.)).cursor_row_resize()
+                        } else {
+                            this.h_full().w(px(4.)).cursor_col_resize()
+                        }
+                    })
+                    .when(split.dragging, |this| this.bg(theme.accent))
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(|this, _, _, cx| {
+                            if let Some(split) = &mut this.split_view {
+                                split.dragging = true;
+                                cx.notify();
+                            }
+                        }),
+                    ),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .min_w_0()
+                    .min_h_0()
+                    .flex()
+                    .flex_col()
+                    .child(self.render_split_bar(split, ix, cx))
+                    // internal pages act on the active tab, so they're drawn in its pane only
+                    .child(div().relative().flex_1().child(webview)),
+            )
+            // over both pages while dragging, so they don't take the mouse away from the divider
+            .when(split.dragging, |this| {
+                let stop = |this: &mut Main, cx: &mut Context<Main>| {
+                    if let Some(split) = &mut this.split_view {
+                        split.dragging = false;
+                        cx.notify();
+                    }
+                };
+                this.child(
+                    div()
+                        .id("split-drag")
+                        .absolute()
+                        .inset_0()
+                        .occlude()
+                        .map(|this| {
+                            if vertical {
+                                this.cursor_row_resize()
+                            } else {
+                                this.cursor_col_resize()
+                            }
+                        })
+                        .on_mouse_move(cx.listener(|this, event: &MouseMoveEvent, _, cx| {
+                            this.drag_split_divider(event, cx)
+                        }))
+                        .on_mouse_up(
+                            MouseButton::Left,
+                            cx.listener(move |this, _, _, cx| stop(this, cx)),
+                        )
+                        .on_mouse_up_out(
+                            MouseButton::Left,
+                            cx.listener(move |this, _, _, cx| stop(this, cx)),
+                        ),
+                )
+            })
+            .into_any_element()
+    }
+}
@@ -0,0 +1,75 @@
+use anyhow::{anyhow, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+// Directory holding all persisted browser state. Tests get a temporary one of their own, so
+// they never read or write the user's profile.
+pub fn config_dir() -> Result<PathBuf> {
+    if cfg!(test) {
+        let name = format!("browser-app-test-{}", std::process::id());
+        return Ok(std::env::temp_dir().join(name));
+    }
+    dirs::config_dir()
+        .map(|dir| dir.join("browser-app"))
+        .ok_or_else(|| anyhow!("no platform config directory"))
+}
+
+// Read and deserialize a JSON file from the config directory, `None` if it doesn't exist yet
+pub fn load_json<T: DeserializeOwned>(file_name: &str) -> Result<Option<T>> {
+    let data = match fs::read(config_dir()?.join(file_name)) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+    Ok(Some(serde_json::from_slice(&data)?))
+}
+
+// Serialize and write a JSON file into the config directory
+pub fn save_json<T: Serialize>(file_name: &str, value: &T) -> Result<()> {
+    let dir = config_dir()?;
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(file_name), serde_json::to_vec_pretty(value)?)?;
+    Ok(())
+}
+
+// Read a JSON-lines file from the config directory, skipping lines that fail to parse
+pub fn load_json_lines<T: DeserializeOwned>(file_name: &str) -> Result<Vec<T>> {
+    let data = match fs::read_to_string(config_dir()?.join(file_name)) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    Ok(data
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+// Append one record to a JSON-lines file in the config directory
+pub fn append_json_line<T: Serialize>(file_name: &str, value: &T) -> Result<()> {
+    let dir = config_dir()?;
+    fs::create_dir_all(&dir)?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dir.join(file_name))?;
+    let mut line = serde_json::to_vec(value)?;
+    line.push(b'\n');
+    file.write_all(&line)?;
+    Ok(())
+}
+
+// Rewrite a JSON-lines file with exactly `values`
+pub fn save_json_lines<T: Serialize>(file_name: &str, values: &[T]) -> Result<()> {
+    let dir = config_dir()?;
+    fs::create_dir_all(&dir)?;
+    let mut data = Vec::new();
+    for value in values {
+        serde_json::to_writer(&mut data, value)?;
+        data.push(b'\n');
+    }
+    fs::write(dir.join(file_name), data)?;
+    Ok(())
+}
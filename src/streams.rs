@@ -0,0 +1,164 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use futures_util::stream::{self, BoxStream, Stream, StreamExt};
+use gpui::{BackgroundExecutor, Task};
+use gpui_webview::wef::Frame;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+// Binary results are sent in pieces of this many bytes, so a large payload doesn't turn into
+// a single huge message for the page to parse
+const BYTES_CHUNK_SIZE: usize = 256 * 1024;
+
+type StreamFactory = Box<dyn Fn(Value) -> anyhow::Result<BoxStream<'static, Value>> + Send + Sync>;
+
+// Message emitted to the page for each stream value and once more when it ends
+#[derive(Debug, Serialize)]
+struct StreamMessage {
+    event: &'static str,
+    id: String,
+    value: Option<Value>,
+    done: bool,
+}
+
+// A piece of a binary result, `{ data }` with base64 bytes or `{ error }` when it failed
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum BytesChunk {
+    Data(String),
+    Error(String),
+}
+
+/// Host functions that push a sequence of values to the page instead of a single result.
+///
+/// Pages consume them with `for await (const value of hostStream(name, arg))`. Leaving the
+/// loop early cancels the stream, which drops the Rust task driving it.
+#[derive(Clone)]
+pub struct StreamRegistry {
+    factories: Arc<HashMap<String, StreamFactory>>,
+    running: Arc<Mutex<HashMap<String, Task<()>>>>,
+    background_executor: BackgroundExecutor,
+}
+
+pub struct StreamRegistryBuilder {
+    factories: HashMap<String, StreamFactory>,
+    background_executor: BackgroundExecutor,
+}
+
+impl StreamRegistry {
+    pub fn builder(background_executor: BackgroundExecutor) -> StreamRegistryBuilder {
+        StreamRegistryBuilder {
+            factories: HashMap::new(),
+            background_executor,
+        }
+    }
+
+    // Start the stream `name` for the page, emitting values to `frame` under `id`
+    pub fn start(&self, frame: Frame, id: String, name: &str, arg: Value) -> Result<(), String> {
+        let factory = self
+            .factories
+            .get(name)
+            .ok_or_else(|| format!("unknown stream: {}", name))?;
+        let mut stream = factory(arg).map_err(|err| err.to_string())?;
+
+        // held until the task is recorded, so a stream that ends right away can't remove
+        // itself before it's there
+        let mut running = self.running.lock().unwrap();
+        let task = self.background_executor.spawn({
+            let running = self.running.clone();
+            let id = id.clone();
+            async move {
+                while let Some(value) = stream.next().await {
+                    frame.emit(StreamMessage {
+                        event: "stream",
+                        id: id.clone(),
+                        value: Some(value),
+                        done: false,
+                    });
+                }
+                frame.emit(StreamMessage {
+                    event: "stream",
+                    id: id.clone(),
+                    value: None,
+                    done: true,
+                });
+                running.lock().unwrap().remove(&id);
+            }
+        });
+        running.insert(id, task);
+        Ok(())
+    }
+
+    // Stop a running stream, dropping its task
+    pub fn cancel(&self, id: &str) {
+        self.running.lock().unwrap().remove(id);
+    }
+
+    // Stop every stream, once the page that started them is gone
+    pub fn cancel_all(&self) {
+        self.running.lock().unwrap().clear();
+    }
+}
+
+impl StreamRegistryBuilder {
+    // Register `f` as a stream that pages can start with a single deserialized argument
+    pub fn register_stream<A, S, T>(
+        mut self,
+        name: &str,
+        f: impl Fn(A) -> S + Send + Sync + 'static,
+    ) -> Self
+    where
+        A: DeserializeOwned,
+        S: Stream<Item = T> + Send + 'static,
+        T: Serialize + Send + 'static,
+    {
+        let factory = move |arg: Value| -> anyhow::Result<BoxStream<'static, Value>> {
+            let arg = serde_json::from_value(arg)?;
+            Ok(f(arg)
+                .filter_map(|value| async move { serde_json::to_value(value).ok() })
+                .boxed())
+        };
+        self.factories.insert(name.to_string(), Box::new(factory));
+        self
+    }
+
+    /// Register `f` as a host function returning binary data, which pages receive as a single
+    /// `Uint8Array` from `await hostBytes(name, arg)`.
+    ///
+    /// `f` runs on the background executor, so it may block, e.g. reading a file. This is not
+    /// a binary transfer: the bridge only carries JSON, so the bytes travel as base64 in
+    /// chunks, a third larger than the data, and are decoded on arrival. Sending them as they
+    /// are would need a binary reply in wef's `FuncRegistry`, which doesn't have one.
+    pub fn register_bytes<A>(
+        self,
+        name: &str,
+        f: impl Fn(A) -> anyhow::Result<Vec<u8>> + Send + Sync + 'static,
+    ) -> Self
+    where
+        A: DeserializeOwned + Send + 'static,
+    {
+        let f = Arc::new(f);
+        self.register_stream(name, move |arg: A| {
+            let f = f.clone();
+            stream::once(async move { f(arg) }).flat_map(|result| {
+                let chunks: Vec<_> = match result {
+                    Ok(bytes) => bytes
+                        .chunks(BYTES_CHUNK_SIZE)
+                        .map(|chunk| BytesChunk::Data(BASE64.encode(chunk)))
+                        .collect(),
+                    Err(err) => vec![BytesChunk::Error(err.to_string())],
+                };
+                stream::iter(chunks)
+            })
+        })
+    }
+
+    pub fn build(self) -> StreamRegistry {
+        StreamRegistry {
+            factories: Arc::new(self.factories),
+            running: Arc::default(),
+            background_executor: self.background_executor,
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use gpui::App;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::bookmarks::Bookmarks;
+use crate::history::History;
+
+const MAX_SUGGESTIONS: usize = 8;
+const DAY_SECS: f32 = 24. * 60. * 60.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SuggestionKind {
+    History,
+    Bookmark,
+}
+
+// An address bar suggestion, with the byte ranges matching what was typed
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    pub url: String,
+    pub title: String,
+    pub kind: SuggestionKind,
+    pub url_match: Option<Range<usize>>,
+    pub title_match: Option<Range<usize>>,
+    score: f32,
+}
+
+impl Suggestion {
+    // Text that completes `prefix` to this URL, if the URL starts with what was typed
+    pub fn inline_completion(&self, prefix: &str) -> Option<String> {
+        let prefix = prefix.trim();
+        let url = strip_url_prefix(&self.url);
+        if prefix.is_empty() || prefix.len() >= url.len() {
+            return None;
+        }
+
+        url.get(..prefix.len())
+            .filter(|start| start.eq_ignore_ascii_case(prefix))
+            .map(|_| url[prefix.len()..].to_string())
+    }
+}
+
+// History and bookmarks matching `prefix`, best first
+//
+// History is ranked by how often and how recently a page was visited; bookmarks and
+// URLs that start with the typed text rank higher.
+pub fn suggest(prefix: &str, cx: &App) -> Vec<Suggestion> {
+    let query = prefix.trim().to_ascii_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_secs())
+        .unwrap_or_default();
+    let mut suggestions: HashMap<String, Suggestion> = HashMap::new();
+
+    for entry in cx.global::<History>().entries() {
+        let age_days = now.saturating_sub(entry.visited_at) as f32 / DAY_SECS;
+        let frecency = (1. + entry.visit_count.max(1) as f32).ln() * 10. / (1. + age_days / 7.);
+        if let Some(suggestion) = matching(
+            &entry.url,
+            &entry.title,
+            &query,
+            SuggestionKind::History,
+            frecency,
+        ) {
+            suggestions.insert(entry.url.clone(), suggestion);
+        }
+    }
+
+    for bookmark in cx.global::<Bookmarks>().all() {
+        let Some(mut suggestion) = matching(
+            &bookmark.url,
+            &bookmark.title,
+            &query,
+            SuggestionKind::Bookmark,
+            20.,
+        ) else {
+            continue;
+        };
+
+        // a bookmarked page keeps its history ranking, boosted
+        if let Some(visited) = suggestions.get(&bookmark.url) {
+            suggestion.score += visited.score;
+        }
+        suggestions.insert(bookmark.url.clone(), suggestion);
+    }
+
+    let mut suggestions: Vec<_> = suggestions.into_values().collect();
+    suggestions.sort_by(|a, b| b.score.total_cmp(&a.score));
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+fn matching(
+    url: &str,
+    title: &str,
+    query: &str,
+    kind: SuggestionKind,
+    score: f32,
+) -> Option<Suggestion> {
+    // ASCII lowercasing keeps byte offsets valid for the original text
+    let url_match = find(url, query);
+    let title_match = find(title, query);
+    if url_match.is_none() && title_match.is_none() {
+        return None;
+    }
+
+    let prefix_bonus = if strip_url_prefix(url)
+        .to_ascii_lowercase()
+        .starts_with(query)
+    {
+        50.
+    } else if url_match.is_some() {
+        10.
+    } else {
+        0.
+    };
+
+    Some(Suggestion {
+        url: url.to_string(),
+        title: title.to_string(),
+        kind,
+        url_match,
+        title_match,
+        score: score + prefix_bonus,
+    })
+}
+
+// Where the lowercase `query` is in `text`, ignoring ASCII case
+pub fn find(text: &str, query: &str) -> Option<Range<usize>> {
+    let start = text.to_ascii_lowercase().find(query)?;
+    Some(start..start + query.len())
+}
+
+// The part of a URL people type: no scheme and no leading "www."
+fn strip_url_prefix(url: &str) -> &str {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    url.strip_prefix("www.").unwrap_or(url)
+}
@@ -0,0 +1,355 @@
+use gpui::{
+    div, prelude::*, px, AnyWindowHandle, App, Context, Entity, Global, IntoElement, KeyDownEvent,
+    Render, SharedString, Subscription, WeakEntity, Window,
+};
+use gpui_component::{
+    input::{InputEvent, InputState, TextInput},
+    ContextModal,
+};
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::bridge::TabId;
+use crate::suggestions;
+use crate::theme::Theme;
+use crate::widgets::svg_button;
+use crate::{favicon_icon, highlighted, Main, ToggleTabSearch};
+
+// Every open browser window in the order they were opened, so a window can reach the tabs
+// of the others. Closed windows drop out once their `Main` is released.
+#[derive(Default)]
+pub struct OpenWindows(Vec<(AnyWindowHandle, WeakEntity<Main>)>);
+
+impl Global for OpenWindows {}
+
+impl OpenWindows {
+    pub fn add(window: AnyWindowHandle, main: WeakEntity<Main>, cx: &mut App) {
+        cx.default_global::<Self>().0.push((window, main));
+    }
+
+    pub(crate) fn all(cx: &mut App) -> Vec<(AnyWindowHandle, Entity<Main>)> {
+        let windows = cx.default_global::<Self>();
+        windows.0.retain(|(_, main)| main.upgrade().is_some());
+        windows
+            .0
+            .iter()
+            .filter_map(|(window, main)| Some((*window, main.upgrade()?)))
+            .collect()
+    }
+}
+
+struct TabRow {
+    window: AnyWindowHandle,
+    main: WeakEntity<Main>,
+    tab_id: TabId,
+    title: SharedString,
+    url: SharedString,
+    favicon: Option<Arc<gpui::Image>>,
+    pinned: bool,
+    // Which window the tab is in, e.g. "Window 2"
+    window_label: SharedString,
+}
+
+struct TabMatch {
+    row: usize,
+    title_match: Option<Range<usize>>,
+    url_match: Option<Range<usize>>,
+}
+
+// Open tabs of every window, filtered by title and URL
+pub struct TabSearch {
+    query: Entity<InputState>,
+    rows: Vec<TabRow>,
+    matches: Vec<TabMatch>,
+    selected: usize,
+    _subscription: Subscription,
+}
+
+fn tab_rows(
+    window: AnyWindowHandle,
+    main: &Main,
+    entity: WeakEntity<Main>,
+    window_label: SharedString,
+) -> impl Iterator<Item = TabRow> + '_ {
+    main.tabs.iter().map(move |tab| TabRow {
+        window,
+        main: entity.clone(),
+        tab_id: tab.id,
+        title: tab.title.clone(),
+        url: tab.url.clone(),
+        favicon: tab.favicon.clone(),
+        pinned: tab.pinned,
+        window_label: window_label.clone(),
+    })
+}
+
+impl TabSearch {
+    fn new(rows: Vec<TabRow>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let query = cx.new(|cx| InputState::new(window, cx).placeholder("Search tabs"));
+        let subscription = cx.subscribe_in(&query, window, |this, _, event: &InputEvent, _, cx| {
+            if let InputEvent::Change(_) = event {
+                this.update_matches(cx);
+            }
+        });
+
+        let mut this = Self {
+            query,
+            rows,
+            matches: Vec::new(),
+            selected: 0,
+            _subscription: subscription,
+        };
+        this.update_matches(cx);
+        this
+    }
+
+    fn update_matches(&mut self, cx: &mut Context<Self>) {
+        let query = self.query.read(cx).value().trim().to_ascii_lowercase();
+        self.matches = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter_map(|(row, tab)| {
+                if query.is_empty() {
+                    return Some(TabMatch {
+                        row,
+                        title_match: None,
+                        url_match: None,
+                    });
+                }
+                let title_match = suggestions::find(&tab.title, &query);
+                let url_match = suggestions::find(&tab.url, &query);
+                (title_match.is_some() || url_match.is_some()).then_some(TabMatch {
+                    row,
+                    title_match,
+                    url_match,
+                })
+            })
+            .collect();
+        self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+        cx.notify();
+    }
+
+    // Bring the tab's window to the front and show the tab
+    fn select(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(tab) = self.matches.get(ix).map(|tab| &self.rows[tab.row]) else {
+            return;
+        };
+        let (handle, main, tab_id) = (tab.window, tab.main.clone(), tab.tab_id);
+        window.close_modal(cx);
+        // the tab may be in this window, which is being updated right now
+        cx.defer(move |cx| {
+            _ = handle.update(cx, |_, window, cx| {
+                window.activate_window();
+                _ = main.update(cx, |main, cx| {
+                    if let Some(ix) = main.tabs.iter().position(|tab| tab.id == tab_id) {
+                        main.activate_tab(ix, window, cx);
+                    }
+                });
+            });
+        });
+    }
+
+    fn close(&mut self, row: usize, cx: &mut Context<Self>) {
+        let tab = self.rows.remove(row);
+        cx.defer(move |cx| {
+            _ = tab.window.update(cx, |_, window, cx| {
+                _ = tab.main.update(cx, |main, cx| {
+                    if let Some(ix) = main.tabs.iter().position(|t| t.id == tab.tab_id) {
+                        main.close_tab(ix, window, cx);
+                    }
+                });
+            });
+        });
+        self.update_matches(cx);
+    }
+
+    fn on_key_down(&mut self, event: &KeyDownEvent, window: &mut Window, cx: &mut Context<Self>) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let last = self.matches.len() - 1;
+        match event.keystroke.key.as_str() {
+            "down" => {
+                self.selected = if self.selected < last {
+                    self.selected + 1
+                } else {
+                    0
+                }
+            }
+            "up" => {
+                self.selected = if self.selected > 0 {
+                    self.selected - 1
+                } else {
+                    last
+                }
+            }
+            "enter" => self.select(self.selected, window, cx),
+            _ => return,
+        }
+        cx.stop_propagation();
+        cx.notify();
+    }
+}
+
+impl Render for TabSearch {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let theme = *cx.global::<Theme>();
+        let this = cx.entity();
+
+        div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .capture_key_down(cx.listener(Self::on_key_down))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .h_8()
+                    .px_3()
+                    .border_1()
+                    .border_color(theme.border)
+                    .rounded_md()
+                    .child(
+                        TextInput::new(&self.query)
+                            .text_color(theme.text_secondary)
+                            .text_xs()
+                            .border_0(),
+                    ),
+            )
+            .child(
+                div()
+                    .id("tab-search-matches")
+                    .max_h(px(360.))
+                    .flex()
+                    .flex_col()
+                    .overflow_y_scroll()
+                    .when(self.matches.is_empty(), |this| {
+                        this.child(
+                            div()
+                                .px_3()
+                                .py_1()
+                                .text_xs()
+                                .text_color(theme.text_muted)
+                                .child("No matching tabs"),
+                        )
+                    })
+                    .children(self.matches.iter().enumerate().map(|(ix, tab_match)| {
+                        let row = tab_match.row;
+                        let tab = &self.rows[row];
+                        let title = if tab.title.is_empty() {
+                            &tab.url
+                        } else {
+                            &tab.title
+                        };
+                        div()
+                            .id(("tab-search", tab.tab_id))
+                            .flex()
+                            .items_center()
+                            .gap_2()
+                            .px_3()
+                            .py_1()
+                            .rounded_md()
+                            .cursor_pointer()
+                            .when(ix == self.selected, |this| this.bg(theme.hover))
+                            .hover(|this| this.bg(theme.hover))
+                            .on_click(
+                                cx.listener(move |this, _, window, cx| this.select(ix, window, cx)),
+                            )
+                            .child(favicon_icon(tab.favicon.clone(), 10.0))
+                            .child(
+                                div()
+                                    .flex()
+                                    .flex_col()
+                                    .flex_1()
+                                    .min_w_0()
+                                    .child(
+                                        div().truncate().text_xs().text_color(theme.text).child(
+                                            highlighted(
+                                                title,
+                                                tab_match
+                                                    .title_match
+                                                    .clone()
+                                                    .filter(|_| !tab.title.is_empty()),
+                                            ),
+                                        ),
+                                    )
+                                    .child(
+                                        div()
+                                            .truncate()
+                                            .text_xs()
+                                            .text_color(theme.text_muted)
+                                            .child(highlighted(
+                                                &tab.url,
+                                                tab_match.url_match.clone(),
+                                            )),
+                                    ),
+                            )
+                            .child(
+                                div()
+                                    .flex_none()
+                                    .text_xs()
+                                    .text_color(theme.text_muted)
+                                    .child(tab.window_label.clone()),
+                            )
+                            // pinned tabs have to be unpinned before they can be closed
+                            .child(svg_button(
+                                "close.svg",
+                                10.0,
+                                theme.icon_muted,
+                                !tab.pinned,
+                                {
+                                    let this = this.clone();
+                                    move |_, cx| this.update(cx, |this, cx| this.close(row, cx))
+                                },
+                            ))
+                    })),
+            )
+    }
+}
+
+impl Main {
+    pub(crate) fn open_tab_search(
+        &mut self,
+        _: &ToggleTabSearch,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if window.has_active_modal(cx) {
+            window.close_modal(cx);
+            return;
+        }
+
+        // this window's `Main` is being updated, so its tabs come from `self`
+        let current = window.window_handle();
+        let mut rows = Vec::new();
+        for (number, (handle, main)) in OpenWindows::all(cx).into_iter().enumerate() {
+            if handle == current {
+                rows.extend(tab_rows(
+                    handle,
+                    self,
+                    main.downgrade(),
+                    "This window".into(),
+                ));
+                continue;
+            }
+            let entity = main.downgrade();
+            let main = main.read(cx);
+            let label = if main.private {
+                format!("Private window {}", number + 1)
+            } else {
+                format!("Window {}", number + 1)
+            };
+            rows.extend(tab_rows(handle, main, entity, label.into()));
+        }
+        let search = cx.new(|cx| TabSearch::new(rows, window, cx));
+        window.open_modal(cx, {
+            let search = search.clone();
+            move |modal, _, _| modal.width(px(480.)).child(search.clone())
+        });
+        // the modal takes focus as it opens
+        let query = search.read(cx).query.focus_handle(cx);
+        window.defer(cx, move |window, _| window.focus(&query));
+    }
+}
@@ -0,0 +1,263 @@
+use gpui::{div, prelude::*, px, Context, IntoElement, Task, Timer};
+use std::time::Duration;
+
+use crate::bridge::TabId;
+use crate::downloads;
+use crate::message_pump::MessagePump;
+use crate::theme::Theme;
+use crate::widgets::svg_button;
+use crate::Main;
+
+// How often open tabs are asked for their memory use while the task manager is shown
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+// Asks the page for its numbers, answered through `reportMemory`. `performance.memory` is
+// Chromium-only and rounded unless precise memory info is enabled, hence -1 when missing.
+const MEMORY_SCRIPT: &str = r#"(() => {
+  const memory = performance.memory;
+  jsBridge.reportMemory(
+    memory ? memory.usedJSHeapSize : -1,
+    document.getElementsByTagName("*").length
+  );
+})();"#;
+
+// What a page reported about itself; CEF doesn't expose renderer process memory, CPU or
+// process IDs per browser, so this is what the page's own JavaScript can see
+#[derive(Debug, Clone, Copy)]
+pub struct TabUsage {
+    // Bytes of JavaScript heap in use, `None` where the page can't tell
+    pub js_heap_used: Option<u64>,
+    pub dom_nodes: u32,
+}
+
+impl TabUsage {
+    pub fn new(js_heap_used: f64, dom_nodes: u32) -> Self {
+        Self {
+            js_heap_used: (js_heap_used >= 0.).then_some(js_heap_used as u64),
+            dom_nodes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    // Tab strip order
+    Tab,
+    // Most memory first
+    Memory,
+}
+
+// Popover listing open tabs with their memory use, polling only while it's open
+pub struct TaskManager {
+    pub sort: SortBy,
+    _poll: Task<()>,
+}
+
+impl Main {
+    pub(crate) fn toggle_task_manager(&mut self, cx: &mut Context<Self>) {
+        if self.task_manager.take().is_none() {
+            let poll = cx.spawn(async move |this, cx| loop {
+                if this
+                    .update(cx, |this, cx| this.sample_tab_usage(cx))
+                    .is_err()
+                {
+                    break;
+                }
+                Timer::after(POLL_INTERVAL).await;
+            });
+            self.task_manager = Some(TaskManager {
+                sort: SortBy::Tab,
+                _poll: poll,
+            });
+        }
+        cx.notify();
+    }
+
+    // Ask every loaded page for its memory use, answered with `MemoryUsage` bridge events
+    pub(crate) fn sample_tab_usage(&mut self, _: &mut Context<Self>) {
+        for tab in &self.tabs {
+            if let Some(frame) = &tab.main_frame {
+                frame.execute_javascript(MEMORY_SCRIPT);
+            }
+        }
+    }
+
+    pub(crate) fn report_tab_usage(
+        &mut self,
+        tab_id: TabId,
+        usage: TabUsage,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.id == tab_id) {
+            tab.usage = Some(usage);
+            if self.task_manager.is_some() {
+                cx.notify();
+            }
+        }
+    }
+
+    pub(crate) fn render_task_manager(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let task_manager = self.task_manager.as_ref()?;
+        let theme = *cx.global::<Theme>();
+        let this = cx.entity();
+
+        let mut rows: Vec<_> = self
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(ix, tab)| (ix, tab, tab.usage))
+            .collect();
+        if task_manager.sort == SortBy::Memory {
+            rows.sort_by_key(|(_, _, usage)| {
+                std::cmp::Reverse(usage.and_then(|usage| usage.js_heap_used).unwrap_or(0))
+            });
+        }
+        let total: u64 = rows
+            .iter()
+            .filter_map(|(_, _, usage)| usage.and_then(|usage| usage.js_heap_used))
+            .sum();
+
+        let header = |id: &'static str, label: &'static str, sort: SortBy| {
+            div()
+                .id(id)
+                .cursor_pointer()
+                .text_color(if task_manager.sort == sort {
+                    theme.text
+                } else {
+                    theme.text_muted
+                })
+                .hover(|this| this.text_color(theme.text))
+                .child(label)
+                .on_click(cx.listener(move |this, _, _, cx| {
+                    if let Some(task_manager) = &mut this.task_manager {
+                        task_manager.sort = sort;
+                        cx.notify();
+                    }
+                }))
+        };
+
+        Some(
+            div()
+                .absolute()
+                .top_2()
+                .right_2()
+                .w(px(420.))
+                .occlude()
+                .flex()
+                .flex_col()
+                .rounded_md()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.surface)
+                .text_xs()
+                .child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_3()
+                        .px_3()
+                        .py_2()
+                        .border_b_1()
+                        .border_color(theme.border)
+                        .child(
+                            div()
+                                .flex_1()
+                                .child(header("sort-by-tab", "Tab", SortBy::Tab)),
+                        )
+                        .child(div().w(px(72.)).child(header(
+                            "sort-by-memory",
+                            "JS memory",
+                            SortBy::Memory,
+                        )))
+                        .child(div().w(px(56.)).text_color(theme.text_muted).child("Nodes"))
+                        .child(svg_button("close.svg", 10.0, theme.icon_muted, true, {
+                            let this = this.clone();
+                            move |_, cx| this.update(cx, |this, cx| this.toggle_task_manager(cx))
+                        })),
+                )
+                .child(
+                    div()
+                        .id("task-manager-rows")
+                        .max_h(px(360.))
+                        .flex()
+                        .flex_col()
+                        .overflow_y_scroll()
+                        .children(rows.into_iter().map(|(ix, tab, usage)| {
+                            let title = if tab.title.is_empty() {
+                                tab.url.clone()
+                            } else {
+                                tab.title.clone()
+                            };
+                            div()
+                                .id(("task-manager-row", tab.id))
+                                .flex()
+                                .items_center()
+                                .gap_3()
+                                .px_3()
+                                .py_1()
+                                .child(
+                                    div()
+                                        .flex_1()
+                                        .truncate()
+                                        .text_color(theme.text_secondary)
+                                        .child(title),
+                                )
+                                .child(
+                                    div().w(px(72.)).text_color(theme.text_dim).child(
+                                        usage
+                                            .and_then(|usage| usage.js_heap_used)
+                                            .map(downloads::format_bytes)
+                                            .unwrap_or_else(|| "—".to_string()),
+                                    ),
+                                )
+                                .child(
+                                    div().w(px(56.)).text_color(theme.text_dim).child(
+                                        usage
+                                            .map(|usage| usage.dom_nodes.to_string())
+                                            .unwrap_or_else(|| "—".to_string()),
+                                    ),
+                                )
+                                // pinned tabs have to be unpinned before they can be closed
+                                .child(svg_button(
+                                    "close.svg",
+                                    10.0,
+                                    theme.icon_muted,
+                                    !tab.pinned,
+                                    {
+                                        let this = this.clone();
+                                        move |window, cx| {
+                                            this.update(cx, |this, cx| {
+                                                this.close_tab(ix, window, cx)
+                                            })
+                                        }
+                                    },
+                                ))
+                        })),
+                )
+                .child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .gap_1()
+                        .px_3()
+                        .py_2()
+                        .border_t_1()
+                        .border_color(theme.border)
+                        .text_color(theme.text_muted)
+                        .child(format!(
+                            "{} tabs · {} of JavaScript memory",
+                            self.tabs.len(),
+                            downloads::format_bytes(total)
+                        ))
+                        .children(MessagePump::work_stats(cx).map(|stats| {
+                            format!(
+                                "Message loop at {} fps · {:.2} ms per frame, {:.2} ms max",
+                                stats.fps,
+                                stats.average().as_secs_f64() * 1000.,
+                                stats.max.as_secs_f64() * 1000.
+                            )
+                        })),
+                ),
+        )
+    }
+}
@@ -0,0 +1,192 @@
+use gpui::{rgb, rgba, App, Global, Rgba, WindowAppearance, WindowBackgroundAppearance};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::BrowserSettings;
+
+// Which colors the browser's own UI uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeMode {
+    // Light or dark with the system appearance
+    #[default]
+    System,
+    Light,
+    Dark,
+    // A preset with the accent and background from `CustomTheme`
+    Custom,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomTheme {
+    // The preset the other colors come from
+    pub dark: bool,
+    // `#rrggbb`, or `#rrggbbaa` for the background which the window blurs through
+    pub accent: String,
+    pub background: String,
+}
+
+impl Default for CustomTheme {
+    fn default() -> Self {
+        Self {
+            dark: true,
+            accent: "#0a84ff".to_string(),
+            background: "#0404055e".to_string(),
+        }
+    }
+}
+
+// Chrome colors of the active theme, read by everything that draws the toolbar, tab strip
+// and popovers. Kept up to date with the settings and system appearance by `init`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub dark: bool,
+    // Behind the tab strip and toolbar, translucent over the blurred window
+    pub background: Rgba,
+    // Menus and popovers
+    pub surface: Rgba,
+    pub border: Rgba,
+    // Address bar fill, top left to bottom right
+    pub field: [Rgba; 2],
+    pub text: Rgba,
+    pub text_secondary: Rgba,
+    pub text_dim: Rgba,
+    pub text_muted: Rgba,
+    // Close and other secondary icons
+    pub icon_muted: Rgba,
+    pub hover: Rgba,
+    pub hover_subtle: Rgba,
+    pub selected: Rgba,
+    pub accent: Rgba,
+    // Behind pages the browser draws itself, such as the new tab page
+    pub page: Rgba,
+    // Errors and destructive actions, warnings, and secure connections
+    pub danger: Rgba,
+    pub warning: Rgba,
+    pub success: Rgba,
+}
+
+impl Global for Theme {}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            dark: true,
+            background: rgba(0x0404055e),
+            surface: rgba(0x181818f2),
+            border: rgba(0xd3d9d92b),
+            field: [rgba(0x2e2e2e1c), rgba(0x6161621c)],
+            text: rgb(0xf2f2f2),
+            text_secondary: rgb(0xd1d1d1),
+            text_dim: rgb(0xa1a1a1),
+            text_muted: rgb(0x7a7a7a),
+            icon_muted: rgba(0xffffffb3),
+            hover: rgba(0xffffff1a),
+            hover_subtle: rgba(0xffffff08),
+            selected: rgba(0xffffff0f),
+            accent: rgb(0x0a84ff),
+            page: rgb(0x0a0a0a),
+            danger: rgb(0xff453a),
+            warning: rgb(0xff9f0a),
+            success: rgb(0x30d158),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            dark: false,
+            background: rgba(0xf2f2f7c7),
+            surface: rgba(0xfbfbfbf5),
+            border: rgba(0x0000001f),
+            field: [rgba(0xffffff66), rgba(0xffffffa6)],
+            text: rgb(0x1c1c1e),
+            text_secondary: rgb(0x3a3a3c),
+            text_dim: rgb(0x636366),
+            text_muted: rgb(0x8e8e93),
+            icon_muted: rgba(0x000000a6),
+            hover: rgba(0x0000001a),
+            hover_subtle: rgba(0x0000000a),
+            selected: rgba(0x0000000f),
+            accent: rgb(0x007aff),
+            page: rgb(0xffffff),
+            danger: rgb(0xff3b30),
+            warning: rgb(0xff9500),
+            success: rgb(0x34c759),
+        }
+    }
+
+    fn resolve(mode: ThemeMode, custom: &CustomTheme, appearance: WindowAppearance) -> Self {
+        let system_dark = matches!(
+            appearance,
+            WindowAppearance::Dark | WindowAppearance::VibrantDark
+        );
+        match mode {
+            ThemeMode::System if system_dark => Self::dark(),
+            ThemeMode::System | ThemeMode::Light => Self::light(),
+            ThemeMode::Dark => Self::dark(),
+            ThemeMode::Custom => {
+                let mut theme = if custom.dark {
+                    Self::dark()
+                } else {
+                    Self::light()
+                };
+                if let Some(accent) = parse_color(&custom.accent) {
+                    theme.accent = accent;
+                }
+                if let Some(background) = parse_color(&custom.background) {
+                    theme.background = background;
+                }
+                theme
+            }
+        }
+    }
+
+    // An opaque background has nothing to blur through
+    pub fn window_background(&self) -> WindowBackgroundAppearance {
+        if self.background.a >= 1. {
+            WindowBackgroundAppearance::Opaque
+        } else {
+            WindowBackgroundAppearance::Blurred
+        }
+    }
+
+    pub fn init(cx: &mut App) {
+        let theme = Self::current(cx);
+        cx.set_global(theme);
+        cx.observe_global::<BrowserSettings>(Self::sync).detach();
+    }
+
+    // Pick the theme again, e.g. after the system appearance changed, which every window
+    // reports through `observe_window_appearance`
+    pub fn sync(cx: &mut App) {
+        let theme = Self::current(cx);
+        if *cx.global::<Self>() != theme {
+            cx.set_global(theme);
+        }
+    }
+
+    fn current(cx: &App) -> Self {
+        let settings = cx.global::<BrowserSettings>();
+        Self::resolve(
+            settings.theme,
+            &settings.custom_theme,
+            cx.window_appearance(),
+        )
+    }
+}
+
+// `#rrggbb` or `#rrggbbaa`
+pub fn parse_color(text: &str) -> Option<Rgba> {
+    let hex = text.trim().strip_prefix('#')?;
+    let value = u32::from_str_radix(hex, 16).ok()?;
+    match hex.len() {
+        6 => Some(rgb(value)),
+        8 => Some(rgba(value)),
+        _ => None,
+    }
+}
+
+// `color` with its alpha replaced, e.g. a faint wash of the danger color behind an error
+pub fn with_alpha(color: Rgba, alpha: f32) -> Rgba {
+    Rgba { a: alpha, ..color }
+}
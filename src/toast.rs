@@ -0,0 +1,169 @@
+use gpui::{
+    div, linear_color_stop, linear_gradient, prelude::*, px, rgb, rgba, App, AppContext, Context,
+    Entity, Global, IntoElement, Render, SharedString, Timer, Window,
+};
+use std::time::{Duration, Instant};
+
+// How long a toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+
+// Older toasts are dropped once the stack is this tall
+const MAX_TOASTS: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ToastKind {
+    Info,
+    Success,
+    Error,
+}
+
+impl ToastKind {
+    fn accent(self) -> u32 {
+        match self {
+            ToastKind::Info => 0x0a84ff,
+            ToastKind::Success => 0x30d158,
+            ToastKind::Error => 0xff453a,
+        }
+    }
+}
+
+pub struct Toast {
+    id: usize,
+    pub message: SharedString,
+    pub kind: ToastKind,
+    pub created_at: Instant,
+}
+
+struct GlobalToasts(Entity<ToastManager>);
+
+impl Global for GlobalToasts {}
+
+// Transient messages shown in the bottom corner of every window, newest at the bottom
+pub struct ToastManager {
+    toasts: Vec<Toast>,
+    next_id: usize,
+}
+
+impl ToastManager {
+    pub fn init(cx: &mut App) {
+        let manager = cx.new(|_| Self {
+            toasts: Vec::new(),
+            next_id: 0,
+        });
+        cx.set_global(GlobalToasts(manager));
+    }
+
+    pub fn global(cx: &App) -> Entity<Self> {
+        cx.global::<GlobalToasts>().0.clone()
+    }
+
+    pub fn push(&mut self, message: SharedString, kind: ToastKind, cx: &mut Context<Self>) {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.toasts.push(Toast {
+            id,
+            message,
+            kind,
+            created_at: Instant::now(),
+        });
+        if self.toasts.len() > MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+
+        cx.spawn(async move |this, cx| {
+            Timer::after(TOAST_DURATION).await;
+            this.update(cx, |this, cx| this.expire(cx)).ok();
+        })
+        .detach();
+        cx.notify();
+    }
+
+    pub fn dismiss(&mut self, id: usize, cx: &mut Context<Self>) {
+        self.toasts.retain(|toast| toast.id != id);
+        cx.notify();
+    }
+
+    fn expire(&mut self, cx: &mut Context<Self>) {
+        let count = self.toasts.len();
+        self.toasts
+            .retain(|toast| toast.created_at.elapsed() < TOAST_DURATION);
+        if self.toasts.len() != count {
+            cx.notify();
+        }
+    }
+}
+
+pub fn toast_info(cx: &mut App, message: impl Into<SharedString>) {
+    show(cx, message.into(), ToastKind::Info);
+}
+
+pub fn toast_success(cx: &mut App, message: impl Into<SharedString>) {
+    show(cx, message.into(), ToastKind::Success);
+}
+
+pub fn toast_error(cx: &mut App, message: impl Into<SharedString>) {
+    show(cx, message.into(), ToastKind::Error);
+}
+
+fn show(cx: &mut App, message: SharedString, kind: ToastKind) {
+    ToastManager::global(cx).update(cx, |manager, cx| manager.push(message, kind, cx));
+}
+
+impl Render for ToastManager {
+    fn render(&mut self, _: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        div()
+            .absolute()
+            .bottom(px(16.))
+            .right(px(16.))
+            .flex()
+            .flex_col()
+            .items_end()
+            .gap_2()
+            .children(self.toasts.iter().map(|toast| {
+                let id = toast.id;
+                div()
+                    .id(("toast", id))
+                    .occlude()
+                    .relative()
+                    .flex()
+                    .items_center()
+                    .gap_2()
+                    .max_w(px(360.))
+                    .px_3()
+                    .py_2()
+                    .rounded_md()
+                    .border_1()
+                    .border_color(rgba(0xd3d9d92b))
+                    .bg(rgba(0x181818f2))
+                    .cursor_pointer()
+                    .on_click(cx.listener(move |this, _, _, cx| this.dismiss(id, cx)))
+                    // same sheen as the toolbar buttons, over the opaque backing
+                    .child(
+                        div()
+                            .size_full()
+                            .absolute()
+                            .top_0()
+                            .left_0()
+                            .rounded_md()
+                            .bg(linear_gradient(
+                                150.,
+                                linear_color_stop(rgba(0x2e2e2e1c), 0.05),
+                                linear_color_stop(rgba(0x6161621c), 0.85),
+                            )),
+                    )
+                    .child(
+                        div()
+                            .size(px(6.))
+                            .flex_none()
+                            .rounded_full()
+                            .bg(rgb(toast.kind.accent())),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(rgb(0xf2f2f2))
+                            .child(toast.message.clone()),
+                    )
+            }))
+    }
+}
@@ -0,0 +1,457 @@
+use gpui::{div, prelude::*, px, Context, IntoElement, Task, Timer, Window};
+use gpui_webview::wef::Frame;
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use crate::bridge::TabId;
+use crate::settings::BrowserSettings;
+use crate::theme::Theme;
+use crate::toast::{toast_error, toast_info};
+use crate::widgets::svg_button;
+use crate::{Main, ToggleReadAloud};
+
+pub const DEFAULT_RATE: f32 = 1.;
+pub const MIN_RATE: f32 = 0.5;
+pub const MAX_RATE: f32 = 3.;
+// Change in speed for each press of - or +
+const RATE_STEP: f32 = 0.25;
+// How often the system voice is checked for having finished its sentence
+const SYSTEM_POLL_INTERVAL: Duration = Duration::from_millis(100);
+// Words per minute of `say` and eSpeak at normal speed
+const DEFAULT_WORDS_PER_MINUTE: f32 = 175.;
+
+// Which voice reads pages aloud
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TtsEngine {
+    // The Web Speech API of the page being read
+    #[default]
+    Page,
+    // The platform's speech command: `say` on macOS, Speech Dispatcher or eSpeak on Linux,
+    // and System.Speech through PowerShell on Windows
+    System,
+}
+
+// Commands to try for the system voice, reading the text from stdin
+fn system_commands(rate: f32) -> Vec<(&'static str, Vec<String>)> {
+    let words_per_minute = (DEFAULT_WORDS_PER_MINUTE * rate).round().to_string();
+    if cfg!(target_os = "macos") {
+        vec![("say", vec!["-r".into(), words_per_minute])]
+    } else if cfg!(target_os = "windows") {
+        // System.Speech rates go from -10 to 10, about 3x slower or faster
+        let rate = ((rate - 1.) * 5.).round().clamp(-10., 10.);
+        let script = format!(
+            "Add-Type -AssemblyName System.Speech; \
+             $voice = New-Object System.Speech.Synthesis.SpeechSynthesizer; \
+             $voice.Rate = {}; $voice.Speak([Console]::In.ReadToEnd())",
+            rate
+        );
+        vec![(
+            "powershell",
+            vec![
+                "-NoProfile".into(),
+                "-NonInteractive".into(),
+                "-Command".into(),
+                script,
+            ],
+        )]
+    } else {
+        let rate = ((rate - 1.) * 100.).round().clamp(-100., 100.);
+        vec![
+            (
+                "spd-say",
+                vec!["-e".into(), "-w".into(), "-r".into(), rate.to_string()],
+            ),
+            (
+                "espeak",
+                vec!["--stdin".into(), "-s".into(), words_per_minute],
+            ),
+        ]
+    }
+}
+
+// The system voice speaking a sentence, silenced when dropped
+struct SystemSpeech {
+    child: Child,
+    program: &'static str,
+}
+
+impl SystemSpeech {
+    fn spawn(text: &str, rate: f32) -> std::io::Result<Self> {
+        let mut last_error = None;
+        for (program, args) in system_commands(rate) {
+            let mut command = Command::new(program);
+            command
+                .args(args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null());
+            #[cfg(windows)]
+            {
+                use std::os::windows::process::CommandExt;
+                // no console window flashing up for each sentence
+                command.creation_flags(0x0800_0000);
+            }
+            match command.spawn() {
+                Ok(mut child) => {
+                    if let Some(mut stdin) = child.stdin.take() {
+                        stdin.write_all(text.as_bytes())?;
+                    }
+                    return Ok(Self { child, program });
+                }
+                // try the next one
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => last_error = Some(err),
+                Err(err) => return Err(err),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| std::io::ErrorKind::NotFound.into()))
+    }
+}
+
+impl Drop for SystemSpeech {
+    fn drop(&mut self) {
+        if !matches!(self.child.try_wait(), Ok(None)) {
+            return;
+        }
+        _ = self.child.kill();
+        _ = self.child.wait();
+        // Speech Dispatcher keeps speaking what it was sent after the client is gone
+        if self.program == "spd-say" {
+            _ = Command::new("spd-say").arg("-C").spawn();
+        }
+    }
+}
+
+// A page being read aloud, one sentence at a time. The page splits and highlights the
+// sentences, see `scripts/tts.js`.
+pub struct ReadAloud {
+    pub tab_id: TabId,
+    // Picked when reading started
+    engine: TtsEngine,
+    sentences: Vec<String>,
+    current: usize,
+    paused: bool,
+    speech: Option<SystemSpeech>,
+    _poll: Option<Task<()>>,
+}
+
+impl Main {
+    pub(crate) fn toggle_read_aloud(
+        &mut self,
+        _: &ToggleReadAloud,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.read_aloud.is_some() {
+            self.stop_read_aloud(cx);
+            return;
+        }
+        let Some(frame) = &self.tabs[self.active_tab].main_frame else {
+            toast_info(cx, "Wait for the page to load to read it aloud");
+            return;
+        };
+        // the page replies with `ttsSentences`
+        frame.execute_javascript("window.__browserTts && window.__browserTts.collect()");
+    }
+
+    pub(crate) fn start_read_aloud(
+        &mut self,
+        tab_id: TabId,
+        sentences: Vec<String>,
+        cx: &mut Context<Self>,
+    ) {
+        if sentences.is_empty() {
+            toast_info(cx, "Couldn't find any text to read on this page");
+            return;
+        }
+        self.read_aloud = Some(ReadAloud {
+            tab_id,
+            engine: cx.global::<BrowserSettings>().tts_engine,
+            sentences,
+            current: 0,
+            paused: false,
+            speech: None,
+            _poll: None,
+        });
+        self.speak_sentence(cx);
+    }
+
+    // Start the current sentence from its beginning
+    fn speak_sentence(&mut self, cx: &mut Context<Self>) {
+        let rate = cx.global::<BrowserSettings>().tts_rate;
+        let Some(read_aloud) = &mut self.read_aloud else {
+            return;
+        };
+        let Some(frame) = self
+            .tabs
+            .iter()
+            .find(|tab| tab.id == read_aloud.tab_id)
+            .and_then(|tab| tab.main_frame.clone())
+        else {
+            self.stop_read_aloud(cx);
+            return;
+        };
+
+        let index = read_aloud.current;
+        // silences the system voice when starting over mid-sentence
+        read_aloud.speech = None;
+        match read_aloud.engine {
+            TtsEngine::Page => frame.execute_javascript(&format!(
+                "window.__browserTts && window.__browserTts.speak({}, {})",
+                index, rate
+            )),
+            TtsEngine::System => {
+                frame.execute_javascript(&format!(
+                    "window.__browserTts && window.__browserTts.highlight({})",
+                    index
+                ));
+                match SystemSpeech::spawn(&read_aloud.sentences[index], rate) {
+                    Ok(speech) => {
+                        read_aloud.speech = Some(speech);
+                        read_aloud._poll = Some(cx.spawn(async move |this, cx| loop {
+                            Timer::after(SYSTEM_POLL_INTERVAL).await;
+                            match this.update(cx, |this, cx| this.poll_system_speech(cx)) {
+                                Ok(false) => {}
+                                _ => break,
+                            }
+                        }));
+                    }
+                    Err(err) => {
+                        log::error!("Failed to start the system voice: {}", err);
+                        toast_error(cx, "Couldn't start the system voice");
+                        self.stop_read_aloud(cx);
+                        return;
+                    }
+                }
+            }
+        }
+        cx.notify();
+    }
+
+    // Whether the system voice is done with its sentence, moving on to the next one if so
+    fn poll_system_speech(&mut self, cx: &mut Context<Self>) -> bool {
+        let Some(speech) = self
+            .read_aloud
+            .as_mut()
+            .and_then(|read_aloud| read_aloud.speech.as_mut())
+        else {
+            return true;
+        };
+        match speech.child.try_wait() {
+            Ok(None) => false,
+            Ok(Some(status)) if status.success() => {
+                self.sentence_spoken(cx);
+                true
+            }
+            Ok(Some(status)) => {
+                log::error!("The system voice exited with {}", status);
+                toast_error(cx, "The system voice stopped reading");
+                self.stop_read_aloud(cx);
+                true
+            }
+            Err(err) => {
+                log::error!("Failed to wait for the system voice: {}", err);
+                self.stop_read_aloud(cx);
+                true
+            }
+        }
+    }
+
+    // The page engine finished sentence `index`
+    pub(crate) fn read_aloud_spoken(
+        &mut self,
+        tab_id: TabId,
+        index: usize,
+        error: String,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(read_aloud) = &self.read_aloud else {
+            return;
+        };
+        if read_aloud.tab_id != tab_id || read_aloud.current != index || read_aloud.paused {
+            return;
+        }
+        match error.as_str() {
+            "" => self.sentence_spoken(cx),
+            // Chromium only lets pages speak after the user interacted with them
+            "not-allowed" => {
+                toast_info(
+                    cx,
+                    "The page isn't allowed to speak yet, click it or pick the system voice",
+                );
+                self.stop_read_aloud(cx);
+            }
+            error => {
+                log::error!("Failed to read sentence {} aloud: {}", index, error);
+                toast_error(cx, "Couldn't read the page aloud");
+                self.stop_read_aloud(cx);
+            }
+        }
+    }
+
+    fn sentence_spoken(&mut self, cx: &mut Context<Self>) {
+        let Some(read_aloud) = &mut self.read_aloud else {
+            return;
+        };
+        read_aloud.speech = None;
+        if read_aloud.current + 1 < read_aloud.sentences.len() {
+            read_aloud.current += 1;
+            self.speak_sentence(cx);
+        } else {
+            self.stop_read_aloud(cx);
+        }
+    }
+
+    // Pausing stops the sentence, which starts over on resume
+    fn toggle_read_aloud_pause(&mut self, cx: &mut Context<Self>) {
+        let Some(read_aloud) = &mut self.read_aloud else {
+            return;
+        };
+        read_aloud.paused = !read_aloud.paused;
+        if !read_aloud.paused {
+            self.speak_sentence(cx);
+            return;
+        }
+        read_aloud.speech = None;
+        read_aloud._poll = None;
+        let tab_id = read_aloud.tab_id;
+        if let Some(frame) = self.read_aloud_frame(tab_id) {
+            frame.execute_javascript("window.__browserTts && window.__browserTts.pause()");
+        }
+        cx.notify();
+    }
+
+    fn set_read_aloud_rate(&mut self, rate: f32, cx: &mut Context<Self>) {
+        cx.update_global::<BrowserSettings, _>(|settings, _| {
+            settings.tts_rate = rate.clamp(MIN_RATE, MAX_RATE);
+            settings.save();
+        });
+        // the voice can't change speed mid-sentence
+        if self
+            .read_aloud
+            .as_ref()
+            .is_some_and(|read_aloud| !read_aloud.paused)
+        {
+            self.speak_sentence(cx);
+        }
+        cx.notify();
+    }
+
+    pub(crate) fn stop_read_aloud(&mut self, cx: &mut Context<Self>) {
+        let Some(read_aloud) = self.read_aloud.take() else {
+            return;
+        };
+        if let Some(frame) = self.read_aloud_frame(read_aloud.tab_id) {
+            frame.execute_javascript("window.__browserTts && window.__browserTts.stop()");
+        }
+        cx.notify();
+    }
+
+    fn read_aloud_frame(&self, tab_id: TabId) -> Option<&Frame> {
+        self.tabs
+            .iter()
+            .find(|tab| tab.id == tab_id)
+            .and_then(|tab| tab.main_frame.as_ref())
+    }
+
+    // Player over the bottom right of the page while a tab is being read aloud
+    pub(crate) fn render_read_aloud_bar(&self, cx: &mut Context<Self>) -> Option<impl IntoElement> {
+        let theme = *cx.global::<Theme>();
+        let read_aloud = self.read_aloud.as_ref()?;
+        let rate = cx.global::<BrowserSettings>().tts_rate;
+        let this = cx.entity();
+        let rate_button = |id: &'static str, label: &'static str, enabled: bool| {
+            div()
+                .id(id)
+                .px_1()
+                .rounded_md()
+                .text_xs()
+                .text_color(theme.text)
+                .when(enabled, |this| {
+                    this.cursor_pointer()
+                        .hover(|this| this.bg(theme.hover_subtle))
+                })
+                .when(!enabled, |this| this.opacity(0.4))
+                .child(label)
+        };
+        // reading another tab than the one shown
+        let title = self
+            .tabs
+            .iter()
+            .find(|tab| tab.id == read_aloud.tab_id)
+            .filter(|tab| tab.id != self.tabs[self.active_tab].id)
+            .map(|tab| tab.title.clone());
+
+        Some(
+            div()
+                .absolute()
+                .bottom_4()
+                .right_4()
+                .flex()
+                .items_center()
+                .gap_2()
+                .px_3()
+                .h_8()
+                .rounded_md()
+                .border_1()
+                .border_color(theme.border)
+                .bg(theme.surface)
+                .child(svg_button(
+                    if read_aloud.paused {
+                        "play.svg"
+                    } else {
+                        "pause.svg"
+                    },
+                    12.0,
+                    theme.text,
+                    true,
+                    {
+                        let this = this.clone();
+                        move |_, cx| this.update(cx, |this, cx| this.toggle_read_aloud_pause(cx))
+                    },
+                ))
+                .child(svg_button("square.svg", 12.0, theme.text, true, {
+                    let this = this.clone();
+                    move |_, cx| this.update(cx, |this, cx| this.stop_read_aloud(cx))
+                }))
+                .child(
+                    div()
+                        .flex_none()
+                        .text_xs()
+                        .text_color(theme.text_muted)
+                        .child(format!(
+                            "{}/{}",
+                            read_aloud.current + 1,
+                            read_aloud.sentences.len()
+                        )),
+                )
+                .children(title.map(|title| {
+                    div()
+                        .max_w(px(160.))
+                        .truncate()
+                        .text_xs()
+                        .text_color(theme.text_muted)
+                        .child(title)
+                }))
+                .child(
+                    rate_button("read-aloud-slower", "-", rate > MIN_RATE).on_click(cx.listener(
+                        move |this, _, _, cx| this.set_read_aloud_rate(rate - RATE_STEP, cx),
+                    )),
+                )
+                .child(
+                    div()
+                        .flex_none()
+                        .text_xs()
+                        .text_color(theme.text)
+                        .child(format!("{}×", rate)),
+                )
+                .child(
+                    rate_button("read-aloud-faster", "+", rate < MAX_RATE).on_click(cx.listener(
+                        move |this, _, _, cx| this.set_read_aloud_rate(rate + RATE_STEP, cx),
+                    )),
+                ),
+        )
+    }
+}
@@ -0,0 +1,38 @@
+use gpui::{App, Global};
+
+use crate::settings::BrowserSettings;
+
+// Presets offered per tab. Sites pick their layout from the platform and the "Mobile" token.
+const DESKTOP_USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Safari/537.36";
+const MOBILE_USER_AGENT: &str = "Mozilla/5.0 (Linux; Android 14; Pixel 8) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0.0.0 Mobile Safari/537.36";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserAgentPreset {
+    Desktop,
+    Mobile,
+}
+
+impl UserAgentPreset {
+    pub fn user_agent(self) -> &'static str {
+        match self {
+            UserAgentPreset::Desktop => DESKTOP_USER_AGENT,
+            UserAgentPreset::Mobile => MOBILE_USER_AGENT,
+        }
+    }
+}
+
+// CEF's own user agent, as reported by the first page loaded without an override
+pub struct BuiltinUserAgent(pub String);
+
+impl Global for BuiltinUserAgent {}
+
+// The user agent pages see unless their tab overrides it, `None` until it's known
+pub fn effective_user_agent(cx: &App) -> Option<String> {
+    cx.global::<BrowserSettings>()
+        .user_agent
+        .clone()
+        .or_else(|| {
+            cx.try_global::<BuiltinUserAgent>()
+                .map(|builtin| builtin.0.clone())
+        })
+}
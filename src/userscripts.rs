@@ -0,0 +1,355 @@
+use gpui::{App, Global};
+use gpui_webview::wef::Frame;
+use serde::{Deserialize, Serialize};
+use url::{Position, Url};
+
+use crate::storage;
+
+const USER_SCRIPTS_FILE: &str = "userscripts.json";
+
+// When a script runs relative to the page's own scripts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunAt {
+    // As the new document starts loading, before the page's scripts have run
+    DocumentStart,
+    // Once the page has finished loading
+    #[default]
+    DocumentEnd,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserScript {
+    pub id: u64,
+    pub name: String,
+    // Pages the script runs on, see `matches_pattern`
+    pub matches: Vec<String>,
+    #[serde(default)]
+    pub run_at: RunAt,
+    pub source: String,
+    pub enabled: bool,
+    // Also run in iframes, not only the top frame
+    #[serde(default)]
+    pub all_frames: bool,
+}
+
+impl UserScript {
+    fn matches_url(&self, url: &str) -> bool {
+        self.matches
+            .iter()
+            .any(|pattern| matches_pattern(pattern, url))
+    }
+}
+
+// Scripts added by the user to run on matching pages, persisted to disk on every change
+pub struct UserScripts {
+    scripts: Vec<UserScript>,
+}
+
+impl Global for UserScripts {}
+
+impl UserScripts {
+    pub fn load() -> Self {
+        let scripts = storage::load_json::<Vec<UserScript>>(USER_SCRIPTS_FILE)
+            .unwrap_or_else(|err| {
+                log::error!("Failed to load user scripts: {}", err);
+                None
+            })
+            .unwrap_or_default();
+        Self { scripts }
+    }
+
+    pub fn all(&self) -> &[UserScript] {
+        &self.scripts
+    }
+
+    // Add an enabled script and return its id, `None` if it has no source or patterns
+    pub fn add(
+        &mut self,
+        name: &str,
+        matches: Vec<String>,
+        run_at: RunAt,
+        all_frames: bool,
+        source: &str,
+    ) -> Option<u64> {
+        let matches: Vec<String> = matches
+            .into_iter()
+            .map(|pattern| pattern.trim().to_string())
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+        if source.trim().is_empty() || matches.is_empty() {
+            return None;
+        }
+
+        let id = self
+            .scripts
+            .iter()
+            .map(|script| script.id + 1)
+            .max()
+            .unwrap_or(0);
+        let name = match name.trim() {
+            "" => format!("Script {}", id + 1),
+            name => name.to_string(),
+        };
+        self.scripts.push(UserScript {
+            id,
+            name,
+            matches,
+            run_at,
+            source: source.to_string(),
+            enabled: true,
+            all_frames,
+        });
+        self.save();
+        Some(id)
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        let len = self.scripts.len();
+        self.scripts.retain(|script| script.id != id);
+        if self.scripts.len() != len {
+            self.save();
+        }
+    }
+
+    pub fn set_enabled(&mut self, id: u64, enabled: bool) {
+        if let Some(script) = self.scripts.iter_mut().find(|script| script.id == id) {
+            if script.enabled != enabled {
+                script.enabled = enabled;
+                self.save();
+            }
+        }
+    }
+
+    // Enabled scripts to run at `run_at` in a frame showing `url`
+    fn matching<'a>(
+        &'a self,
+        url: &'a str,
+        run_at: RunAt,
+        main_frame: bool,
+    ) -> impl Iterator<Item = &'a UserScript> {
+        self.scripts.iter().filter(move |script| {
+            script.enabled
+                && script.run_at == run_at
+                && (main_frame || script.all_frames)
+                && script.matches_url(url)
+        })
+    }
+
+    fn save(&self) {
+        if let Err(err) = storage::save_json(USER_SCRIPTS_FILE, &self.scripts) {
+            log::error!("Failed to save user scripts: {}", err);
+        }
+    }
+}
+
+// Run the scripts matching the frame's page. Each script is isolated in its own function, so
+// one that throws doesn't stop the others.
+pub fn inject(frame: &Frame, run_at: RunAt, cx: &App) {
+    let Some(scripts) = cx.try_global::<UserScripts>() else {
+        return;
+    };
+
+    let url = frame.url();
+    for script in scripts.matching(&url, run_at, frame.is_main()) {
+        let name = serde_json::to_string(&script.name).unwrap_or_default();
+        frame.execute_javascript(&format!(
+            "(() => {{ try {{\n{}\n}} catch (err) {{ console.error(\"User script\", {}, \"failed:\", err); }} }})();",
+            script.source, name
+        ));
+    }
+}
+
+// Whether `url` matches a user script pattern:
+// - `*` matches every web page
+// - a pattern with a scheme, like `*://*.example.com/docs/*`, is matched part by part: the
+//   scheme and the path are globs, and the host is matched as below
+// - anything else is a glob over the host, where `*.example.com` also matches `example.com`
+pub fn matches_pattern(pattern: &str, url: &str) -> bool {
+    let pattern = pattern.trim();
+    let Ok(parsed) = Url::parse(url) else {
+        return false;
+    };
+    if !matches!(parsed.scheme(), "http" | "https" | "file") {
+        return false;
+    }
+
+    if pattern == "*" {
+        return true;
+    }
+    let Some((scheme, rest)) = pattern.split_once("://") else {
+        return matches_host(pattern, &parsed);
+    };
+    // the host can't be matched as part of the whole URL, where a `*` in it would also
+    // run over `/`, `?` and `@`
+    let (host, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let path = if path.is_empty() { "/" } else { path };
+    glob(&scheme.to_lowercase(), parsed.scheme())
+        && matches_host(host, &parsed)
+        && glob(path, &parsed[Position::BeforePath..])
+}
+
+// A glob over the host, including the port when the pattern has one
+fn matches_host(pattern: &str, url: &Url) -> bool {
+    let pattern = pattern.to_lowercase();
+    let host = url.host_str().unwrap_or_default();
+    let host = match url.port_or_known_default() {
+        Some(port) if pattern.contains(':') => format!("{}:{}", host, port),
+        _ => host.to_string(),
+    };
+    glob(&pattern, &host)
+        || pattern
+            .strip_prefix("*.")
+            .is_some_and(|domain| domain == host)
+}
+
+// `*` matches any run of characters, everything else matches itself
+fn glob(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // no `*` at all
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(ix) => rest = &rest[ix + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_matches_every_web_page() {
+        assert!(matches_pattern("*", "https://example.com/"));
+        assert!(matches_pattern("*", "file:///home/me/page.html"));
+        assert!(!matches_pattern("*", "about:blank"));
+        assert!(!matches_pattern("*", "chrome://settings"));
+        assert!(!matches_pattern("*", "not a url"));
+    }
+
+    #[test]
+    fn host_patterns() {
+        assert!(matches_pattern("example.com", "https://example.com/docs"));
+        assert!(matches_pattern("Example.COM", "http://example.com/"));
+        assert!(!matches_pattern("example.com", "https://www.example.com/"));
+        assert!(matches_pattern("*.example.com", "https://www.example.com/"));
+        assert!(matches_pattern("*.example.com", "https://a.b.example.com/"));
+        assert!(matches_pattern("*.example.com", "https://example.com/"));
+        assert!(!matches_pattern(
+            "*.example.com",
+            "https://evilexample.com/"
+        ));
+        assert!(!matches_pattern(
+            "*.example.com",
+            "https://example.com.evil.net/"
+        ));
+        assert!(matches_pattern(
+            "localhost:3000",
+            "http://localhost:3000/app"
+        ));
+        assert!(!matches_pattern(
+            "localhost:3000",
+            "http://localhost:8080/app"
+        ));
+    }
+
+    #[test]
+    fn scheme_wildcards() {
+        assert!(matches_pattern("*://example.com/*", "http://example.com/"));
+        assert!(matches_pattern(
+            "*://example.com/*",
+            "https://example.com/a?b=c"
+        ));
+        assert!(!matches_pattern("*://example.com/*", "ftp://example.com/"));
+        assert!(matches_pattern(
+            "https://example.com/*",
+            "https://example.com/"
+        ));
+        assert!(!matches_pattern(
+            "https://example.com/*",
+            "http://example.com/"
+        ));
+        assert!(matches_pattern(
+            "HTTPS://example.com/*",
+            "https://example.com/"
+        ));
+    }
+
+    #[test]
+    fn host_wildcards_in_urls() {
+        let pattern = "*://*.example.com/*";
+        assert!(matches_pattern(pattern, "https://example.com/"));
+        assert!(matches_pattern(pattern, "https://mail.example.com/inbox"));
+        assert!(!matches_pattern(pattern, "https://evilexample.com/"));
+        assert!(!matches_pattern(pattern, "https://evil.net/.example.com/"));
+        assert!(!matches_pattern(
+            pattern,
+            "https://evil.net/?://x.example.com/"
+        ));
+        assert!(!matches_pattern(pattern, "https://x.example.com@evil.net/"));
+        assert!(matches_pattern(
+            "http://localhost:3000/*",
+            "http://localhost:3000/app"
+        ));
+        assert!(!matches_pattern(
+            "http://localhost:3000/*",
+            "http://localhost:4000/app"
+        ));
+    }
+
+    #[test]
+    fn path_globs() {
+        let pattern = "https://example.com/docs/*";
+        assert!(matches_pattern(pattern, "https://example.com/docs/"));
+        assert!(matches_pattern(
+            pattern,
+            "https://example.com/docs/a/b?page=2"
+        ));
+        assert!(!matches_pattern(pattern, "https://example.com/doc"));
+        assert!(!matches_pattern(pattern, "https://example.com/blog/docs/"));
+        assert!(matches_pattern(
+            "https://example.com/*/edit",
+            "https://example.com/posts/1/edit"
+        ));
+        assert!(!matches_pattern(
+            "https://example.com/*/edit",
+            "https://example.com/posts/1/view"
+        ));
+        assert!(matches_pattern(
+            "https://example.com",
+            "https://example.com"
+        ));
+        assert!(!matches_pattern(
+            "https://example.com",
+            "https://example.com/page"
+        ));
+        assert!(matches_pattern(
+            "file:///home/*",
+            "file:///home/me/page.html"
+        ));
+    }
+
+    #[test]
+    fn glob_stars() {
+        assert!(glob("abc", "abc"));
+        assert!(!glob("abc", "abcd"));
+        assert!(glob("*c", "abc"));
+        assert!(glob("a*", "abc"));
+        assert!(glob("a*c", "abbbc"));
+        assert!(glob("a*b*c", "axbyc"));
+        assert!(!glob("a*b*c", "axcyb"));
+        assert!(glob("*", ""));
+        assert!(!glob("a*a", "a"));
+    }
+}
@@ -0,0 +1,255 @@
+use gpui_webview::{wef::PdfPrintSettings, WebView};
+use image::RgbaImage;
+use std::path::Path;
+
+// Zoom factors stepped through by zoom in/out, matching common browser presets
+const ZOOM_LEVELS: &[f32] = &[
+    0.25, 0.33, 0.5, 0.67, 0.75, 0.8, 0.9, 1.0, 1.1, 1.25, 1.5, 1.75, 2.0, 2.5, 3.0, 4.0, 5.0,
+];
+pub const MIN_ZOOM: f32 = 0.25;
+pub const MAX_ZOOM: f32 = 5.0;
+
+// Paper sizes offered for PDF export, in inches
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PageSize {
+    Letter,
+    Legal,
+    A4,
+    Custom { width: f64, height: f64 },
+}
+
+impl PageSize {
+    fn dimensions(self) -> (f64, f64) {
+        match self {
+            PageSize::Letter => (8.5, 11.0),
+            PageSize::Legal => (8.5, 14.0),
+            PageSize::A4 => (8.27, 11.69),
+            PageSize::Custom { width, height } => (width, height),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PdfOptions {
+    pub page_size: PageSize,
+    pub landscape: bool,
+    // Margin on every side, in inches
+    pub margin: f64,
+    pub print_backgrounds: bool,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            page_size: PageSize::Letter,
+            landscape: false,
+            margin: 0.4,
+            print_backgrounds: true,
+        }
+    }
+}
+
+// Navigation helpers layered on top of the browser owned by a `WebView`
+pub trait WebViewExt {
+    /// Whether there is a previous entry in the navigation history.
+    fn can_go_back(&self) -> bool;
+
+    /// Navigate one entry back in the history, if possible.
+    fn go_back(&self);
+
+    /// Whether there is a next entry in the navigation history.
+    fn can_go_forward(&self) -> bool;
+
+    /// Navigate one entry forward in the history, if possible.
+    fn go_forward(&self);
+
+    /// Reload the current page.
+    fn reload(&self);
+
+    /// Reload the current page, bypassing the HTTP cache.
+    fn reload_ignoring_cache(&self);
+
+    /// Cancel the in-flight navigation.
+    fn stop_loading(&self);
+
+    /// Set the page zoom factor, where `1.0` is 100%.
+    fn set_zoom(&self, zoom: f32);
+
+    /// Step up from `current` to the next zoom preset, returning the new factor.
+    fn zoom_in(&self, current: f32) -> f32;
+
+    /// Step down from `current` to the previous zoom preset, returning the new factor.
+    fn zoom_out(&self, current: f32) -> f32;
+
+    /// Return to 100%.
+    fn reset_zoom(&self) -> f32;
+
+    /// Send `user_agent` with this page's requests from now on, `None` restores the default.
+    ///
+    /// Goes through the DevTools protocol, CEF has no per-browser user agent setting.
+    fn set_user_agent(&self, user_agent: Option<&str>);
+
+    /// Allow or prevent the page's scripts from running, which lasts across navigations.
+    ///
+    /// Goes through the DevTools protocol, CEF only has a JavaScript setting for the whole
+    /// browser when it's created. Scripts the browser injects don't run either while it's off.
+    fn set_javascript_enabled(&self, enabled: bool);
+
+    /// Silence or restore all audio from this page, including audio started later.
+    fn set_muted(&self, muted: bool);
+
+    /// Float the page's video in a Picture-in-Picture window, or bring it back.
+    ///
+    /// Goes through the DevTools protocol, which can run the page script as a user gesture;
+    /// the Picture-in-Picture API refuses requests without one. Only videos in the main frame
+    /// can be picked, frames don't pass the gesture on.
+    fn toggle_picture_in_picture(&self);
+
+    /// Open the developer tools for this page, or close them if they are open.
+    ///
+    /// DevTools open in their own OS window rather than docked in the browser window:
+    /// docking would need a second CEF view, which wef doesn't expose.
+    fn toggle_devtools(&self);
+
+    /// The frame last painted for this page, covering the visible viewport.
+    ///
+    /// `None` until the page has painted at least once.
+    fn capture_viewport(&self) -> Option<RgbaImage>;
+
+    /// Render the whole document to a PDF at `path`.
+    ///
+    /// The returned channel receives whether the file was written once CEF is done.
+    fn print_to_pdf(&self, path: &Path, options: &PdfOptions) -> flume::Receiver<bool>;
+}
+
+impl WebViewExt for WebView {
+    fn can_go_back(&self) -> bool {
+        self.browser().can_go_back()
+    }
+
+    fn go_back(&self) {
+        if self.can_go_back() {
+            self.browser().go_back();
+        }
+    }
+
+    fn can_go_forward(&self) -> bool {
+        self.browser().can_go_forward()
+    }
+
+    fn go_forward(&self) {
+        if self.can_go_forward() {
+            self.browser().go_forward();
+        }
+    }
+
+    fn reload(&self) {
+        self.browser().reload();
+    }
+
+    fn reload_ignoring_cache(&self) {
+        self.browser().reload_ignore_cache();
+    }
+
+    fn stop_loading(&self) {
+        self.browser().stop_load();
+    }
+
+    fn set_zoom(&self, zoom: f32) {
+        // CEF zoom levels are logarithmic, each step scaling by 20%
+        let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM) as f64;
+        self.browser().set_zoom_level(zoom.ln() / 1.2f64.ln());
+    }
+
+    fn zoom_in(&self, current: f32) -> f32 {
+        let zoom = ZOOM_LEVELS
+            .iter()
+            .copied()
+            .find(|level| *level > current + f32::EPSILON)
+            .unwrap_or(MAX_ZOOM);
+        self.set_zoom(zoom);
+        zoom
+    }
+
+    fn zoom_out(&self, current: f32) -> f32 {
+        let zoom = ZOOM_LEVELS
+            .iter()
+            .copied()
+            .rev()
+            .find(|level| *level < current - f32::EPSILON)
+            .unwrap_or(MIN_ZOOM);
+        self.set_zoom(zoom);
+        zoom
+    }
+
+    fn reset_zoom(&self) -> f32 {
+        self.set_zoom(1.0);
+        1.0
+    }
+
+    fn set_user_agent(&self, user_agent: Option<&str>) {
+        // an empty user agent clears the override
+        let params = serde_json::json!({ "userAgent": user_agent.unwrap_or_default() });
+        self.browser()
+            .execute_devtools_method("Emulation.setUserAgentOverride", &params.to_string());
+    }
+
+    fn set_javascript_enabled(&self, enabled: bool) {
+        let params = serde_json::json!({ "value": !enabled });
+        self.browser()
+            .execute_devtools_method("Emulation.setScriptExecutionDisabled", &params.to_string());
+    }
+
+    fn set_muted(&self, muted: bool) {
+        self.browser().set_audio_muted(muted);
+    }
+
+    fn toggle_picture_in_picture(&self) {
+        let params = serde_json::json!({
+            "expression": "window.__browserTogglePictureInPicture?.()",
+            "userGesture": true,
+        });
+        self.browser()
+            .execute_devtools_method("Runtime.evaluate", &params.to_string());
+    }
+
+    fn toggle_devtools(&self) {
+        let browser = self.browser();
+        if browser.has_devtools() {
+            browser.close_devtools();
+        } else {
+            browser.open_devtools();
+        }
+    }
+
+    fn capture_viewport(&self) -> Option<RgbaImage> {
+        // CEF renders offscreen, the view keeps its last frame as BGRA
+        let frame = self.frame_buffer()?;
+        let mut image = RgbaImage::from_raw(frame.width(), frame.height(), frame.data().to_vec())?;
+        for pixel in image.pixels_mut() {
+            pixel.0.swap(0, 2);
+        }
+        Some(image)
+    }
+
+    fn print_to_pdf(&self, path: &Path, options: &PdfOptions) -> flume::Receiver<bool> {
+        let (width, height) = options.page_size.dimensions();
+        let settings = PdfPrintSettings {
+            paper_width: width,
+            paper_height: height,
+            landscape: options.landscape,
+            margin_top: options.margin,
+            margin_right: options.margin,
+            margin_bottom: options.margin,
+            margin_left: options.margin,
+            print_background: options.print_backgrounds,
+            ..Default::default()
+        };
+
+        let (tx, rx) = flume::bounded(1);
+        self.browser().print_to_pdf(path, settings, move |ok| {
+            _ = tx.send(ok);
+        });
+        rx
+    }
+}
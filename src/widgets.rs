@@ -0,0 +1,151 @@
+use gpui::{
+    canvas, div, prelude::*, px, relative, rgb, rgba, svg, App, DispatchPhase, Hsla, IntoElement,
+    MouseButton, MouseDownEvent, MouseMoveEvent, Pixels, SharedString, Window,
+};
+use std::rc::Rc;
+
+use crate::theme::Theme;
+
+// SVG button component
+pub fn svg_button(
+    svg_path: &str,
+    size: f32,
+    color: impl Into<Hsla>,
+    enabled: bool,
+    on_click: impl Fn(&mut Window, &mut App) + 'static,
+) -> impl IntoElement {
+    SvgButton {
+        path: SharedString::from(svg_path.to_string()),
+        size,
+        color: color.into(),
+        enabled,
+        on_click: Box::new(on_click),
+    }
+}
+
+// Rendered late so the hover color comes from the active theme
+#[derive(IntoElement)]
+pub struct SvgButton {
+    path: SharedString,
+    size: f32,
+    color: Hsla,
+    enabled: bool,
+    on_click: Box<dyn Fn(&mut Window, &mut App)>,
+}
+
+impl RenderOnce for SvgButton {
+    fn render(self, _: &mut Window, cx: &mut App) -> impl IntoElement {
+        let hover = cx.global::<Theme>().hover_subtle;
+        let on_click = self.on_click;
+
+        div()
+            .id(self.path.clone())
+            .flex()
+            .items_center()
+            .justify_center()
+            .size(px(self.size)) // Add padding around SVG
+            .rounded_md()
+            .when(self.enabled, |this| {
+                this.cursor_pointer()
+                    .hover(|this| this.bg(hover))
+                    .on_click(move |_, window, cx| on_click(window, cx))
+            })
+            .when(!self.enabled, |this| this.opacity(0.4)) // Dimmed, no hover or click
+            .child(
+                svg()
+                    .path(self.path)
+                    .size(px(self.size))
+                    .text_color(self.color),
+            )
+    }
+}
+
+// Horizontal slider for `value` from 0 to 1, set by clicking or dragging along the track
+pub fn slider(
+    value: f32,
+    color: impl Into<Hsla>,
+    on_change: impl Fn(f32, &mut Window, &mut App) + 'static,
+) -> impl IntoElement {
+    let color = color.into();
+    let value = value.clamp(0., 1.);
+    let on_change = Rc::new(on_change);
+
+    div()
+        .relative()
+        .flex_1()
+        .h(px(16.))
+        .cursor_pointer()
+        .child(
+            div()
+                .absolute()
+                .left_0()
+                .right_0()
+                .top(px(7.))
+                .h(px(2.))
+                .rounded_full()
+                .bg(rgba(0xffffff26)),
+        )
+        .child(
+            div()
+                .absolute()
+                .left_0()
+                .top(px(7.))
+                .w(relative(value))
+                .h(px(2.))
+                .rounded_full()
+                .bg(color),
+        )
+        .child(
+            div()
+                .absolute()
+                .left(relative(value))
+                .top(px(3.))
+                .ml(px(-5.))
+                .size(px(10.))
+                .rounded_full()
+                .bg(color),
+        )
+        // the track's bounds are only known once laid out
+        .child(
+            canvas(
+                |_, _, _| {},
+                move |bounds, _, window, _| {
+                    let fraction =
+                        move |x: Pixels| ((x - bounds.left()) / bounds.size.width).clamp(0., 1.);
+                    window.on_mouse_event({
+                        let on_change = on_change.clone();
+                        move |event: &MouseDownEvent, phase, window, cx| {
+                            if phase == DispatchPhase::Bubble
+                                && event.button == MouseButton::Left
+                                && bounds.contains(&event.position)
+                            {
+                                on_change(fraction(event.position.x), window, cx);
+                            }
+                        }
+                    });
+                    window.on_mouse_event(move |event: &MouseMoveEvent, phase, window, cx| {
+                        if phase == DispatchPhase::Bubble
+                            && event.pressed_button == Some(MouseButton::Left)
+                            && bounds.contains(&event.position)
+                        {
+                            on_change(fraction(event.position.x), window, cx);
+                        }
+                    });
+                },
+            )
+            .absolute()
+            .size_full(),
+        )
+}
+
+// Small orange label flagging something to be careful with, e.g. a downloaded program
+pub fn warning_badge(label: &'static str) -> impl IntoElement {
+    div()
+        .flex_none()
+        .px_1()
+        .rounded_md()
+        .bg(rgba(0xff9f0a33))
+        .text_xs()
+        .text_color(rgb(0xff9f0a))
+        .child(label)
+}
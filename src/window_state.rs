@@ -0,0 +1,84 @@
+use gpui::{point, px, size, App, Bounds, Pixels, Window, WindowBounds};
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+const WINDOW_STATE_FILE: &str = "window.json";
+const DEFAULT_SIZE: (f32, f32) = (800., 600.);
+// How much of a restored window has to land on a display to count as visible
+const MIN_VISIBLE: f32 = 100.;
+
+// Bounds of the browser window, restored on the next launch
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WindowState {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub maximized: bool,
+}
+
+impl WindowState {
+    pub fn load() -> Option<Self> {
+        match storage::load_json::<WindowState>(WINDOW_STATE_FILE) {
+            Ok(state) => state,
+            Err(err) => {
+                log::error!("Failed to restore window state: {}", err);
+                None
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        if let Err(err) = storage::save_json(WINDOW_STATE_FILE, self) {
+            log::error!("Failed to save window state: {}", err);
+        }
+    }
+
+    pub fn from_window(window: &Window) -> Self {
+        // maximized and fullscreen bounds are the ones to restore to
+        let (bounds, maximized) = match window.window_bounds() {
+            WindowBounds::Windowed(bounds) => (bounds, false),
+            WindowBounds::Maximized(bounds) | WindowBounds::Fullscreen(bounds) => (bounds, true),
+        };
+
+        Self {
+            x: bounds.origin.x.into(),
+            y: bounds.origin.y.into(),
+            width: bounds.size.width.into(),
+            height: bounds.size.height.into(),
+            maximized,
+        }
+    }
+
+    // Saved bounds, recentered when they no longer overlap any connected display
+    pub fn window_bounds(&self, cx: &App) -> WindowBounds {
+        let bounds = Bounds {
+            origin: point(px(self.x), px(self.y)),
+            size: size(px(self.width.max(200.)), px(self.height.max(150.))),
+        };
+        let bounds = if is_on_screen(bounds, cx) {
+            bounds
+        } else {
+            Bounds::centered(None, bounds.size, cx)
+        };
+
+        if self.maximized {
+            WindowBounds::Maximized(bounds)
+        } else {
+            WindowBounds::Windowed(bounds)
+        }
+    }
+}
+
+pub fn default_bounds(cx: &App) -> WindowBounds {
+    let (width, height) = DEFAULT_SIZE;
+    WindowBounds::Windowed(Bounds::centered(None, size(px(width), px(height)), cx))
+}
+
+fn is_on_screen(bounds: Bounds<Pixels>, cx: &App) -> bool {
+    cx.displays().iter().any(|display| {
+        let visible = display.bounds().intersect(&bounds);
+        visible.size.width >= px(MIN_VISIBLE) && visible.size.height >= px(MIN_VISIBLE / 2.)
+    })
+}